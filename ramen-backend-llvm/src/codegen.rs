@@ -1,7 +1,8 @@
-use inkwell::{builder::Builder, context::Context, module::Module, values::{AnyValue, AnyValueEnum, BasicValueEnum}};
-use ramen_common::{ast::{self, NodeId}, scope::ScopeRef, session::Session, visitor::{walk_expression, walk_function, ASTPass, ScopeStack, Visitor}};
+use hashbrown::HashMap;
+use inkwell::{attributes::{Attribute, AttributeLoc}, basic_block::BasicBlock, builder::{Builder, BuilderError}, context::Context, module::{Linkage, Module}, types::{AnyType, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, IntType}, values::{AnyValue, AnyValueEnum, ArrayValue, BasicMetadataValueEnum, BasicValue, BasicValueEnum, CallSiteValue, FunctionValue, IntValue, PointerValue}, AddressSpace, FloatPredicate, IntPredicate};
+use ramen_common::{ast::{self, NodeId}, attributes::{InlineMode, KnownAttribute}, consts::ConstValue, defs::{DefId, Definition}, Loc, scope::ScopeRef, session::{AnyId, Session}, types::{CallableType, EnumType, RamenType}, visitor::{walk_function, walk_item, ScopeStack, Visitor}};
 
-use crate::{error::CodegenError, types::AsLLType};
+use crate::{error::CodegenError, types::{c_return_type, closure_function_type, is_returned_indirectly, size_and_align, struct_size_and_align, AsLLType, EnumTypeExt}};
 
 pub struct LLVMBackendCodegenPass<'sess, 'ctx> {
     pub session: &'sess Session,
@@ -9,69 +10,1674 @@ pub struct LLVMBackendCodegenPass<'sess, 'ctx> {
 
     context: &'ctx Context,
     module: Module<'ctx>,
-    builder: Builder<'ctx> 
+    builder: Builder<'ctx>,
+
+    /// Stack slots of parameters and local variables, keyed by their definition.
+    locals: HashMap<DefId, PointerValue<'ctx>>,
+    /// Hidden `sret` pointer of the current function, if it returns indirectly.
+    return_slot: Option<PointerValue<'ctx>>,
+    bounds_checks: bool,
+    /// Types of type parameters in the instance of generic function being generated.
+    type_arguments: HashMap<DefId, RamenType>,
+    /// Functions whose body was generated, keyed by their symbol.
+    defined_functions: HashMap<String, DefId>
 }
 
-pub fn generate_llvm_module(session: &Session, scope: ScopeRef, mod_id: NodeId, module: &ast::Module) -> Result<(), CodegenError> {
-    let context = Context::create();
+pub fn generate_llvm_module<'ctx>(
+    context: &'ctx Context,
+    session: &Session,
+    scope: ScopeRef,
+    mod_id: NodeId,
+    module: &ast::Module
+) -> Result<Module<'ctx>, CodegenError> {
     let mut codegen = LLVMBackendCodegenPass {
         session,
         stack: ScopeStack::new(),
 
-        context: &context,
+        context,
         module: context.create_module(&module.name),
-        builder: context.create_builder()
+        builder: context.create_builder(),
+
+        locals: HashMap::new(),
+        return_slot: None,
+        bounds_checks: session.options.bounds_checks,
+        type_arguments: HashMap::new(),
+        defined_functions: HashMap::new()
     };
 
     codegen.stack.push_scope(scope);
     codegen.visit_module(mod_id, module)?;
 
-    println!("Finished codegen:\n{}", codegen.module.print_to_string());
-
     session.exit_if_errors();
-    Ok(())
+    Ok(codegen.module)
+}
+
+impl<'sess, 'ctx> LLVMBackendCodegenPass<'sess, 'ctx> {
+    fn basic_type(&self, ty: &RamenType) -> Result<BasicTypeEnum<'ctx>, CodegenError> {
+        ty.as_llvm_type(self.context)?.try_into().map_err(|_| CodegenError::NotBasicType(ty.to_string()))
+    }
+
+    /// Type of the node with type parameters replaced by types of the current instance.
+    /// Type arguments may be structs resolved through a pointer, so they are completed as well.
+    fn get_type(&self, id: impl Into<AnyId>) -> Option<RamenType> {
+        Some(self.session.complete_type(&self.session.get_type(id)?.substitute(&self.type_arguments)))
+    }
+
+    /// Type arguments the path refers to a generic function with, empty for anything else.
+    fn get_type_arguments(&self, path_id: NodeId) -> Vec<RamenType> {
+        self.session.get_type_arguments(path_id)
+            .map(|arguments| arguments.iter().map(|argument| argument.substitute(&self.type_arguments)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Symbol of generic function instance like `main.max<int64>`, types are written with their full path like `main.math.Point`.
+    fn instance_symbol(&self, symbol: &str, type_arguments: &[RamenType]) -> String {
+        format!("{symbol}<{}>", type_arguments.iter()
+            .map(|argument| self.session.type_symbol(argument))
+            .collect::<Vec<_>>()
+            .join(", "))
+    }
+
+    /// Functions can be used before they are visited, so declaration is created by whichever comes first.
+    /// Generic functions have separate declaration for each list of type arguments.
+    fn get_or_declare_function(&self, def_id: DefId, type_arguments: &[RamenType]) -> Result<FunctionValue<'ctx>, CodegenError> {
+        let symbol = self.session.get_symbol(def_id)
+            .expect("Function symbol should have been set by frontend.");
+        let symbol = if type_arguments.is_empty() { symbol } else { self.instance_symbol(&symbol, type_arguments) };
+        if let Some(function) = self.module.get_function(&symbol) {
+            return Ok(function);
+        }
+
+        let Some(RamenType::Callable(generic)) = self.session.get_type(def_id) else {
+            unreachable!("Function type should have been resolved by frontend.")
+        };
+        let callable = generic.instantiate(type_arguments);
+        // Only functions meant to be called from outside of ramen are visible outside of the module,
+        // which lets LLVM inline and drop the rest.
+        let is_c_function = self.session.get_extern(def_id).is_some() || self.session.is_exported(def_id);
+        let function_type = callable.as_llvm_type(self.context)?.into_function_type();
+        // Small tuples are returned from C functions in registers, see `build_memory_cast` for how they get there.
+        let function_type = match c_return_type(&callable.return_type, self.context).filter(|_| is_c_function) {
+            Some(return_type) => return_type.fn_type(&function_type.get_param_types().into_iter().map(Into::into).collect::<Vec<_>>(), callable.is_vararg),
+            None => function_type
+        };
+        let function = self.module.add_function(
+            &symbol,
+            function_type,
+            Some(if is_c_function { Linkage::External } else { Linkage::Internal })
+        );
+        if is_returned_indirectly(&callable.return_type) {
+            function.add_attribute(AttributeLoc::Param(0), self.sret_attribute(&callable.return_type)?);
+        }
+        if is_c_function {
+            for (index, ty) in callable.parameter_types.iter().enumerate() {
+                if let Some(attribute) = self.c_extension_attribute(ty) {
+                    function.add_attribute(AttributeLoc::Param(index as u32), attribute);
+                }
+            }
+            if let Some(attribute) = self.c_extension_attribute(&callable.return_type) {
+                function.add_attribute(AttributeLoc::Return, attribute);
+            }
+        }
+        self.add_function_attributes(function, def_id);
+        Ok(function)
+    }
+
+    /// Lowers attributes like `@inline(always)` or `@cold` to their LLVM counterparts.
+    fn add_function_attributes(&self, function: FunctionValue<'ctx>, def_id: DefId) {
+        for attribute in self.session.get_attributes(def_id) {
+            let (kind, value) = match attribute {
+                KnownAttribute::Inline(InlineMode::Hint) => ("inlinehint", 0),
+                KnownAttribute::Inline(InlineMode::Always) => ("alwaysinline", 0),
+                KnownAttribute::Inline(InlineMode::Never) => ("noinline", 0),
+                KnownAttribute::Cold => ("cold", 0),
+                KnownAttribute::NoReturn => ("noreturn", 0),
+                KnownAttribute::NoUnwind => ("nounwind", 0),
+                // Encoded memory effects of zero mean no memory is accessed at all, `memory(none)`.
+                // LLVM before 16 has no `memory` attribute, there the same is said by `readnone`.
+                KnownAttribute::Pure if Attribute::get_named_enum_kind_id("memory") == 0 => ("readnone", 0),
+                KnownAttribute::Pure => ("memory", 0),
+                _ => continue
+            };
+            function.add_attribute(AttributeLoc::Function, self.context.create_enum_attribute(Attribute::get_named_enum_kind_id(kind), value));
+        }
+    }
+
+    /// C expects integers narrower than `int` to be extended by whoever produces them, and booleans to be zero or one.
+    /// All ramen integers are signed, so they are always sign extended.
+    fn c_extension_attribute(&self, ty: &RamenType) -> Option<Attribute> {
+        let kind = match ty {
+            RamenType::Bool => "zeroext",
+            RamenType::Integer(width) if *width < 32 => "signext",
+            _ => return None
+        };
+        Some(self.context.create_enum_attribute(Attribute::get_named_enum_kind_id(kind), 0))
+    }
+
+    /// Applies C default argument promotions to value passed in place of `...`.
+    fn build_vararg_promotion(&self, value: BasicValueEnum<'ctx>, ty: &RamenType) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let int_type = self.context.i32_type();
+        Ok(match ty {
+            RamenType::Bool => self.builder.build_int_z_extend(value.into_int_value(), int_type, "")?.into(),
+            RamenType::Integer(width) if *width < 32 => self.builder.build_int_s_extend(value.into_int_value(), int_type, "")?.into(),
+            RamenType::Float(32) => self.builder.build_float_ext(value.into_float_value(), self.context.f64_type(), "")?.into(),
+            _ => value
+        })
+    }
+
+    /// Function the path refers to, trait methods are resolved to the implementation for their `Self` type.
+    fn get_path_function(&self, def_id: DefId, path_id: NodeId) -> Result<FunctionValue<'ctx>, CodegenError> {
+        let type_arguments = self.get_type_arguments(path_id);
+        match self.session.get_def(def_id) {
+            Some(Definition::TraitMethod { parent }) => {
+                let implementation = self.session.find_impl(parent, &type_arguments[0])
+                    .expect("Implementation should have been checked by frontend.");
+                self.get_or_declare_function(implementation.methods[&def_id], &type_arguments[1..])
+            }
+            _ => self.get_or_declare_function(def_id, &type_arguments)
+        }
+    }
+
+    /// Reinterprets value as type `ty` by storing it to memory and loading it back,
+    /// which is how tuples are moved to and from registers C returns them in.
+    /// Both are at most two eightbytes, see `c_return_type`, so a slot of two `i64` fits either of them.
+    fn build_memory_cast(&self, value: BasicValueEnum<'ctx>, ty: BasicTypeEnum<'ctx>) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        if value.get_type() == ty {
+            return Ok(value);
+        }
+        let slot = self.build_entry_alloca(self.context.i64_type().array_type(2).into(), "")?;
+        self.builder.build_store(slot, value)?;
+        Ok(self.builder.build_load(ty, slot, "")?)
+    }
+
+    fn sret_attribute(&self, ty: &RamenType) -> Result<Attribute, CodegenError> {
+        Ok(self.context.create_type_attribute(
+            Attribute::get_named_enum_kind_id("sret"),
+            self.basic_type(ty)?.as_any_type_enum()
+        ))
+    }
+
+    /// Generates body of the function, or of its instance when type arguments are given.
+    fn build_function(&mut self, id: NodeId, function: &ast::Function, type_arguments: &[RamenType]) -> Result<(), CodegenError> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find function definition reference.");
+        let ll_function = self.get_or_declare_function(def_id, type_arguments)?;
+        let symbol = ll_function.get_name().to_string_lossy().into_owned();
+        // Functions nested in generic function are visited again with every instance of their parent,
+        // any other function with the same symbol would silently reuse the body of the first one.
+        if ll_function.count_basic_blocks() > 0 {
+            return match self.defined_functions.get(&symbol) {
+                Some(&defined) if defined == def_id => Ok(()),
+                _ => Err(CodegenError::SymbolRedefined { symbol })
+            };
+        }
+        self.defined_functions.insert(symbol, def_id);
+
+        // Nested functions are generated in the middle of their parent.
+        let previous_block = self.builder.get_insert_block();
+        let basic_block = self.context.append_basic_block(ll_function, "entry");
+        self.builder.position_at_end(basic_block);
+
+        let Some(RamenType::Callable(callable)) = self.get_type(def_id) else {
+            unreachable!("Function type should have been resolved by frontend.")
+        };
+        let previous_return_slot = self.return_slot.take();
+        let mut ll_parameters = ll_function.get_param_iter();
+        if is_returned_indirectly(&callable.return_type) {
+            self.return_slot = ll_parameters.next().map(|slot| slot.into_pointer_value());
+        }
+
+        for (parameter, value) in function.parameters.iter().zip(ll_parameters) {
+            let slot = self.build_entry_alloca(value.get_type(), pattern_name(&parameter.parameter.pattern))?;
+            self.builder.build_store(slot, value)?;
+
+            let parameter_type = self.get_type(parameter.parameter.ty.id).expect("Parameter type should have been resolved by frontend.");
+            self.bind_pattern(&parameter.parameter.pattern, slot, &parameter_type)?;
+        }
+
+        walk_function(self, id, function)?;
+        self.return_slot = previous_return_slot;
+
+        let is_terminated = self.builder.get_insert_block()
+            .and_then(|block| block.get_terminator())
+            .is_some();
+        if !is_terminated && ll_function.get_type().get_return_type().is_none() {
+            self.builder.build_return(None)?;
+        }
+
+        match previous_block {
+            Some(block) => self.builder.position_at_end(block),
+            None => self.builder.clear_insertion_position()
+        }
+        Ok(())
+    }
+
+    /// Allocas are placed at the start of the entry block so LLVM can promote them to registers.
+    fn build_entry_alloca(&self, ty: BasicTypeEnum<'ctx>, name: &str) -> Result<PointerValue<'ctx>, CodegenError> {
+        let function = self.builder.get_insert_block()
+            .and_then(|block| block.get_parent())
+            .expect("Allocas can be only built inside of a function.");
+        let entry = function.get_first_basic_block().expect("Function should have an entry block.");
+
+        let entry_builder = self.context.create_builder();
+        match entry.get_first_instruction() {
+            Some(instruction) => entry_builder.position_before(&instruction),
+            None => entry_builder.position_at_end(entry)
+        }
+        Ok(entry_builder.build_alloca(ty, name)?)
+    }
+
+    fn build_expression(&mut self, expression: &ast::Expression) -> Result<Option<BasicValueEnum<'ctx>>, CodegenError> {
+        let value = match self.visit_expression(expression)? {
+            Some(value) => {
+                let ty = value.get_type();
+                Some(value.try_into().map_err(|_| CodegenError::NotBasicType(ty.print_to_string().to_string()))?)
+            }
+            None => None
+        };
+
+        match (value, self.session.get_coercion(expression.id)) {
+            (Some(value), Some(target_type)) => {
+                let source_type = self.get_type(expression.id).expect("Expression type should have been resolved by frontend.");
+                Ok(Some(self.build_trait_object(value, &source_type, &target_type.substitute(&self.type_arguments))?))
+            }
+            (value, _) => Ok(value)
+        }
+    }
+
+    /// Turns pointer to value of type implementing the trait into pointer to trait object.
+    fn build_trait_object(&mut self, pointer: BasicValueEnum<'ctx>, source_type: &RamenType, target_type: &RamenType) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let (Some(pointee), Some(RamenType::Dyn { trait_id, .. })) = (source_type.pointee(), target_type.pointee()) else {
+            unreachable!("Only pointers can be converted to trait objects.")
+        };
+        // Type parameter of generic function can be instantiated with the trait object itself.
+        if let RamenType::Dyn { .. } = pointee { return Ok(pointer); }
+
+        let vtable = self.get_or_build_vtable(pointee, *trait_id)?;
+        let object = self.basic_type(target_type)?.into_struct_type().get_undef();
+        let object = self.builder.build_insert_value(object, pointer, 0, "")?;
+        Ok(self.builder.build_insert_value(object, vtable, 1, "")?.as_basic_value_enum())
+    }
+
+    /// Vtable of type's implementation of the trait, holding pointers to its methods in the order trait declares them.
+    /// Every pair of type and trait has a single vtable shared by all of its trait objects.
+    fn get_or_build_vtable(&self, ty: &RamenType, trait_id: DefId) -> Result<PointerValue<'ctx>, CodegenError> {
+        let implementation = self.session.find_impl(trait_id, ty)
+            .expect("Implementation should have been checked by frontend.");
+        let symbol = format!("{}.vtable", self.session.get_symbol(implementation.def_id).expect("Impl symbol should have been set by frontend."));
+        if let Some(vtable) = self.module.get_global(&symbol) {
+            return Ok(vtable.as_pointer_value());
+        }
+
+        let trait_definition = self.session.get_trait(trait_id).expect("Cannot find trait definition.");
+        let methods = trait_definition.methods.iter()
+            .map(|(_, method)| Ok(self.get_or_declare_function(implementation.methods[method], &[])?.as_global_value().as_pointer_value()))
+            .collect::<Result<Vec<_>, CodegenError>>()?;
+
+        let ptr_type = self.context.ptr_type(AddressSpace::default());
+        let vtable = self.module.add_global(ptr_type.array_type(methods.len() as _), None, &symbol);
+        vtable.set_initializer(&ptr_type.const_array(&methods));
+        vtable.set_constant(true);
+        vtable.set_linkage(Linkage::Private);
+        Ok(vtable.as_pointer_value())
+    }
+
+    /// Calls method of trait object through its vtable, passing just the data pointer in place of the object.
+    fn build_dynamic_call(&mut self, id: NodeId, method: DefId, trait_id: DefId, mut argument_values: Vec<BasicValueEnum<'ctx>>) -> Result<VisitorReturn<'ctx>, CodegenError> {
+        let index = self.session.get_trait(trait_id).expect("Cannot find trait definition.").methods.iter()
+            .position(|(_, trait_method)| *trait_method == method)
+            .expect("Method should be declared by its trait.");
+
+        let object = argument_values[0].into_struct_value();
+        argument_values[0] = self.builder.build_extract_value(object, 0, "data")?;
+        let vtable = self.builder.build_extract_value(object, 1, "vtable")?.into_pointer_value();
+
+        let ptr_type = self.context.ptr_type(AddressSpace::default());
+        let slot = unsafe { self.builder.build_in_bounds_gep(ptr_type, vtable, &[self.context.i64_type().const_int(index as _, false)], "")? };
+        let function_pointer = self.builder.build_load(ptr_type, slot, "")?.into_pointer_value();
+
+        // Object safety guarantees `Self` only appears behind the first pointer, so any type can stand in for it.
+        let Some(RamenType::Callable(generic)) = self.session.get_type(method) else {
+            unreachable!("Trait method type should have been resolved by frontend.")
+        };
+        let function_type = generic.instantiate(&[RamenType::Unit]).as_llvm_type(self.context)?.into_function_type();
+        self.build_call_with_return(id, argument_values, |builder, arguments| builder.build_indirect_call(function_type, function_pointer, arguments, ""))
+    }
+
+    /// Calls method with the receiver as its first argument, taking its address or dereferencing it if the method expects so.
+    fn build_method_call(&mut self, id: NodeId, callee: &ast::Expression, method: DefId, arguments: &[ast::Expression]) -> Result<VisitorReturn<'ctx>, CodegenError> {
+        let Some(RamenType::Callable(callable)) = self.get_type(callee.id) else {
+            unreachable!("Method type should have been resolved by frontend.")
+        };
+        let parameter_type = &callable.parameter_types[0];
+
+        let (receiver_ptr, receiver_type) = match &callee.kind {
+            ast::ExpressionKind::Field(base, _) => {
+                let receiver_type = self.get_type(base.id).expect("Receiver type should have been resolved by frontend.");
+                (self.build_place(base)?, receiver_type)
+            }
+            ast::ExpressionKind::Path(path) => {
+                let def_id = self.session.get_ref_target(callee.id).expect("Path should have been resolved by frontend.");
+                let fields = self.session.get_path_fields(callee.id);
+                let receiver_fields = &path.segments[path.segments.len() - fields..path.segments.len() - 1];
+                self.build_variable_place(def_id, receiver_fields)?.expect("Receiver variable should have its slot.")
+            }
+            _ => unreachable!("Only fields and paths can be called with method syntax.")
+        };
+
+        let receiver_value = if receiver_type.coerces_to(parameter_type) {
+            self.builder.build_load(self.basic_type(&receiver_type)?, receiver_ptr, "")?
+        } else if receiver_type.pointee() == Some(parameter_type) {
+            let pointer = self.builder.build_load(self.context.ptr_type(AddressSpace::default()), receiver_ptr, "")?.into_pointer_value();
+            self.build_load_aligned(parameter_type, pointer)?
+        } else {
+            receiver_ptr.as_basic_value_enum()
+        };
+
+        let mut argument_values = vec![receiver_value];
+        for argument in arguments.iter() {
+            argument_values.push(self.build_expression(argument)?.expect("Unit values cannot be passed as arguments."));
+        }
+
+        let type_arguments = self.get_type_arguments(callee.id);
+        if let (Some(Definition::TraitMethod { parent }), Some(RamenType::Dyn { .. })) = (self.session.get_def(method), type_arguments.first()) {
+            return self.build_dynamic_call(id, method, parent, argument_values);
+        }
+        let function = self.get_or_declare_function(method, &type_arguments)?;
+        self.build_call_with_return(id, argument_values, |builder, arguments| builder.build_call(function, arguments, ""))
+    }
+
+    /// Pairs code of function value with its environment.
+    fn build_function_value(&self, code: FunctionValue<'ctx>, environment: PointerValue<'ctx>) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let ptr_type = self.context.ptr_type(AddressSpace::default());
+        let value = self.context.struct_type(&[ptr_type.into(), ptr_type.into()], false).get_undef();
+        let value = self.builder.build_insert_value(value, code.as_global_value().as_pointer_value(), 0, "")?;
+        Ok(self.builder.build_insert_value(value, environment, 1, "")?.as_basic_value_enum())
+    }
+
+    /// Function values expect pointer to environment first, so functions used as values are called through trampoline ignoring it.
+    fn get_or_build_trampoline(&self, function: FunctionValue<'ctx>, callable: &CallableType) -> Result<FunctionValue<'ctx>, CodegenError> {
+        let symbol = format!("{}.trampoline", function.get_name().to_str().expect("Function symbol should be valid UTF-8."));
+        if let Some(trampoline) = self.module.get_function(&symbol) {
+            return Ok(trampoline);
+        }
+
+        let returns_indirectly = is_returned_indirectly(&callable.return_type);
+        let function_type = closure_function_type(callable).as_llvm_type(self.context)?.into_function_type();
+        let trampoline = self.module.add_function(&symbol, function_type, Some(Linkage::Private));
+        if returns_indirectly {
+            trampoline.add_attribute(AttributeLoc::Param(0), self.sret_attribute(&callable.return_type)?);
+        }
+
+        let previous_block = self.builder.get_insert_block();
+        self.builder.position_at_end(self.context.append_basic_block(trampoline, "entry"));
+
+        // Return slot stays in front, only the environment is dropped.
+        let mut arguments = trampoline.get_params().into_iter().map(BasicMetadataValueEnum::from).collect::<Vec<_>>();
+        arguments.remove(returns_indirectly as usize);
+        let call = self.builder.build_call(function, &arguments, "")?;
+        if returns_indirectly {
+            call.add_attribute(AttributeLoc::Param(0), self.sret_attribute(&callable.return_type)?);
+        }
+        match call.try_as_basic_value().left() {
+            Some(value) => {
+                self.builder.build_return(Some(&self.build_memory_cast(value, self.basic_type(&callable.return_type)?)?))?
+            }
+            None => self.builder.build_return(None)?
+        };
+
+        if let Some(block) = previous_block {
+            self.builder.position_at_end(block);
+        }
+        Ok(trampoline)
+    }
+
+    /// Calls function value through its code pointer, passing its environment before the arguments.
+    fn build_indirect_call(&mut self, id: NodeId, callee: &ast::Expression, mut argument_values: Vec<BasicValueEnum<'ctx>>) -> Result<VisitorReturn<'ctx>, CodegenError> {
+        let Some(RamenType::Callable(callable)) = self.get_type(callee.id) else {
+            unreachable!("Callee should have been checked to be callable by frontend.")
+        };
+        let value = self.build_expression(callee)?.expect("Function value should not be a unit value.").into_struct_value();
+        let code = self.builder.build_extract_value(value, 0, "code")?.into_pointer_value();
+        argument_values.insert(0, self.builder.build_extract_value(value, 1, "environment")?);
+
+        let function_type = closure_function_type(&callable).as_llvm_type(self.context)?.into_function_type();
+        self.build_call_with_return(id, argument_values, |builder, arguments| builder.build_indirect_call(function_type, code, arguments, ""))
+    }
+
+    /// Builds the call, passing hidden return slot first if the result of call `id` is returned indirectly.
+    fn build_call_with_return(
+        &mut self,
+        id: NodeId,
+        argument_values: Vec<BasicValueEnum<'ctx>>,
+        call: impl FnOnce(&Builder<'ctx>, &[BasicMetadataValueEnum<'ctx>]) -> Result<CallSiteValue<'ctx>, BuilderError>
+    ) -> Result<VisitorReturn<'ctx>, CodegenError> {
+        let mut arguments = argument_values.into_iter().map(BasicMetadataValueEnum::from).collect::<Vec<_>>();
+
+        let return_type = self.get_type(id).expect("Call type should have been resolved by frontend.");
+        if is_returned_indirectly(&return_type) {
+            let return_slot = self.build_entry_alloca(self.basic_type(&return_type)?, "")?;
+            arguments.insert(0, return_slot.into());
+            let call = call(&self.builder, &arguments)?;
+            call.add_attribute(AttributeLoc::Param(0), self.sret_attribute(&return_type)?);
+            return Ok(Some(self.builder.build_load(self.basic_type(&return_type)?, return_slot, "")?.as_any_value_enum()));
+        }
+
+        let call = call(&self.builder, &arguments)?;
+        match call.try_as_basic_value().left() {
+            Some(value) => Ok(Some(self.build_memory_cast(value, self.basic_type(&return_type)?)?.as_any_value_enum())),
+            None => Ok(None)
+        }
+    }
+
+    fn current_function(&self) -> FunctionValue<'ctx> {
+        self.builder.get_insert_block()
+            .and_then(|block| block.get_parent())
+            .expect("Builder should be positioned inside of a function.")
+    }
+
+    /// Pointer to a field of variant payload stored inside of the enum at `enum_ptr`.
+    fn build_variant_field_gep(
+        &self,
+        enum_type: &EnumType,
+        enum_ptr: PointerValue<'ctx>,
+        index: usize,
+        field_index: usize
+    ) -> Result<PointerValue<'ctx>, CodegenError> {
+        let ll_enum_type = enum_type.as_llvm_type(self.context)?.into_struct_type();
+        let payload_ptr = self.builder.build_struct_gep(ll_enum_type, enum_ptr, 1, "payload")?;
+        let payload_type = enum_type.variant_payload_type(self.context, index)?;
+        Ok(self.builder.build_struct_gep(payload_type, payload_ptr, field_index as _, "")?)
+    }
+
+    fn build_load_aligned(&self, ty: &RamenType, ptr: PointerValue<'ctx>) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let value = self.builder.build_load(self.basic_type(ty)?, ptr, "")?;
+        let (_, align) = size_and_align(ty);
+        value.as_instruction_value().expect("Load should be an instruction.")
+            .set_alignment(align as _).map_err(|reason| CodegenError::InvalidAlignment { align: align as _, reason: reason.to_string() })?;
+        Ok(value)
+    }
+
+    fn build_enum_tag(&self, enum_type: &EnumType, enum_ptr: PointerValue<'ctx>) -> Result<IntValue<'ctx>, CodegenError> {
+        let ll_enum_type = enum_type.as_llvm_type(self.context)?.into_struct_type();
+        let tag_ptr = self.builder.build_struct_gep(ll_enum_type, enum_ptr, 0, "tag")?;
+        let tag_type = self.context.custom_width_int_type(enum_type.tag_width());
+        Ok(self.builder.build_load(tag_type, tag_ptr, "tag")?.into_int_value())
+    }
+
+    /// Returns `None` for patterns that cannot be a single `switch` case.
+    fn switch_case(&self, pattern: &ast::Pattern) -> Option<SwitchCase> {
+        match &pattern.kind {
+            ast::PatternKind::Wildcard | ast::PatternKind::Binding(_) => Some(SwitchCase::Default),
+            ast::PatternKind::Integer(value) => Some(SwitchCase::Values(vec![*value])),
+            ast::PatternKind::Bool(value) => Some(SwitchCase::Values(vec![*value as i128])),
+            ast::PatternKind::Variant(_, fields) => {
+                let irrefutable = fields.iter().all(|field| matches!(field.kind, ast::PatternKind::Wildcard | ast::PatternKind::Binding(_)));
+                let def_id = self.session.get_ref_target(pattern.id)?;
+                match self.session.get_def(def_id) {
+                    Some(Definition::Variant { index, .. }) if irrefutable => Some(SwitchCase::Values(vec![index as i128])),
+                    _ => None
+                }
+            }
+            ast::PatternKind::Or(alternatives) => {
+                let mut values = Vec::new();
+                for alternative in alternatives.iter() {
+                    match self.switch_case(alternative)? {
+                        SwitchCase::Values(alternative_values) => values.extend(alternative_values),
+                        SwitchCase::Default => return Some(SwitchCase::Default)
+                    }
+                }
+                Some(SwitchCase::Values(values))
+            }
+            ast::PatternKind::Range { .. } | ast::PatternKind::Tuple(_) => None
+        }
+    }
+
+    /// Builds condition which is true when value at `ptr` matches the pattern, `None` means it always matches.
+    fn build_pattern_test(&self, pattern: &ast::Pattern, ptr: PointerValue<'ctx>, ty: &RamenType) -> Result<Option<IntValue<'ctx>>, CodegenError> {
+        Ok(match &pattern.kind {
+            ast::PatternKind::Wildcard | ast::PatternKind::Binding(_) => None,
+            ast::PatternKind::Integer(value) => {
+                let int_type = self.basic_type(ty)?.into_int_type();
+                let scrutinee = self.build_load_aligned(ty, ptr)?.into_int_value();
+                Some(self.builder.build_int_compare(IntPredicate::EQ, scrutinee, const_int_i128(int_type, *value), "")?)
+            }
+            ast::PatternKind::Bool(value) => {
+                let scrutinee = self.build_load_aligned(ty, ptr)?.into_int_value();
+                Some(self.builder.build_int_compare(IntPredicate::EQ, scrutinee, self.context.bool_type().const_int(*value as _, false), "")?)
+            }
+            ast::PatternKind::Range { start, end, inclusive } => {
+                let int_type = self.basic_type(ty)?.into_int_type();
+                let scrutinee = self.build_load_aligned(ty, ptr)?.into_int_value();
+                let above = self.builder.build_int_compare(IntPredicate::SGE, scrutinee, const_int_i128(int_type, *start), "")?;
+                let end_predicate = if *inclusive { IntPredicate::SLE } else { IntPredicate::SLT };
+                let below = self.builder.build_int_compare(end_predicate, scrutinee, const_int_i128(int_type, *end), "")?;
+                Some(self.builder.build_and(above, below, "")?)
+            }
+            ast::PatternKind::Variant(_, fields) => {
+                let RamenType::Enum(enum_type) = ty else { unreachable!("Variant pattern should match an enum.") };
+                let def_id = self.session.get_ref_target(pattern.id).expect("Variant pattern should have been resolved by frontend.");
+                let Some(Definition::Variant { index, .. }) = self.session.get_def(def_id) else {
+                    unreachable!("Variant pattern should refer to a variant.")
+                };
+
+                let tag = self.build_enum_tag(enum_type, ptr)?;
+                let mut condition = self.builder.build_int_compare(IntPredicate::EQ, tag, tag.get_type().const_int(index as _, false), "")?;
+                for (field_index, (field, field_type)) in fields.iter().zip(enum_type.variants[index].fields.iter()).enumerate() {
+                    let field_ptr = self.build_variant_field_gep(enum_type, ptr, index, field_index)?;
+                    if let Some(field_condition) = self.build_pattern_test(field, field_ptr, field_type)? {
+                        condition = self.builder.build_and(condition, field_condition, "")?;
+                    }
+                }
+                Some(condition)
+            }
+            ast::PatternKind::Tuple(elements) => {
+                let RamenType::Tuple(element_types) = ty else { unreachable!("Tuple pattern should match a tuple.") };
+                let tuple_type = self.basic_type(ty)?.into_struct_type();
+
+                let mut condition = None;
+                for (index, (element, element_type)) in elements.iter().zip(element_types.iter()).enumerate() {
+                    let element_ptr = self.builder.build_struct_gep(tuple_type, ptr, index as _, "")?;
+                    let Some(element_condition) = self.build_pattern_test(element, element_ptr, element_type)? else { continue };
+                    condition = Some(match condition {
+                        Some(condition) => self.builder.build_and(condition, element_condition, "")?,
+                        None => element_condition
+                    });
+                }
+                condition
+            }
+            ast::PatternKind::Or(alternatives) => {
+                let mut condition = None;
+                for alternative in alternatives.iter() {
+                    let Some(alternative_condition) = self.build_pattern_test(alternative, ptr, ty)? else { return Ok(None) };
+                    condition = Some(match condition {
+                        Some(condition) => self.builder.build_or(condition, alternative_condition, "")?,
+                        None => alternative_condition
+                    });
+                }
+                condition
+            }
+        })
+    }
+
+    /// Bindings refer directly to the matched value, so no copy is needed.
+    fn bind_pattern(&mut self, pattern: &ast::Pattern, ptr: PointerValue<'ctx>, ty: &RamenType) -> Result<(), CodegenError> {
+        match &pattern.kind {
+            ast::PatternKind::Binding(_) => {
+                let def_id = self.session.get_ref_target(pattern.id).expect("Cannot find binding definition reference.");
+                self.locals.insert(def_id, ptr);
+            }
+            ast::PatternKind::Variant(_, fields) => {
+                let RamenType::Enum(enum_type) = ty else { unreachable!("Variant pattern should match an enum.") };
+                let def_id = self.session.get_ref_target(pattern.id).expect("Variant pattern should have been resolved by frontend.");
+                let Some(Definition::Variant { index, .. }) = self.session.get_def(def_id) else {
+                    unreachable!("Variant pattern should refer to a variant.")
+                };
+                for (field_index, (field, field_type)) in fields.iter().zip(enum_type.variants[index].fields.iter()).enumerate() {
+                    let field_ptr = self.build_variant_field_gep(enum_type, ptr, index, field_index)?;
+                    self.bind_pattern(field, field_ptr, field_type)?;
+                }
+            }
+            ast::PatternKind::Tuple(elements) => {
+                let RamenType::Tuple(element_types) = ty else { unreachable!("Tuple pattern should match a tuple.") };
+                let tuple_type = self.basic_type(ty)?.into_struct_type();
+                for (index, (element, element_type)) in elements.iter().zip(element_types.iter()).enumerate() {
+                    let element_ptr = self.builder.build_struct_gep(tuple_type, ptr, index as _, "")?;
+                    self.bind_pattern(element, element_ptr, element_type)?;
+                }
+            }
+            // Or-patterns cannot contain bindings.
+            _ => ()
+        }
+        Ok(())
+    }
+
+    /// Builds body of the arm and stores its value into the result slot.
+    fn build_match_arm(
+        &mut self,
+        arm: &ast::MatchArm,
+        scrutinee_ptr: Option<PointerValue<'ctx>>,
+        scrutinee_type: &RamenType,
+        result_slot: Option<PointerValue<'ctx>>,
+        end_block: BasicBlock<'ctx>
+    ) -> Result<(), CodegenError> {
+        if let Some(scrutinee_ptr) = scrutinee_ptr {
+            self.bind_pattern(&arm.pattern, scrutinee_ptr, scrutinee_type)?;
+        }
+        let value = self.build_expression(&arm.body)?;
+        if let (Some(result_slot), Some(value)) = (result_slot, value) {
+            self.builder.build_store(result_slot, value)?;
+        }
+        self.builder.build_unconditional_branch(end_block)?;
+        Ok(())
+    }
+
+    /// Pointer to memory holding value of the expression.
+    /// Variables and their elements are accessed in place, anything else is stored in a temporary slot.
+    fn build_place(&mut self, expression: &ast::Expression) -> Result<PointerValue<'ctx>, CodegenError> {
+        match &expression.kind {
+            ast::ExpressionKind::Path(path) => {
+                if let Some(ptr) = self.build_path_place(expression.id, path)? {
+                    return Ok(ptr);
+                }
+            }
+            ast::ExpressionKind::Field(base, name) => {
+                let base_type = self.get_type(base.id).expect("Accessed expression type should have been resolved by frontend.");
+                let base_ptr = self.build_place(base)?;
+                return Ok(self.build_field_ptr(base_ptr, &base_type, name)?.0);
+            }
+            ast::ExpressionKind::TupleIndex(tuple, index) => {
+                let tuple_type = self.basic_type(&self.get_type(tuple.id).expect("Tuple type should have been resolved by frontend."))?;
+                let tuple_ptr = self.build_place(tuple)?;
+                return Ok(self.builder.build_struct_gep(tuple_type, tuple_ptr, *index as _, "")?);
+            }
+            ast::ExpressionKind::Index(base, index) => return self.build_element_ptr(base, index),
+            ast::ExpressionKind::Deref(pointer) => {
+                return Ok(self.build_expression(pointer)?.expect("Pointer should not be a unit value.").into_pointer_value());
+            }
+            _ => ()
+        }
+
+        let value = self.build_expression(expression)?.expect("Unit values have no place in memory.");
+        let slot = self.build_entry_alloca(value.get_type(), "")?;
+        self.builder.build_store(slot, value)?;
+        Ok(slot)
+    }
+
+    /// Pointer to the variable the path refers to or to its field, `None` for paths to anything else.
+    fn build_path_place(&mut self, path_id: NodeId, path: &ast::Path) -> Result<Option<PointerValue<'ctx>>, CodegenError> {
+        let def_id = self.session.get_ref_target(path_id).expect("Path should have been resolved by frontend.");
+        let fields = self.session.get_path_fields(path_id);
+        Ok(self.build_variable_place(def_id, &path.segments[path.segments.len() - fields..])?.map(|(ptr, _)| ptr))
+    }
+
+    /// Pointer to the variable or to its field reached through `fields` and type of the place, `None` if `def_id` is not a variable.
+    /// Statics live in their globals, constants are copied into a temporary slot.
+    fn build_variable_place(&mut self, def_id: DefId, fields: &[ast::PathSegment]) -> Result<Option<(PointerValue<'ctx>, RamenType)>, CodegenError> {
+        let slot = match (self.session.get_def(def_id), self.locals.get(&def_id)) {
+            (Some(Definition::Variable), Some(slot)) => *slot,
+            (Some(Definition::Static { .. }), _) => self.get_or_declare_static(def_id)?,
+            (Some(Definition::Const), _) => {
+                let ty = self.get_type(def_id).expect("Constant type should have been resolved by frontend.");
+                let value = self.session.get_constant(def_id).expect("Constant should have been evaluated by frontend.");
+                let value = self.build_constant_value(&value, &ty)?.expect("Unit values have no place in memory.");
+                let slot = self.build_entry_alloca(value.get_type(), "")?;
+                self.builder.build_store(slot, value)?;
+                slot
+            }
+            _ => return Ok(None)
+        };
+
+        let mut ptr = slot;
+        let mut ty = self.get_type(def_id).expect("Variable type should have been resolved by frontend.");
+        for field in fields.iter() {
+            (ptr, ty) = self.build_field_ptr(ptr, &ty, &field.name)?;
+        }
+        Ok(Some((ptr, ty)))
+    }
+
+    /// Pointer to field of struct stored at `base_ptr` and type of the field.
+    /// If the base is a pointer to struct instead, the field is accessed through it.
+    fn build_field_ptr(&mut self, base_ptr: PointerValue<'ctx>, base_type: &RamenType, name: &str) -> Result<(PointerValue<'ctx>, RamenType), CodegenError> {
+        let (struct_ptr, struct_type) = match base_type {
+            RamenType::Reference { target, .. } | RamenType::Pointer { target, .. } => {
+                let struct_ptr = self.builder.build_load(self.context.ptr_type(AddressSpace::default()), base_ptr, "")?;
+                (struct_ptr.into_pointer_value(), self.session.complete_type(target))
+            }
+            _ => (base_ptr, base_type.clone())
+        };
+        let RamenType::Struct(struct_type_info) = &struct_type else { unreachable!("Only structs have named fields.") };
+
+        let index = struct_type_info.field_index(name).expect("Field should have been checked by frontend.");
+        let field_ptr = self.builder.build_struct_gep(self.basic_type(&struct_type)?, struct_ptr, index as _, name)?;
+        Ok((field_ptr, struct_type_info.fields[index].ty.clone()))
+    }
+
+    /// Returns pointer to the first element and number of elements of an array or slice.
+    fn build_elements(&mut self, base: &ast::Expression) -> Result<(PointerValue<'ctx>, IntValue<'ctx>), CodegenError> {
+        let base_type = self.get_type(base.id).expect("Indexed expression type should have been resolved by frontend.");
+        match &base_type {
+            RamenType::Array(_, length) => {
+                let array_type = self.basic_type(&base_type)?;
+                let array_ptr = self.build_place(base)?;
+                let zero = self.context.i64_type().const_zero();
+                let data = unsafe { self.builder.build_in_bounds_gep(array_type, array_ptr, &[zero, zero], "")? };
+                Ok((data, self.context.i64_type().const_int(*length as _, false)))
+            }
+            RamenType::Slice(_) => {
+                let slice = self.build_expression(base)?.expect("Slice should not be a unit value.").into_struct_value();
+                let data = self.builder.build_extract_value(slice, 0, "data")?.into_pointer_value();
+                let length = self.builder.build_extract_value(slice, 1, "length")?.into_int_value();
+                Ok((data, length))
+            }
+            _ => unreachable!("Only arrays and slices can be indexed.")
+        }
+    }
+
+    /// Indices of any width are converted to 64 bits, negative ones end up out of bounds.
+    /// Index sign extended to at least 64 bits. Wider indices keep their width, so that bounds checks see all of their bits,
+    /// and are narrowed by `narrow_index` only once they are checked.
+    fn build_index(&mut self, index: &ast::Expression) -> Result<IntValue<'ctx>, CodegenError> {
+        let index = self.build_expression(index)?.expect("Index should not be a unit value.").into_int_value();
+        if index.get_type().get_bit_width() >= 64 { return Ok(index); }
+        Ok(self.builder.build_int_s_extend(index, self.context.i64_type(), "")?)
+    }
+
+    /// Index which passed its bounds check, so it fits into 64 bits.
+    fn narrow_index(&mut self, index: IntValue<'ctx>) -> Result<IntValue<'ctx>, CodegenError> {
+        Ok(self.builder.build_int_cast_sign_flag(index, self.context.i64_type(), false, "")?)
+    }
+
+    fn build_element_ptr(&mut self, base: &ast::Expression, index: &ast::Expression) -> Result<PointerValue<'ctx>, CodegenError> {
+        let (data, length) = self.build_elements(base)?;
+        let index_value = self.build_index(index)?;
+        self.build_bounds_check(IntPredicate::UGE, index_value, length, &index.location)?;
+        let index_value = self.narrow_index(index_value)?;
+
+        let (RamenType::Array(element_type, _) | RamenType::Slice(element_type)) = self.get_type(base.id).unwrap() else {
+            unreachable!("Only arrays and slices can be indexed.")
+        };
+        let element_type = self.basic_type(&element_type)?;
+        Ok(unsafe { self.builder.build_in_bounds_gep(element_type, data, &[index_value], "")? })
+    }
+
+    /// Right operand of `&&` and `||` is only evaluated when the left one does not decide the result.
+    fn build_logical_expression(&mut self, operator: ast::BinaryOperator, left: &ast::Expression, right: &ast::Expression) -> Result<VisitorReturn<'ctx>, CodegenError> {
+        let left_value = self.build_expression(left)?.expect("Operand should not be a unit value.").into_int_value();
+        let left_block = self.builder.get_insert_block().unwrap();
+
+        let function = self.current_function();
+        let right_block = self.context.append_basic_block(function, "logical.right");
+        let end_block = self.context.append_basic_block(function, "logical.end");
+        match operator {
+            ast::BinaryOperator::And => self.builder.build_conditional_branch(left_value, right_block, end_block)?,
+            _ => self.builder.build_conditional_branch(left_value, end_block, right_block)?
+        };
+
+        self.builder.position_at_end(right_block);
+        let right_value = self.build_expression(right)?.expect("Operand should not be a unit value.").into_int_value();
+        let right_block = self.builder.get_insert_block().unwrap();
+        self.builder.build_unconditional_branch(end_block)?;
+
+        // Skipping the right operand means the result is the left one.
+        self.builder.position_at_end(end_block);
+        let result = self.builder.build_phi(self.context.bool_type(), "")?;
+        result.add_incoming(&[(&left_value, left_block), (&right_value, right_block)]);
+        Ok(Some(result.as_basic_value().as_any_value_enum()))
+    }
+
+    /// Calls the panic hook when `value <predicate> limit` holds, unless bounds checks are disabled.
+    fn build_bounds_check(&mut self, predicate: IntPredicate, value: IntValue<'ctx>, limit: IntValue<'ctx>, location: &Loc) -> Result<(), CodegenError> {
+        if !self.bounds_checks { return Ok(()); }
+
+        let function = self.current_function();
+        let failure_block = self.context.append_basic_block(function, "bounds.failure");
+        let success_block = self.context.append_basic_block(function, "bounds.ok");
+        // Both sides are compared at the wider of their widths, as narrowing an index first could bring it into bounds.
+        let width = value.get_type().get_bit_width().max(limit.get_type().get_bit_width());
+        let compared_type = self.context.custom_width_int_type(width);
+        let value = self.builder.build_int_cast_sign_flag(value, compared_type, true, "")?;
+        let limit = self.builder.build_int_cast_sign_flag(limit, compared_type, false, "")?;
+        let is_out_of_bounds = self.builder.build_int_compare(predicate, value, limit, "")?;
+        self.builder.build_conditional_branch(is_out_of_bounds, failure_block, success_block)?;
+
+        self.builder.position_at_end(failure_block);
+        let location = self.builder.build_global_string_ptr(&self.session.describe_location(location), "location")?;
+        // Runtime function called with source location, index and length when bounds check fails.
+        let i64_type = self.context.i64_type();
+        let hook = self.get_or_declare_panic_hook("ramen_panic_bounds_check", &[i64_type.into(), i64_type.into()]);
+        let value = self.clamp_to_i64(value)?;
+        let limit = self.clamp_to_i64(limit)?;
+        self.builder.build_call(hook, &[
+            location.as_pointer_value().into(),
+            value.into(),
+            limit.into()
+        ], "")?;
+        self.builder.build_unreachable()?;
+
+        self.builder.position_at_end(success_block);
+        Ok(())
+    }
+
+    /// Unsigned value reported to panic hooks, which take 64 bits, values too large for them are reported as the largest one.
+    fn clamp_to_i64(&mut self, value: IntValue<'ctx>) -> Result<IntValue<'ctx>, CodegenError> {
+        let i64_type = self.context.i64_type();
+        if value.get_type().get_bit_width() <= 64 { return Ok(value); }
+
+        let largest = i64_type.const_all_ones();
+        let is_too_large = self.builder.build_int_compare(IntPredicate::UGT, value, self.builder.build_int_z_extend(largest, value.get_type(), "")?, "")?;
+        let truncated = self.builder.build_int_truncate(value, i64_type, "")?;
+        Ok(self.builder.build_select(is_too_large, largest, truncated, "")?.into_int_value())
+    }
+
+    /// Calls the panic hook when divisor is zero, which LLVM leaves undefined.
+    fn build_division_check(&mut self, divisor: IntValue<'ctx>, location: &Loc) -> Result<(), CodegenError> {
+        let function = self.current_function();
+        let failure_block = self.context.append_basic_block(function, "division.failure");
+        let success_block = self.context.append_basic_block(function, "division.ok");
+        let is_zero = self.builder.build_int_compare(IntPredicate::EQ, divisor, divisor.get_type().const_zero(), "")?;
+        self.builder.build_conditional_branch(is_zero, failure_block, success_block)?;
+
+        self.builder.position_at_end(failure_block);
+        let location = self.builder.build_global_string_ptr(&self.session.describe_location(location), "location")?;
+        // Runtime function called with source location of the divisor.
+        let hook = self.get_or_declare_panic_hook("ramen_panic_division_by_zero", &[]);
+        self.builder.build_call(hook, &[location.as_pointer_value().into()], "")?;
+        self.builder.build_unreachable()?;
+
+        self.builder.position_at_end(success_block);
+        Ok(())
+    }
+
+    /// Signed division or remainder, checked for zero divisor.
+    /// Smallest integer divided by -1 wraps around like other arithmetic, instead of being undefined like in LLVM.
+    fn build_division(&mut self, operator: ast::BinaryOperator, left: IntValue<'ctx>, right: IntValue<'ctx>, location: &Loc) -> Result<IntValue<'ctx>, CodegenError> {
+        self.build_division_check(right, location)?;
+
+        let ty = right.get_type();
+        let is_minus_one = self.builder.build_int_compare(IntPredicate::EQ, right, ty.const_all_ones(), "")?;
+        let divisor = self.builder.build_select(is_minus_one, ty.const_int(1, false), right, "")?.into_int_value();
+        Ok(match operator {
+            ast::BinaryOperator::Divide => {
+                let quotient = self.builder.build_int_signed_div(left, divisor, "")?;
+                let negated = self.builder.build_int_neg(left, "")?;
+                self.builder.build_select(is_minus_one, negated, quotient, "")?.into_int_value()
+            }
+            // Remainder of division by 1 is zero, just like by -1.
+            _ => self.builder.build_int_signed_rem(left, divisor, "")?
+        })
+    }
+
+    /// Runtime function called with source location and further details of a failed check, it must not return.
+    fn get_or_declare_panic_hook(&self, name: &str, details: &[BasicMetadataTypeEnum<'ctx>]) -> FunctionValue<'ctx> {
+        if let Some(hook) = self.module.get_function(name) {
+            return hook;
+        }
+
+        let mut parameter_types = vec![self.context.ptr_type(AddressSpace::default()).into()];
+        parameter_types.extend_from_slice(details);
+        let hook_type = self.context.void_type().fn_type(&parameter_types, false);
+        let hook = self.module.add_function(name, hook_type, None);
+        for attribute in ["noreturn", "cold"] {
+            hook.add_attribute(AttributeLoc::Function, self.context.create_enum_attribute(Attribute::get_named_enum_kind_id(attribute), 0));
+        }
+        hook
+    }
+
+    /// Global holding value of the static, declared on its first use.
+    fn get_or_declare_static(&self, def_id: DefId) -> Result<PointerValue<'ctx>, CodegenError> {
+        let symbol = self.session.get_symbol(def_id).expect("Static should have a symbol.");
+        if let Some(global) = self.module.get_global(&symbol) {
+            return Ok(global.as_pointer_value());
+        }
+
+        let Some(Definition::Static { mutable }) = self.session.get_def(def_id) else { unreachable!("Only statics have globals.") };
+        let ty = self.get_type(def_id).expect("Static type should have been resolved by frontend.");
+        let value = self.session.get_constant(def_id).expect("Static should have been evaluated by frontend.");
+        let initializer = self.build_constant(&value, &ty)?.expect("Statics of unit type have no global.");
+
+        let global = self.module.add_global(initializer.get_type(), None, &symbol);
+        global.set_initializer(&initializer);
+        global.set_constant(!mutable);
+        global.set_linkage(Linkage::Internal);
+        Ok(global.as_pointer_value())
+    }
+
+    /// Constant of value computed by frontend, `None` for unit values.
+    /// Enums with payload have no constant of their own type, so it is loaded from a global with the same layout.
+    fn build_constant_value(&self, value: &ConstValue, ty: &RamenType) -> Result<Option<BasicValueEnum<'ctx>>, CodegenError> {
+        let Some(constant) = self.build_constant(value, ty)? else { return Ok(None) };
+        let basic_type = self.basic_type(ty)?;
+        if constant.get_type() == basic_type {
+            return Ok(Some(constant));
+        }
+
+        let global = self.module.add_global(constant.get_type(), None, "constant");
+        global.set_initializer(&constant);
+        global.set_constant(true);
+        global.set_linkage(Linkage::Private);
+        global.set_unnamed_addr(true);
+        Ok(Some(self.builder.build_load(basic_type, global.as_pointer_value(), "")?))
+    }
+
+    /// Constant with the same layout as `ty`, which is not necessarily of the same LLVM type.
+    fn build_constant(&self, value: &ConstValue, ty: &RamenType) -> Result<Option<BasicValueEnum<'ctx>>, CodegenError> {
+        let constant = match (value, ty) {
+            (ConstValue::Unit, _) => return Ok(None),
+            (ConstValue::Bool(value), _) => self.context.bool_type().const_int(*value as _, false).into(),
+            (ConstValue::Integer(value), _) => const_int_i128(self.basic_type(ty)?.into_int_type(), *value).into(),
+            (ConstValue::Float(value), _) => self.basic_type(ty)?.into_float_type().const_float(*value).into(),
+            (ConstValue::Aggregate(elements), _) => {
+                let element_types = match ty {
+                    RamenType::Tuple(elements) => elements.clone(),
+                    RamenType::Struct(struct_type) => struct_type.fields.iter().map(|field| field.ty.clone()).collect(),
+                    RamenType::Array(element, length) => vec![element.as_ref().clone(); *length],
+                    _ => unreachable!("Only tuples, structs and arrays have aggregate values.")
+                };
+                let mut element_values = Vec::new();
+                for (element, element_type) in elements.iter().zip(element_types.iter()) {
+                    element_values.push(self.build_constant(element, element_type)?.expect("Unit values cannot be stored in aggregates."));
+                }
+
+                let basic_type = self.basic_type(ty)?;
+                match basic_type {
+                    BasicTypeEnum::ArrayType(array_type) if element_values.iter().all(|element| element.get_type() == array_type.get_element_type()) =>
+                        const_array(array_type.get_element_type(), &element_values).into(),
+                    BasicTypeEnum::StructType(struct_type) if element_values.iter().zip(struct_type.get_field_types()).all(|(element, field)| element.get_type() == field) =>
+                        struct_type.const_named_struct(&element_values).into(),
+                    _ => self.context.const_struct(&element_values, false).into()
+                }
+            }
+            (ConstValue::Variant { index, fields }, RamenType::Enum(enum_type)) => {
+                let ll_enum_type = enum_type.as_llvm_type(self.context)?.into_struct_type();
+                let tag = self.context.custom_width_int_type(enum_type.tag_width()).const_int(*index as _, false);
+                let [_, payload_type] = ll_enum_type.get_field_types()[..] else {
+                    return Ok(Some(ll_enum_type.const_named_struct(&[tag.into()]).into()));
+                };
+                if fields.is_empty() {
+                    return Ok(Some(ll_enum_type.const_named_struct(&[tag.into(), payload_type.const_zero()]).into()));
+                }
+
+                let mut field_values = Vec::new();
+                for (field, field_type) in fields.iter().zip(enum_type.variants[*index].fields.iter()) {
+                    field_values.push(self.build_constant(field, field_type)?.expect("Unit values cannot be stored in enum payload."));
+                }
+                let payload = self.context.const_struct(&field_values, false);
+
+                // Payload is placed where the payload array starts, with padding keeping the size of the enum.
+                // Empty array in front gives the struct alignment of the enum.
+                let (size, align) = size_and_align(ty);
+                let tag_size = enum_type.tag_width() as u64 / 8;
+                let payload_offset = tag_size.next_multiple_of(align);
+                let (payload_size, _) = struct_size_and_align(&enum_type.variants[*index].fields);
+                let i8_type = self.context.i8_type();
+                let chunk_type = payload_type.into_array_type().get_element_type();
+                self.context.const_struct(&[
+                    const_array(chunk_type, &[]).into(),
+                    tag.into(),
+                    i8_type.array_type((payload_offset - tag_size) as _).get_undef().into(),
+                    payload.into(),
+                    i8_type.array_type((size - payload_offset - payload_size) as _).get_undef().into()
+                ], false).into()
+            }
+            _ => unreachable!("Constant value should match its type.")
+        };
+        Ok(Some(constant))
+    }
+
+    fn build_enum_value(
+        &mut self,
+        enum_type: &EnumType,
+        index: usize,
+        payload: &[BasicValueEnum<'ctx>]
+    ) -> Result<BasicValueEnum<'ctx>, CodegenError> {
+        let ll_enum_type = enum_type.as_llvm_type(self.context)?.into_struct_type();
+        let enum_ptr = self.build_entry_alloca(ll_enum_type.into(), &enum_type.variants[index].name)?;
+
+        let tag_ptr = self.builder.build_struct_gep(ll_enum_type, enum_ptr, 0, "tag")?;
+        let tag_type = self.context.custom_width_int_type(enum_type.tag_width());
+        self.builder.build_store(tag_ptr, tag_type.const_int(index as _, false))?;
+
+        if !payload.is_empty() {
+            let payload_type = enum_type.variant_payload_type(self.context, index)?;
+            let mut payload_value = payload_type.get_undef();
+            for (field_index, field) in payload.iter().enumerate() {
+                payload_value = self.builder.build_insert_value(payload_value, *field, field_index as _, "")?
+                    .into_struct_value();
+            }
+
+            // Payload array may be less aligned than the variant itself.
+            let (_, payload_align) = size_and_align(&RamenType::Enum(Box::new(enum_type.clone())));
+            let payload_ptr = self.builder.build_struct_gep(ll_enum_type, enum_ptr, 1, "payload")?;
+            self.builder.build_store(payload_ptr, payload_value)?
+                .set_alignment(payload_align as _).map_err(|reason| CodegenError::InvalidAlignment { align: payload_align as _, reason: reason.to_string() })?;
+        }
+
+        Ok(self.builder.build_load(ll_enum_type, enum_ptr, "")?)
+    }
+}
+
+/// How a match arm is lowered when the whole match can become a `switch`.
+enum SwitchCase {
+    Values(Vec<i128>),
+    Default
+}
+
+/// Name for the stack slot holding value matched by the pattern.
+fn pattern_name(pattern: &ast::Pattern) -> &str {
+    match &pattern.kind {
+        ast::PatternKind::Binding(name) => name,
+        _ => ""
+    }
+}
+
+fn const_int_i128(int_type: IntType<'_>, value: i128) -> IntValue<'_> {
+    int_type.const_int_arbitrary_precision(&[value as u64, (value >> 64) as u64])
+}
+
+/// Constant array of elements which all have type `element_type`.
+fn const_array<'ctx>(element_type: BasicTypeEnum<'ctx>, elements: &[BasicValueEnum<'ctx>]) -> ArrayValue<'ctx> {
+    match element_type {
+        BasicTypeEnum::IntType(int_type) => int_type.const_array(&elements.iter().map(|element| element.into_int_value()).collect::<Vec<_>>()),
+        BasicTypeEnum::FloatType(float_type) => float_type.const_array(&elements.iter().map(|element| element.into_float_value()).collect::<Vec<_>>()),
+        BasicTypeEnum::ArrayType(array_type) => array_type.const_array(&elements.iter().map(|element| element.into_array_value()).collect::<Vec<_>>()),
+        BasicTypeEnum::StructType(struct_type) => struct_type.const_array(&elements.iter().map(|element| element.into_struct_value()).collect::<Vec<_>>()),
+        BasicTypeEnum::PointerType(ptr_type) => ptr_type.const_array(&elements.iter().map(|element| element.into_pointer_value()).collect::<Vec<_>>()),
+        BasicTypeEnum::VectorType(vector_type) => vector_type.const_array(&elements.iter().map(|element| element.into_vector_value()).collect::<Vec<_>>())
+    }
+}
+
+fn int_predicate(operator: ast::BinaryOperator) -> IntPredicate {
+    match operator {
+        ast::BinaryOperator::Equal => IntPredicate::EQ,
+        ast::BinaryOperator::NotEqual => IntPredicate::NE,
+        ast::BinaryOperator::Less => IntPredicate::SLT,
+        ast::BinaryOperator::LessEqual => IntPredicate::SLE,
+        ast::BinaryOperator::Greater => IntPredicate::SGT,
+        ast::BinaryOperator::GreaterEqual => IntPredicate::SGE,
+        _ => unreachable!("Operator {operator} does not compare.")
+    }
+}
+
+/// Comparisons are false when either float is NaN, except for `!=`.
+fn float_predicate(operator: ast::BinaryOperator) -> FloatPredicate {
+    match operator {
+        ast::BinaryOperator::Equal => FloatPredicate::OEQ,
+        ast::BinaryOperator::NotEqual => FloatPredicate::UNE,
+        ast::BinaryOperator::Less => FloatPredicate::OLT,
+        ast::BinaryOperator::LessEqual => FloatPredicate::OLE,
+        ast::BinaryOperator::Greater => FloatPredicate::OGT,
+        ast::BinaryOperator::GreaterEqual => FloatPredicate::OGE,
+        _ => unreachable!("Operator {operator} does not compare.")
+    }
 }
 
 type VisitorReturn<'ctx> = Option<AnyValueEnum<'ctx>>;
+
 impl<'sess, 'ctx> Visitor<VisitorReturn<'ctx>> for LLVMBackendCodegenPass<'sess, 'ctx> {
     type Error = CodegenError;
 
     fn default_return(&self) -> VisitorReturn<'ctx> { None }
-    fn get_scope_stack<'a>(&'a self) -> &'a ScopeStack { &self.stack }
-    fn get_session<'a>(&'a self) -> &'a Session { &self.session }
+    fn get_scope_stack(&self) -> &ScopeStack { &self.stack }
+    fn get_session(&self) -> &Session { self.session }
+
+    fn visit_item(&mut self, item: &ast::Item) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let previous_bounds_checks = self.bounds_checks;
+        let def_id = self.session.get_ref_target(item.id).expect("Cannot find item definition reference.");
+        if self.session.has_attribute(def_id, &KnownAttribute::NoBoundsCheck) {
+            self.bounds_checks = false;
+        }
+
+        let result = walk_item(self, item);
+        self.bounds_checks = previous_bounds_checks;
+        result
+    }
 
     fn visit_function(&mut self, id: NodeId, function: &ast::Function) -> Result<VisitorReturn<'ctx>, Self::Error> {
-        let ll_function = self.module.add_function(
-            &self.session.get_symbol(id)
-                .expect("Function symbol should have been set by frontend."),
-            self.session.get_type(id)
-                .expect("Function type should have been resolved by frontend.")
-                .as_llvm_type(&self.context)?.into_function_type(),
-            None // TODO: Replace with linkage based on modifiers like extern "abi".
-        );
-        
-        let basic_block = self.context.append_basic_block(ll_function, "entry");
-        self.builder.position_at_end(basic_block);
-        walk_function(self, id, function)?;
-        self.builder.clear_insertion_position(); // temporary.
+        let def_id = self.session.get_ref_target(id).expect("Cannot find function definition reference.");
+        if self.session.get_extern(def_id).is_some() {
+            self.get_or_declare_function(def_id, &[])?;
+            return Ok(None);
+        }
+
+        let Some(RamenType::Callable(callable)) = self.session.get_type(def_id) else {
+            unreachable!("Function type should have been resolved by frontend.")
+        };
+        if callable.type_parameters.is_empty() {
+            self.build_function(id, function, &[])?;
+            return Ok(None);
+        }
+
+        // Generic function is generated once for every list of type arguments it is used with.
+        let previous_type_arguments = std::mem::take(&mut self.type_arguments);
+        for type_arguments in self.session.get_instances(def_id) {
+            self.type_arguments = callable.type_argument_map(&type_arguments);
+            self.build_function(id, function, &type_arguments)?;
+        }
+        self.type_arguments = previous_type_arguments;
+        Ok(None)
+    }
+
+    fn visit_trait(&mut self, _id: NodeId, _trait: &ast::Trait) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        // Method signatures have no code, implementations are generated instead.
+        Ok(None)
+    }
+
+    fn visit_const(&mut self, _id: NodeId, _const: &ast::Const) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        // Values of constants are inlined wherever they are used.
+        Ok(None)
+    }
+
+    fn visit_static(&mut self, id: NodeId, _static: &ast::Static) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find static definition reference.");
+        let static_type = self.get_type(def_id).expect("Static type should have been resolved by frontend.");
+        if static_type != RamenType::Unit {
+            self.get_or_declare_static(def_id)?;
+        }
+        Ok(None)
+    }
+
+    fn visit_type(&mut self, _ty: &ast::Type) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        // Types were resolved by frontend, lengths of arrays in them have no code.
         Ok(None)
     }
 
     fn visit_return_statement(&mut self, _id: NodeId, value: &ast::Expression) -> Result<VisitorReturn<'ctx>, Self::Error> {
-        let return_value: BasicValueEnum = walk_expression(self, value)?
-            .expect("Temporary unwrap, this will error readably later on")
-            .try_into().map_err::<CodegenError, _>(|_| todo!())?;
-        self.builder.build_return(Some(&return_value))?;
+        match (self.build_expression(value)?, self.return_slot) {
+            (Some(return_value), Some(return_slot)) => {
+                self.builder.build_store(return_slot, return_value)?;
+                self.builder.build_return(None)?
+            }
+            (Some(return_value), None) => {
+                let return_type = self.current_function().get_type().get_return_type().expect("Function returning value should have return type.");
+                self.builder.build_return(Some(&self.build_memory_cast(return_value, return_type)?))?
+            }
+            (None, _) => self.builder.build_return(None)?
+        };
+        Ok(None)
+    }
+
+    fn visit_let_statement(&mut self, _id: NodeId, pattern: &ast::Pattern, _ty: Option<&ast::Type>, value: &ast::Expression) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        if let Some(value) = self.build_expression(value)? {
+            let slot = self.build_entry_alloca(value.get_type(), pattern_name(pattern))?;
+            self.builder.build_store(slot, value)?;
+
+            let value_type = self.get_type(pattern.id).expect("Pattern type should have been resolved by frontend.");
+            self.bind_pattern(pattern, slot, &value_type)?;
+        }
+        Ok(None)
+    }
+
+    fn visit_assign_statement(&mut self, _id: NodeId, target: &ast::Expression, value: &ast::Expression) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let value = self.build_expression(value)?;
+        let target_ptr = self.build_place(target)?;
+        if let Some(value) = value {
+            self.builder.build_store(target_ptr, value)?;
+        }
         Ok(None)
     }
 
     fn visit_literal_expression(&mut self, id: NodeId, literal: &ast::Literal) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let literal_type = self.get_type(id).expect("This should have been set by type resolution/checking");
         match &literal {
             ast::Literal::Integer(value) => {
-                let int_type = self.session.get_type(id).expect("This should have been set by type resolution/checking");
-                let value = int_type.as_llvm_type(&self.context).unwrap().into_int_type().const_int(*value as _, true); // temporary.
+                // Frontend made sure the literal fits into its type.
+                let value = const_int_i128(literal_type.as_llvm_type(self.context)?.into_int_type(), *value as i128);
                 Ok(Some(AnyValueEnum::IntValue(value)))
             }
+            ast::Literal::Float(value) => {
+                let value = literal_type.as_llvm_type(self.context)?.into_float_type().const_float(*value);
+                Ok(Some(AnyValueEnum::FloatValue(value)))
+            }
+            ast::Literal::Bool(value) => {
+                Ok(Some(AnyValueEnum::IntValue(self.context.bool_type().const_int(*value as _, false))))
+            }
+        }
+    }
+
+    fn visit_path_expression(&mut self, id: NodeId, path: &ast::Path) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Path should have been resolved by frontend.");
+        match self.session.get_def(def_id) {
+            Some(Definition::Variable | Definition::Static { .. }) => {
+                let variable_type = self.get_type(id).expect("Path type should have been resolved by frontend.");
+                if variable_type == RamenType::Unit { return Ok(None); }
+
+                let ptr = self.build_path_place(id, path)?.expect("Variable should have its slot.");
+                Ok(Some(self.builder.build_load(self.basic_type(&variable_type)?, ptr, "")?.as_any_value_enum()))
+            }
+            Some(Definition::Function | Definition::TraitMethod { .. } | Definition::Method { .. }) => {
+                let function = self.get_path_function(def_id, id)?;
+                let Some(RamenType::Callable(callable)) = self.get_type(id) else {
+                    unreachable!("Function type should have been resolved by frontend.")
+                };
+                let trampoline = self.get_or_build_trampoline(function, &callable)?;
+                let null = self.context.ptr_type(AddressSpace::default()).const_null();
+                Ok(Some(self.build_function_value(trampoline, null)?.as_any_value_enum()))
+            }
+            Some(Definition::Variant { index, .. }) => {
+                let Some(RamenType::Enum(enum_type)) = self.get_type(id) else {
+                    unreachable!("Variant used as a value should not have payload.")
+                };
+                Ok(Some(self.build_enum_value(&enum_type, index, &[])?.as_any_value_enum()))
+            }
+            Some(Definition::Const) => {
+                let mut value = self.session.get_constant(def_id).expect("Constant should have been evaluated by frontend.");
+                let mut ty = self.get_type(def_id).expect("Constant type should have been resolved by frontend.");
+                let fields = self.session.get_path_fields(id);
+                for segment in path.segments[path.segments.len() - fields..].iter() {
+                    let RamenType::Struct(struct_type) = ty else { unreachable!("Only structs have named fields.") };
+                    let index = struct_type.field_index(&segment.name).expect("Field should have been checked by frontend.");
+                    value = value.field(index).cloned().expect("Struct constant should have all of its fields.");
+                    ty = struct_type.fields[index].ty.clone();
+                }
+                Ok(self.build_constant_value(&value, &ty)?.map(|value| value.as_any_value_enum()))
+            }
+            definition => unreachable!("Path cannot refer to {definition:?} in expression.")
+        }
+    }
+
+    fn visit_tuple_expression(&mut self, id: NodeId, elements: &[ast::Expression]) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let mut element_values = Vec::new();
+        for element in elements.iter() {
+            element_values.push(self.build_expression(element)?.expect("Unit values cannot be stored in tuples."));
+        }
+        if element_values.is_empty() { return Ok(None); }
+
+        let tuple_type = self.basic_type(&self.get_type(id).expect("Tuple type should have been resolved by frontend."))?;
+        let mut tuple_value = tuple_type.into_struct_type().get_undef();
+        for (index, element) in element_values.into_iter().enumerate() {
+            tuple_value = self.builder.build_insert_value(tuple_value, element, index as _, "")?
+                .into_struct_value();
+        }
+        Ok(Some(tuple_value.as_any_value_enum()))
+    }
+
+    fn visit_tuple_index_expression(&mut self, _id: NodeId, tuple: &ast::Expression, index: usize) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let tuple_value = self.build_expression(tuple)?.expect("Tuple should not be a unit value.").into_struct_value();
+        Ok(Some(self.builder.build_extract_value(tuple_value, index as _, "")?.as_any_value_enum()))
+    }
+
+    fn visit_field_expression(&mut self, id: NodeId, base: &ast::Expression, name: &str) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let field_type = self.get_type(id).expect("Field type should have been resolved by frontend.");
+        let base_type = self.get_type(base.id).expect("Accessed expression type should have been resolved by frontend.");
+        let base_ptr = self.build_place(base)?;
+        let (field_ptr, _) = self.build_field_ptr(base_ptr, &base_type, name)?;
+        Ok(Some(self.builder.build_load(self.basic_type(&field_type)?, field_ptr, "")?.as_any_value_enum()))
+    }
+
+    fn visit_array_expression(&mut self, id: NodeId, elements: &[ast::Expression]) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let array_type = self.basic_type(&self.get_type(id).expect("Array type should have been resolved by frontend."))?;
+        let mut array_value = array_type.into_array_type().get_undef();
+        for (index, element) in elements.iter().enumerate() {
+            let element = self.build_expression(element)?.expect("Unit values cannot be stored in arrays.");
+            array_value = self.builder.build_insert_value(array_value, element, index as _, "")?
+                .into_array_value();
+        }
+        Ok(Some(array_value.as_any_value_enum()))
+    }
+
+    fn visit_array_repeat_expression(&mut self, id: NodeId, value: &ast::Expression, _count: &ast::Expression) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let value = self.build_expression(value)?.expect("Unit values cannot be stored in arrays.");
+        let repeat_type = self.get_type(id).expect("Array type should have been resolved by frontend.");
+        let RamenType::Array(_, count) = repeat_type else { unreachable!("Repeat expression should have array type.") };
+        let array_type = self.basic_type(&repeat_type)?;
+        let array_ptr = self.build_entry_alloca(array_type, "repeat")?;
+
+        // Value is stored in a loop, so long arrays do not blow up the code size.
+        if count > 0 {
+            let i64_type = self.context.i64_type();
+            let function = self.current_function();
+            let entry_block = self.builder.get_insert_block().unwrap();
+            let loop_block = self.context.append_basic_block(function, "repeat.loop");
+            let end_block = self.context.append_basic_block(function, "repeat.end");
+            self.builder.build_unconditional_branch(loop_block)?;
+
+            self.builder.position_at_end(loop_block);
+            let index = self.builder.build_phi(i64_type, "index")?;
+            let element_ptr = unsafe { self.builder.build_in_bounds_gep(array_type, array_ptr, &[i64_type.const_zero(), index.as_basic_value().into_int_value()], "")? };
+            self.builder.build_store(element_ptr, value)?;
+
+            let next_index = self.builder.build_int_add(index.as_basic_value().into_int_value(), i64_type.const_int(1, false), "")?;
+            let is_done = self.builder.build_int_compare(IntPredicate::EQ, next_index, i64_type.const_int(count as _, false), "")?;
+            index.add_incoming(&[(&i64_type.const_zero(), entry_block), (&next_index, loop_block)]);
+            self.builder.build_conditional_branch(is_done, end_block, loop_block)?;
+            self.builder.position_at_end(end_block);
+        }
+
+        Ok(Some(self.builder.build_load(array_type, array_ptr, "")?.as_any_value_enum()))
+    }
+
+    fn visit_index_expression(&mut self, id: NodeId, base: &ast::Expression, index: &ast::Expression) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let element_type = self.get_type(id).expect("Element type should have been resolved by frontend.");
+        let element_ptr = self.build_element_ptr(base, index)?;
+        Ok(Some(self.builder.build_load(self.basic_type(&element_type)?, element_ptr, "")?.as_any_value_enum()))
+    }
+
+    fn visit_slice_expression(
+        &mut self,
+        id: NodeId,
+        base: &ast::Expression,
+        start: Option<&ast::Expression>,
+        end: Option<&ast::Expression>,
+        inclusive: bool
+    ) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let slice_type = self.get_type(id).expect("Slice type should have been resolved by frontend.");
+        let RamenType::Slice(element_type) = &slice_type else { unreachable!("Slicing should produce a slice.") };
+        let i64_type = self.context.i64_type();
+
+        let (data, length) = self.build_elements(base)?;
+        let start_value = match start {
+            Some(start) => self.build_index(start)?,
+            None => i64_type.const_zero()
+        };
+        let end_value = match end {
+            Some(end) => self.build_index(end)?,
+            None => length
+        };
+
+        // Each check points at the bound it is about, or at the sliced value when the bound is implicit.
+        // Inclusive end has to be an index of an element, then adding one to it cannot overflow.
+        let is_inclusive = inclusive && end.is_some();
+        let end_predicate = if is_inclusive { IntPredicate::UGE } else { IntPredicate::UGT };
+        self.build_bounds_check(end_predicate, end_value, length, &end.unwrap_or(base).location)?;
+        let mut end_value = self.narrow_index(end_value)?;
+        if is_inclusive {
+            end_value = self.builder.build_int_add(end_value, i64_type.const_int(1, false), "")?;
+        }
+        self.build_bounds_check(IntPredicate::UGT, start_value, end_value, &start.unwrap_or(base).location)?;
+        let start_value = self.narrow_index(start_value)?;
+
+        let element_type = self.basic_type(element_type)?;
+        let slice_data = unsafe { self.builder.build_in_bounds_gep(element_type, data, &[start_value], "")? };
+        let slice_length = self.builder.build_int_sub(end_value, start_value, "")?;
+
+        let mut slice_value = self.basic_type(&slice_type)?.into_struct_type().get_undef();
+        slice_value = self.builder.build_insert_value(slice_value, slice_data, 0, "")?.into_struct_value();
+        slice_value = self.builder.build_insert_value(slice_value, slice_length, 1, "")?.into_struct_value();
+        Ok(Some(slice_value.as_any_value_enum()))
+    }
+
+    fn visit_address_of_expression(&mut self, _id: NodeId, value: &ast::Expression, _mutable: bool) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        Ok(Some(self.build_place(value)?.as_any_value_enum()))
+    }
+
+    fn visit_deref_expression(&mut self, id: NodeId, value: &ast::Expression) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let pointer = self.build_expression(value)?.expect("Pointer should not be a unit value.").into_pointer_value();
+        let target_type = self.get_type(id).expect("Dereferenced type should have been resolved by frontend.");
+        if target_type == RamenType::Unit { return Ok(None); }
+
+        Ok(Some(self.build_load_aligned(&target_type, pointer)?.as_any_value_enum()))
+    }
+
+    fn visit_unary_expression(&mut self, _id: NodeId, operator: ast::UnaryOperator, operand: &ast::Expression) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        // Negated literal is a constant of its own, as the literal alone may not fit into its type, like `128` in `-128` of `int8`.
+        if let (ast::UnaryOperator::Negate, ast::ExpressionKind::Literal(ast::Literal::Integer(value))) = (operator, &operand.kind) {
+            let int_type = self.basic_type(&self.get_type(operand.id).expect("Literal type should have been resolved by frontend."))?.into_int_type();
+            return Ok(Some(const_int_i128(int_type, -(*value as i128)).as_any_value_enum()));
+        }
+
+        let value = self.build_expression(operand)?.expect("Operand should not be a unit value.");
+        let result: BasicValueEnum = match (operator, value) {
+            (ast::UnaryOperator::Negate, BasicValueEnum::IntValue(value)) => self.builder.build_int_neg(value, "")?.into(),
+            (ast::UnaryOperator::Negate, BasicValueEnum::FloatValue(value)) => self.builder.build_float_neg(value, "")?.into(),
+            (ast::UnaryOperator::Not, BasicValueEnum::IntValue(value)) => self.builder.build_not(value, "")?.into(),
+            _ => unreachable!("Operand should have been checked by frontend.")
+        };
+        Ok(Some(result.as_any_value_enum()))
+    }
+
+    fn visit_binary_expression(
+        &mut self,
+        _id: NodeId,
+        operator: ast::BinaryOperator,
+        left: &ast::Expression,
+        right: &ast::Expression
+    ) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        if operator.is_logical() {
+            return self.build_logical_expression(operator, left, right);
         }
+
+        let left_value = self.build_expression(left)?.expect("Operand should not be a unit value.");
+        let right_value = self.build_expression(right)?.expect("Operand should not be a unit value.");
+
+        // Integers are signed, their arithmetic wraps around.
+        let result: BasicValueEnum = match (left_value, right_value) {
+            (BasicValueEnum::IntValue(left_value), BasicValueEnum::IntValue(right_value)) => match operator {
+                ast::BinaryOperator::Add => self.builder.build_int_add(left_value, right_value, "")?.into(),
+                ast::BinaryOperator::Subtract => self.builder.build_int_sub(left_value, right_value, "")?.into(),
+                ast::BinaryOperator::Multiply => self.builder.build_int_mul(left_value, right_value, "")?.into(),
+                ast::BinaryOperator::Divide | ast::BinaryOperator::Remainder => self.build_division(operator, left_value, right_value, &right.location)?.into(),
+                _ => self.builder.build_int_compare(int_predicate(operator), left_value, right_value, "")?.into()
+            },
+            (BasicValueEnum::PointerValue(left_value), BasicValueEnum::PointerValue(right_value)) =>
+                self.builder.build_int_compare(int_predicate(operator), left_value, right_value, "")?.into(),
+            (BasicValueEnum::FloatValue(left_value), BasicValueEnum::FloatValue(right_value)) => match operator {
+                ast::BinaryOperator::Add => self.builder.build_float_add(left_value, right_value, "")?.into(),
+                ast::BinaryOperator::Subtract => self.builder.build_float_sub(left_value, right_value, "")?.into(),
+                ast::BinaryOperator::Multiply => self.builder.build_float_mul(left_value, right_value, "")?.into(),
+                ast::BinaryOperator::Divide => self.builder.build_float_div(left_value, right_value, "")?.into(),
+                ast::BinaryOperator::Remainder => self.builder.build_float_rem(left_value, right_value, "")?.into(),
+                _ => self.builder.build_float_compare(float_predicate(operator), left_value, right_value, "")?.into()
+            },
+            _ => unreachable!("Operands should have been checked by frontend.")
+        };
+        Ok(Some(result.as_any_value_enum()))
     }
-}
\ No newline at end of file
+
+    fn visit_call_expression(&mut self, id: NodeId, callee: &ast::Expression, arguments: &[ast::Expression]) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        // Calls with method syntax refer to the method themselves, their callee is the receiver.
+        if let Some(method) = self.session.get_ref_target(id) {
+            return self.build_method_call(id, callee, method, arguments);
+        }
+
+        let variadic_from = match self.get_type(callee.id) {
+            Some(RamenType::Callable(callable)) if callable.is_vararg => callable.parameter_types.len(),
+            _ => arguments.len()
+        };
+        let mut argument_values = Vec::new();
+        for (index, argument) in arguments.iter().enumerate() {
+            let value = self.build_expression(argument)?.expect("Unit values cannot be passed as arguments.");
+            let value = if index < variadic_from { value } else {
+                self.build_vararg_promotion(value, &self.get_type(argument.id).expect("Argument type should have been resolved by frontend."))?
+            };
+            argument_values.push(value);
+        }
+
+        let callee_def_id = match &callee.kind {
+            ast::ExpressionKind::Path(_) => self.session.get_ref_target(callee.id),
+            _ => None
+        };
+        match callee_def_id.and_then(|def_id| Some((def_id, self.session.get_def(def_id)?))) {
+            Some((def_id, Definition::TraitMethod { parent })) if matches!(self.get_type_arguments(callee.id).first(), Some(RamenType::Dyn { .. })) =>
+                self.build_dynamic_call(id, def_id, parent, argument_values),
+            Some((def_id, Definition::Function | Definition::TraitMethod { .. } | Definition::Method { .. })) => {
+                let function = self.get_path_function(def_id, callee.id)?;
+                self.build_call_with_return(id, argument_values, |builder, arguments| builder.build_call(function, arguments, ""))
+            }
+            Some((_, Definition::Variant { index, .. })) => {
+                let Some(RamenType::Enum(enum_type)) = self.get_type(id) else {
+                    unreachable!("Variant constructor should return its enum.")
+                };
+                Ok(Some(self.build_enum_value(&enum_type, index, &argument_values)?.as_any_value_enum()))
+            }
+            Some((_, Definition::Constructor { .. })) => {
+                let struct_type = self.basic_type(&self.get_type(id).expect("Constructor call type should have been resolved by frontend."))?;
+                let mut struct_value = struct_type.into_struct_type().get_undef();
+                for (index, field) in argument_values.into_iter().enumerate() {
+                    struct_value = self.builder.build_insert_value(struct_value, field, index as _, "")?
+                        .into_struct_value();
+                }
+                Ok(Some(struct_value.as_any_value_enum()))
+            }
+            _ => self.build_indirect_call(id, callee, argument_values)
+        }
+    }
+
+    fn visit_closure_expression(&mut self, id: NodeId, closure: &ast::Closure) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let Some(RamenType::Callable(callable)) = self.get_type(id) else {
+            unreachable!("Closure type should have been resolved by frontend.")
+        };
+
+        // Environment holds copies of captured variables for `move` closures and pointers to them otherwise.
+        // Copies are on the heap, so `move` closures can be returned, the pointers are only valid in the creating frame anyway.
+        // Function values are copied freely and nothing knows when the last copy is gone, so the heap environment is leaked.
+        let captures = self.session.get_captures(id).into_iter()
+            .map(|def_id| (def_id, self.get_type(def_id).expect("Captured variable type should have been resolved by frontend.")))
+            .filter(|(_, ty)| *ty != RamenType::Unit)
+            .collect::<Vec<_>>();
+        let ptr_type = self.context.ptr_type(AddressSpace::default());
+        let environment_type = self.context.struct_type(&captures.iter()
+            .map(|(_, ty)| if closure.by_value { self.basic_type(ty) } else { Ok(ptr_type.into()) })
+            .collect::<Result<Vec<_>, CodegenError>>()?, false);
+
+        let environment = if captures.is_empty() { ptr_type.const_null() } else {
+            let environment = if closure.by_value { self.builder.build_malloc(environment_type, "environment")? }
+                else { self.build_entry_alloca(environment_type.into(), "environment")? };
+            for (index, (def_id, ty)) in captures.iter().enumerate() {
+                let slot = self.locals[def_id];
+                let value = if closure.by_value { self.builder.build_load(self.basic_type(ty)?, slot, "")? }
+                    else { slot.as_basic_value_enum() };
+                let field = self.builder.build_struct_gep(environment_type, environment, index as _, "")?;
+                self.builder.build_store(field, value)?;
+            }
+            environment
+        };
+
+        let symbol = format!("{}.closure", self.current_function().get_name().to_str().expect("Function symbol should be valid UTF-8."));
+        let returns_indirectly = is_returned_indirectly(&callable.return_type);
+        let code = self.module.add_function(
+            &symbol,
+            closure_function_type(&callable).as_llvm_type(self.context)?.into_function_type(),
+            Some(Linkage::Private)
+        );
+        if returns_indirectly {
+            code.add_attribute(AttributeLoc::Param(0), self.sret_attribute(&callable.return_type)?);
+        }
+
+        let previous_block = self.builder.get_insert_block();
+        self.builder.position_at_end(self.context.append_basic_block(code, "entry"));
+
+        let mut ll_parameters = code.get_param_iter();
+        let return_slot = if returns_indirectly { ll_parameters.next().map(|slot| slot.into_pointer_value()) } else { None };
+        let environment_parameter = ll_parameters.next().expect("Closure should take its environment.").into_pointer_value();
+
+        // Captured variables are accessed through the environment while the closure is generated.
+        let mut outer_slots = Vec::new();
+        for (index, (def_id, _)) in captures.iter().enumerate() {
+            let field = self.builder.build_struct_gep(environment_type, environment_parameter, index as _, "")?;
+            let slot = if closure.by_value { field }
+                else { self.builder.build_load(ptr_type, field, "")?.into_pointer_value() };
+            outer_slots.push((*def_id, self.locals.insert(*def_id, slot)));
+        }
+
+        for (parameter, value) in closure.parameters.iter().zip(ll_parameters) {
+            let slot = self.build_entry_alloca(value.get_type(), pattern_name(&parameter.pattern))?;
+            self.builder.build_store(slot, value)?;
+
+            let parameter_type = self.get_type(parameter.id).expect("Parameter type should have been resolved by frontend.");
+            self.bind_pattern(&parameter.pattern, slot, &parameter_type)?;
+        }
+
+        let value = self.build_expression(&closure.body)?;
+        match (return_slot, value) {
+            (Some(return_slot), Some(value)) => {
+                self.builder.build_store(return_slot, value)?;
+                self.builder.build_return(None)?
+            }
+            (_, Some(value)) => self.builder.build_return(Some(&value))?,
+            (_, None) => self.builder.build_return(None)?
+        };
+
+        for (def_id, slot) in outer_slots {
+            match slot {
+                Some(slot) => self.locals.insert(def_id, slot),
+                None => self.locals.remove(&def_id)
+            };
+        }
+        if let Some(block) = previous_block {
+            self.builder.position_at_end(block);
+        }
+
+        Ok(Some(self.build_function_value(code, environment)?.as_any_value_enum()))
+    }
+
+    fn visit_match_expression(&mut self, id: NodeId, scrutinee: &ast::Expression, arms: &[ast::MatchArm]) -> Result<VisitorReturn<'ctx>, Self::Error> {
+        let scrutinee_type = self.get_type(scrutinee.id).expect("Scrutinee type should have been resolved by frontend.");
+        let result_type = self.get_type(id).expect("Match type should have been resolved by frontend.");
+
+        // Scrutinee is kept in memory, so payload fields can be accessed and bound in place.
+        let scrutinee_ptr = match self.build_expression(scrutinee)? {
+            Some(value) => {
+                let slot = self.build_entry_alloca(value.get_type(), "scrutinee")?;
+                self.builder.build_store(slot, value)?;
+                Some(slot)
+            }
+            None => None
+        };
+        let result_slot = match result_type {
+            RamenType::Unit => None,
+            _ => Some(self.build_entry_alloca(self.basic_type(&result_type)?, "match.result")?)
+        };
+
+        let function = self.current_function();
+        let end_block = self.context.append_basic_block(function, "match.end");
+        // Exhaustiveness is checked by frontend, so falling through every arm is impossible.
+        let unreachable_block = self.context.append_basic_block(function, "match.unreachable");
+
+        let switch_cases = match (&scrutinee_type, scrutinee_ptr) {
+            (RamenType::Bool | RamenType::Integer(_) | RamenType::Enum(_), Some(_)) => arms.iter()
+                .map(|arm| self.switch_case(&arm.pattern))
+                .collect::<Option<Vec<_>>>(),
+            _ => None
+        };
+
+        if let (Some(switch_cases), Some(scrutinee_ptr)) = (switch_cases, scrutinee_ptr) {
+            let discriminant = match &scrutinee_type {
+                RamenType::Enum(enum_type) => self.build_enum_tag(enum_type, scrutinee_ptr)?,
+                _ => self.build_load_aligned(&scrutinee_type, scrutinee_ptr)?.into_int_value()
+            };
+
+            let mut cases = Vec::new();
+            let mut default_block = None;
+            let mut arm_blocks = Vec::new();
+            for switch_case in switch_cases {
+                let arm_block = self.context.append_basic_block(function, "match.arm");
+                match switch_case {
+                    SwitchCase::Values(values) => for value in values {
+                        // Values repeated by unreachable arms would make the switch invalid.
+                        let case_value = const_int_i128(discriminant.get_type(), value);
+                        if !cases.iter().any(|(existing, _)| *existing == case_value) {
+                            cases.push((case_value, arm_block));
+                        }
+                    },
+                    SwitchCase::Default => { default_block.get_or_insert(arm_block); }
+                }
+                arm_blocks.push(arm_block);
+            }
+            self.builder.build_switch(discriminant, default_block.unwrap_or(unreachable_block), &cases)?;
+
+            for (arm, arm_block) in arms.iter().zip(arm_blocks) {
+                self.builder.position_at_end(arm_block);
+                self.build_match_arm(arm, Some(scrutinee_ptr), &scrutinee_type, result_slot, end_block)?;
+            }
+        } else {
+            let mut next_block = None;
+            for arm in arms.iter() {
+                let condition = match scrutinee_ptr {
+                    Some(scrutinee_ptr) => self.build_pattern_test(&arm.pattern, scrutinee_ptr, &scrutinee_type)?,
+                    None => None
+                };
+                let arm_block = self.context.append_basic_block(function, "match.arm");
+                next_block = match condition {
+                    Some(condition) => {
+                        let next_block = self.context.append_basic_block(function, "match.next");
+                        self.builder.build_conditional_branch(condition, arm_block, next_block)?;
+                        Some(next_block)
+                    }
+                    None => {
+                        self.builder.build_unconditional_branch(arm_block)?;
+                        None
+                    }
+                };
+
+                self.builder.position_at_end(arm_block);
+                self.build_match_arm(arm, scrutinee_ptr, &scrutinee_type, result_slot, end_block)?;
+
+                // Arms after the irrefutable one can never be reached.
+                let Some(next_block) = next_block else { break };
+                self.builder.position_at_end(next_block);
+            }
+            if next_block.is_some() {
+                self.builder.build_unconditional_branch(unreachable_block)?;
+            }
+        }
+
+        self.builder.position_at_end(unreachable_block);
+        self.builder.build_unreachable()?;
+
+        self.builder.position_at_end(end_block);
+        Ok(match result_slot {
+            Some(result_slot) => Some(self.builder.build_load(self.basic_type(&result_type)?, result_slot, "")?.as_any_value_enum()),
+            None => None
+        })
+    }
+}
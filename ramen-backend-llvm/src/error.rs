@@ -1,25 +1,62 @@
 use inkwell::builder::BuilderError;
-use ramen_common::{error::Diagnostic, session::Session, Loc, ariadne};
+use ramen_common::{error::Diagnostic, session::{Session, SourceId}, Loc, ariadne};
 
+/// Error of the backend, these come from code the frontend accepted, so they point at bugs in the compiler.
 #[derive(Debug, Clone)]
 pub enum CodegenError {
-
+    /// LLVM refused to build an instruction, like when the builder is not positioned in a block.
+    Builder(String),
+    /// Type has no LLVM value representation, but a value of it is needed.
+    NotBasicType(String),
+    /// LLVM refused alignment of a stack slot or global.
+    InvalidAlignment {
+        align: u32,
+        reason: String
+    },
+    /// Two different functions ended up with the same symbol.
+    SymbolRedefined {
+        symbol: String
+    }
 }
 
 impl Diagnostic for CodegenError {
     fn is_fatal(&self) -> bool { true }
 
     fn get_location(&self) -> Loc {
-        todo!()
+        Loc::new(SourceId::dummy(), 0..0)
     }
 
-    fn build_report(&self, session: &Session) -> ariadne::Report<'static, Loc> {
-        todo!()
+    fn build_report(&self, _session: &Session) -> ariadne::Report<'static, Loc> {
+        use ariadne::{Report, ReportKind, Config};
+
+        let loc = self.get_location();
+        let report = Report::build(
+            ReportKind::Error,
+            loc.file,
+            loc.span.start
+        ).with_config(Config::default().with_cross_gap(true));
+
+        let report = match self {
+            Self::Builder(reason) => {
+                report.with_code("C01").with_message(format!("Failed to build instruction: {reason}."))
+            }
+            Self::NotBasicType(ty) => {
+                report.with_code("C02").with_message(format!("Type `{ty}` has no value representation."))
+            }
+            Self::InvalidAlignment { align, reason } => {
+                report.with_code("C03").with_message(format!("Invalid alignment {align}: {reason}."))
+            }
+            Self::SymbolRedefined { symbol } => {
+                report.with_code("C04").with_message(format!("Symbol `{symbol}` is defined more than once."))
+            }
+        };
+
+        report.finish()
     }
 }
 
 impl From<BuilderError> for CodegenError {
     fn from(value: BuilderError) -> Self {
-        todo!()
+        Self::Builder(value.to_string())
     }
-}
\ No newline at end of file
+}
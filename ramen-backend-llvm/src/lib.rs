@@ -1,3 +1,5 @@
+pub extern crate inkwell;
+
 pub mod codegen;
 pub mod error;
-pub mod types;
\ No newline at end of file
+pub mod types;
@@ -1,5 +1,5 @@
-use inkwell::{context::Context, types::{AnyTypeEnum, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType, VoidType}};
-use ramen_common::types::{CallableType, RamenType};
+use inkwell::{context::Context, AddressSpace, types::{AnyTypeEnum, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType, StructType}};
+use ramen_common::types::{CallableType, EnumType, RamenType};
 
 use crate::error::CodegenError;
 
@@ -11,13 +11,50 @@ pub trait AsLLType {
 
 impl AsLLType for RamenType {
     type Error = CodegenError;
-    
+
     fn as_llvm_type<'ctx>(&self, context: &'ctx Context) -> Result<AnyTypeEnum<'ctx>, Self::Error> {
         match self {
             Self::Unit => Ok(AnyTypeEnum::VoidType(context.void_type())),
+            Self::Bool => Ok(AnyTypeEnum::IntType(context.bool_type())),
             Self::Integer(width) => Ok(AnyTypeEnum::IntType(context.custom_width_int_type(*width as _))),
-            Self::Callable(callable) => callable.as_llvm_type(context),
-            _ => todo!("Throw apropriate error")
+            Self::Float(32) => Ok(AnyTypeEnum::FloatType(context.f32_type())),
+            Self::Float(64) => Ok(AnyTypeEnum::FloatType(context.f64_type())),
+            Self::Tuple(elements) => {
+                let element_types = elements.iter()
+                    .map(|element| element.as_llvm_type(context)?.try_into().map_err(|_| CodegenError::NotBasicType(element.to_string())))
+                    .collect::<Result<Vec<BasicTypeEnum>, CodegenError>>()?;
+                Ok(AnyTypeEnum::StructType(context.struct_type(&element_types, false)))
+            }
+            Self::Array(element, length) => {
+                let element_type: BasicTypeEnum = element.as_llvm_type(context)?.try_into().map_err(|_| CodegenError::NotBasicType(element.to_string()))?;
+                Ok(AnyTypeEnum::ArrayType(element_type.array_type(*length as _)))
+            }
+            Self::Slice(_) => Ok(AnyTypeEnum::StructType(context.struct_type(&[
+                context.ptr_type(AddressSpace::default()).into(),
+                context.i64_type().into()
+            ], false))),
+            // Pointers to trait objects carry pointer to vtable of the actual type along with the data pointer.
+            Self::Reference { target, .. } | Self::Pointer { target, .. } if matches!(**target, Self::Dyn { .. }) =>
+                Ok(AnyTypeEnum::StructType(context.struct_type(&[
+                    context.ptr_type(AddressSpace::default()).into(),
+                    context.ptr_type(AddressSpace::default()).into()
+                ], false))),
+            // Pointers are opaque, type of the pointee is only known to loads and stores.
+            Self::Reference { .. } | Self::Pointer { .. } => Ok(AnyTypeEnum::PointerType(context.ptr_type(AddressSpace::default()))),
+            // Function values carry pointer to environment of closure along with pointer to its code.
+            Self::Callable(_) => Ok(AnyTypeEnum::StructType(context.struct_type(&[
+                context.ptr_type(AddressSpace::default()).into(),
+                context.ptr_type(AddressSpace::default()).into()
+            ], false))),
+            Self::Enum(enum_type) => enum_type.as_llvm_type(context),
+            // Every instance of generic struct gets its own layout, as its fields are already substituted.
+            Self::Struct(struct_type) => {
+                let field_types = struct_type.fields.iter()
+                    .map(|field| field.ty.as_llvm_type(context)?.try_into().map_err(|_| CodegenError::NotBasicType(field.ty.to_string())))
+                    .collect::<Result<Vec<BasicTypeEnum>, CodegenError>>()?;
+                Ok(AnyTypeEnum::StructType(context.struct_type(&field_types, false)))
+            }
+            _ => Err(CodegenError::NotBasicType(self.to_string()))
         }
     }
 }
@@ -26,15 +63,20 @@ impl AsLLType for CallableType {
     type Error = CodegenError;
 
     fn as_llvm_type<'ctx>(&self, context: &'ctx Context) -> Result<AnyTypeEnum<'ctx>, Self::Error> {
-        println!("{:?}", self.return_type);
-        let return_type = self.return_type.as_llvm_type(context)?;
+        let mut parameter_types = self.parameter_types.iter().map(|ty| {
+            let basic_ty: BasicTypeEnum = ty.as_llvm_type(context)?.try_into().map_err(|_| CodegenError::NotBasicType(ty.to_string()))?;
+            Ok(basic_ty.into())
+        }).collect::<Result<Vec<BasicMetadataTypeEnum>, CodegenError>>()?;
+
+        // Large aggregates are written through a hidden pointer passed as the first argument.
+        let return_type = if is_returned_indirectly(&self.return_type) {
+            parameter_types.insert(0, context.ptr_type(AddressSpace::default()).into());
+            AnyTypeEnum::VoidType(context.void_type())
+        } else { self.return_type.as_llvm_type(context)? };
 
         let fn_type = build_fn_type_from_any_type(
             return_type,
-            self.parameter_types.iter().map(|ty| {
-                let basic_ty: BasicTypeEnum = ty.as_llvm_type(context)?.try_into().map_err::<CodegenError, _>(|_| todo!())?;
-                Ok(basic_ty.into())
-            }).collect::<Result<Vec<BasicMetadataTypeEnum>, CodegenError>>()?.as_slice(),
+            parameter_types.as_slice(),
             self.is_vararg
         )?;
 
@@ -42,17 +84,168 @@ impl AsLLType for CallableType {
     }
 }
 
+/// Enums are lowered to `{ tag, payload }` where payload is an integer array
+/// big enough and aligned enough to hold payload of any variant.
+impl AsLLType for EnumType {
+    type Error = CodegenError;
+
+    fn as_llvm_type<'ctx>(&self, context: &'ctx Context) -> Result<AnyTypeEnum<'ctx>, Self::Error> {
+        let tag_type = context.custom_width_int_type(self.tag_width()).as_basic_type_enum();
+        let (payload_size, payload_align) = self.variants.iter()
+            .map(|variant| struct_size_and_align(&variant.fields))
+            .fold((0u64, 1u64), |(size, align), (v_size, v_align)| (size.max(v_size), align.max(v_align)));
+
+        let fields = if payload_size == 0 { vec![tag_type] } else {
+            let chunk_type = context.custom_width_int_type((payload_align * 8) as _);
+            vec![tag_type, chunk_type.array_type(payload_size.div_ceil(payload_align) as _).as_basic_type_enum()]
+        };
+
+        Ok(AnyTypeEnum::StructType(context.struct_type(&fields, false)))
+    }
+}
+
+pub trait EnumTypeExt {
+    fn tag_width(&self) -> u32;
+    fn variant_payload_type<'ctx>(&self, context: &'ctx Context, index: usize) -> Result<StructType<'ctx>, CodegenError>;
+}
+
+impl EnumTypeExt for EnumType {
+    /// Smallest width of the tag that can represent every variant.
+    fn tag_width(&self) -> u32 {
+        match self.variants.len() {
+            0..=256 => 8,
+            257..=65536 => 16,
+            _ => 32
+        }
+    }
+
+    /// Layout of payload for given variant, it is stored in place of the enum payload array.
+    fn variant_payload_type<'ctx>(&self, context: &'ctx Context, index: usize) -> Result<StructType<'ctx>, CodegenError> {
+        let fields = self.variants[index].fields.iter()
+            .map(|field| field.as_llvm_type(context)?.try_into().map_err(|_| CodegenError::NotBasicType(field.to_string())))
+            .collect::<Result<Vec<BasicTypeEnum>, CodegenError>>()?;
+        Ok(context.struct_type(&fields, false))
+    }
+}
+
+/// Size and alignment of type in bytes, following what LLVM picks for common 64-bit targets.
+pub fn size_and_align(ty: &RamenType) -> (u64, u64) {
+    match ty {
+        RamenType::Unit => (0, 1),
+        RamenType::Bool => (1, 1),
+        RamenType::Integer(width) => {
+            let bytes = (*width as u64).div_ceil(8).next_power_of_two();
+            (bytes, bytes.min(8))
+        }
+        RamenType::Float(width) => (*width as u64 / 8, *width as u64 / 8),
+        RamenType::Tuple(elements) => struct_size_and_align(elements),
+        RamenType::Struct(struct_type) => struct_size_and_align(&struct_type.fields.iter()
+            .map(|field| field.ty.clone())
+            .collect::<Vec<_>>()),
+        RamenType::Array(element, length) => {
+            let (element_size, element_align) = size_and_align(element);
+            (element_size * *length as u64, element_align)
+        }
+        RamenType::Slice(_) | RamenType::Callable(_) => (16, 8),
+        RamenType::Reference { target, .. } | RamenType::Pointer { target, .. } if matches!(**target, RamenType::Dyn { .. }) => (16, 8),
+        RamenType::Reference { .. } | RamenType::Pointer { .. } => (8, 8),
+        RamenType::Enum(enum_type) => {
+            let tag_size = enum_type.tag_width() as u64 / 8;
+            let (payload_size, payload_align) = enum_type.variants.iter()
+                .map(|variant| struct_size_and_align(&variant.fields))
+                .fold((0u64, 1u64), |(size, align), (v_size, v_align)| (size.max(v_size), align.max(v_align)));
+
+            let align = tag_size.max(payload_align);
+            let size = tag_size.next_multiple_of(payload_align) + payload_size.next_multiple_of(payload_align);
+            (size.next_multiple_of(align), align)
+        }
+        RamenType::Dyn { .. } => unreachable!("Trait objects are only used behind pointers."),
+        RamenType::Parameter(_) => unreachable!("Type parameters should have been substituted before codegen.")
+    }
+}
+
+/// Signature of code of function value, which takes pointer to its environment before the actual parameters.
+pub fn closure_function_type(callable: &CallableType) -> CallableType {
+    let environment = RamenType::Pointer { target: Box::new(RamenType::Unit), mutable: false };
+    CallableType {
+        parameter_types: std::iter::once(environment).chain(callable.parameter_types.iter().cloned()).collect(),
+        ..callable.clone()
+    }
+}
+
+/// Following the C ABI, aggregates bigger than two registers are returned through `sret` pointer
+/// instead of by value.
+pub fn is_returned_indirectly(ty: &RamenType) -> bool {
+    matches!(ty, RamenType::Tuple(_) | RamenType::Array(..) | RamenType::Enum(_) | RamenType::Struct(_)) && size_and_align(ty).0 > 16
+}
+
+/// Following the x86-64 System V ABI, C returns tuples of at most two eightbytes in registers,
+/// each eightbyte as floats if it holds nothing else and as an integer of its size otherwise.
+/// `None` for any other type, which C returns the same way as ramen does.
+pub fn c_return_type<'ctx>(ty: &RamenType, context: &'ctx Context) -> Option<BasicTypeEnum<'ctx>> {
+    let (size, _) = size_and_align(ty);
+    if !matches!(ty, RamenType::Tuple(_)) || size > 16 {
+        return None;
+    }
+
+    let mut scalars = Vec::new();
+    collect_scalars(ty, 0, &mut scalars);
+    let eightbytes = (0..size).step_by(8).map(|start| {
+        let end = size.min(start + 8);
+        let fields = scalars.iter()
+            .filter(|(offset, _)| (start..end).contains(offset))
+            .map(|(_, ty)| ty)
+            .collect::<Vec<_>>();
+        match fields[..] {
+            [RamenType::Float(64)] => context.f64_type().into(),
+            [RamenType::Float(32)] => context.f32_type().into(),
+            [RamenType::Float(32), RamenType::Float(32)] => context.f32_type().vec_type(2).into(),
+            _ => context.custom_width_int_type(((end - start) * 8) as _).into()
+        }
+    }).collect::<Vec<BasicTypeEnum>>();
+
+    match eightbytes[..] {
+        [eightbyte] => Some(eightbyte),
+        _ => Some(context.struct_type(&eightbytes, false).into())
+    }
+}
+
+/// Scalars of possibly nested tuple together with their offsets from its start.
+fn collect_scalars(ty: &RamenType, offset: u64, scalars: &mut Vec<(u64, RamenType)>) {
+    let RamenType::Tuple(elements) = ty else {
+        scalars.push((offset, ty.clone()));
+        return;
+    };
+    let mut end = offset;
+    for element in elements.iter() {
+        let (size, align) = size_and_align(element);
+        let start = end.next_multiple_of(align);
+        collect_scalars(element, start, scalars);
+        end = start + size;
+    }
+}
+
+/// Size and alignment of non-packed struct with given fields.
+pub fn struct_size_and_align(fields: &[RamenType]) -> (u64, u64) {
+    let (size, align) = fields.iter().fold((0u64, 1u64), |(offset, align), field| {
+        let (field_size, field_align) = size_and_align(field);
+        (offset.next_multiple_of(field_align) + field_size, align.max(field_align))
+    });
+    (size.next_multiple_of(align), align)
+}
+
 fn build_fn_type_from_any_type<'ctx>(
-    return_type: AnyTypeEnum<'ctx>, 
-    argument_types: &[BasicMetadataTypeEnum<'ctx>], 
+    return_type: AnyTypeEnum<'ctx>,
+    argument_types: &[BasicMetadataTypeEnum<'ctx>],
     is_vararg: bool
 ) -> Result<FunctionType<'ctx>, CodegenError> {
     match return_type {
         AnyTypeEnum::VoidType(void_type) =>
             Ok(void_type.fn_type(argument_types, is_vararg)),
         _ => {
-            let return_type: BasicTypeEnum = return_type.try_into().map_err::<CodegenError, _>(|_| todo!("Yes"))?;
+            let return_type: BasicTypeEnum = return_type.try_into()
+                .map_err(|_| CodegenError::NotBasicType(return_type.print_to_string().to_string()))?;
             Ok(return_type.fn_type(argument_types, is_vararg))
         }
     }
-}
\ No newline at end of file
+}
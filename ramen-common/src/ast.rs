@@ -1,94 +1,435 @@
-use std::sync::atomic::AtomicUsize;
+use std::cell::Cell;
 
 use crate::Loc;
 
-static _NEXT_NODE_ID: AtomicUsize = AtomicUsize::new(0);
-
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(transparent)]
 pub struct NodeId(usize);
 
-impl NodeId {
-    pub fn next() -> Self {
-        Self(_NEXT_NODE_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
-    }
-}
-
 impl std::fmt::Display for NodeId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "ID:{}", self.0)
     }
 }
 
-#[derive(Debug)]
+/// Allocator of node ids owned by session, so that ids of the same code do not depend on what else ran in the process.
+#[derive(Debug, Clone, Default)]
+pub struct NodeIds(Cell<usize>);
+
+impl NodeIds {
+    pub fn next(&self) -> NodeId {
+        let id = self.0.get();
+        self.0.set(id + 1);
+        NodeId(id)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Item {
     pub location: Loc,
     pub attributes: Attributes,
+    pub visibility: Visibility,
     pub kind: ItemKind,
     pub id: NodeId,
 }
 
-#[derive(Debug)]
+impl Item {
+    /// Name the item is declared with, `impl` and `use` items have none
+    pub fn name(&self) -> Option<&str> {
+        match &self.kind {
+            ItemKind::Module(module) => Some(&module.name),
+            ItemKind::Function(function) => Some(&function.name),
+            ItemKind::Enum(enum_) => Some(&enum_.name),
+            ItemKind::Struct(struct_) => Some(&struct_.name),
+            ItemKind::Trait(trait_) => Some(&trait_.name),
+            ItemKind::Const(const_) => Some(&const_.name),
+            ItemKind::Static(static_) => Some(&static_.name),
+            ItemKind::TypeAlias(alias) => Some(&alias.name),
+            ItemKind::Impl(_) | ItemKind::Use(_) => None
+        }
+    }
+}
+
+/// Where item or field can be used from, ordered from the least visible
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub enum Visibility {
+    /// Only inside the module it is defined in and modules nested in it
+    #[default]
+    Private,
+    /// `pub(module)`, anywhere in the compiled module tree, but not by code compiled separately from it
+    Module,
+    /// `pub`
+    Public
+}
+
+#[derive(Debug, Clone)]
 pub enum ItemKind {
     Module(Module),
 
-    Function(Function)
+    Function(Function),
+    Enum(Enum),
+    Struct(Struct),
+    Trait(Trait),
+    Impl(Impl),
+    Use(Use),
+    Const(Const),
+    Static(Static),
+    TypeAlias(TypeAlias)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Module {
     pub name: String,
     pub items: Vec<Item>,
+    /// Declared as `mod name` without body, its items are loaded from `name.rm` or `name/mod.rm`
+    pub is_file_module: bool
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Function {
     pub name: String,
+    /// Type parameters like `T` in `func max<T>(a: T, b: T): T`
+    pub generics: Vec<GenericParameter>,
     pub return_type: Option<Type>,
     pub parameters: Vec<ValueParameter>,
-    pub body: Block
+    /// First parameter is a `self` receiver, so the function can be called with method syntax like `point.len()`
+    pub has_receiver: bool,
+    /// Takes any number of arguments after the declared parameters, like C `printf`
+    pub is_vararg: bool,
+    /// Missing for method signatures in traits and `@extern` declarations
+    pub body: Option<Block>,
+    /// Declared as `const func`, so it can be called in constant expressions
+    pub is_const: bool
+}
+
+#[derive(Debug, Clone)]
+pub struct GenericParameter {
+    pub location: Loc,
+    pub name: String,
+    /// Traits the type argument has to implement like `Ord` in `T: Ord`
+    pub bounds: Vec<TraitRef>,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone)]
+pub struct Enum {
+    pub name: String,
+    pub generics: Vec<GenericParameter>,
+    pub variants: Vec<Variant>
+}
+
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub location: Loc,
+    pub name: String,
+    pub fields: Vec<Type>,
+    pub id: NodeId,
+}
+
+/// Structure with named fields, constructed by calling it like `Pair(1, true)`
+///
+/// `newtype UserId = int64` is a struct with a single public field `0`, so it is distinct from the wrapped type.
+/// It is converted by calling it like `UserId(1)` and back by accessing the field like `id.0`.
+#[derive(Debug, Clone)]
+pub struct Struct {
+    pub name: String,
+    pub generics: Vec<GenericParameter>,
+    pub fields: Vec<Field>
+}
+
+/// Another name for a type like `type Meters = float64`, the two can be used interchangeably
+#[derive(Debug, Clone)]
+pub struct TypeAlias {
+    pub name: String,
+    pub ty: Type
+}
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub location: Loc,
+    pub visibility: Visibility,
+    pub name: String,
+    pub ty: Type,
+    pub id: NodeId,
+}
+
+/// Set of method signatures types can implement, like `trait Ord { func compare(a: &Self, b: &Self): int32 }`
+#[derive(Debug, Clone)]
+pub struct Trait {
+    pub name: String,
+    /// Functions without bodies
+    pub items: Vec<Item>
+}
+
+/// Implementation of trait for a type like `impl Ord for int32 { ... }`,
+/// or inherent implementation with methods of the type itself like `impl Point { ... }`
+#[derive(Debug, Clone)]
+pub struct Impl {
+    pub trait_: Option<TraitRef>,
+    pub self_type: Type,
+    pub items: Vec<Item>
+}
+
+/// `use a.b.c`, bringing names defined elsewhere into scope, `pub use` also makes them usable from other modules
+#[derive(Debug, Clone)]
+pub struct Use {
+    pub tree: UseTree
+}
+
+/// Part of `use` item following a common `prefix`, like `b.{c, d as e}` in `use a.b.{c, d as e}`
+#[derive(Debug, Clone)]
+pub struct UseTree {
+    pub location: Loc,
+    pub prefix: Vec<PathSegment>,
+    pub kind: UseTreeKind,
+    pub id: NodeId
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub enum UseTreeKind {
+    /// Imports last segment of the prefix, optionally under another name like `use a.b as c`
+    Simple(Option<String>),
+    /// `use a.*` imports every member of `a`
+    Glob,
+    /// `use a.{b, c.*}`
+    Nested(Vec<UseTree>)
+}
+
+/// `const NAME: type = value`, value is computed at compile time and copied to every place using it
+#[derive(Debug, Clone)]
+pub struct Const {
+    pub name: String,
+    pub ty: Type,
+    pub value: Expression
+}
+
+/// `static NAME: type = value`, single value stored for the whole run of the program, `static mut` can be assigned to
+#[derive(Debug, Clone)]
+pub struct Static {
+    pub name: String,
+    pub ty: Type,
+    /// Computed at compile time just like the value of a constant
+    pub value: Expression,
+    pub mutable: bool
+}
+
+/// Trait referenced by its path in bounds, impls and trait objects
+#[derive(Debug, Clone)]
+pub struct TraitRef {
+    pub location: Loc,
+    pub path: Path,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone)]
 pub struct Block {
     pub location: Loc,
     pub statements: Vec<Statement>,
     pub id: NodeId,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Statement {
     pub location: Loc,
     pub kind: StatementKind,
     pub id: NodeId,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum StatementKind {
     Item(Item),
     Expression(Expression),
 
+    /// `let pattern: type = value`, type annotation is optional
+    Let { pattern: Pattern, ty: Option<Type>, value: Expression },
+    /// `target = value`, target has to be a place like `*pointer` or `array[index]`
+    Assign { target: Expression, value: Expression },
     Return(Expression)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Expression {
     pub location: Loc,
     pub kind: ExpressionKind,
     pub id: NodeId,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ExpressionKind {
-    Literal(Literal)
+    Literal(Literal),
+    Path(Path),
+
+    /// Tuple like `(1, 2.0)`, empty one is the unit value
+    Tuple(Vec<Expression>),
+    /// Tuple field access like `pair.0`
+    TupleIndex(Box<Expression>, usize),
+    /// Struct field access like `pairs[0].first`, fields of plain variables are part of their path
+    Field(Box<Expression>, String),
+    /// Array literal like `[1, 2, 3]`
+    Array(Vec<Expression>),
+    /// Array with single value repeated like `[0; 16]`, the count is a constant expression
+    ArrayRepeat(Box<Expression>, Box<Expression>),
+    /// Indexing of arrays and slices like `a[i]`
+    Index(Box<Expression>, Box<Expression>),
+    /// Range slicing like `a[1..3]`, both bounds are optional
+    Slice { base: Box<Expression>, start: Option<Box<Expression>>, end: Option<Box<Expression>>, inclusive: bool },
+    /// Reference to a place like `&value` or `&mut value`
+    AddressOf { value: Box<Expression>, mutable: bool },
+    /// Value behind a reference or pointer like `*pointer`
+    Deref(Box<Expression>),
+    Unary(UnaryOperator, Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+
+    Call(Box<Expression>, Vec<Expression>),
+    Match(Box<Expression>, Vec<MatchArm>),
+    /// Anonymous function like `|x: int32| x`, capturing variables it uses from its surroundings
+    Closure(Closure)
+}
+
+#[derive(Debug, Clone)]
+pub struct Closure {
+    pub parameters: Vec<ClosureParameter>,
+    pub body: Box<Expression>,
+    /// `move` closures copy captured variables instead of referring to them,
+    /// the copies are allocated on the heap and never freed since function values have no owner
+    pub by_value: bool
+}
+
+#[derive(Debug, Clone)]
+pub struct ClosureParameter {
+    pub location: Loc,
+    pub pattern: Pattern,
+    /// Can be left out when the closure is used where a function type is expected
+    pub ty: Option<Type>,
+    pub id: NodeId
 }
 
 #[derive(Debug, Clone)]
 pub enum Literal {
-    Integer(usize)
+    Integer(usize),
+    Float(f64),
+    Bool(bool)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOperator {
+    /// Arithmetic negation like `-x`
+    Negate,
+    /// Logical negation like `!done`
+    Not
+}
+
+impl std::fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Negate => write!(f, "-"),
+            Self::Not => write!(f, "!")
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    /// Integer division rounds towards zero
+    Divide,
+    /// Remainder has the sign of the dividend
+    Remainder,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    /// Logical operators evaluate their right operand only when the left one does not decide the result
+    And,
+    Or
+}
+
+impl BinaryOperator {
+    pub fn is_arithmetic(self) -> bool {
+        matches!(self, Self::Add | Self::Subtract | Self::Multiply | Self::Divide | Self::Remainder)
+    }
+
+    pub fn is_ordering(self) -> bool {
+        matches!(self, Self::Less | Self::LessEqual | Self::Greater | Self::GreaterEqual)
+    }
+
+    pub fn is_logical(self) -> bool {
+        matches!(self, Self::And | Self::Or)
+    }
+}
+
+impl std::fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Add => write!(f, "+"),
+            Self::Subtract => write!(f, "-"),
+            Self::Multiply => write!(f, "*"),
+            Self::Divide => write!(f, "/"),
+            Self::Remainder => write!(f, "%"),
+            Self::Equal => write!(f, "=="),
+            Self::NotEqual => write!(f, "!="),
+            Self::Less => write!(f, "<"),
+            Self::LessEqual => write!(f, "<="),
+            Self::Greater => write!(f, ">"),
+            Self::GreaterEqual => write!(f, ">="),
+            Self::And => write!(f, "&&"),
+            Self::Or => write!(f, "||")
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub location: Loc,
+    pub pattern: Pattern,
+    pub body: Expression,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub location: Loc,
+    pub kind: PatternKind,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone)]
+pub enum PatternKind {
+    /// `_`, matches anything without binding it
+    Wildcard,
+    /// Binds matched value to a new variable
+    Binding(String),
+    Integer(i128),
+    Bool(bool),
+    /// Integer range like `1..5` or `1..=5`
+    Range { start: i128, end: i128, inclusive: bool },
+    /// Enum variant with patterns for its payload like `Shape.Circle(r)`
+    Variant(Path, Vec<Pattern>),
+    /// Tuple with pattern for each element like `(a, _)`
+    Tuple(Vec<Pattern>),
+    /// Alternatives separated by `|`
+    Or(Vec<Pattern>)
+}
+
+/// Dot separated sequence of names like `Shape.Circle` or `module.function`
+#[derive(Debug, Clone)]
+pub struct Path {
+    pub segments: Vec<PathSegment>
+}
+
+#[derive(Debug, Clone)]
+pub struct PathSegment {
+    pub location: Loc,
+    pub name: String,
+    /// Explicit type arguments like `int64` in `max<int64>`
+    pub type_arguments: Vec<Type>,
+}
+
+#[derive(Debug, Clone)]
 pub struct ValueParameter {
     pub location: Loc,
     pub parameter: Parameter,
@@ -96,10 +437,11 @@ pub struct ValueParameter {
     pub id: NodeId,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Parameter {
     pub location: Loc,
-    pub name: String,
+    /// Parameters can be destructured just like `let` statements
+    pub pattern: Pattern,
     pub ty: Type,
     pub id: NodeId,
 }
@@ -114,13 +456,43 @@ pub struct Type {
 #[derive(Debug, Clone)]
 pub enum TypeKind {
     Unit,
+    Bool,
     /// Integer type of arbitrary width
-    Integer(usize)
+    Integer(usize),
+    /// Floating point type, either 32 or 64 bits wide
+    Float(usize),
+    /// Tuple of at least one element like `(int32, bool)`
+    Tuple(Vec<Type>),
+    /// Fixed-size array like `[int32; 4]` or `[int32; SIZE]`, the length is a constant expression
+    Array(Box<Type>, Box<Expression>),
+    /// Pointer and length pair like `[int32]`
+    Slice(Box<Type>),
+    /// Reference like `&T` or `&mut T`
+    Reference { target: Box<Type>, mutable: bool },
+    /// Raw pointer like `*T` or `*mut T`
+    Pointer { target: Box<Type>, mutable: bool },
+    /// User-defined type referenced by name
+    Path(Path),
+    /// Trait object like `dyn Show`, only usable behind a reference or pointer
+    Dyn(TraitRef),
+    /// Function value like `func(int32, int32): bool`, return type defaults to unit
+    Function { parameters: Vec<Type>, return_type: Option<Box<Type>> }
 }
 
 #[derive(Debug, Clone)]
 pub struct Attributes(pub Vec<Attribute>);
 
+impl Attributes {
+    pub fn has_marker(&self, name: &str) -> bool {
+        self.0.iter().any(|attribute| matches!(&attribute.kind, AttributeKind::Marker(marker) if marker == name))
+    }
+
+    /// First attribute of the given name, with or without arguments.
+    pub fn get(&self, name: &str) -> Option<&Attribute> {
+        self.0.iter().find(|attribute| attribute.name() == name)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Attribute {
     pub location: Loc,
@@ -128,7 +500,31 @@ pub struct Attribute {
     pub id: NodeId,
 }
 
+impl Attribute {
+    pub fn name(&self) -> &str {
+        match &self.kind {
+            AttributeKind::Marker(name) | AttributeKind::Call(name, _) => name
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum AttributeKind {
-    Marker(String)
+    Marker(String),
+    /// Attribute with arguments like `@inline(always)` or `@deprecated(note = "Use `max` instead.")`
+    Call(String, Vec<AttributeArgument>)
+}
+
+#[derive(Debug, Clone)]
+pub struct AttributeArgument {
+    pub location: Loc,
+    /// Set for arguments written as `name = value`
+    pub name: Option<String>,
+    pub value: AttributeValue
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    Identifier(String)
 }
\ No newline at end of file
@@ -0,0 +1,39 @@
+/// Attribute recognized by the compiler, with its arguments already validated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KnownAttribute {
+    /// Function implemented outside of ramen, called using the given convention.
+    Extern(Abi),
+    /// Function callable from outside of ramen under its plain name.
+    Export,
+    /// Function emitted under its plain name, which also makes it exported.
+    NoMangle,
+    /// Indexing and slicing inside of the item is not checked at runtime.
+    NoBoundsCheck,
+    Inline(InlineMode),
+    /// Function is rarely called, so calls to it are optimized for size.
+    Cold,
+    /// Function never returns to its caller.
+    NoReturn,
+    /// Function never unwinds, which is useful to know about extern functions.
+    NoUnwind,
+    /// Function has no side effects and its result depends only on its arguments.
+    Pure,
+    /// Uses of the item produce a warning, optionally with a note.
+    Deprecated { note: Option<String> },
+    /// Boolean constant has to evaluate to `true`, otherwise compilation fails with the note.
+    StaticAssert { note: Option<String> }
+}
+
+/// Calling convention of function declared with `@extern`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Abi {
+    C
+}
+
+/// Argument of `@inline`, without one the function is only a candidate for inlining.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InlineMode {
+    Hint,
+    Always,
+    Never
+}
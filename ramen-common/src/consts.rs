@@ -0,0 +1,23 @@
+/// Value computed at compile time, its layout is given by the type of the expression or definition it belongs to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstValue {
+    Unit,
+    Bool(bool),
+    /// Integer of any width, sign-extended to 128 bits
+    Integer(i128),
+    Float(f64),
+    /// Elements of tuple or array, or fields of struct in declaration order
+    Aggregate(Vec<ConstValue>),
+    /// Enum variant with given tag and values of its payload
+    Variant { index: usize, fields: Vec<ConstValue> }
+}
+
+impl ConstValue {
+    /// Element of tuple or array, or field of struct with given index.
+    pub fn field(&self, index: usize) -> Option<&ConstValue> {
+        match self {
+            Self::Aggregate(elements) => elements.get(index),
+            _ => None
+        }
+    }
+}
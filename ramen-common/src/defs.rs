@@ -1,13 +1,89 @@
-use std::rc::Rc;
+use std::{cell::Cell, rc::Rc};
+
+use hashbrown::HashMap;
+
+use crate::{ast::Visibility, types::RamenType, Loc};
+
+/// Id of definition, allocated separately from ids of the nodes referring to it.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct DefId(usize);
+
+impl std::fmt::Display for DefId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DEF:{}", self.0)
+    }
+}
+
+/// Allocator of definition ids owned by session.
+#[derive(Debug, Clone, Default)]
+pub struct DefIds(Cell<usize>);
+
+impl DefIds {
+    pub fn next(&self) -> DefId {
+        let id = self.0.get();
+        self.0.set(id + 1);
+        DefId(id)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum Definition {
     Variable,
     Module,
-    Function
+    Function,
+    Enum,
+    /// Variant of enum `parent`, `index` is also the value of its tag.
+    Variant { parent: DefId, index: usize },
+    /// Struct type, calling its name goes through the separate `constructor` definition.
+    Struct { constructor: DefId },
+    /// Function-like constructor of struct `parent`, taking its fields in order.
+    Constructor { parent: DefId },
+    /// Type parameter of generic function, enum or struct `owner`.
+    TypeParameter { owner: DefId },
+    Trait,
+    /// Method signature declared in trait `parent`, calls to it are resolved to one of its implementations.
+    TraitMethod { parent: DefId },
+    /// Function in inherent implementation `parent`, callable with method syntax if it `has_receiver`.
+    Method { parent: DefId, has_receiver: bool },
+    /// Implementation of trait or inherent implementation, the name `Self` inside of it refers to this definition.
+    Impl,
+    /// `use` item, names it imports refer directly to the imported definitions.
+    Use,
+    /// Constant whose value is copied to every place using it.
+    Const,
+    /// Static variable stored in a single place, which can be assigned to if it is `mutable`.
+    Static { mutable: bool },
+    /// Type alias, whose type is the aliased type.
+    TypeAlias
+}
+
+impl Definition {
+    /// Whether path to this definition can continue with names of fields, like `origin.x`.
+    pub fn has_value_fields(&self) -> bool {
+        matches!(self, Self::Variable | Self::Const | Self::Static { .. })
+    }
+}
+
+/// Trait details needed to check its implementations and bounds.
+#[derive(Debug, Clone)]
+pub struct TraitDefinition {
+    pub name: String,
+    /// Implicit type parameter `Self` of every method, standing for the implementing type.
+    pub self_parameter: DefId,
+    /// Names and definitions of methods in declaration order.
+    pub methods: Vec<(String, DefId)>
 }
 
 #[derive(Debug, Clone)]
+pub struct Implementation {
+    pub def_id: DefId,
+    pub self_type: RamenType,
+    /// Maps methods of the trait to methods of this implementation.
+    pub methods: HashMap<DefId, DefId>
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct FunctionDefinition {}
 
 impl FunctionDefinition {
@@ -20,4 +96,13 @@ impl FunctionDefinition {
     }
 }
 
-pub type ModuleDefinitionRef = Rc<FunctionDefinition>;
\ No newline at end of file
+pub type ModuleDefinitionRef = Rc<FunctionDefinition>;
+
+/// Where definition, field or import can be used from.
+#[derive(Debug, Clone)]
+pub struct Privacy {
+    pub visibility: Visibility,
+    /// Module containing the definition, private definitions are visible only inside of it.
+    pub module: DefId,
+    pub location: Loc
+}
@@ -1,4 +1,6 @@
-use crate::{session::{Session, SourceId}, Loc};
+use std::rc::Rc;
+
+use crate::{session::{Session, SourceId}, types::RamenType, Loc};
 
 impl ariadne::Span for Loc {
     type SourceId = SourceId;
@@ -15,19 +17,1032 @@ pub trait Diagnostic {
     fn build_report(&self, session: &Session) -> ariadne::Report<'static, Loc>;
 }
 
+/// Types of mismatch the way they were written, the expected one by type annotation and the found one
+/// by declaration of the variable or function producing it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrittenTypes {
+    pub expected: Option<Rc<String>>,
+    pub found: Option<Rc<String>>
+}
+
+impl WrittenTypes {
+    /// `None` unless at least one of the types names type aliases.
+    pub fn new(expected: Option<Rc<String>>, found: Option<Rc<String>>) -> Option<Rc<Self>> {
+        (expected.is_some() || found.is_some()).then(|| Rc::new(Self { expected, found }))
+    }
+}
+
+/// Error originating from name or type resolution.
 #[derive(Debug, Clone)]
 pub enum ResolutionError {
-
+    UnresolvedName {
+        name: String,
+        location: Loc
+    },
+    UnresolvedType {
+        name: String,
+        location: Loc
+    },
+    TypeMismatch {
+        expected: RamenType,
+        found: RamenType,
+        /// Either type the way it was written, if it names type aliases. Kept
+        /// behind a thin pointer so that errors stay small.
+        written: Option<Rc<WrittenTypes>>,
+        location: Loc
+    },
+    NotCallable {
+        found: RamenType,
+        location: Loc
+    },
+    ArgumentCountMismatch {
+        expected: usize,
+        found: usize,
+        location: Loc
+    },
+    /// Type contains itself without any indirection, so it would have infinite size.
+    RecursiveType {
+        name: String,
+        location: Loc
+    },
+    InvalidPattern {
+        ty: RamenType,
+        location: Loc
+    },
+    PatternFieldCountMismatch {
+        expected: usize,
+        found: usize,
+        location: Loc
+    },
+    BindingInOrPattern {
+        location: Loc
+    },
+    NonExhaustiveMatch {
+        missing: Vec<String>,
+        location: Loc
+    },
+    /// Pattern in `let` or parameter which does not match every value of its type.
+    RefutablePattern {
+        missing: Vec<String>,
+        location: Loc
+    },
+    InvalidTupleIndex {
+        ty: RamenType,
+        index: usize,
+        location: Loc
+    },
+    /// Type of expression cannot be inferred from its surroundings.
+    TypeAnnotationsNeeded {
+        location: Loc
+    },
+    NotIndexable {
+        found: RamenType,
+        location: Loc
+    },
+    NotDereferenceable {
+        found: RamenType,
+        location: Loc
+    },
+    /// Mutation of a place reached through a reference or pointer that is not mutable.
+    MutationThroughImmutable {
+        ty: RamenType,
+        location: Loc
+    },
+    /// Left side of assignment that does not refer to memory, like a call or a literal.
+    InvalidAssignmentTarget {
+        location: Loc
+    },
+    InvalidOperands {
+        operator: String,
+        ty: RamenType,
+        location: Loc
+    },
+    GenericArgumentCountMismatch {
+        expected: usize,
+        found: usize,
+        location: Loc
+    },
+    /// Nested function using type parameter of the function it is declared in.
+    OuterTypeParameter {
+        name: String,
+        location: Loc
+    },
+    /// Generic function instantiating itself with ever growing type arguments.
+    RecursiveInstantiation {
+        name: String,
+        location: Loc
+    },
+    NoField {
+        ty: RamenType,
+        name: String,
+        location: Loc
+    },
+    /// Path used as trait bound or implemented trait that does not refer to a trait.
+    NotATrait {
+        name: String,
+        location: Loc
+    },
+    MissingTraitMethods {
+        trait_name: String,
+        missing: Vec<String>,
+        location: Loc
+    },
+    /// Method in implementation that is not declared by the trait.
+    NotTraitMember {
+        name: String,
+        trait_name: String,
+        location: Loc
+    },
+    ConflictingImpl {
+        trait_name: String,
+        ty: RamenType,
+        location: Loc
+    },
+    /// Type used where trait is required does not implement it.
+    MissingImpl {
+        trait_name: String,
+        ty: RamenType,
+        location: Loc
+    },
+    /// Trait used as `dyn` that has a method which cannot be called through a vtable.
+    NotObjectSafe {
+        trait_name: String,
+        method: String,
+        reason: &'static str,
+        location: Loc
+    },
+    /// Trait object used by value instead of behind a reference or pointer.
+    UnsizedTraitObject {
+        ty: RamenType,
+        location: Loc
+    },
+    /// Inherent implementation for something other than a non-generic struct or enum.
+    InvalidImplTarget {
+        location: Loc
+    },
+    /// Method of inherent implementation whose name is already taken by another member of the type.
+    DuplicateMember {
+        name: String,
+        location: Loc
+    },
+    /// Method call on a type without method of that name.
+    NoMethod {
+        ty: RamenType,
+        name: String,
+        location: Loc
+    },
+    /// ABI in `@extern` that is not supported.
+    UnsupportedAbi {
+        abi: String,
+        location: Loc
+    },
+    /// Function without body that is not declared with `@extern`.
+    MissingFunctionBody {
+        name: String,
+        location: Loc
+    },
+    /// Function declared with `@extern` that is not a plain bodiless declaration.
+    InvalidExternFunction {
+        name: String,
+        reason: &'static str,
+        location: Loc
+    },
+    /// Function marked `@export` or `@no_mangle` that cannot have a plain C symbol.
+    InvalidExport {
+        name: String,
+        reason: &'static str,
+        location: Loc
+    },
+    /// Parameter `...` in function implemented in ramen.
+    InvalidVararg {
+        location: Loc
+    },
+    /// Type passed to or returned from extern function that has no C equivalent.
+    NotFfiSafe {
+        ty: RamenType,
+        location: Loc
+    },
+    UnknownAttribute {
+        name: String,
+        location: Loc
+    },
+    /// Attribute placed on kind of item it has no meaning for, like `@inline` on a struct.
+    MisplacedAttribute {
+        name: String,
+        target: &'static str,
+        location: Loc
+    },
+    InvalidAttributeArguments {
+        name: String,
+        usage: &'static str,
+        location: Loc
+    },
+    /// Use of item marked `@deprecated`.
+    /// This is only a warning.
+    DeprecatedUse {
+        name: String,
+        note: Option<String>,
+        location: Loc
+    },
+    /// Match arm that can never be reached because previous arms cover all its values.
+    /// This is only a warning.
+    UnreachablePattern {
+        location: Loc
+    },
+    /// Import of name already defined or imported in the same scope.
+    ConflictingImport {
+        name: String,
+        location: Loc
+    },
+    UnresolvedImport {
+        name: String,
+        location: Loc
+    },
+    /// Use of name brought in by multiple glob imports with different definitions.
+    AmbiguousName {
+        name: String,
+        location: Loc
+    },
+    /// Imports depending on each other, like `use b.x` in module `a` and `use a.x` in module `b`.
+    ImportCycle {
+        name: String,
+        location: Loc
+    },
+    /// This is only a warning.
+    UnusedImport {
+        name: String,
+        location: Loc
+    },
+    /// Use of private item, field or import outside of the module it is defined in.
+    PrivateAccess {
+        name: String,
+        location: Loc,
+        definition: Loc
+    },
+    /// Constant whose value depends on itself, directly or through other constants.
+    ConstantCycle {
+        name: String,
+        location: Loc
+    },
+    /// Expression like function call or use of a variable in a place where value has to be known at compile time.
+    NotConstant {
+        what: &'static str,
+        location: Loc
+    },
+    /// Constant expression which would fail at runtime, like indexing out of bounds.
+    EvaluationFailed {
+        reason: String,
+        location: Loc
+    },
+    InvalidArrayLength {
+        value: i128,
+        location: Loc
+    },
+    /// Assignment to or mutable borrow of static not declared as `static mut`.
+    ImmutableStatic {
+        name: String,
+        location: Loc
+    },
+    /// Result of arithmetic in constant expression does not fit in its type.
+    ArithmeticOverflow {
+        operator: String,
+        ty: RamenType,
+        location: Loc
+    },
+    DivisionByZero {
+        location: Loc
+    },
+    /// Type alias which expands to itself, like `type List = [List; 2]`.
+    AliasCycle {
+        name: String,
+        location: Loc
+    },
+    /// Integer pattern which no value of the matched type can have, like `300` for `int8`.
+    PatternOutOfRange {
+        value: i128,
+        ty: RamenType,
+        location: Loc
+    },
+    /// Method call on a type implementing several traits with method of that name.
+    AmbiguousMethod {
+        name: String,
+        traits: Vec<String>,
+        location: Loc
+    },
+    /// Returned closure which refers to variables of the returning function.
+    EscapingClosure {
+        location: Loc
+    },
+    /// Two functions exported under the same plain name, like `@export func f` in two modules, or exported one declared with `@extern` too.
+    DuplicateSymbol {
+        symbol: String,
+        location: Loc
+    },
+    /// Function marked `@noreturn` which can return to its caller.
+    NoReturnReturns {
+        name: String,
+        location: Loc
+    },
+    /// Attribute given more than once to the same item, like `@inline @inline(never)`.
+    ConflictingAttribute {
+        name: String,
+        location: Loc
+    },
+    /// Import more visible than the definition it brings in, like `pub use` of private function.
+    PrivateReexport {
+        name: String,
+        location: Loc,
+        definition: Loc
+    },
+    /// Constant marked `@static_assert` which evaluated to `false`.
+    StaticAssertionFailed {
+        name: String,
+        note: Option<String>,
+        location: Loc
+    },
+    /// Item named the same as another item of the same scope, like two functions
+    /// or a function and a struct, whose constructor shares the namespace of functions.
+    DuplicateDefinition {
+        name: String,
+        location: Loc
+    },
+    /// Integer literal which does not fit into its type, like `300` as `int8`.
+    LiteralOutOfRange {
+        value: i128,
+        ty: RamenType,
+        location: Loc
+    },
+    /// Type less visible than the function whose signature names it, like private struct returned by `pub func`.
+    PrivateTypeInPublicInterface {
+        name: String,
+        location: Loc,
+        definition: Loc
+    }
 }
 
 impl Diagnostic for ResolutionError {
-    fn is_fatal(&self) -> bool { true }
+    fn is_fatal(&self) -> bool {
+        !matches!(self, Self::UnreachablePattern { .. } | Self::DeprecatedUse { .. } | Self::UnusedImport { .. })
+    }
 
     fn get_location(&self) -> Loc {
-        todo!()
+        match self {
+            Self::UnresolvedName { location, .. } => location.clone(),
+            Self::UnresolvedType { location, .. } => location.clone(),
+            Self::TypeMismatch { location, .. } => location.clone(),
+            Self::NotCallable { location, .. } => location.clone(),
+            Self::ArgumentCountMismatch { location, .. } => location.clone(),
+            Self::RecursiveType { location, .. } => location.clone(),
+            Self::InvalidPattern { location, .. } => location.clone(),
+            Self::PatternFieldCountMismatch { location, .. } => location.clone(),
+            Self::BindingInOrPattern { location } => location.clone(),
+            Self::NonExhaustiveMatch { location, .. } => location.clone(),
+            Self::RefutablePattern { location, .. } => location.clone(),
+            Self::InvalidTupleIndex { location, .. } => location.clone(),
+            Self::TypeAnnotationsNeeded { location } => location.clone(),
+            Self::NotIndexable { location, .. } => location.clone(),
+            Self::NotDereferenceable { location, .. } => location.clone(),
+            Self::MutationThroughImmutable { location, .. } => location.clone(),
+            Self::InvalidAssignmentTarget { location } => location.clone(),
+            Self::InvalidOperands { location, .. } => location.clone(),
+            Self::GenericArgumentCountMismatch { location, .. } => location.clone(),
+            Self::OuterTypeParameter { location, .. } => location.clone(),
+            Self::RecursiveInstantiation { location, .. } => location.clone(),
+            Self::NoField { location, .. } => location.clone(),
+            Self::NotATrait { location, .. } => location.clone(),
+            Self::MissingTraitMethods { location, .. } => location.clone(),
+            Self::NotTraitMember { location, .. } => location.clone(),
+            Self::ConflictingImpl { location, .. } => location.clone(),
+            Self::MissingImpl { location, .. } => location.clone(),
+            Self::NotObjectSafe { location, .. } => location.clone(),
+            Self::UnsizedTraitObject { location, .. } => location.clone(),
+            Self::InvalidImplTarget { location } => location.clone(),
+            Self::DuplicateMember { location, .. } => location.clone(),
+            Self::NoMethod { location, .. } => location.clone(),
+            Self::UnsupportedAbi { location, .. } => location.clone(),
+            Self::MissingFunctionBody { location, .. } => location.clone(),
+            Self::InvalidExternFunction { location, .. } => location.clone(),
+            Self::InvalidExport { location, .. } => location.clone(),
+            Self::InvalidVararg { location } => location.clone(),
+            Self::NotFfiSafe { location, .. } => location.clone(),
+            Self::UnknownAttribute { location, .. } => location.clone(),
+            Self::MisplacedAttribute { location, .. } => location.clone(),
+            Self::InvalidAttributeArguments { location, .. } => location.clone(),
+            Self::DeprecatedUse { location, .. } => location.clone(),
+            Self::UnreachablePattern { location } => location.clone(),
+            Self::ConflictingImport { location, .. } => location.clone(),
+            Self::UnresolvedImport { location, .. } => location.clone(),
+            Self::AmbiguousName { location, .. } => location.clone(),
+            Self::ImportCycle { location, .. } => location.clone(),
+            Self::UnusedImport { location, .. } => location.clone(),
+            Self::PrivateAccess { location, .. } => location.clone(),
+            Self::ConstantCycle { location, .. } => location.clone(),
+            Self::NotConstant { location, .. } => location.clone(),
+            Self::EvaluationFailed { location, .. } => location.clone(),
+            Self::InvalidArrayLength { location, .. } => location.clone(),
+            Self::ImmutableStatic { location, .. } => location.clone(),
+            Self::ArithmeticOverflow { location, .. } => location.clone(),
+            Self::DivisionByZero { location } => location.clone(),
+            Self::AliasCycle { location, .. } => location.clone(),
+            Self::PatternOutOfRange { location, .. } => location.clone(),
+            Self::AmbiguousMethod { location, .. } => location.clone(),
+            Self::EscapingClosure { location } => location.clone(),
+            Self::DuplicateSymbol { location, .. } => location.clone(),
+            Self::NoReturnReturns { location, .. } => location.clone(),
+            Self::ConflictingAttribute { location, .. } => location.clone(),
+            Self::PrivateReexport { location, .. } => location.clone(),
+            Self::StaticAssertionFailed { location, .. } => location.clone(),
+            Self::DuplicateDefinition { location, .. } => location.clone(),
+            Self::LiteralOutOfRange { location, .. } => location.clone(),
+            Self::PrivateTypeInPublicInterface { location, .. } => location.clone(),
+        }
     }
 
-    fn build_report(&self, session: &Session) -> ariadne::Report<'static, Loc> {
-        todo!()
+    fn build_report(&self, _session: &Session) -> ariadne::Report<'static, Loc> {
+        use ariadne::{Report, ReportKind, Config, Label};
+
+        let loc = self.get_location();
+        let mut report = Report::build(
+            if self.is_fatal() { ReportKind::Error } else { ReportKind::Warning },
+            loc.file,
+            loc.span.start
+        ).with_config(Config::default().with_cross_gap(true));
+
+        report = match self {
+            Self::UnresolvedName { name, .. } => {
+                report.with_code("R01").with_message(format!("Cannot find name `{name}` in this scope."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Not found in this scope.")
+                    .with_priority(4)
+                )
+            }
+            Self::UnresolvedType { name, .. } => {
+                report.with_code("R02").with_message(format!("Cannot find type `{name}` in this scope."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Not found in this scope.")
+                    .with_priority(4)
+                )
+            }
+            Self::TypeMismatch { expected, found, written, .. } => {
+                let written = written.as_deref();
+                let expected = written.and_then(|written| written.expected.as_deref()).map_or_else(|| expected.to_string(), String::clone);
+                let found = written.and_then(|written| written.found.as_deref()).map_or_else(|| found.to_string(), String::clone);
+                report.with_code("R03").with_message("Mismatched types.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Expected `{expected}` but found `{found}`."))
+                    .with_priority(4)
+                )
+            }
+            Self::NotCallable { found, .. } => {
+                report.with_code("R04").with_message("Attempt to call a value that is not callable.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("This has type `{found}` which cannot be called."))
+                    .with_priority(4)
+                )
+            }
+            Self::ArgumentCountMismatch { expected, found, .. } => {
+                report.with_code("R05").with_message("Wrong number of arguments.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Expected {expected} arguments but got {found}."))
+                    .with_priority(4)
+                )
+            }
+            Self::RecursiveType { name, .. } => {
+                report.with_code("R06").with_message(format!("Recursive type `{name}` has infinite size."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("This type contains itself without indirection.")
+                    .with_priority(4)
+                )
+            }
+            Self::InvalidPattern { ty, .. } => {
+                report.with_code("R07").with_message("Invalid pattern.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("This pattern cannot match values of type `{ty}`."))
+                    .with_priority(4)
+                )
+            }
+            Self::PatternFieldCountMismatch { expected, found, .. } => {
+                report.with_code("R08").with_message("Wrong number of fields in pattern.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Expected {expected} fields but got {found}."))
+                    .with_priority(4)
+                )
+            }
+            Self::BindingInOrPattern { .. } => {
+                report.with_code("R09").with_message("Bindings are not allowed inside of or-patterns.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Consider matching each alternative in a separate arm.")
+                    .with_priority(4)
+                )
+            }
+            Self::NonExhaustiveMatch { missing, .. } => {
+                report.with_code("R10").with_message("Non-exhaustive match.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Patterns {} are not covered.", missing.iter()
+                        .map(|pattern| format!("`{pattern}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")))
+                    .with_priority(4)
+                )
+            }
+            Self::RefutablePattern { missing, .. } => {
+                report.with_code("R12").with_message("Refutable pattern in binding.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Patterns {} are not covered, use match instead.", missing.iter()
+                        .map(|pattern| format!("`{pattern}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")))
+                    .with_priority(4)
+                )
+            }
+            Self::InvalidTupleIndex { ty, index, .. } => {
+                report.with_code("R13").with_message(format!("No field `{index}` on type `{ty}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Only tuples can be indexed with numbers, up to their length.")
+                    .with_priority(4)
+                )
+            }
+            Self::TypeAnnotationsNeeded { .. } => {
+                report.with_code("R14").with_message("Type annotations needed.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Cannot infer type of this expression.")
+                    .with_priority(4)
+                )
+            }
+            Self::NotIndexable { found, .. } => {
+                report.with_code("R15").with_message(format!("Cannot index into a value of type `{found}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Only arrays and slices can be indexed.")
+                    .with_priority(4)
+                )
+            }
+            Self::NotDereferenceable { found, .. } => {
+                report.with_code("R16").with_message(format!("Cannot dereference a value of type `{found}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Only references and pointers can be dereferenced.")
+                    .with_priority(4)
+                )
+            }
+            Self::MutationThroughImmutable { ty, .. } => {
+                report.with_code("R17").with_message(format!("Cannot mutate data behind `{ty}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("This is not a mutable reference or pointer.")
+                    .with_priority(4)
+                )
+            }
+            Self::InvalidAssignmentTarget { .. } => {
+                report.with_code("R18").with_message("Invalid left side of assignment.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Only dereferenced pointers and their elements can be assigned to.")
+                    .with_priority(4)
+                )
+            }
+            Self::InvalidOperands { operator, ty, .. } => {
+                report.with_code("R19").with_message(format!("Operator `{operator}` cannot be applied to `{ty}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Unsupported operand type.")
+                    .with_priority(4)
+                )
+            }
+            Self::GenericArgumentCountMismatch { expected, found, .. } => {
+                report.with_code("R20").with_message("Wrong number of type arguments.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Expected {expected} type arguments but got {found}."))
+                    .with_priority(4)
+                )
+            }
+            Self::OuterTypeParameter { name, .. } => {
+                report.with_code("R21").with_message(format!("Cannot use type parameter `{name}` of outer function."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Nested functions have to declare their own type parameters.")
+                    .with_priority(4)
+                )
+            }
+            Self::RecursiveInstantiation { name, .. } => {
+                report.with_code("R22").with_message(format!("Instantiating `{name}` requires infinitely many instances."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("This call instantiates the function with ever growing type arguments.")
+                    .with_priority(4)
+                )
+            }
+            Self::NoField { ty, name, .. } => {
+                report.with_code("R23").with_message(format!("No field `{name}` on type `{ty}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Only structs have named fields.")
+                    .with_priority(4)
+                )
+            }
+            Self::NotATrait { name, .. } => {
+                report.with_code("R24").with_message(format!("`{name}` is not a trait."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Only traits can be used as bounds and implemented.")
+                    .with_priority(4)
+                )
+            }
+            Self::MissingTraitMethods { trait_name, missing, .. } => {
+                report.with_code("R25").with_message(format!("Implementation of `{trait_name}` is missing methods."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Methods {} are not implemented.", missing.iter()
+                        .map(|name| format!("`{name}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")))
+                    .with_priority(4)
+                )
+            }
+            Self::NotTraitMember { name, trait_name, .. } => {
+                report.with_code("R26").with_message(format!("Method `{name}` is not a member of trait `{trait_name}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Implementations can only contain methods declared by their trait.")
+                    .with_priority(4)
+                )
+            }
+            Self::ConflictingImpl { trait_name, ty, .. } => {
+                report.with_code("R27").with_message(format!("Conflicting implementations of `{trait_name}` for `{ty}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Trait is already implemented for this type.")
+                    .with_priority(4)
+                )
+            }
+            Self::MissingImpl { trait_name, ty, .. } => {
+                report.with_code("R28").with_message(format!("Trait `{trait_name}` is not implemented for `{ty}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Type used here has to implement the trait.")
+                    .with_priority(4)
+                )
+            }
+            Self::NotObjectSafe { trait_name, method, reason, .. } => {
+                report.with_code("R29").with_message(format!("Trait `{trait_name}` cannot be used as `dyn {trait_name}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Method `{method}` {reason}."))
+                    .with_priority(4)
+                )
+            }
+            Self::UnsizedTraitObject { ty, .. } => {
+                report.with_code("R30").with_message(format!("Trait object `{ty}` used by value."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Trait objects can only be used behind a reference or pointer.")
+                    .with_priority(4)
+                )
+            }
+            Self::InvalidImplTarget { .. } => {
+                report.with_code("R31").with_message("Invalid target of inherent implementation.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Inherent implementations can only be written for non-generic structs and enums.")
+                    .with_priority(4)
+                )
+            }
+            Self::DuplicateMember { name, .. } => {
+                report.with_code("R32").with_message(format!("Duplicate definition of member `{name}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Type already has a member of this name.")
+                    .with_priority(4)
+                )
+            }
+            Self::NoMethod { ty, name, .. } => {
+                report.with_code("R33").with_message(format!("No method `{name}` found for type `{ty}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Method called here.")
+                    .with_priority(4)
+                )
+            }
+            Self::UnsupportedAbi { abi, .. } => {
+                report.with_code("R34").with_message(format!("Unsupported ABI `{abi}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Only the \"C\" ABI is supported.")
+                    .with_priority(4)
+                )
+            }
+            Self::MissingFunctionBody { name, .. } => {
+                report.with_code("R35").with_message(format!("Function `{name}` has no body."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Only functions declared with `@extern` can be left without a body.")
+                    .with_priority(4)
+                )
+            }
+            Self::InvalidExternFunction { name, reason, .. } => {
+                report.with_code("R36").with_message(format!("Invalid extern function `{name}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Extern function {reason}."))
+                    .with_priority(4)
+                )
+            }
+            Self::InvalidExport { name, reason, .. } => {
+                report.with_code("R39").with_message(format!("Function `{name}` cannot be exported."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Exported function {reason}."))
+                    .with_priority(4)
+                )
+            }
+            Self::InvalidVararg { .. } => {
+                report.with_code("R37").with_message("Variable arguments in function implemented in ramen.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Only functions declared with `@extern` can take variable arguments.")
+                    .with_priority(4)
+                )
+            }
+            Self::NotFfiSafe { ty, .. } => {
+                report.with_code("R38").with_message(format!("Type `{ty}` cannot be passed to or returned from C functions."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Only integers, floats, booleans and thin pointers can cross the C boundary, tuples of them can be returned too.")
+                    .with_priority(4)
+                )
+            }
+            Self::UnknownAttribute { name, .. } => {
+                report.with_code("R40").with_message(format!("Unknown attribute `@{name}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Attribute is not known to the compiler.")
+                    .with_priority(4)
+                )
+            }
+            Self::MisplacedAttribute { name, target, .. } => {
+                report.with_code("R41").with_message(format!("Attribute `@{name}` cannot be used on {target}."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Attribute has no meaning here.")
+                    .with_priority(4)
+                )
+            }
+            Self::InvalidAttributeArguments { name, usage, .. } => {
+                report.with_code("R42").with_message(format!("Invalid arguments of attribute `@{name}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Expected {usage}."))
+                    .with_priority(4)
+                )
+            }
+            Self::DeprecatedUse { name, note, .. } => {
+                report.with_code("R43").with_message(format!("Use of deprecated `{name}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message(note.clone().unwrap_or_else(|| "Item is marked `@deprecated`.".to_string()))
+                    .with_priority(4)
+                )
+            }
+            Self::UnreachablePattern { .. } => {
+                report.with_code("R11").with_message("Unreachable pattern.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Previous arms already match every value this one could.")
+                    .with_priority(4)
+                )
+            }
+            Self::ConflictingImport { name, .. } => {
+                report.with_code("R44").with_message(format!("Name `{name}` is already defined in this scope."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Imported name conflicts with another definition or import, consider importing it under another name with `as`.")
+                    .with_priority(4)
+                )
+            }
+            Self::UnresolvedImport { name, .. } => {
+                report.with_code("R45").with_message(format!("Cannot find `{name}` to import."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("No module or type has a member with this name.")
+                    .with_priority(4)
+                )
+            }
+            Self::AmbiguousName { name, .. } => {
+                report.with_code("R46").with_message(format!("Name `{name}` is ambiguous."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Multiple glob imports bring in different definitions with this name, import the intended one by name.")
+                    .with_priority(4)
+                )
+            }
+            Self::ImportCycle { name, .. } => {
+                report.with_code("R47").with_message(format!("Import of `{name}` depends on itself."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("This name is only ever imported, never defined.")
+                    .with_priority(4)
+                )
+            }
+            Self::UnusedImport { name, .. } => {
+                report.with_code("R48").with_message(format!("Unused import `{name}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Imported name is never used, consider removing it.")
+                    .with_priority(4)
+                )
+            }
+            Self::PrivateAccess { name, definition, .. } => {
+                report.with_code("R49").with_message(format!("`{name}` is private."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Used outside of the module it is defined in.")
+                    .with_priority(4)
+                )
+                .with_label(
+                    Label::new(definition.clone())
+                    .with_message("Defined here, consider making it `pub`.")
+                    .with_priority(3)
+                )
+            }
+            Self::ConstantCycle { name, .. } => {
+                report.with_code("R50").with_message(format!("Value of constant `{name}` depends on itself."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Evaluating this requires its own value.")
+                    .with_priority(4)
+                )
+            }
+            Self::NotConstant { what, .. } => {
+                report.with_code("R51").with_message(format!("{what} cannot be used in constant expressions."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Value of this is only known at runtime.")
+                    .with_priority(4)
+                )
+            }
+            Self::EvaluationFailed { reason, .. } => {
+                report.with_code("R52").with_message("Evaluation of constant expression failed.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message(reason)
+                    .with_priority(4)
+                )
+            }
+            Self::InvalidArrayLength { value, .. } => {
+                report.with_code("R53").with_message(format!("Invalid array length `{value}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Array length has to be non-negative and fit in 64 bits.")
+                    .with_priority(4)
+                )
+            }
+            Self::ImmutableStatic { name, .. } => {
+                report.with_code("R54").with_message(format!("Cannot mutate immutable static `{name}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Declare it as `static mut` to allow changing it.")
+                    .with_priority(4)
+                )
+            }
+            Self::ArithmeticOverflow { operator, ty, .. } => {
+                report.with_code("R55").with_message(format!("Overflow in constant evaluation of `{operator}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Result does not fit in `{ty}`."))
+                    .with_priority(4)
+                )
+            }
+            Self::DivisionByZero { .. } => {
+                report.with_code("R56").with_message("Division by zero in constant evaluation.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message("This is zero.")
+                    .with_priority(4)
+                )
+            }
+            Self::AliasCycle { name, .. } => {
+                report.with_code("R57").with_message(format!("Type alias `{name}` expands to itself."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Expanding this type leads back to the alias.")
+                    .with_priority(4)
+                )
+            }
+            Self::PatternOutOfRange { value, ty, .. } => {
+                report.with_code("R58").with_message(format!("Pattern `{value}` is out of range of `{ty}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("This value can never be matched.")
+                    .with_priority(4)
+                )
+            }
+            Self::AmbiguousMethod { name, traits, .. } => {
+                let traits = traits.iter().map(|name| format!("`{name}`")).collect::<Vec<_>>().join(", ");
+                report.with_code("R59").with_message(format!("Method `{name}` is ambiguous."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Traits {traits} all implement it, call it as `Trait.{name}` instead."))
+                    .with_priority(4)
+                )
+            }
+            Self::EscapingClosure { .. } => {
+                report.with_code("R60").with_message("Closure cannot outlive variables it refers to.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message("This closure refers to variables of the returning function, use `move` closure to copy them instead.")
+                    .with_priority(4)
+                )
+            }
+            Self::DuplicateSymbol { symbol, .. } => {
+                report.with_code("R61").with_message(format!("Symbol `{symbol}` is defined more than once."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Another function is already exported or declared as extern under this name.")
+                    .with_priority(4)
+                )
+            }
+            Self::NoReturnReturns { name, .. } => {
+                report.with_code("R62").with_message(format!("Function `{name}` is marked `@noreturn`, but can return."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Function returns to its caller here, call another `@noreturn` function before.")
+                    .with_priority(4)
+                )
+            }
+            Self::ConflictingAttribute { name, .. } => {
+                report.with_code("R63").with_message(format!("Attribute `@{name}` is used more than once."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("This conflicts with the earlier use on the same item.")
+                    .with_priority(4)
+                )
+            }
+            Self::PrivateReexport { name, definition, .. } => {
+                report.with_code("R64").with_message(format!("`{name}` cannot be re-exported."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("This import is more visible than the definition it brings in.")
+                    .with_priority(4)
+                )
+                .with_label(
+                    Label::new(definition.clone())
+                    .with_message("Defined here, consider making it as visible as the import.")
+                    .with_priority(3)
+                )
+            }
+            Self::StaticAssertionFailed { name, note, .. } => {
+                report.with_code("R65").with_message(format!("Static assertion `{name}` failed."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message(note.clone().unwrap_or_else(|| "This evaluates to `false`.".to_string()))
+                    .with_priority(4)
+                )
+            }
+            Self::DuplicateDefinition { name, .. } => {
+                report.with_code("R66").with_message(format!("`{name}` is defined more than once."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Another item of this scope already has this name.")
+                    .with_priority(4)
+                )
+            }
+            Self::LiteralOutOfRange { value, ty, .. } => {
+                report.with_code("R67").with_message(format!("Literal `{value}` is out of range of `{ty}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Value does not fit in `{ty}`, consider using a wider integer type."))
+                    .with_priority(4)
+                )
+            }
+            Self::PrivateTypeInPublicInterface { name, definition, .. } => {
+                report.with_code("R68").with_message(format!("Private type `{name}` in public interface."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Function is visible in more places than this type.")
+                    .with_priority(4)
+                )
+                .with_label(
+                    Label::new(definition.clone())
+                    .with_message("Defined here, consider making it as visible as the function.")
+                    .with_priority(3)
+                )
+            }
+        };
+
+        report.finish()
     }
-}
\ No newline at end of file
+}
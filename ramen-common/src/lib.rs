@@ -8,6 +8,8 @@ pub mod visitor;
 pub mod scope;
 pub mod defs;
 pub mod types;
+pub mod attributes;
+pub mod consts;
 
 pub extern crate ariadne;
 
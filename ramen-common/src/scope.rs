@@ -1,18 +1,56 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, fmt::Write, ptr, rc::Rc};
 
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 
-use crate::ast::NodeId;
+use crate::{ast::NodeId, defs::DefId, session::AnyId};
 
 #[derive(Debug, Clone)]
 pub struct Scope {
     pub parent: Option<ScopeRef>,
-    pub namespaces: [RefCell<HashMap<String, NodeId>>; 2],
+    pub namespaces: [RefCell<HashMap<String, DefId>>; 2],
+    /// Names brought in by `use`, they are shadowed by names defined in this scope.
+    pub imports: [RefCell<HashMap<String, Import>>; 2],
     pub name: Option<String>
 }
 
 pub type ScopeRef = Rc<Scope>;
 
+/// Name imported into scope by `use`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub target: DefId,
+    /// Use tree which brought the name in.
+    pub tree: NodeId,
+    /// Glob imports are shadowed by imports of single names.
+    pub is_glob: bool,
+    /// Another glob import brings in different definition under the same name, so the name cannot be used.
+    pub is_ambiguous: bool
+}
+
+/// What a name found in scope refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Binding {
+    Definition(DefId),
+    Import(Import)
+}
+
+impl Binding {
+    pub fn target(&self) -> DefId {
+        match self {
+            Self::Definition(def_id) => *def_id,
+            Self::Import(import) => import.target
+        }
+    }
+
+    /// Node whose privacy decides where the name can be used, imports have their own.
+    pub fn privacy_id(&self) -> AnyId {
+        match self {
+            Self::Definition(def_id) => (*def_id).into(),
+            Self::Import(import) => import.tree.into()
+        }
+    }
+}
+
 impl Scope {
     pub const NS_NAMES: usize = 0;
     pub const NS_TYPES: usize = 1;
@@ -24,7 +62,11 @@ impl Scope {
                 RefCell::default(),
                 RefCell::default(),
             ],
-            name: None
+            imports: [
+                RefCell::default(),
+                RefCell::default(),
+            ],
+            name
         }
     }
 
@@ -36,54 +78,140 @@ impl Scope {
         &self,
         namespace: usize,
         name: impl AsRef<str>,
-        id: NodeId
+        id: DefId
     ) {
         let mut ns = self.namespaces[namespace].borrow_mut();
         ns.insert(name.as_ref().to_string(), id);
     }
 
-    fn search<F, U>(
-        &self,
-        namespace: usize,
-        name: impl AsRef<str>,
-        transform: F,
-        default: U
-    ) -> U
-    where F: Fn(NodeId) -> U {
-        if let Some(symbol) = self.namespaces[namespace].borrow().get(name.as_ref()) {
-            transform(*symbol)
-        } else if let Some(parent) = &self.parent {
-            parent.search(namespace, name, transform, default)
-        } else { 
-            default
+    fn get_binding(&self, namespace: usize, name: &str) -> Option<Binding> {
+        if let Some(symbol) = self.namespaces[namespace].borrow().get(name) {
+            return Some(Binding::Definition(*symbol));
         }
+        self.imports[namespace].borrow().get(name).cloned().map(Binding::Import)
+    }
+
+    /// Looks up name in given namespace of this scope or any of its parents, together with the import it comes from.
+    pub fn search_binding(&self, namespace: usize, name: impl AsRef<str>) -> Option<Binding> {
+        match self.get_binding(namespace, name.as_ref()) {
+            Some(binding) => Some(binding),
+            None => self.parent.as_ref()?.search_binding(namespace, name)
+        }
+    }
+
+    /// Looks up name in given namespace of this scope or any of its parents.
+    pub fn search(&self, namespace: usize, name: impl AsRef<str>) -> Option<DefId> {
+        self.search_binding(namespace, name).map(|binding| binding.target())
+    }
+
+    /// Looks up member of this scope, without falling back to parents.
+    pub fn get_local_binding(&self, namespace: usize, name: impl AsRef<str>) -> Option<Binding> {
+        self.get_binding(namespace, name.as_ref())
+    }
+
+    /// Looks up name only in this scope, without falling back to parents.
+    /// Used to resolve trailing segments of paths like `Shape.Circle`.
+    pub fn get_local(&self, namespace: usize, name: impl AsRef<str>) -> Option<DefId> {
+        self.get_local_binding(namespace, name).map(|binding| binding.target())
+    }
+
+    /// Members of this scope in given namespace, as imported by glob imports of it.
+    pub fn get_members(&self, namespace: usize) -> Vec<(String, Binding)> {
+        let definitions = self.namespaces[namespace].borrow().iter()
+            .map(|(name, def_id)| (name.clone(), Binding::Definition(*def_id)))
+            .collect::<Vec<_>>();
+        let imports = self.imports[namespace].borrow().iter()
+            .filter(|(_, import)| !import.is_ambiguous)
+            .map(|(name, import)| (name.clone(), Binding::Import(import.clone())))
+            .collect::<Vec<_>>();
+        definitions.into_iter().chain(imports).collect()
+    }
+
+    /// Binds imported name, names defined in this scope shadow glob imports and glob imports are shadowed by single imports.
+    /// Returns whether anything changed, or the conflicting definition if the name is already bound to something else.
+    pub fn import(&self, namespace: usize, name: impl AsRef<str>, import: Import) -> Result<bool, DefId> {
+        let name = name.as_ref();
+        if let Some(&def_id) = self.namespaces[namespace].borrow().get(name) {
+            return if import.is_glob || def_id == import.target { Ok(false) } else { Err(def_id) };
+        }
+
+        let mut imports = self.imports[namespace].borrow_mut();
+        let Some(existing) = imports.get_mut(name) else {
+            imports.insert(name.to_string(), import);
+            return Ok(true);
+        };
+        match (existing.is_glob, import.is_glob) {
+            (true, false) => {
+                *existing = import;
+                Ok(true)
+            }
+            (false, false) if existing.target != import.target => Err(existing.target),
+            (true, true) if existing.target != import.target && !existing.is_ambiguous => {
+                existing.is_ambiguous = true;
+                Ok(true)
+            }
+            _ => Ok(false)
+        }
+    }
+
+    /// Names bound in given namespace of this scope, including imported ones, in alphabetical order.
+    pub fn names(&self, namespace: usize) -> Vec<String> {
+        let mut names = self.get_members(namespace).into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// This scope followed by its parents, up to the outermost scope.
+    pub fn ancestors(&self) -> impl Iterator<Item = &Scope> {
+        std::iter::successors(Some(self), |scope| scope.parent.as_deref())
+    }
+
+    /// Everything that can be named from this scope in given namespace, in alphabetical order.
+    /// Names bound in inner scopes shadow the same names of their parents, like they do when searching.
+    pub fn visible_members(&self, namespace: usize) -> Vec<(String, Binding)> {
+        let mut members = HashMap::new();
+        for scope in self.ancestors() {
+            for (name, binding) in scope.get_members(namespace) {
+                members.entry(name).or_insert(binding);
+            }
+        }
+        let mut members = members.into_iter().collect::<Vec<_>>();
+        members.sort_by(|(a, _), (b, _)| a.cmp(b));
+        members
     }
 
     // ==< Names >==
-    pub fn define_name(&self, name: impl AsRef<str>, id: NodeId) {
+    pub fn define_name(&self, name: impl AsRef<str>, id: DefId) {
         self.define(Scope::NS_NAMES, name, id);
     }
-    pub fn search_name(&self, name: impl AsRef<str>) -> Option<NodeId> {
-        self.search(Scope::NS_NAMES, name, Some, None)
+    pub fn search_name(&self, name: impl AsRef<str>) -> Option<DefId> {
+        self.search(Scope::NS_NAMES, name)
     }
 
     // ==< Types >==
+    pub fn define_type(&self, name: impl AsRef<str>, id: DefId) {
+        self.define(Scope::NS_TYPES, name, id);
+    }
+    pub fn search_type(&self, name: impl AsRef<str>) -> Option<DefId> {
+        self.search(Scope::NS_TYPES, name)
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ScopeMapRef {
-    scopes: RefCell<HashMap<NodeId, ScopeRef>>,
+    /// Scopes of definitions and of nodes like closures, which have names of their own.
+    scopes: RefCell<HashMap<AnyId, ScopeRef>>,
 }
 
 impl ScopeMapRef {
     pub fn new() -> Self {
-        let self_ = Self {
+        Self {
             scopes: RefCell::default(),
-        };
-        self_
+        }
     }
 
-    pub fn get_or_new(&self, id: NodeId, parent: Option<ScopeRef>, name: Option<String>) -> ScopeRef {
+    pub fn get_or_new(&self, id: impl Into<AnyId>, parent: Option<ScopeRef>, name: Option<String>) -> ScopeRef {
+        let id = id.into();
         let scope = self.scopes.borrow().get(&id).cloned();
         match scope {
             Some(scope) => scope,
@@ -91,7 +219,8 @@ impl ScopeMapRef {
         }
     }
 
-    pub fn add(&self, id: NodeId, parent: Option<ScopeRef>, name: Option<String>) -> ScopeRef {
+    pub fn add(&self, id: impl Into<AnyId>, parent: Option<ScopeRef>, name: Option<String>) -> ScopeRef {
+        let id = id.into();
         let scope = Scope::new_ref(parent, name);
         let previous = self.scopes.borrow_mut().insert(id, scope.clone());
         #[cfg(debug_assertions)]
@@ -101,7 +230,69 @@ impl ScopeMapRef {
         scope
     }
 
-    pub fn get(&self, id: NodeId) -> Option<ScopeRef> {
-        self.scopes.borrow().get(&id).cloned()
+    pub fn get(&self, id: impl Into<AnyId>) -> Option<ScopeRef> {
+        self.scopes.borrow().get(&id.into()).cloned()
+    }
+
+    /// Tree of all scopes in the map with the names they bind, each scope indented under its parent.
+    /// Parents which are not in the map themselves, like the global scope, are shown without their id.
+    pub fn dump(&self) -> String {
+        let scopes = self.scopes.borrow();
+        let mut tree = ScopeTree {
+            ids: scopes.iter().map(|(id, scope)| (Rc::as_ptr(scope), *id)).collect(),
+            children: HashMap::new()
+        };
+
+        let mut roots = Vec::new();
+        let mut seen = HashSet::new();
+        for scope in scopes.values() {
+            for ancestor in scope.ancestors() {
+                if !seen.insert(ptr::from_ref(ancestor)) { break; }
+                match &ancestor.parent {
+                    Some(parent) => tree.children.entry(Rc::as_ptr(parent)).or_default().push(ancestor),
+                    None => roots.push(ancestor)
+                }
+            }
+        }
+
+        let mut output = String::new();
+        for root in tree.sorted(roots) {
+            tree.write(root, 0, &mut output);
+        }
+        output
+    }
+}
+
+/// Scopes of `ScopeMapRef` linked from parents to children, for dumping them.
+struct ScopeTree<'a> {
+    ids: HashMap<*const Scope, AnyId>,
+    children: HashMap<*const Scope, Vec<&'a Scope>>
+}
+
+impl<'a> ScopeTree<'a> {
+    fn sorted(&self, mut scopes: Vec<&'a Scope>) -> Vec<&'a Scope> {
+        scopes.sort_by_key(|scope| self.ids.get(&ptr::from_ref(*scope)));
+        scopes
+    }
+
+    fn write(&self, scope: &'a Scope, depth: usize, output: &mut String) {
+        let indent = "  ".repeat(depth);
+        let name = scope.name.as_deref().unwrap_or("<anonymous>");
+        match self.ids.get(&ptr::from_ref(scope)) {
+            Some(id) => writeln!(output, "{indent}{name} {id}"),
+            None => writeln!(output, "{indent}{name}")
+        }.unwrap();
+
+        for (label, namespace) in [("names", Scope::NS_NAMES), ("types", Scope::NS_TYPES)] {
+            let names = scope.names(namespace);
+            if !names.is_empty() {
+                writeln!(output, "{indent}  {label}: {}", names.join(", ")).unwrap();
+            }
+        }
+
+        let children = self.children.get(&ptr::from_ref(scope)).cloned().unwrap_or_default();
+        for child in self.sorted(children) {
+            self.write(child, depth + 1, output);
+        }
     }
 }
\ No newline at end of file
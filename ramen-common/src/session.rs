@@ -1,9 +1,9 @@
-use std::cell::{Cell, RefCell};
+use std::{cell::{Cell, RefCell}, path::PathBuf, rc::Rc};
 
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use slotmap::SlotMap;
 
-use crate::{ast::NodeId, defs::Definition, error::Diagnostic, scope::ScopeMapRef, types::RamenType};
+use crate::{ast::{self, NodeId, NodeIds, Visibility}, attributes::{Abi, KnownAttribute}, consts::ConstValue, defs::{DefId, DefIds, Definition, Implementation, Privacy, TraitDefinition}, error::Diagnostic, scope::ScopeMapRef, types::RamenType, Loc};
 
 slotmap::new_key_type! {
     pub struct SourceId;
@@ -16,16 +16,107 @@ impl SourceId {
 }
 
 /// Compiler session containing cache, name bindings and more.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Session {
     pub sources: RefCell<SlotMap<SourceId, RamenSource>>,
     pub errors: Cell<usize>,
+    pub warnings: Cell<usize>,
+    pub options: BuildOptions,
+
+    /// Ids of parsed nodes and of definitions, counted separately by every session.
+    pub node_ids: NodeIds,
+    pub def_ids: DefIds,
 
     pub scopes: ScopeMapRef,
-    pub refs: RefCell<HashMap<NodeId, NodeId>>,
-    pub defs: RefCell<HashMap<NodeId, Definition>>,
-    pub types: RefCell<HashMap<NodeId, RamenType>>,
-    pub symbols: RefCell<HashMap<NodeId, String>>
+    /// Definitions nodes refer to, like the variable named by a path.
+    pub refs: RefCell<HashMap<NodeId, DefId>>,
+    pub defs: RefCell<HashMap<DefId, Definition>>,
+    /// Types of expressions and other nodes as well as of definitions.
+    pub types: RefCell<HashMap<AnyId, RamenType>>,
+    /// Structs and enums without their fields, available before the declaration is complete
+    /// so that types behind pointers can refer back to it, like `*Node` in `Node`.
+    pub type_headers: RefCell<HashMap<DefId, RamenType>>,
+    /// Declared types of variables, parameters and globals and return types of functions
+    /// the way they were written, for those that name type aliases.
+    pub written_types: RefCell<HashMap<DefId, Rc<String>>>,
+    pub symbols: RefCell<HashMap<DefId, String>>,
+    /// Functions known by their plain name, exported ones and `@extern` declarations, keyed by the symbol.
+    pub plain_symbols: RefCell<HashMap<String, DefId>>,
+    /// Type arguments of generic functions, keyed by the path referring to the function.
+    pub type_arguments: RefCell<HashMap<NodeId, Vec<RamenType>>>,
+    /// Every distinct list of type arguments a generic function is used with, keyed by its definition.
+    pub instances: RefCell<HashMap<DefId, Vec<Vec<RamenType>>>>,
+    /// Number of trailing segments of a path expression that access fields of the variable it names, like in `pair.first`.
+    pub path_fields: RefCell<HashMap<NodeId, usize>>,
+    /// Types expressions are implicitly converted to, like `&Point` to `&dyn Show`.
+    pub coercions: RefCell<HashMap<NodeId, RamenType>>,
+    /// Method lists of traits, keyed by the trait definition.
+    pub traits: RefCell<HashMap<DefId, TraitDefinition>>,
+    /// Implementations of each trait, keyed by the trait definition.
+    pub impls: RefCell<HashMap<DefId, Vec<Implementation>>>,
+    /// Traits type parameters are bounded by, keyed by the type parameter definition.
+    pub trait_bounds: RefCell<HashMap<DefId, Vec<DefId>>>,
+    /// Variables used by closures but defined outside of them in order of first use, keyed by the closure expression.
+    pub captures: RefCell<HashMap<NodeId, Vec<DefId>>>,
+    /// Validated attributes of items, keyed by the item definition.
+    pub attributes: RefCell<HashMap<DefId, Vec<KnownAttribute>>>,
+    /// Use trees whose imported names were used, the rest is reported as unused.
+    pub used_imports: RefCell<HashSet<NodeId>>,
+    /// Privacy of definitions, struct fields and use trees, those without it are visible wherever they can be named.
+    pub privacy: RefCell<HashMap<AnyId, Privacy>>,
+    /// Values of constants and initializers of statics keyed by their definition, and of array lengths keyed by the length expression.
+    pub constants: RefCell<HashMap<AnyId, ConstValue>>,
+    /// Items and declarations of `const func`s, which are interpreted when called from constant expressions, keyed by their definition.
+    pub const_functions: RefCell<HashMap<DefId, (NodeId, Rc<ast::Function>)>>,
+    /// `const func`s whose bodies were type checked early, as constant expressions called them before type resolution got to them.
+    pub checked_const_functions: RefCell<HashSet<DefId>>
+}
+
+/// Key of tables filled for both nodes and definitions, like types of expressions and of variables.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AnyId {
+    Node(NodeId),
+    Def(DefId)
+}
+
+impl std::fmt::Display for AnyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Node(id) => id.fmt(f),
+            Self::Def(id) => id.fmt(f)
+        }
+    }
+}
+
+impl From<NodeId> for AnyId {
+    fn from(id: NodeId) -> Self {
+        Self::Node(id)
+    }
+}
+
+impl From<DefId> for AnyId {
+    fn from(id: DefId) -> Self {
+        Self::Def(id)
+    }
+}
+
+/// Options affecting generated code, usually set from command line.
+#[derive(Debug, Clone)]
+pub struct BuildOptions {
+    /// Check bounds at runtime when indexing or slicing arrays and slices.
+    /// Can be also disabled for single function with `@no_bounds_check`.
+    pub bounds_checks: bool,
+    /// Number of expressions constant evaluation can go through before giving up, which stops runaway recursion.
+    pub const_eval_steps: usize
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        Self {
+            bounds_checks: true,
+            const_eval_steps: 1_000_000
+        }
+    }
 }
 
 impl Session {
@@ -33,32 +124,54 @@ impl Session {
         Self {
             sources: RefCell::default(),
             errors: Cell::new(0),
+            warnings: Cell::new(0),
+            options: BuildOptions::default(),
+
+            node_ids: NodeIds::default(),
+            def_ids: DefIds::default(),
 
             scopes: ScopeMapRef::new(),
             refs: RefCell::default(),
             defs: RefCell::default(),
             types: RefCell::default(),
+            type_headers: RefCell::default(),
+            written_types: RefCell::default(),
             symbols: RefCell::default(),
+            plain_symbols: RefCell::default(),
+            type_arguments: RefCell::default(),
+            instances: RefCell::default(),
+            path_fields: RefCell::default(),
+            coercions: RefCell::default(),
+            traits: RefCell::default(),
+            impls: RefCell::default(),
+            trait_bounds: RefCell::default(),
+            captures: RefCell::default(),
+            attributes: RefCell::default(),
+            used_imports: RefCell::default(),
+            privacy: RefCell::default(),
+            constants: RefCell::default(),
+            const_functions: RefCell::default(),
+            checked_const_functions: RefCell::default()
         }
     }
 
     // ==< Ref-related >==
-    pub fn set_ref(&self, source: NodeId, target: NodeId) {
+    pub fn set_ref(&self, source: NodeId, target: DefId) {
         self.refs.borrow_mut().insert(source, target);
     }
 
-    pub fn get_ref_target(&self, id: NodeId) -> Option<NodeId> {
+    pub fn get_ref_target(&self, id: NodeId) -> Option<DefId> {
         self.refs.borrow().get(&id).cloned()
     }
 
     // ==< Def-related >==
-    pub fn alloc_def(&self, ref_id: NodeId) -> NodeId {
-        let def_id = NodeId::next();
+    pub fn alloc_def(&self, ref_id: NodeId) -> DefId {
+        let def_id = self.def_ids.next();
         self.set_ref(ref_id, def_id);
         def_id
     }
 
-    pub fn set_def(&self, def_id: NodeId, def: Definition) {
+    pub fn set_def(&self, def_id: DefId, def: Definition) {
         #[cfg(debug_assertions)]
         if let Some(existing) = self.defs.borrow().get(&def_id) {
             panic!("Trying to override definition {def_id} with {def:?}, but is is already set to {existing:?}");
@@ -67,42 +180,279 @@ impl Session {
         self.defs.borrow_mut().insert(def_id, def);
     }
 
-    pub fn get_def(&self, def_id: NodeId) -> Option<Definition> {
+    pub fn get_def(&self, def_id: DefId) -> Option<Definition> {
         self.defs.borrow().get(&def_id).cloned()
     }
 
     // ==< Type-related >==
-    pub fn try_bind_type(&self, target: NodeId, source: NodeId) -> bool {
+    pub fn try_bind_type(&self, target: impl Into<AnyId>, source: impl Into<AnyId>) -> bool {
         if let Some(ty) = self.get_type(source) {
             self.set_type(target, ty);
             true
         } else { false }
     }
-    pub fn set_type(&self, id: NodeId, ty: RamenType) {
-        self.types.borrow_mut().insert(id, ty);
+    pub fn set_type(&self, id: impl Into<AnyId>, ty: RamenType) {
+        self.types.borrow_mut().insert(id.into(), ty);
+    }
+    pub fn get_type(&self, id: impl Into<AnyId>) -> Option<RamenType> {
+        self.types.borrow_mut().get(&id.into()).cloned()
+    }
+
+    pub fn set_type_header(&self, def_id: DefId, ty: RamenType) {
+        self.type_headers.borrow_mut().insert(def_id, ty);
+    }
+
+    pub fn get_type_header(&self, def_id: DefId) -> Option<RamenType> {
+        self.type_headers.borrow().get(&def_id).cloned()
+    }
+
+    pub fn set_written_type(&self, def_id: DefId, written: Rc<String>) {
+        self.written_types.borrow_mut().insert(def_id, written);
+    }
+
+    pub fn get_written_type(&self, def_id: DefId) -> Option<Rc<String>> {
+        self.written_types.borrow().get(&def_id).cloned()
     }
-    pub fn get_type(&self, id: NodeId) -> Option<RamenType> {
-        self.types.borrow_mut().get(&id).cloned()
+
+    /// Struct or enum together with its fields, which are missing if the type was resolved
+    /// through a pointer before its declaration was complete. Other types are returned as they are.
+    pub fn complete_type(&self, ty: &RamenType) -> RamenType {
+        match ty.def_id().and_then(|def_id| self.get_type(def_id)) {
+            Some(declared_type) => declared_type.apply_type_arguments(ty.type_arguments()),
+            None => ty.clone()
+        }
     }
 
     // ==< Symbol-related >==
-    pub fn set_symbol(&self, node: NodeId, symbol: impl AsRef<str>) {
-        self.symbols.borrow_mut().insert(node, symbol.as_ref().to_string());
+    pub fn set_symbol(&self, def_id: DefId, symbol: impl AsRef<str>) {
+        self.symbols.borrow_mut().insert(def_id, symbol.as_ref().to_string());
+    }
+
+    pub fn get_symbol(&self, def_id: DefId) -> Option<String> {
+        self.symbols.borrow().get(&def_id).cloned()
+    }
+
+    /// Records function known by its plain name, returns other function already known by it if there is one.
+    pub fn add_plain_symbol(&self, symbol: &str, def_id: DefId) -> Option<DefId> {
+        let mut plain_symbols = self.plain_symbols.borrow_mut();
+        match plain_symbols.get(symbol) {
+            Some(&existing) if existing != def_id => Some(existing),
+            _ => {
+                plain_symbols.insert(symbol.to_string(), def_id);
+                None
+            }
+        }
+    }
+
+    /// Type with structs, enums and traits named by their symbols, for use in symbols of other items.
+    /// Unlike display names, these differ between same-named types of different modules.
+    pub fn type_symbol(&self, ty: &RamenType) -> String {
+        ty.display_with(&|def_id, name| self.get_symbol(def_id).unwrap_or_else(|| name.to_string())).to_string()
+    }
+
+    // ==< Generics-related >==
+    pub fn set_type_arguments(&self, id: NodeId, arguments: Vec<RamenType>) {
+        self.type_arguments.borrow_mut().insert(id, arguments);
+    }
+
+    pub fn get_type_arguments(&self, id: NodeId) -> Option<Vec<RamenType>> {
+        self.type_arguments.borrow().get(&id).cloned()
+    }
+
+    /// Records new instance of generic definition, returns `false` if it was already known.
+    pub fn add_instance(&self, def_id: DefId, arguments: Vec<RamenType>) -> bool {
+        let mut instances = self.instances.borrow_mut();
+        let def_instances = instances.entry(def_id).or_default();
+        if def_instances.contains(&arguments) { return false; }
+
+        def_instances.push(arguments);
+        true
+    }
+
+    pub fn get_instances(&self, def_id: DefId) -> Vec<Vec<RamenType>> {
+        self.instances.borrow().get(&def_id).cloned().unwrap_or_default()
+    }
+
+    // ==< Field-related >==
+    pub fn set_path_fields(&self, path_id: NodeId, count: usize) {
+        self.path_fields.borrow_mut().insert(path_id, count);
+    }
+
+    pub fn get_path_fields(&self, path_id: NodeId) -> usize {
+        self.path_fields.borrow().get(&path_id).cloned().unwrap_or_default()
+    }
+
+    pub fn set_coercion(&self, id: NodeId, target: RamenType) {
+        self.coercions.borrow_mut().insert(id, target);
+    }
+
+    pub fn get_coercion(&self, id: NodeId) -> Option<RamenType> {
+        self.coercions.borrow().get(&id).cloned()
+    }
+
+    // ==< Trait-related >==
+    pub fn set_trait(&self, def_id: DefId, definition: TraitDefinition) {
+        self.traits.borrow_mut().insert(def_id, definition);
+    }
+
+    pub fn get_trait(&self, def_id: DefId) -> Option<TraitDefinition> {
+        self.traits.borrow().get(&def_id).cloned()
+    }
+
+    pub fn add_impl(&self, trait_id: DefId, implementation: Implementation) {
+        self.impls.borrow_mut().entry(trait_id).or_default().push(implementation);
     }
 
-    pub fn get_symbol(&self, node: NodeId) -> Option<String> {
-        self.symbols.borrow().get(&node).cloned()
+    /// Implementation of the trait for exactly the given type.
+    pub fn find_impl(&self, trait_id: DefId, ty: &RamenType) -> Option<Implementation> {
+        self.impls.borrow().get(&trait_id)?.iter()
+            .find(|implementation| implementation.self_type == *ty)
+            .cloned()
+    }
+
+    pub fn set_trait_bounds(&self, parameter_id: DefId, bounds: Vec<DefId>) {
+        self.trait_bounds.borrow_mut().insert(parameter_id, bounds);
+    }
+
+    pub fn get_trait_bounds(&self, parameter_id: DefId) -> Vec<DefId> {
+        self.trait_bounds.borrow().get(&parameter_id).cloned().unwrap_or_default()
+    }
+
+    // ==< Closure-related >==
+    pub fn add_capture(&self, closure_id: NodeId, def_id: DefId) {
+        let mut captures = self.captures.borrow_mut();
+        let captures = captures.entry(closure_id).or_default();
+        if !captures.contains(&def_id) {
+            captures.push(def_id);
+        }
+    }
+
+    pub fn get_captures(&self, closure_id: NodeId) -> Vec<DefId> {
+        self.captures.borrow().get(&closure_id).cloned().unwrap_or_default()
+    }
+
+    // ==< Attribute-related >==
+    pub fn set_attributes(&self, def_id: DefId, attributes: Vec<KnownAttribute>) {
+        self.attributes.borrow_mut().insert(def_id, attributes);
+    }
+
+    pub fn get_attributes(&self, def_id: DefId) -> Vec<KnownAttribute> {
+        self.attributes.borrow().get(&def_id).cloned().unwrap_or_default()
+    }
+
+    /// First attribute of the definition `query` maps to some value, like `@inline` to its mode.
+    pub fn find_attribute<T>(&self, def_id: DefId, query: impl Fn(&KnownAttribute) -> Option<T>) -> Option<T> {
+        self.attributes.borrow().get(&def_id)?.iter().find_map(query)
+    }
+
+    pub fn has_attribute(&self, def_id: DefId, attribute: &KnownAttribute) -> bool {
+        self.attributes.borrow().get(&def_id).is_some_and(|attributes| attributes.contains(attribute))
+    }
+
+    /// Calling convention of function implemented outside of ramen.
+    pub fn get_extern(&self, def_id: DefId) -> Option<Abi> {
+        self.find_attribute(def_id, |attribute| match attribute {
+            KnownAttribute::Extern(abi) => Some(*abi),
+            _ => None
+        })
+    }
+
+    /// Whether function is visible outside of the compiled object under its plain name.
+    pub fn is_exported(&self, def_id: DefId) -> bool {
+        self.has_attribute(def_id, &KnownAttribute::Export) || self.has_attribute(def_id, &KnownAttribute::NoMangle)
+    }
+
+    // ==< Import-related >==
+    pub fn mark_import_used(&self, tree: NodeId) {
+        self.used_imports.borrow_mut().insert(tree);
+    }
+
+    pub fn is_import_used(&self, tree: NodeId) -> bool {
+        self.used_imports.borrow().contains(&tree)
+    }
+
+    // ==< Privacy-related >==
+    pub fn set_privacy(&self, id: impl Into<AnyId>, privacy: Privacy) {
+        self.privacy.borrow_mut().insert(id.into(), privacy);
+    }
+
+    pub fn get_privacy(&self, id: impl Into<AnyId>) -> Option<Privacy> {
+        self.privacy.borrow().get(&id.into()).cloned()
+    }
+
+    /// Whether `id` can be used inside of module `from`, private definitions are visible in their module and modules nested in it,
+    /// `pub(module)` ones anywhere in the module tree they are defined in.
+    pub fn is_visible(&self, id: impl Into<AnyId>, from: DefId) -> bool {
+        let Some(privacy) = self.get_privacy(id) else { return true };
+        match privacy.visibility {
+            Visibility::Public => true,
+            Visibility::Module => self.enclosing_modules(from).last() == self.enclosing_modules(privacy.module).last(),
+            Visibility::Private => self.enclosing_modules(from).any(|module| module == privacy.module)
+        }
+    }
+
+    /// The module itself followed by modules it is nested in, up to the root of its module tree.
+    fn enclosing_modules(&self, module: DefId) -> impl Iterator<Item = DefId> + '_ {
+        std::iter::successors(Some(module), |&current| self.get_privacy(current).map(|parent| parent.module))
+    }
+
+    // ==< Constant-related >==
+    pub fn set_constant(&self, id: impl Into<AnyId>, value: ConstValue) {
+        self.constants.borrow_mut().insert(id.into(), value);
+    }
+
+    pub fn get_constant(&self, id: impl Into<AnyId>) -> Option<ConstValue> {
+        self.constants.borrow().get(&id.into()).cloned()
+    }
+
+    pub fn add_const_function(&self, def_id: DefId, item_id: NodeId, function: ast::Function) {
+        self.const_functions.borrow_mut().insert(def_id, (item_id, Rc::new(function)));
+    }
+
+    /// Item and declaration of `const func`, `None` for any other function.
+    pub fn get_const_function(&self, def_id: DefId) -> Option<(NodeId, Rc<ast::Function>)> {
+        self.const_functions.borrow().get(&def_id).cloned()
+    }
+
+    /// Marks body of `const func` as type checked, returns whether it was not checked before.
+    pub fn mark_const_function_checked(&self, def_id: DefId) -> bool {
+        self.checked_const_functions.borrow_mut().insert(def_id)
+    }
+
+    // ==< Source-related >==
+    pub fn add_source(&self, path: PathBuf, text: String) -> SourceId {
+        self.sources.borrow_mut().insert(RamenSource { path, text })
+    }
+
+    pub fn get_source_text(&self, id: SourceId) -> Option<String> {
+        self.sources.borrow().get(id).map(|source| source.text.clone())
+    }
+
+    /// Start of the location as `path:line:column`, for messages shown outside of diagnostics, like runtime panics.
+    pub fn describe_location(&self, location: &Loc) -> String {
+        let sources = self.sources.borrow();
+        let Some(source) = sources.get(location.file) else { return "<unknown>".to_string() };
+        let before = source.text.get(..location.span.start).unwrap_or(&source.text);
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+        format!("{}:{line}:{column}", source.path.display())
     }
 
     // ==< Reporting >==
     pub fn print_diagnostic(&self, diag: &dyn Diagnostic) {
         if diag.is_fatal() {
             self.errors.set(self.errors.get() + 1);
+        } else {
+            self.warnings.set(self.warnings.get() + 1);
         }
 
-        let report = diag.build_report(&self);
+        let report = diag.build_report(self);
 
-        eprintln!("{report:?}") // TODO: Replace with proper error printing after implementing source cache
+        // Code that was not loaded from a file, like in tests, has no source to show.
+        let sources = self.sources.borrow();
+        if !sources.contains_key(diag.get_location().file) || report.eprint(SourceCache::new(&sources)).is_err() {
+            eprintln!("{report:?}")
+        }
     }
 
     pub fn exit_if_errors(&self) {
@@ -112,5 +462,38 @@ impl Session {
     }
 }
 
+/// Source file registered in the session, locations refer to it by its `SourceId`.
 #[derive(Debug, Clone, PartialEq)]
-pub enum RamenSource {}
\ No newline at end of file
+pub struct RamenSource {
+    pub path: PathBuf,
+    pub text: String
+}
+
+/// Sources of diagnostics, converted to the form `ariadne` needs only once they are printed.
+struct SourceCache<'a> {
+    sources: &'a SlotMap<SourceId, RamenSource>,
+    cache: HashMap<SourceId, ariadne::Source>
+}
+
+impl<'a> SourceCache<'a> {
+    fn new(sources: &'a SlotMap<SourceId, RamenSource>) -> Self {
+        Self {
+            sources,
+            cache: HashMap::new()
+        }
+    }
+}
+
+impl ariadne::Cache<SourceId> for SourceCache<'_> {
+    type Storage = String;
+
+    fn fetch(&mut self, id: &SourceId) -> Result<&ariadne::Source, Box<dyn std::fmt::Debug + '_>> {
+        let source = self.sources.get(*id).ok_or_else(|| Box::new(format!("Unknown source {id:?}")) as Box<dyn std::fmt::Debug>)?;
+        Ok(self.cache.entry(*id).or_insert_with(|| ariadne::Source::from(source.text.clone())))
+    }
+
+    fn display<'b>(&self, id: &'b SourceId) -> Option<Box<dyn std::fmt::Display + 'b>> {
+        let source = self.sources.get(*id)?;
+        Some(Box::new(source.path.display().to_string()))
+    }
+}
\ No newline at end of file
@@ -1,17 +1,230 @@
-#[derive(Debug, Clone)]
+use hashbrown::HashMap;
+
+use crate::{ast::NodeId, defs::DefId};
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum RamenType {
     // Primitive types
     Unit,
+    Bool,
     Integer(usize),
+    Float(usize),
 
     // More complex types
-    Callable(Box<CallableType>)
+    /// Tuple of at least one element, empty tuple is just unit.
+    Tuple(Vec<RamenType>),
+    Array(Box<RamenType>, usize),
+    /// Pointer to elements together with their count.
+    Slice(Box<RamenType>),
+    Reference { target: Box<RamenType>, mutable: bool },
+    /// Raw pointer, unlike reference it can be converted from and to any other pointer.
+    Pointer { target: Box<RamenType>, mutable: bool },
+    Callable(Box<CallableType>),
+    Enum(Box<EnumType>),
+    Struct(Box<StructType>),
+    /// Value of any type implementing trait `trait_id`, behind a pointer together with its vtable.
+    Dyn { trait_id: DefId, name: String },
+    /// Type parameter of generic item, replaced by concrete type in each instantiation.
+    Parameter(TypeParameter)
 }
 
-#[derive(Debug, Clone)]
+impl std::fmt::Display for RamenType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.display_with(&|_, name| name.to_string()).fmt(f)
+    }
+}
+
+/// Formats type with names of structs, enums and traits chosen by `name`, see `RamenType::display_with`.
+pub struct DisplayType<'a> {
+    ty: &'a RamenType,
+    name: &'a dyn Fn(DefId, &str) -> String
+}
+
+impl std::fmt::Display for DisplayType<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let display = |ty: &'_ RamenType| ty.display_with(self.name).to_string();
+        let display_list = |types: &[RamenType]| types.iter().map(display).collect::<Vec<_>>().join(", ");
+        // Type arguments like `<int32, bool>`, nothing if there are none.
+        let display_arguments = |types: &[RamenType]| if types.is_empty() { String::new() } else { format!("<{}>", display_list(types)) };
+
+        match self.ty {
+            RamenType::Unit => write!(f, "unit"),
+            RamenType::Bool => write!(f, "bool"),
+            RamenType::Integer(width) => write!(f, "int{width}"),
+            RamenType::Float(width) => write!(f, "float{width}"),
+            RamenType::Tuple(elements) if elements.len() == 1 => write!(f, "({},)", display(&elements[0])),
+            RamenType::Tuple(elements) => write!(f, "({})", display_list(elements)),
+            RamenType::Array(element, length) => write!(f, "[{}; {length}]", display(element)),
+            RamenType::Slice(element) => write!(f, "[{}]", display(element)),
+            RamenType::Reference { target, mutable } => write!(f, "&{}{}", if *mutable { "mut " } else { "" }, display(target)),
+            RamenType::Pointer { target, mutable } => write!(f, "*{}{}", if *mutable { "mut " } else { "" }, display(target)),
+            RamenType::Callable(callable) => {
+                let generics = if callable.type_parameters.is_empty() { String::new() } else {
+                    format!("<{}>", callable.type_parameters.iter()
+                        .map(|parameter| parameter.name.clone())
+                        .collect::<Vec<_>>()
+                        .join(", "))
+                };
+                write!(
+                    f, "func{generics}({}{}): {}",
+                    display_list(&callable.parameter_types),
+                    if callable.is_vararg { ", ..." } else { "" },
+                    display(&callable.return_type)
+                )
+            }
+            RamenType::Enum(enum_type) =>
+                write!(f, "{}{}", (self.name)(enum_type.def_id, &enum_type.name), display_arguments(&enum_type.type_arguments)),
+            RamenType::Struct(struct_type) =>
+                write!(f, "{}{}", (self.name)(struct_type.def_id, &struct_type.name), display_arguments(&struct_type.type_arguments)),
+            RamenType::Dyn { trait_id, name } => write!(f, "dyn {}", (self.name)(*trait_id, name)),
+            RamenType::Parameter(parameter) => write!(f, "{}", parameter.name),
+        }
+    }
+}
+
+impl RamenType {
+    /// Formats the type with structs, enums and traits named by `name`, which gets their definition and declared name.
+    pub fn display_with<'a>(&'a self, name: &'a dyn Fn(DefId, &str) -> String) -> DisplayType<'a> {
+        DisplayType { ty: self, name }
+    }
+
+    /// Whether value of this type can be used where `target` is expected.
+    /// Mutable pointers can lose their mutability and references can become raw pointers.
+    pub fn coerces_to(&self, target: &RamenType) -> bool {
+        match (self, target) {
+            (
+                Self::Reference { target: from, mutable: from_mutable } | Self::Pointer { target: from, mutable: from_mutable },
+                Self::Reference { target: to, mutable: to_mutable } | Self::Pointer { target: to, mutable: to_mutable }
+            ) if !matches!((self, target), (Self::Pointer { .. }, Self::Reference { .. })) =>
+                from == to && (*from_mutable || !*to_mutable),
+            _ => self == target
+        }
+    }
+
+    /// Replaces type parameters with types they are mapped to, unmapped ones are kept.
+    pub fn substitute(&self, arguments: &HashMap<DefId, RamenType>) -> RamenType {
+        if arguments.is_empty() { return self.clone(); }
+
+        match self {
+            Self::Parameter(parameter) => arguments.get(&parameter.def_id).cloned().unwrap_or_else(|| self.clone()),
+            Self::Tuple(elements) => Self::Tuple(elements.iter().map(|element| element.substitute(arguments)).collect()),
+            Self::Array(element, length) => Self::Array(Box::new(element.substitute(arguments)), *length),
+            Self::Slice(element) => Self::Slice(Box::new(element.substitute(arguments))),
+            Self::Reference { target, mutable } => Self::Reference { target: Box::new(target.substitute(arguments)), mutable: *mutable },
+            Self::Pointer { target, mutable } => Self::Pointer { target: Box::new(target.substitute(arguments)), mutable: *mutable },
+            Self::Callable(callable) => Self::Callable(Box::new(CallableType {
+                return_type: callable.return_type.substitute(arguments),
+                parameter_types: callable.parameter_types.iter().map(|ty| ty.substitute(arguments)).collect(),
+                type_parameters: callable.type_parameters.clone(),
+                is_vararg: callable.is_vararg
+            })),
+            Self::Enum(enum_type) => Self::Enum(Box::new(EnumType {
+                def_id: enum_type.def_id,
+                name: enum_type.name.clone(),
+                type_arguments: enum_type.type_arguments.iter().map(|ty| ty.substitute(arguments)).collect(),
+                variants: enum_type.variants.iter().map(|variant| VariantType {
+                    name: variant.name.clone(),
+                    fields: variant.fields.iter().map(|ty| ty.substitute(arguments)).collect()
+                }).collect()
+            })),
+            Self::Struct(struct_type) => Self::Struct(Box::new(StructType {
+                def_id: struct_type.def_id,
+                name: struct_type.name.clone(),
+                type_arguments: struct_type.type_arguments.iter().map(|ty| ty.substitute(arguments)).collect(),
+                fields: struct_type.fields.iter().map(|field| FieldType {
+                    id: field.id,
+                    name: field.name.clone(),
+                    ty: field.ty.substitute(arguments)
+                }).collect()
+            })),
+            Self::Unit | Self::Bool | Self::Integer(_) | Self::Float(_) | Self::Dyn { .. } => self.clone()
+        }
+    }
+
+    /// Whether any of the given type parameters appears in this type.
+    pub fn contains_parameter(&self, def_ids: &[DefId]) -> bool {
+        match self {
+            Self::Parameter(parameter) => def_ids.contains(&parameter.def_id),
+            Self::Tuple(elements) => elements.iter().any(|element| element.contains_parameter(def_ids)),
+            Self::Array(element, _) | Self::Slice(element) |
+            Self::Reference { target: element, .. } | Self::Pointer { target: element, .. } => element.contains_parameter(def_ids),
+            Self::Callable(callable) => callable.return_type.contains_parameter(def_ids) ||
+                callable.parameter_types.iter().any(|ty| ty.contains_parameter(def_ids)),
+            // Fields are substituted together with the arguments, so checking arguments is enough.
+            Self::Enum(enum_type) => enum_type.type_arguments.iter().any(|ty| ty.contains_parameter(def_ids)),
+            Self::Struct(struct_type) => struct_type.type_arguments.iter().any(|ty| ty.contains_parameter(def_ids)),
+            Self::Unit | Self::Bool | Self::Integer(_) | Self::Float(_) | Self::Dyn { .. } => false
+        }
+    }
+
+    /// Definition of enum or struct.
+    pub fn def_id(&self) -> Option<DefId> {
+        match self {
+            Self::Enum(enum_type) => Some(enum_type.def_id),
+            Self::Struct(struct_type) => Some(struct_type.def_id),
+            _ => None
+        }
+    }
+
+    /// Type arguments of generic enum or struct, empty for anything else.
+    pub fn type_arguments(&self) -> &[RamenType] {
+        match self {
+            Self::Enum(enum_type) => &enum_type.type_arguments,
+            Self::Struct(struct_type) => &struct_type.type_arguments,
+            _ => &[]
+        }
+    }
+
+    /// Applies type arguments to declared type of generic enum or struct,
+    /// whose own type arguments are still its type parameters. Expects both to have the same length.
+    pub fn apply_type_arguments(&self, arguments: &[RamenType]) -> RamenType {
+        let map = self.type_arguments().iter()
+            .zip(arguments.iter().cloned())
+            .filter_map(|(parameter, argument)| match parameter {
+                Self::Parameter(parameter) => Some((parameter.def_id, argument)),
+                _ => None
+            })
+            .collect();
+        self.substitute(&map)
+    }
+
+    /// Type of the value behind a reference or pointer.
+    pub fn pointee(&self) -> Option<&RamenType> {
+        match self {
+            Self::Reference { target, .. } | Self::Pointer { target, .. } => Some(target),
+            _ => None
+        }
+    }
+
+    /// Whether values of this type can be passed to C functions as they are.
+    /// Slices and trait objects are behind fat pointers, which have no C equivalent.
+    /// There are no unsigned integers, so C `unsigned char *` is declared as `*int8`,
+    /// and unsigned C integers narrower than `int` cannot be passed by value, as they would be sign extended.
+    pub fn is_ffi_safe(&self) -> bool {
+        match self {
+            Self::Bool | Self::Float(_) => true,
+            Self::Integer(width) => matches!(width, 8 | 16 | 32 | 64),
+            Self::Reference { target, .. } | Self::Pointer { target, .. } => !matches!(**target, Self::Slice(_) | Self::Dyn { .. }),
+            _ => false
+        }
+    }
+
+    /// Whether values of this type can be returned from C functions,
+    /// tuples of values which can be passed to them are returned like C structs with the same fields.
+    pub fn is_ffi_safe_return(&self) -> bool {
+        match self {
+            Self::Tuple(elements) => elements.iter().all(|element| element.is_ffi_safe_return()),
+            _ => self.is_ffi_safe()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct CallableType {
     pub return_type: RamenType,
     pub parameter_types: Vec<RamenType>,
+    /// Generic callables have to be instantiated before they can be called.
+    pub type_parameters: Vec<TypeParameter>,
     pub is_vararg: bool
 }
 
@@ -20,7 +233,86 @@ impl CallableType {
         Self {
             return_type,
             parameter_types,
+            type_parameters: Vec::new(),
             is_vararg: false
         }
     }
-}
\ No newline at end of file
+
+    /// Maps type parameters to their arguments in order, expects both to have the same length.
+    pub fn type_argument_map(&self, arguments: &[RamenType]) -> HashMap<DefId, RamenType> {
+        self.type_parameters.iter()
+            .map(|parameter| parameter.def_id)
+            .zip(arguments.iter().cloned())
+            .collect()
+    }
+
+    /// Non-generic callable with type parameters replaced by given arguments.
+    pub fn instantiate(&self, arguments: &[RamenType]) -> CallableType {
+        let map = self.type_argument_map(arguments);
+        CallableType {
+            return_type: self.return_type.substitute(&map),
+            parameter_types: self.parameter_types.iter().map(|ty| ty.substitute(&map)).collect(),
+            type_parameters: Vec::new(),
+            is_vararg: self.is_vararg
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeParameter {
+    pub def_id: DefId,
+    pub name: String
+}
+
+/// Tagged union, each variant carries its own list of payload types.
+#[derive(Debug, Clone)]
+pub struct EnumType {
+    pub def_id: DefId,
+    pub name: String,
+    /// Types the enum is instantiated with, type parameters themselves in its declared type.
+    pub type_arguments: Vec<RamenType>,
+    pub variants: Vec<VariantType>
+}
+
+impl PartialEq for EnumType {
+    // Enums are nominal, two declarations with the same shape are still different types.
+    fn eq(&self, other: &Self) -> bool {
+        self.def_id == other.def_id && self.type_arguments == other.type_arguments
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantType {
+    pub name: String,
+    pub fields: Vec<RamenType>
+}
+
+#[derive(Debug, Clone)]
+pub struct StructType {
+    pub def_id: DefId,
+    pub name: String,
+    /// Types the struct is instantiated with, type parameters themselves in its declared type.
+    pub type_arguments: Vec<RamenType>,
+    pub fields: Vec<FieldType>
+}
+
+impl PartialEq for StructType {
+    // Structs are nominal just like enums.
+    fn eq(&self, other: &Self) -> bool {
+        self.def_id == other.def_id && self.type_arguments == other.type_arguments
+    }
+}
+
+impl StructType {
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field.name == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldType {
+    /// Declaration of the field, its privacy is recorded for it.
+    pub id: NodeId,
+    pub name: String,
+    pub ty: RamenType
+}
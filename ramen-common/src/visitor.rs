@@ -2,7 +2,7 @@ use std::cell::RefCell;
 
 use crate::{ast::{self, NodeId}, error::Diagnostic, scope::ScopeRef, session::Session};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ScopeStack {
     stack: RefCell<Vec<ScopeRef>>
 }
@@ -26,11 +26,11 @@ impl ScopeStack {
         self.stack.borrow().last().cloned().expect("Scope stack should have at least one scope.")
     }
 
+    /// Names of the named scopes on the stack from the outermost one, like `main.outer.inner`.
     pub fn get_namespace_prefix(&self) -> String {
         self.stack
-            .borrow().iter().rev()
-            .filter(|s| s.name.is_some())
-            .map(|s| s.name.clone().unwrap())
+            .borrow().iter()
+            .filter_map(|s| s.name.clone())
             .collect::<Vec<String>>()
             .join(".")
     }
@@ -45,8 +45,8 @@ pub trait Visitor<T> where Self: Sized {
 
     fn default_return(&self) -> T;
 
-    fn get_scope_stack<'a>(&'a self) -> &'a ScopeStack;
-    fn get_session<'a>(&'a self) -> &'a Session;
+    fn get_scope_stack(&self) -> &ScopeStack;
+    fn get_session(&self) -> &Session;
 
     fn with_scope<F>(&mut self, scope: ScopeRef, f: F) -> Result<T, Self::Error>
     where F: FnOnce(&mut Self) -> Result<T, Self::Error> {
@@ -58,21 +58,51 @@ pub trait Visitor<T> where Self: Sized {
 
     // ==< Items >==
     fn visit_item(&mut self, item: &ast::Item) -> Result<T, Self::Error> { walk_item(self, item) }
-    fn visit_item_stream(&mut self, stream: &Vec<ast::Item>) -> Result<T, Self::Error> { walk_item_stream(self, stream) }
+    fn visit_item_stream(&mut self, stream: &[ast::Item]) -> Result<T, Self::Error> { walk_item_stream(self, stream) }
 
     fn visit_module(&mut self, id: NodeId, module: &ast::Module) -> Result<T, Self::Error> { walk_module(self, id, module) }
     fn visit_function(&mut self, id: NodeId, function: &ast::Function) -> Result<T, Self::Error> { walk_function(self, id, function) }
+    fn visit_enum(&mut self, id: NodeId, enum_: &ast::Enum) -> Result<T, Self::Error> { walk_enum(self, id, enum_) }
+    fn visit_variant(&mut self, variant: &ast::Variant) -> Result<T, Self::Error> { walk_variant(self, variant) }
+    fn visit_struct(&mut self, id: NodeId, struct_: &ast::Struct) -> Result<T, Self::Error> { walk_struct(self, id, struct_) }
+    fn visit_trait(&mut self, id: NodeId, trait_: &ast::Trait) -> Result<T, Self::Error> { walk_trait(self, id, trait_) }
+    fn visit_impl(&mut self, id: NodeId, impl_: &ast::Impl) -> Result<T, Self::Error> { walk_impl(self, id, impl_) }
+    fn visit_use(&mut self, _id: NodeId, _use: &ast::Use) -> Result<T, Self::Error> { Ok(self.default_return()) }
+    fn visit_const(&mut self, _id: NodeId, const_: &ast::Const) -> Result<T, Self::Error> { walk_global(self, &const_.ty, &const_.value) }
+    fn visit_static(&mut self, _id: NodeId, static_: &ast::Static) -> Result<T, Self::Error> { walk_global(self, &static_.ty, &static_.value) }
+    fn visit_type_alias(&mut self, _id: NodeId, alias: &ast::TypeAlias) -> Result<T, Self::Error> { self.visit_type(&alias.ty) }
 
     // ==< Statements >==
     fn visit_statement(&mut self, statement: &ast::Statement) -> Result<T, Self::Error> { walk_statement(self, statement) }
-    fn visit_statement_stream(&mut self, stream: &Vec<ast::Statement>) -> Result<T, Self::Error> { walk_statement_stream(self, stream) }
+    fn visit_statement_stream(&mut self, stream: &[ast::Statement]) -> Result<T, Self::Error> { walk_statement_stream(self, stream) }
 
+    fn visit_let_statement(&mut self, _id: NodeId, pattern: &ast::Pattern, ty: Option<&ast::Type>, value: &ast::Expression) -> Result<T, Self::Error> { walk_let_statement(self, pattern, ty, value) }
+    fn visit_assign_statement(&mut self, _id: NodeId, target: &ast::Expression, value: &ast::Expression) -> Result<T, Self::Error> { walk_assign_statement(self, target, value) }
     fn visit_return_statement(&mut self, _id: NodeId, value: &ast::Expression) -> Result<T, Self::Error> { walk_expression(self, value) }
 
     // ==< Expressions >==
     fn visit_expression(&mut self, expression: &ast::Expression) -> Result<T, Self::Error> { walk_expression(self, expression) }
 
     fn visit_literal_expression(&mut self, _id: NodeId, _literal: &ast::Literal) -> Result<T, Self::Error> { Ok(self.default_return()) }
+    fn visit_path_expression(&mut self, _id: NodeId, _path: &ast::Path) -> Result<T, Self::Error> { Ok(self.default_return()) }
+    fn visit_tuple_expression(&mut self, _id: NodeId, elements: &[ast::Expression]) -> Result<T, Self::Error> { walk_expression_list(self, elements) }
+    fn visit_tuple_index_expression(&mut self, _id: NodeId, tuple: &ast::Expression, _index: usize) -> Result<T, Self::Error> { self.visit_expression(tuple) }
+    fn visit_field_expression(&mut self, _id: NodeId, base: &ast::Expression, _name: &str) -> Result<T, Self::Error> { self.visit_expression(base) }
+    fn visit_array_expression(&mut self, _id: NodeId, elements: &[ast::Expression]) -> Result<T, Self::Error> { walk_expression_list(self, elements) }
+    fn visit_array_repeat_expression(&mut self, _id: NodeId, value: &ast::Expression, count: &ast::Expression) -> Result<T, Self::Error> { walk_array_repeat_expression(self, value, count) }
+    fn visit_index_expression(&mut self, _id: NodeId, base: &ast::Expression, index: &ast::Expression) -> Result<T, Self::Error> { walk_index_expression(self, base, index) }
+    fn visit_slice_expression(&mut self, _id: NodeId, base: &ast::Expression, start: Option<&ast::Expression>, end: Option<&ast::Expression>, _inclusive: bool) -> Result<T, Self::Error> { walk_slice_expression(self, base, start, end) }
+    fn visit_address_of_expression(&mut self, _id: NodeId, value: &ast::Expression, _mutable: bool) -> Result<T, Self::Error> { self.visit_expression(value) }
+    fn visit_deref_expression(&mut self, _id: NodeId, value: &ast::Expression) -> Result<T, Self::Error> { self.visit_expression(value) }
+    fn visit_unary_expression(&mut self, _id: NodeId, _operator: ast::UnaryOperator, operand: &ast::Expression) -> Result<T, Self::Error> { self.visit_expression(operand) }
+    fn visit_binary_expression(&mut self, _id: NodeId, _operator: ast::BinaryOperator, left: &ast::Expression, right: &ast::Expression) -> Result<T, Self::Error> { walk_binary_expression(self, left, right) }
+    fn visit_call_expression(&mut self, _id: NodeId, callee: &ast::Expression, arguments: &[ast::Expression]) -> Result<T, Self::Error> { walk_call_expression(self, callee, arguments) }
+    fn visit_match_expression(&mut self, _id: NodeId, scrutinee: &ast::Expression, arms: &[ast::MatchArm]) -> Result<T, Self::Error> { walk_match_expression(self, scrutinee, arms) }
+    fn visit_match_arm(&mut self, arm: &ast::MatchArm) -> Result<T, Self::Error> { walk_match_arm(self, arm) }
+    fn visit_closure_expression(&mut self, _id: NodeId, closure: &ast::Closure) -> Result<T, Self::Error> { walk_closure_expression(self, closure) }
+
+    // ==< Patterns >==
+    fn visit_pattern(&mut self, pattern: &ast::Pattern) -> Result<T, Self::Error> { walk_pattern(self, pattern) }
 
     // ==< Types >==
     fn visit_type(&mut self, ty: &ast::Type) -> Result<T, Self::Error> { walk_type(self, ty) }
@@ -90,10 +120,18 @@ where V: Visitor<T> {
     match &item.kind {
         ast::ItemKind::Module(module) => visitor.visit_module(item.id, module),
         ast::ItemKind::Function(function) => visitor.visit_function(item.id, function),
+        ast::ItemKind::Enum(enum_) => visitor.visit_enum(item.id, enum_),
+        ast::ItemKind::Struct(struct_) => visitor.visit_struct(item.id, struct_),
+        ast::ItemKind::Trait(trait_) => visitor.visit_trait(item.id, trait_),
+        ast::ItemKind::Impl(impl_) => visitor.visit_impl(item.id, impl_),
+        ast::ItemKind::Use(use_) => visitor.visit_use(item.id, use_),
+        ast::ItemKind::Const(const_) => visitor.visit_const(item.id, const_),
+        ast::ItemKind::Static(static_) => visitor.visit_static(item.id, static_),
+        ast::ItemKind::TypeAlias(alias) => visitor.visit_type_alias(item.id, alias),
     }
 }
 
-pub fn walk_item_stream<V, T>(visitor: &mut V, stream: &Vec<ast::Item>) -> Result<T, V::Error>
+pub fn walk_item_stream<V, T>(visitor: &mut V, stream: &[ast::Item]) -> Result<T, V::Error>
 where V: Visitor<T> {
     for item in stream.iter() {
         visitor.visit_item(item)?;
@@ -122,15 +160,75 @@ where V: Visitor<T> {
         }
 
         if let Some(return_type) = &function.return_type {
-            visitor.visit_type(&return_type)?;
+            visitor.visit_type(return_type)?;
+        }
+        if let Some(body) = &function.body {
+            visitor.visit_block(body)?;
         }
-        visitor.visit_block(&function.body)?;
         Ok(visitor.default_return())
     })?;
 
     Ok(visitor.default_return())
 }
 
+pub fn walk_enum<V, T>(visitor: &mut V, id: NodeId, enum_: &ast::Enum) -> Result<T, V::Error>
+where V: Visitor<T> {
+    let def_id = visitor.get_session().get_ref_target(id).expect("Cannot find enum definition reference.");
+    let enum_scope = visitor.get_session().scopes.get(def_id).expect("Cannot find enum scope.");
+
+    visitor.with_scope(enum_scope, |visitor| {
+        for variant in enum_.variants.iter() {
+            visitor.visit_variant(variant)?;
+        }
+        Ok(visitor.default_return())
+    })
+}
+
+pub fn walk_variant<V, T>(visitor: &mut V, variant: &ast::Variant) -> Result<T, V::Error>
+where V: Visitor<T> {
+    for field in variant.fields.iter() {
+        visitor.visit_type(field)?;
+    }
+    Ok(visitor.default_return())
+}
+
+pub fn walk_struct<V, T>(visitor: &mut V, id: NodeId, struct_: &ast::Struct) -> Result<T, V::Error>
+where V: Visitor<T> {
+    let def_id = visitor.get_session().get_ref_target(id).expect("Cannot find struct definition reference.");
+    let struct_scope = visitor.get_session().scopes.get(def_id).expect("Cannot find struct scope.");
+
+    visitor.with_scope(struct_scope, |visitor| {
+        for field in struct_.fields.iter() {
+            visitor.visit_type(&field.ty)?;
+        }
+        Ok(visitor.default_return())
+    })
+}
+
+pub fn walk_trait<V, T>(visitor: &mut V, id: NodeId, trait_: &ast::Trait) -> Result<T, V::Error>
+where V: Visitor<T> {
+    let def_id = visitor.get_session().get_ref_target(id).expect("Cannot find trait definition reference.");
+    let trait_scope = visitor.get_session().scopes.get(def_id).expect("Cannot find trait scope.");
+
+    visitor.with_scope(trait_scope, |visitor| visitor.visit_item_stream(&trait_.items))
+}
+
+pub fn walk_impl<V, T>(visitor: &mut V, id: NodeId, impl_: &ast::Impl) -> Result<T, V::Error>
+where V: Visitor<T> {
+    let def_id = visitor.get_session().get_ref_target(id).expect("Cannot find impl definition reference.");
+    let impl_scope = visitor.get_session().scopes.get(def_id).expect("Cannot find impl scope.");
+
+    visitor.visit_type(&impl_.self_type)?;
+    visitor.with_scope(impl_scope, |visitor| visitor.visit_item_stream(&impl_.items))
+}
+
+/// Visits type and value of constant or static.
+pub fn walk_global<V, T>(visitor: &mut V, ty: &ast::Type, value: &ast::Expression) -> Result<T, V::Error>
+where V: Visitor<T> {
+    visitor.visit_type(ty)?;
+    visitor.visit_expression(value)
+}
+
 // ==< Statements >==
 pub fn walk_statement<V, T>(visitor: &mut V, statement: &ast::Statement) -> Result<T, V::Error>
 where V: Visitor<T> {
@@ -138,11 +236,29 @@ where V: Visitor<T> {
         ast::StatementKind::Item(item) => visitor.visit_item(item),
         ast::StatementKind::Expression(expression) => visitor.visit_expression(expression),
 
+        ast::StatementKind::Let { pattern, ty, value } => visitor.visit_let_statement(statement.id, pattern, ty.as_ref(), value),
+        ast::StatementKind::Assign { target, value } => visitor.visit_assign_statement(statement.id, target, value),
         ast::StatementKind::Return(expression) => visitor.visit_return_statement(statement.id, expression),
     }
 }
 
-pub fn walk_statement_stream<V, T>(visitor: &mut V, stream: &Vec<ast::Statement>) -> Result<T, V::Error>
+pub fn walk_let_statement<V, T>(visitor: &mut V, pattern: &ast::Pattern, ty: Option<&ast::Type>, value: &ast::Expression) -> Result<T, V::Error>
+where V: Visitor<T> {
+    // Value is visited first, so it cannot refer to bindings introduced by the pattern.
+    visitor.visit_expression(value)?;
+    if let Some(ty) = ty {
+        visitor.visit_type(ty)?;
+    }
+    visitor.visit_pattern(pattern)
+}
+
+pub fn walk_assign_statement<V, T>(visitor: &mut V, target: &ast::Expression, value: &ast::Expression) -> Result<T, V::Error>
+where V: Visitor<T> {
+    visitor.visit_expression(value)?;
+    visitor.visit_expression(target)
+}
+
+pub fn walk_statement_stream<V, T>(visitor: &mut V, stream: &[ast::Statement]) -> Result<T, V::Error>
 where V: Visitor<T> {
     for item in stream.iter() {
         visitor.visit_statement(item)?;
@@ -155,12 +271,137 @@ pub fn walk_expression<V, T>(visitor: &mut V, expression: &ast::Expression) -> R
 where V: Visitor<T> {
     match &expression.kind {
         ast::ExpressionKind::Literal(literal) => visitor.visit_literal_expression(expression.id, literal),
+        ast::ExpressionKind::Path(path) => visitor.visit_path_expression(expression.id, path),
+
+        ast::ExpressionKind::Tuple(elements) => visitor.visit_tuple_expression(expression.id, elements),
+        ast::ExpressionKind::TupleIndex(tuple, index) => visitor.visit_tuple_index_expression(expression.id, tuple, *index),
+        ast::ExpressionKind::Field(base, name) => visitor.visit_field_expression(expression.id, base, name),
+        ast::ExpressionKind::Array(elements) => visitor.visit_array_expression(expression.id, elements),
+        ast::ExpressionKind::ArrayRepeat(value, count) => visitor.visit_array_repeat_expression(expression.id, value, count),
+        ast::ExpressionKind::Index(base, index) => visitor.visit_index_expression(expression.id, base, index),
+        ast::ExpressionKind::Slice { base, start, end, inclusive } =>
+            visitor.visit_slice_expression(expression.id, base, start.as_deref(), end.as_deref(), *inclusive),
+        ast::ExpressionKind::AddressOf { value, mutable } => visitor.visit_address_of_expression(expression.id, value, *mutable),
+        ast::ExpressionKind::Deref(value) => visitor.visit_deref_expression(expression.id, value),
+        ast::ExpressionKind::Unary(operator, operand) => visitor.visit_unary_expression(expression.id, *operator, operand),
+        ast::ExpressionKind::Binary(operator, left, right) => visitor.visit_binary_expression(expression.id, *operator, left, right),
+
+        ast::ExpressionKind::Call(callee, arguments) => visitor.visit_call_expression(expression.id, callee, arguments),
+        ast::ExpressionKind::Match(scrutinee, arms) => visitor.visit_match_expression(expression.id, scrutinee, arms),
+        ast::ExpressionKind::Closure(closure) => visitor.visit_closure_expression(expression.id, closure),
+    }
+}
+
+pub fn walk_expression_list<V, T>(visitor: &mut V, elements: &[ast::Expression]) -> Result<T, V::Error>
+where V: Visitor<T> {
+    for element in elements.iter() {
+        visitor.visit_expression(element)?;
+    }
+    Ok(visitor.default_return())
+}
+
+pub fn walk_array_repeat_expression<V, T>(visitor: &mut V, value: &ast::Expression, count: &ast::Expression) -> Result<T, V::Error>
+where V: Visitor<T> {
+    visitor.visit_expression(value)?;
+    visitor.visit_expression(count)
+}
+
+pub fn walk_index_expression<V, T>(visitor: &mut V, base: &ast::Expression, index: &ast::Expression) -> Result<T, V::Error>
+where V: Visitor<T> {
+    visitor.visit_expression(base)?;
+    visitor.visit_expression(index)
+}
+
+pub fn walk_slice_expression<V, T>(visitor: &mut V, base: &ast::Expression, start: Option<&ast::Expression>, end: Option<&ast::Expression>) -> Result<T, V::Error>
+where V: Visitor<T> {
+    visitor.visit_expression(base)?;
+    for bound in [start, end].into_iter().flatten() {
+        visitor.visit_expression(bound)?;
+    }
+    Ok(visitor.default_return())
+}
+
+pub fn walk_binary_expression<V, T>(visitor: &mut V, left: &ast::Expression, right: &ast::Expression) -> Result<T, V::Error>
+where V: Visitor<T> {
+    visitor.visit_expression(left)?;
+    visitor.visit_expression(right)
+}
+
+pub fn walk_call_expression<V, T>(visitor: &mut V, callee: &ast::Expression, arguments: &[ast::Expression]) -> Result<T, V::Error>
+where V: Visitor<T> {
+    visitor.visit_expression(callee)?;
+    for argument in arguments.iter() {
+        visitor.visit_expression(argument)?;
+    }
+    Ok(visitor.default_return())
+}
+
+pub fn walk_match_expression<V, T>(visitor: &mut V, scrutinee: &ast::Expression, arms: &[ast::MatchArm]) -> Result<T, V::Error>
+where V: Visitor<T> {
+    visitor.visit_expression(scrutinee)?;
+    for arm in arms.iter() {
+        visitor.visit_match_arm(arm)?;
     }
+    Ok(visitor.default_return())
+}
+
+pub fn walk_match_arm<V, T>(visitor: &mut V, arm: &ast::MatchArm) -> Result<T, V::Error>
+where V: Visitor<T> {
+    visitor.visit_pattern(&arm.pattern)?;
+    visitor.visit_expression(&arm.body)
+}
+
+pub fn walk_closure_expression<V, T>(visitor: &mut V, closure: &ast::Closure) -> Result<T, V::Error>
+where V: Visitor<T> {
+    for parameter in closure.parameters.iter() {
+        if let Some(ty) = &parameter.ty {
+            visitor.visit_type(ty)?;
+        }
+        visitor.visit_pattern(&parameter.pattern)?;
+    }
+    visitor.visit_expression(&closure.body)
+}
+
+// ==< Patterns >==
+pub fn walk_pattern<V, T>(visitor: &mut V, pattern: &ast::Pattern) -> Result<T, V::Error>
+where V: Visitor<T> {
+    match &pattern.kind {
+        ast::PatternKind::Variant(_, patterns) | ast::PatternKind::Tuple(patterns) | ast::PatternKind::Or(patterns) => {
+            for pattern in patterns.iter() {
+                visitor.visit_pattern(pattern)?;
+            }
+        }
+        ast::PatternKind::Wildcard | ast::PatternKind::Binding(_) |
+        ast::PatternKind::Integer(_) | ast::PatternKind::Bool(_) | ast::PatternKind::Range { .. } => ()
+    }
+    Ok(visitor.default_return())
 }
 
 // ==< Types >==
-pub fn walk_type<V, T>(visitor: &mut V, _ty: &ast::Type) -> Result<T, V::Error>
+pub fn walk_type<V, T>(visitor: &mut V, ty: &ast::Type) -> Result<T, V::Error>
 where V: Visitor<T> {
+    match &ty.kind {
+        ast::TypeKind::Tuple(elements) => for element in elements.iter() {
+            visitor.visit_type(element)?;
+        },
+        ast::TypeKind::Array(element, length) => {
+            visitor.visit_type(element)?;
+            visitor.visit_expression(length)?;
+        }
+        ast::TypeKind::Slice(element) |
+        ast::TypeKind::Reference { target: element, .. } | ast::TypeKind::Pointer { target: element, .. } => {
+            visitor.visit_type(element)?;
+        }
+        ast::TypeKind::Function { parameters, return_type } => {
+            for parameter in parameters.iter() {
+                visitor.visit_type(parameter)?;
+            }
+            if let Some(return_type) = return_type {
+                visitor.visit_type(return_type)?;
+            }
+        }
+        _ => ()
+    }
     Ok(visitor.default_return())
 }
 
@@ -177,7 +418,7 @@ where V: Visitor<T> {
 pub fn walk_parameter<V, T>(visitor: &mut V, parameter: &ast::Parameter) -> Result<T, V::Error>
 where V: Visitor<T> {
     visitor.visit_type(&parameter.ty)?;
-    Ok(visitor.default_return())
+    visitor.visit_pattern(&parameter.pattern)
 }
 
 pub fn walk_block<V, T>(visitor: &mut V, block: &ast::Block) -> Result<T, V::Error>
@@ -0,0 +1,353 @@
+use ramen_common::{ast::{self, NodeId}, attributes::{Abi, InlineMode, KnownAttribute}, defs::{DefId, Definition}, error::ResolutionError, scope::ScopeRef, session::Session, visitor::{walk_item, ASTPass, ScopeStack, Visitor}, Loc};
+
+/// Checks attributes of every item against the registry of known attributes and stores them in typed form,
+/// so later passes and backends never look at the raw arguments.
+pub struct AttributeCheckingPass<'sess> {
+    pub session: &'sess Session,
+    pub stack: ScopeStack
+}
+
+impl<'sess> ASTPass<'sess, ()> for AttributeCheckingPass<'sess> {
+    type Error = ResolutionError;
+
+    fn run_on_module(session: &'sess Session, scope: ScopeRef, mod_id: NodeId, module: &ast::Module) -> Result<(), Self::Error> {
+        let mut attribute_checker = Self {
+            session,
+            stack: ScopeStack::new()
+        };
+
+        attribute_checker.stack.push_scope(scope);
+        attribute_checker.visit_module(mod_id, module)?;
+
+        session.exit_if_errors();
+        Ok(())
+    }
+}
+
+/// Kinds of items attributes can be placed on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Target {
+    Function,
+    Struct,
+    Enum,
+    Trait,
+    Impl,
+    Module,
+    Use,
+    Const,
+    Static,
+    TypeAlias
+}
+
+impl Target {
+    fn of(kind: &ast::ItemKind) -> Self {
+        match kind {
+            ast::ItemKind::Function(_) => Self::Function,
+            ast::ItemKind::Struct(_) => Self::Struct,
+            ast::ItemKind::Enum(_) => Self::Enum,
+            ast::ItemKind::Trait(_) => Self::Trait,
+            ast::ItemKind::Impl(_) => Self::Impl,
+            ast::ItemKind::Module(_) => Self::Module,
+            ast::ItemKind::Use(_) => Self::Use,
+            ast::ItemKind::Const(_) => Self::Const,
+            ast::ItemKind::Static(_) => Self::Static,
+            ast::ItemKind::TypeAlias(_) => Self::TypeAlias
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Function => "functions",
+            Self::Struct => "structs",
+            Self::Enum => "enums",
+            Self::Trait => "traits",
+            Self::Impl => "implementations",
+            Self::Module => "modules",
+            Self::Use => "imports",
+            Self::Const => "constants",
+            Self::Static => "statics",
+            Self::TypeAlias => "type aliases"
+        }
+    }
+}
+
+/// Why arguments of attribute were rejected.
+enum ArgumentError {
+    /// Arguments do not have any of the accepted forms.
+    Shape,
+    /// Arguments have accepted form, but their value is not supported.
+    Unsupported(Box<ResolutionError>)
+}
+
+/// Where an attribute can be used and how its arguments are read.
+struct AttributeSpec {
+    name: &'static str,
+    targets: &'static [Target],
+    /// Accepted forms, shown when arguments do not have any of them.
+    usage: &'static str,
+    parse: fn(&[ast::AttributeArgument]) -> Result<KnownAttribute, ArgumentError>
+}
+
+const FUNCTIONS: &[Target] = &[Target::Function];
+
+/// Every attribute known to the compiler, anything else is reported as unknown.
+const REGISTRY: &[AttributeSpec] = &[
+    AttributeSpec { name: "extern", targets: FUNCTIONS, usage: "`@extern` or `@extern(\"C\")`", parse: parse_extern },
+    AttributeSpec { name: "export", targets: FUNCTIONS, usage: "`@export`", parse: |arguments| marker(arguments, KnownAttribute::Export) },
+    AttributeSpec { name: "no_mangle", targets: FUNCTIONS, usage: "`@no_mangle`", parse: |arguments| marker(arguments, KnownAttribute::NoMangle) },
+    AttributeSpec {
+        name: "no_bounds_check",
+        targets: &[Target::Function, Target::Impl, Target::Module],
+        usage: "`@no_bounds_check`",
+        parse: |arguments| marker(arguments, KnownAttribute::NoBoundsCheck)
+    },
+    AttributeSpec { name: "inline", targets: FUNCTIONS, usage: "`@inline`, `@inline(always)` or `@inline(never)`", parse: parse_inline },
+    AttributeSpec { name: "cold", targets: FUNCTIONS, usage: "`@cold`", parse: |arguments| marker(arguments, KnownAttribute::Cold) },
+    AttributeSpec { name: "noreturn", targets: FUNCTIONS, usage: "`@noreturn`", parse: |arguments| marker(arguments, KnownAttribute::NoReturn) },
+    AttributeSpec { name: "nounwind", targets: FUNCTIONS, usage: "`@nounwind`", parse: |arguments| marker(arguments, KnownAttribute::NoUnwind) },
+    AttributeSpec { name: "pure", targets: FUNCTIONS, usage: "`@pure`", parse: |arguments| marker(arguments, KnownAttribute::Pure) },
+    AttributeSpec {
+        name: "deprecated",
+        targets: &[Target::Function, Target::Struct, Target::Enum, Target::Trait, Target::Const, Target::Static],
+        usage: "`@deprecated` or `@deprecated(note = \"...\")`",
+        parse: parse_deprecated
+    },
+    AttributeSpec {
+        name: "static_assert",
+        targets: &[Target::Const],
+        usage: "`@static_assert` or `@static_assert(note = \"...\")`",
+        parse: |arguments| parse_note(arguments).map(|note| KnownAttribute::StaticAssert { note })
+    }
+];
+
+fn marker(arguments: &[ast::AttributeArgument], attribute: KnownAttribute) -> Result<KnownAttribute, ArgumentError> {
+    if arguments.is_empty() { Ok(attribute) } else { Err(ArgumentError::Shape) }
+}
+
+fn parse_extern(arguments: &[ast::AttributeArgument]) -> Result<KnownAttribute, ArgumentError> {
+    match arguments {
+        [] => Ok(KnownAttribute::Extern(Abi::C)),
+        [ast::AttributeArgument { name: None, value: ast::AttributeValue::String(abi), location }] => match abi.as_str() {
+            "C" => Ok(KnownAttribute::Extern(Abi::C)),
+            _ => Err(ArgumentError::Unsupported(Box::new(ResolutionError::UnsupportedAbi {
+                abi: abi.clone(),
+                location: location.clone()
+            })))
+        },
+        _ => Err(ArgumentError::Shape)
+    }
+}
+
+fn parse_inline(arguments: &[ast::AttributeArgument]) -> Result<KnownAttribute, ArgumentError> {
+    let mode = match arguments {
+        [] => InlineMode::Hint,
+        [ast::AttributeArgument { name: None, value: ast::AttributeValue::Identifier(mode), .. }] => match mode.as_str() {
+            "always" => InlineMode::Always,
+            "never" => InlineMode::Never,
+            _ => return Err(ArgumentError::Shape)
+        },
+        _ => return Err(ArgumentError::Shape)
+    };
+    Ok(KnownAttribute::Inline(mode))
+}
+
+fn parse_deprecated(arguments: &[ast::AttributeArgument]) -> Result<KnownAttribute, ArgumentError> {
+    parse_note(arguments).map(|note| KnownAttribute::Deprecated { note })
+}
+
+/// Optional `note = "..."` argument.
+fn parse_note(arguments: &[ast::AttributeArgument]) -> Result<Option<String>, ArgumentError> {
+    match arguments {
+        [] => Ok(None),
+        [ast::AttributeArgument { name: Some(name), value: ast::AttributeValue::String(note), .. }] if name == "note" => Ok(Some(note.clone())),
+        _ => Err(ArgumentError::Shape)
+    }
+}
+
+fn check_attribute(attribute: &ast::Attribute, target: Target) -> Result<KnownAttribute, ResolutionError> {
+    let spec = REGISTRY.iter()
+        .find(|spec| spec.name == attribute.name())
+        .ok_or_else(|| ResolutionError::UnknownAttribute {
+            name: attribute.name().to_string(),
+            location: attribute.location.clone()
+        })?;
+    if !spec.targets.contains(&target) {
+        return Err(ResolutionError::MisplacedAttribute {
+            name: spec.name.to_string(),
+            target: target.describe(),
+            location: attribute.location.clone()
+        });
+    }
+
+    let arguments = match &attribute.kind {
+        ast::AttributeKind::Marker(_) => &[][..],
+        ast::AttributeKind::Call(_, arguments) => arguments.as_slice()
+    };
+    (spec.parse)(arguments).map_err(|error| match error {
+        ArgumentError::Shape => ResolutionError::InvalidAttributeArguments {
+            name: spec.name.to_string(),
+            usage: spec.usage,
+            location: attribute.location.clone()
+        },
+        ArgumentError::Unsupported(error) => *error
+    })
+}
+
+/// Warns about use of item marked `@deprecated`, struct constructors share the attributes of their struct.
+pub(crate) fn check_deprecated(session: &Session, def_id: DefId, name: &str, location: &Loc) {
+    let def_id = match session.get_def(def_id) {
+        Some(Definition::Constructor { parent }) => parent,
+        _ => def_id
+    };
+    let note = session.find_attribute(def_id, |attribute| match attribute {
+        KnownAttribute::Deprecated { note } => Some(note.clone()),
+        _ => None
+    });
+    if let Some(note) = note {
+        session.print_diagnostic(&ResolutionError::DeprecatedUse {
+            name: name.to_string(),
+            note,
+            location: location.clone()
+        });
+    }
+}
+
+/// Makes sure body of `@noreturn` function calls another `@noreturn` function on every path,
+/// as that is the only way not to return. Needs calls to be resolved, so it is done by type resolution.
+pub(crate) fn check_noreturn_body(session: &Session, name: &str, body: &ast::Block) -> Result<(), ResolutionError> {
+    let returns = |location: &Loc| ResolutionError::NoReturnReturns {
+        name: name.to_string(),
+        location: location.clone()
+    };
+    for statement in body.statements.iter() {
+        let expression = match &statement.kind {
+            ast::StatementKind::Expression(expression) | ast::StatementKind::Return(expression) => expression,
+            ast::StatementKind::Let { value, .. } | ast::StatementKind::Assign { value, .. } => value,
+            ast::StatementKind::Item(_) => continue
+        };
+        if diverges(session, expression) {
+            return Ok(());
+        }
+        if let ast::StatementKind::Return(_) = statement.kind {
+            return Err(returns(&statement.location));
+        }
+    }
+    Err(returns(&body.location))
+}
+
+/// Whether evaluation of the expression never finishes, because every path through it calls a `@noreturn` function.
+fn diverges(session: &Session, expression: &ast::Expression) -> bool {
+    match &expression.kind {
+        // Method calls refer to the method from the call, other calls from the callee.
+        ast::ExpressionKind::Call(callee, arguments) => diverges(session, callee)
+            || arguments.iter().any(|argument| diverges(session, argument))
+            || [expression.id, callee.id].into_iter()
+                .filter_map(|id| session.get_ref_target(id))
+                .any(|def_id| session.has_attribute(def_id, &KnownAttribute::NoReturn)),
+        ast::ExpressionKind::Match(scrutinee, arms) => diverges(session, scrutinee)
+            || arms.iter().all(|arm| diverges(session, &arm.body)),
+        _ => false
+    }
+}
+
+impl<'sess> AttributeCheckingPass<'sess> {
+    /// Functions declared with `@extern` are implemented elsewhere and called by their plain name,
+    /// every other function except trait method signatures needs a body.
+    fn check_function_declaration(&self, item: &ast::Item, def_id: DefId, function: &ast::Function) -> Result<(), ResolutionError> {
+        let is_function = matches!(self.session.get_def(def_id), Some(Definition::Function));
+        if self.session.get_extern(def_id).is_some() {
+            let invalid = |reason| ResolutionError::InvalidExternFunction {
+                name: function.name.clone(),
+                reason,
+                location: item.location.clone()
+            };
+            if !is_function {
+                return Err(invalid("cannot be a method"));
+            }
+            if function.body.is_some() {
+                return Err(invalid("cannot have a body"));
+            }
+            if !function.generics.is_empty() {
+                return Err(invalid("cannot be generic"));
+            }
+            if self.session.is_exported(def_id) {
+                return Err(invalid("cannot be exported, as it is defined elsewhere"));
+            }
+            // Declaring the same C function in several places is fine, defining it in ramen as well is not.
+            if self.session.add_plain_symbol(&function.name, def_id).is_some_and(|existing| self.session.get_extern(existing).is_none()) {
+                return Err(ResolutionError::DuplicateSymbol {
+                    symbol: function.name.clone(),
+                    location: item.location.clone()
+                });
+            }
+
+            self.session.set_symbol(def_id, &function.name);
+            return Ok(());
+        }
+
+        if function.is_vararg {
+            return Err(ResolutionError::InvalidVararg { location: item.location.clone() });
+        }
+        if function.body.is_none() && !matches!(self.session.get_def(def_id), Some(Definition::TraitMethod { .. })) {
+            return Err(ResolutionError::MissingFunctionBody {
+                name: function.name.clone(),
+                location: item.location.clone()
+            });
+        }
+
+        // Exported functions keep their plain name so that code outside of ramen can call them.
+        if self.session.is_exported(def_id) {
+            let invalid = |reason| ResolutionError::InvalidExport {
+                name: function.name.clone(),
+                reason,
+                location: item.location.clone()
+            };
+            if !is_function {
+                return Err(invalid("cannot be a method"));
+            }
+            if !function.generics.is_empty() {
+                return Err(invalid("cannot be generic"));
+            }
+            if self.session.add_plain_symbol(&function.name, def_id).is_some() {
+                return Err(ResolutionError::DuplicateSymbol {
+                    symbol: function.name.clone(),
+                    location: item.location.clone()
+                });
+            }
+
+            self.session.set_symbol(def_id, &function.name);
+        }
+        Ok(())
+    }
+}
+
+impl<'sess> Visitor<()> for AttributeCheckingPass<'sess> {
+    type Error = ResolutionError;
+
+    fn default_return(&self) {}
+    fn get_scope_stack(&self) -> &ScopeStack { &self.stack }
+    fn get_session(&self) -> &Session { self.session }
+
+    fn visit_item(&mut self, item: &ast::Item) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(item.id).expect("Cannot find item definition reference.");
+        let target = Target::of(&item.kind);
+        let attributes = item.attributes.0.iter()
+            .map(|attribute| check_attribute(attribute, target))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Repeated attributes either say the same thing or contradict each other, like `@inline @inline(never)`.
+        for (index, attribute) in item.attributes.0.iter().enumerate() {
+            if item.attributes.0[..index].iter().any(|previous| previous.name() == attribute.name()) {
+                return Err(ResolutionError::ConflictingAttribute {
+                    name: attribute.name().to_string(),
+                    location: attribute.location.clone()
+                });
+            }
+        }
+        self.session.set_attributes(def_id, attributes);
+
+        if let ast::ItemKind::Function(function) = &item.kind {
+            self.check_function_declaration(item, def_id, function)?;
+        }
+        walk_item(self, item)
+    }
+}
@@ -1,8 +1,17 @@
-use ramen_common::{ast::{self, NodeId}, defs::Definition, error::ResolutionError, scope::ScopeRef, session::Session, visitor::{walk_function, walk_module, ASTPass, ScopeStack, Visitor}};
+use ramen_common::{ast::{self, NodeId}, defs::{DefId, Definition, Privacy, TraitDefinition}, error::ResolutionError, scope::{Scope, ScopeRef}, session::Session, visitor::{walk_enum, walk_function, walk_impl, walk_item, walk_module, walk_struct, walk_trait, ASTPass, ScopeStack, Visitor}};
+
+use super::attributes::AttributeCheckingPass;
 
 pub struct ItemNameBindingPass<'sess> {
     pub session: &'sess Session,
-    pub stack: ScopeStack
+    pub stack: ScopeStack,
+
+    /// Trait whose method signatures are currently visited.
+    current_trait: Option<DefId>,
+    /// Inherent implementation whose methods are currently visited.
+    current_impl: Option<DefId>,
+    /// Definitions of modules enclosing currently visited item, innermost last.
+    modules: Vec<DefId>
 }
 
 impl<'sess> ASTPass<'sess, ()> for ItemNameBindingPass<'sess> {
@@ -11,23 +20,83 @@ impl<'sess> ASTPass<'sess, ()> for ItemNameBindingPass<'sess> {
     fn run_on_module(session: &'sess Session, scope: ScopeRef, mod_id: NodeId, module: &ast::Module) -> Result<(), Self::Error> {
         let mut name_binder = Self {
             session,
-            stack: ScopeStack::new()
+            stack: ScopeStack::new(),
+
+            current_trait: None,
+            current_impl: None,
+            modules: Vec::new()
         };
 
-        name_binder.stack.push_scope(scope);
+        name_binder.stack.push_scope(scope.clone());
         name_binder.visit_module(mod_id, module)?;
 
+        // Attributes are attached to definitions, so they can be checked only once all items are bound.
+        AttributeCheckingPass::run_on_module(session, scope, mod_id, module)?;
+
         session.exit_if_errors();
         Ok(())
     }
 }
 
+impl<'sess> ItemNameBindingPass<'sess> {
+    fn current_module(&self) -> DefId {
+        *self.modules.last().expect("Items should be bound inside of a module.")
+    }
+
+    /// Items can share their name only if they live in different namespaces, like a type alias and a function.
+    /// Structs take both, as their constructor is named by the struct.
+    fn check_duplicate(&self, item: &ast::Item) -> Result<(), ResolutionError> {
+        let Some(name) = item.name() else { return Ok(()) };
+        let namespaces: &[usize] = match item.kind {
+            ast::ItemKind::Module(_) | ast::ItemKind::Function(_) | ast::ItemKind::Const(_) | ast::ItemKind::Static(_) => &[Scope::NS_NAMES],
+            ast::ItemKind::Enum(_) | ast::ItemKind::Trait(_) | ast::ItemKind::TypeAlias(_) => &[Scope::NS_TYPES],
+            ast::ItemKind::Struct(_) => &[Scope::NS_NAMES, Scope::NS_TYPES],
+            ast::ItemKind::Impl(_) | ast::ItemKind::Use(_) => &[]
+        };
+
+        let scope = self.stack.get_scope();
+        if namespaces.iter().any(|&namespace| scope.get_local(namespace, name).is_some()) {
+            return Err(ResolutionError::DuplicateDefinition {
+                name: name.to_string(),
+                location: item.location.clone()
+            });
+        }
+        Ok(())
+    }
+
+    /// Type parameters are defined in the scope of their owner, so they are visible only inside of it.
+    fn bind_generics(&self, generics: &[ast::GenericParameter], owner: DefId, scope: &ScopeRef) {
+        for generic in generics.iter() {
+            let generic_def_id = self.session.alloc_def(generic.id);
+            self.session.set_def(generic_def_id, Definition::TypeParameter { owner });
+            scope.define_type(&generic.name, generic_def_id);
+        }
+    }
+}
+
 impl<'sess> Visitor<()> for ItemNameBindingPass<'sess> {
     type Error = ResolutionError;
 
-    fn default_return(&self) -> () { () }
-    fn get_scope_stack<'a>(&'a self) -> &'a ScopeStack { &self.stack }
-    fn get_session<'a>(&'a self) -> &'a Session { &self.session }
+    fn default_return(&self) {}
+    fn get_scope_stack(&self) -> &ScopeStack { &self.stack }
+    fn get_session(&self) -> &Session { self.session }
+
+    fn visit_item(&mut self, item: &ast::Item) -> Result<(), Self::Error> {
+        self.check_duplicate(item)?;
+        walk_item(self, item)?;
+
+        let def_id = self.session.get_ref_target(item.id).expect("Cannot find item definition reference.");
+        let privacy = Privacy {
+            visibility: item.visibility,
+            module: self.current_module(),
+            location: item.location.clone()
+        };
+        if let Some(Definition::Struct { constructor }) = self.session.get_def(def_id) {
+            self.session.set_privacy(constructor, privacy.clone());
+        }
+        self.session.set_privacy(def_id, privacy);
+        Ok(())
+    }
 
     fn visit_module(&mut self, id: NodeId, module: &ast::Module) -> Result<(), Self::Error> {
         let module_def_id = self.session.alloc_def(id);
@@ -35,16 +104,154 @@ impl<'sess> Visitor<()> for ItemNameBindingPass<'sess> {
         self.session.scopes.add(module_def_id, Some(self.stack.get_scope()), Some(module.name.clone()));
         self.stack.get_scope().define_name(&module.name, module_def_id);
 
-        walk_module(self, id, module)
+        self.modules.push(module_def_id);
+        let result = walk_module(self, id, module);
+        self.modules.pop();
+        result
     }
 
     fn visit_function(&mut self, id: NodeId, function: &ast::Function) -> Result<(), Self::Error> {
         let function_def_id = self.session.alloc_def(id);
-        self.session.set_def(function_def_id, Definition::Function);
-        self.session.scopes.add(function_def_id, Some(self.stack.get_scope()), Some(function.name.clone()));
+        let function_scope = self.session.scopes.add(function_def_id, Some(self.stack.get_scope()), Some(function.name.clone()));
         self.stack.get_scope().define_name(&function.name, function_def_id);
-        self.session.set_symbol(id, self.stack.prefix_name(".", &function.name));
 
-        walk_function(self, id, function)
+        // Trait methods have no code of their own, so they need no symbol.
+        // Symbols of methods include their type, which is only known after type declaration.
+        match (self.current_trait, self.current_impl) {
+            (Some(parent), _) => self.session.set_def(function_def_id, Definition::TraitMethod { parent }),
+            (None, Some(parent)) => self.session.set_def(function_def_id, Definition::Method { parent, has_receiver: function.has_receiver }),
+            (None, None) => {
+                self.session.set_def(function_def_id, Definition::Function);
+                self.session.set_symbol(function_def_id, self.stack.prefix_name(".", &function.name));
+            }
+        }
+
+        self.bind_generics(&function.generics, function_def_id, &function_scope);
+
+        // Trait methods are called through implementations, which are `const` on their own.
+        if function.is_const && function.body.is_some() && self.current_trait.is_none() {
+            self.session.add_const_function(function_def_id, id, function.clone());
+        }
+
+        // Items nested in the body are not methods anymore.
+        let current_impl = self.current_impl.take();
+        let result = walk_function(self, id, function);
+        self.current_impl = current_impl;
+        result
+    }
+
+    fn visit_enum(&mut self, id: NodeId, enum_: &ast::Enum) -> Result<(), Self::Error> {
+        let enum_def_id = self.session.alloc_def(id);
+        self.session.set_def(enum_def_id, Definition::Enum);
+        self.session.set_symbol(enum_def_id, self.stack.prefix_name(".", &enum_.name));
+        let enum_scope = self.session.scopes.add(enum_def_id, Some(self.stack.get_scope()), Some(enum_.name.clone()));
+        self.stack.get_scope().define_type(&enum_.name, enum_def_id);
+        self.bind_generics(&enum_.generics, enum_def_id, &enum_scope);
+
+        // Variants live in the enum's own scope so they are only reachable through `Enum.Variant` paths.
+        for (index, variant) in enum_.variants.iter().enumerate() {
+            let variant_def_id = self.session.alloc_def(variant.id);
+            self.session.set_def(variant_def_id, Definition::Variant { parent: enum_def_id, index });
+            enum_scope.define_name(&variant.name, variant_def_id);
+        }
+
+        walk_enum(self, id, enum_)
     }
-}
\ No newline at end of file
+
+    fn visit_struct(&mut self, id: NodeId, struct_: &ast::Struct) -> Result<(), Self::Error> {
+        // Type and its constructor need separate definitions, as each of them has its own type.
+        let struct_def_id = self.session.alloc_def(id);
+        let constructor_def_id = self.session.def_ids.next();
+        self.session.set_def(struct_def_id, Definition::Struct { constructor: constructor_def_id });
+        self.session.set_def(constructor_def_id, Definition::Constructor { parent: struct_def_id });
+        self.session.set_symbol(struct_def_id, self.stack.prefix_name(".", &struct_.name));
+
+        let struct_scope = self.session.scopes.add(struct_def_id, Some(self.stack.get_scope()), Some(struct_.name.clone()));
+        self.stack.get_scope().define_type(&struct_.name, struct_def_id);
+        self.stack.get_scope().define_name(&struct_.name, constructor_def_id);
+        self.bind_generics(&struct_.generics, struct_def_id, &struct_scope);
+
+        for field in struct_.fields.iter() {
+            self.session.set_privacy(field.id, Privacy {
+                visibility: field.visibility,
+                module: self.current_module(),
+                location: field.location.clone()
+            });
+        }
+
+        walk_struct(self, id, struct_)
+    }
+
+    fn visit_trait(&mut self, id: NodeId, trait_: &ast::Trait) -> Result<(), Self::Error> {
+        let trait_def_id = self.session.alloc_def(id);
+        self.session.set_def(trait_def_id, Definition::Trait);
+        self.session.set_symbol(trait_def_id, self.stack.prefix_name(".", &trait_.name));
+        let trait_scope = self.session.scopes.add(trait_def_id, Some(self.stack.get_scope()), Some(trait_.name.clone()));
+        self.stack.get_scope().define_type(&trait_.name, trait_def_id);
+
+        // `Self` is an implicit type parameter of every method, bounded by the trait itself.
+        let self_parameter = self.session.def_ids.next();
+        self.session.set_def(self_parameter, Definition::TypeParameter { owner: trait_def_id });
+        self.session.set_trait_bounds(self_parameter, vec![trait_def_id]);
+        trait_scope.define_type("Self", self_parameter);
+
+        self.current_trait = Some(trait_def_id);
+        walk_trait(self, id, trait_)?;
+        self.current_trait = None;
+
+        let methods = trait_.items.iter()
+            .filter_map(|item| match &item.kind {
+                ast::ItemKind::Function(function) => Some((function.name.clone(), self.session.get_ref_target(item.id)?)),
+                _ => None
+            })
+            .collect();
+        self.session.set_trait(trait_def_id, TraitDefinition {
+            name: trait_.name.clone(),
+            self_parameter,
+            methods
+        });
+        Ok(())
+    }
+
+    fn visit_impl(&mut self, id: NodeId, impl_: &ast::Impl) -> Result<(), Self::Error> {
+        // Methods are only reachable through the trait or the implemented type, so the impl itself has no name.
+        let impl_def_id = self.session.alloc_def(id);
+        self.session.set_def(impl_def_id, Definition::Impl);
+        let impl_scope = self.session.scopes.add(impl_def_id, Some(self.stack.get_scope()), None);
+        impl_scope.define_type("Self", impl_def_id);
+
+        self.current_impl = impl_.trait_.is_none().then_some(impl_def_id);
+        let result = walk_impl(self, id, impl_);
+        self.current_impl = None;
+        result
+    }
+
+    fn visit_const(&mut self, id: NodeId, const_: &ast::Const) -> Result<(), Self::Error> {
+        let const_def_id = self.session.alloc_def(id);
+        self.session.set_def(const_def_id, Definition::Const);
+        self.stack.get_scope().define_name(&const_.name, const_def_id);
+        Ok(())
+    }
+
+    fn visit_static(&mut self, id: NodeId, static_: &ast::Static) -> Result<(), Self::Error> {
+        let static_def_id = self.session.alloc_def(id);
+        self.session.set_def(static_def_id, Definition::Static { mutable: static_.mutable });
+        self.session.set_symbol(static_def_id, self.stack.prefix_name(".", &static_.name));
+        self.stack.get_scope().define_name(&static_.name, static_def_id);
+        Ok(())
+    }
+
+    fn visit_type_alias(&mut self, id: NodeId, alias: &ast::TypeAlias) -> Result<(), Self::Error> {
+        let alias_def_id = self.session.alloc_def(id);
+        self.session.set_def(alias_def_id, Definition::TypeAlias);
+        self.stack.get_scope().define_type(&alias.name, alias_def_id);
+        Ok(())
+    }
+
+    fn visit_use(&mut self, id: NodeId, _use: &ast::Use) -> Result<(), Self::Error> {
+        // Imported names can refer to items bound later, so they are bound by import resolution.
+        let use_def_id = self.session.alloc_def(id);
+        self.session.set_def(use_def_id, Definition::Use);
+        Ok(())
+    }
+}
@@ -0,0 +1,531 @@
+use hashbrown::{HashMap, HashSet};
+use ramen_common::{ast::{self, NodeId}, consts::ConstValue, defs::{DefId, Definition}, error::ResolutionError, session::{AnyId, Session}, types::RamenType, visitor::{walk_expression, walk_pattern, walk_statement, ScopeStack, Visitor}};
+
+use super::{type_declaration::{resolve_type, resolve_types}, type_resolution::TypeResolutionPass};
+
+/// Calls of `const func`s nested deeper than this fail, so that evaluation does not overflow the stack of the compiler.
+const MAX_CALL_DEPTH: usize = 128;
+
+/// Why constant expression has no value yet.
+pub(crate) enum Unevaluated {
+    /// Expression uses definition whose type is not resolved yet, so it cannot be type checked.
+    Untyped,
+    /// Expression uses constant `DefId`, whose value is not known yet.
+    Pending(DefId),
+    Error(ResolutionError)
+}
+
+impl From<ResolutionError> for Unevaluated {
+    fn from(error: ResolutionError) -> Self { Self::Error(error) }
+}
+
+/// Type checks initializer of constant or static against its declared type `ty`, which has to be resolved, and computes its value.
+pub(crate) fn evaluate_constant(session: &Session, value: &ast::Expression, ty: &ast::Type) -> Result<ConstValue, Unevaluated> {
+    check_constant_expression(session, value)?;
+    TypeResolutionPass::check_constant(session, value, ty)?;
+    ConstEvaluator::new(session).evaluate(value)
+}
+
+/// Computes length of array type or repeat expression, `None` if it depends on declaration or constant which is not resolved yet.
+pub(crate) fn array_length(session: &Session, length: &ast::Expression) -> Result<Option<usize>, ResolutionError> {
+    match evaluate_length(session, length) {
+        Ok(length) => Ok(Some(length)),
+        Err(Unevaluated::Error(error)) => Err(error),
+        Err(Unevaluated::Untyped | Unevaluated::Pending(_)) => Ok(None)
+    }
+}
+
+/// Lengths are cached by their expression, as types containing them are resolved repeatedly.
+fn evaluate_length(session: &Session, length: &ast::Expression) -> Result<usize, Unevaluated> {
+    let value = match session.get_constant(length.id) {
+        Some(value) => value,
+        None => {
+            check_constant_expression(session, length)?;
+            TypeResolutionPass::check_array_length(session, length)?;
+            let value = ConstEvaluator::new(session).evaluate(length)?;
+            session.set_constant(length.id, value.clone());
+            value
+        }
+    };
+
+    let ConstValue::Integer(value) = value else { unreachable!("Array length should have been checked to be an integer.") };
+    usize::try_from(value).map_err(|_| Unevaluated::Error(ResolutionError::InvalidArrayLength {
+        value,
+        location: length.location.clone()
+    }))
+}
+
+/// Makes sure expression can be evaluated at compile time and every definition it uses has its type resolved.
+fn check_constant_expression(session: &Session, expression: &ast::Expression) -> Result<(), Unevaluated> {
+    let mut checker = ConstantChecker::new(session);
+    checker.visit_expression(expression)?;
+    checker.unevaluated.map_or(Ok(()), Err)
+}
+
+/// Makes sure body of `const func` only does what constant evaluation can, no matter which arguments it gets.
+pub(crate) fn check_const_function_body(session: &Session, def_id: DefId, function: &ast::Function) -> Result<(), ResolutionError> {
+    let mut checker = ConstantChecker::new(session);
+    checker.functions.insert(def_id);
+    checker.check_function(function)
+}
+
+/// Rejects expressions whose value is only known at runtime, like function calls or uses of variables.
+struct ConstantChecker<'sess> {
+    session: &'sess Session,
+    stack: ScopeStack,
+
+    /// Variables bound by patterns inside of the checked expression.
+    locals: HashSet<DefId>,
+    /// `const func`s the expression calls, their bodies are checked too.
+    functions: HashSet<DefId>,
+    /// First reason the expression cannot be type checked yet.
+    unevaluated: Option<Unevaluated>
+}
+
+impl<'sess> ConstantChecker<'sess> {
+    fn new(session: &'sess Session) -> Self {
+        Self {
+            session,
+            stack: ScopeStack::new(),
+
+            locals: HashSet::new(),
+            functions: HashSet::new(),
+            unevaluated: None
+        }
+    }
+
+    fn wait_for(&mut self, reason: Unevaluated) {
+        self.unevaluated.get_or_insert(reason);
+    }
+
+    fn check_path(&mut self, expression: &ast::Expression, path: &ast::Path, is_called: bool) -> Result<(), ResolutionError> {
+        let not_constant = |what| ResolutionError::NotConstant { what, location: expression.location.clone() };
+        let def_id = self.session.get_ref_target(expression.id).expect("Path should have been resolved by name resolution.");
+        let def_type = self.session.get_type(def_id);
+        match self.session.get_def(def_id) {
+            // Variables bound inside of the expression get their types only once it is type checked.
+            Some(Definition::Variable) if self.locals.contains(&def_id) => return Ok(()),
+            Some(Definition::Variable) => return Err(not_constant("Variables")),
+            Some(Definition::Static { .. }) => return Err(not_constant("Statics")),
+            // Called functions were already checked to be `const`.
+            Some(Definition::Function | Definition::Method { .. }) if is_called => (),
+            Some(Definition::Function | Definition::TraitMethod { .. } | Definition::Method { .. }) => return Err(not_constant("Functions")),
+            Some(Definition::Constructor { .. }) if !is_called => return Err(not_constant("Functions")),
+            Some(Definition::Variant { .. }) if !is_called && matches!(def_type, Some(RamenType::Callable(_))) => return Err(not_constant("Functions")),
+            _ => ()
+        }
+
+        if def_type.is_none() {
+            self.wait_for(Unevaluated::Untyped);
+        }
+        for segment in path.segments.iter() {
+            if resolve_types(self.session, &segment.type_arguments)?.is_none() {
+                self.wait_for(Unevaluated::Untyped);
+            }
+        }
+        Ok(())
+    }
+
+    fn check_function(&mut self, function: &ast::Function) -> Result<(), ResolutionError> {
+        for parameter in function.parameters.iter() {
+            self.visit_value_parameter(parameter)?;
+        }
+        if let Some(return_type) = &function.return_type {
+            self.visit_type(return_type)?;
+        }
+        if let Some(body) = &function.body {
+            self.visit_block(body)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'sess> Visitor<()> for ConstantChecker<'sess> {
+    type Error = ResolutionError;
+
+    fn default_return(&self) {}
+    fn get_scope_stack(&self) -> &ScopeStack { &self.stack }
+    fn get_session(&self) -> &Session { self.session }
+
+    fn visit_expression(&mut self, expression: &ast::Expression) -> Result<(), Self::Error> {
+        let not_constant = |what| Err(ResolutionError::NotConstant { what, location: expression.location.clone() });
+        match &expression.kind {
+            ast::ExpressionKind::Path(path) => self.check_path(expression, path, false),
+            // Only constructors of structs and variants and `const func`s can be called, anything else runs code only known at runtime.
+            ast::ExpressionKind::Call(callee, arguments) => {
+                if self.session.get_ref_target(expression.id).is_some() { return not_constant("Method calls") }
+                let ast::ExpressionKind::Path(path) = &callee.kind else { return not_constant("Function calls") };
+                let def_id = self.session.get_ref_target(callee.id).expect("Path should have been resolved by name resolution.");
+                let is_constructor = matches!(self.session.get_def(def_id), Some(Definition::Constructor { .. } | Definition::Variant { .. }));
+                let const_function = self.session.get_const_function(def_id);
+                if !is_constructor && const_function.is_none() {
+                    return not_constant("Calls of functions that are not `const`");
+                }
+                if self.session.get_path_fields(callee.id) > 0 {
+                    return not_constant("Function calls");
+                }
+
+                self.check_path(callee, path, true)?;
+                if let Some((_, function)) = const_function {
+                    if self.functions.insert(def_id) {
+                        self.check_function(&function)?;
+                    }
+                }
+                for argument in arguments.iter() {
+                    self.visit_expression(argument)?;
+                }
+                Ok(())
+            }
+            // Repeat counts are evaluated while the expression is type checked, so they have to be known first.
+            ast::ExpressionKind::ArrayRepeat(value, count) => {
+                self.visit_expression(value)?;
+                match evaluate_length(self.session, count) {
+                    Ok(_) => Ok(()),
+                    Err(Unevaluated::Error(error)) => Err(error),
+                    Err(reason) => {
+                        self.wait_for(reason);
+                        Ok(())
+                    }
+                }
+            }
+            ast::ExpressionKind::Slice { .. } => not_constant("Slices"),
+            ast::ExpressionKind::AddressOf { .. } => not_constant("References"),
+            ast::ExpressionKind::Deref(_) => not_constant("Dereferences"),
+            ast::ExpressionKind::Closure(_) => not_constant("Closures"),
+            _ => walk_expression(self, expression)
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &ast::Pattern) -> Result<(), Self::Error> {
+        if let ast::PatternKind::Binding(_) = &pattern.kind {
+            let def_id = self.session.get_ref_target(pattern.id).expect("Cannot find binding definition reference.");
+            self.locals.insert(def_id);
+        }
+        walk_pattern(self, pattern)
+    }
+
+    /// Types inside of `const func`s, array lengths included, have to be known before they can be type checked.
+    fn visit_type(&mut self, ty: &ast::Type) -> Result<(), Self::Error> {
+        if resolve_type(self.session, ty)?.is_none() {
+            self.wait_for(Unevaluated::Untyped);
+        }
+        Ok(())
+    }
+
+    fn visit_statement(&mut self, statement: &ast::Statement) -> Result<(), Self::Error> {
+        match &statement.kind {
+            // Items nested in `const func`s are not part of their evaluation.
+            ast::StatementKind::Item(_) => Ok(()),
+            ast::StatementKind::Assign { .. } => Err(ResolutionError::NotConstant { what: "Assignments", location: statement.location.clone() }),
+            ast::StatementKind::Return(value) => self.visit_expression(value),
+            _ => walk_statement(self, statement)
+        }
+    }
+}
+
+/// Interpreter computing values of type checked constant expressions.
+struct ConstEvaluator<'sess> {
+    session: &'sess Session,
+    /// Values of variables bound by patterns inside of the evaluated expression or `const func` body.
+    locals: HashMap<DefId, ConstValue>,
+    /// Number of expressions that can still be evaluated.
+    steps: usize,
+    /// Number of `const func` calls being evaluated.
+    depth: usize
+}
+
+impl<'sess> ConstEvaluator<'sess> {
+    fn new(session: &'sess Session) -> Self {
+        Self {
+            session,
+            locals: HashMap::new(),
+            steps: session.options.const_eval_steps,
+            depth: 0
+        }
+    }
+
+    fn get_type(&self, id: impl Into<AnyId>) -> RamenType {
+        self.session.get_type(id).expect("Constant expression should have been type checked.")
+    }
+
+    fn evaluate(&mut self, expression: &ast::Expression) -> Result<ConstValue, Unevaluated> {
+        if self.steps == 0 {
+            return Err(Unevaluated::Error(ResolutionError::EvaluationFailed {
+                reason: format!("Evaluation did not finish in {} steps.", self.session.options.const_eval_steps),
+                location: expression.location.clone()
+            }));
+        }
+        self.steps -= 1;
+
+        let value = match &expression.kind {
+            ast::ExpressionKind::Literal(literal) => match (literal, self.get_type(expression.id)) {
+                // Type resolution made sure the literal fits, negated if it is an operand of `-`.
+                (ast::Literal::Integer(value), RamenType::Integer(_)) => ConstValue::Integer(*value as i128),
+                (ast::Literal::Float(value), ty) => float_value(*value, &ty),
+                (ast::Literal::Bool(value), _) => ConstValue::Bool(*value),
+                (ast::Literal::Integer(_), _) => unreachable!("Integer literal should have integer type.")
+            },
+            ast::ExpressionKind::Path(path) => self.evaluate_path(expression.id, path)?,
+
+            ast::ExpressionKind::Tuple(elements) if elements.is_empty() => ConstValue::Unit,
+            ast::ExpressionKind::Tuple(elements) | ast::ExpressionKind::Array(elements) => ConstValue::Aggregate(self.evaluate_list(elements)?),
+            ast::ExpressionKind::TupleIndex(tuple, index) => self.evaluate(tuple)?.field(*index).cloned()
+                .expect("Tuple index should have been checked by type resolution."),
+            ast::ExpressionKind::Field(base, name) => {
+                let base_type = self.get_type(base.id);
+                let base_value = self.evaluate(base)?;
+                field_value(base_value, &base_type, name).0
+            }
+            ast::ExpressionKind::ArrayRepeat(value, _) => {
+                let RamenType::Array(_, length) = self.get_type(expression.id) else { unreachable!("Repeat expression should have array type.") };
+                ConstValue::Aggregate(vec![self.evaluate(value)?; length])
+            }
+            ast::ExpressionKind::Index(base, index) => {
+                let ConstValue::Aggregate(elements) = self.evaluate(base)? else { unreachable!("Only arrays can be indexed in constant expressions.") };
+                let ConstValue::Integer(position) = self.evaluate(index)? else { unreachable!("Index should have been checked to be an integer.") };
+                usize::try_from(position).ok()
+                    .and_then(|position| elements.get(position).cloned())
+                    .ok_or_else(|| ResolutionError::EvaluationFailed {
+                        reason: format!("Index {position} is out of bounds of array with length {}.", elements.len()),
+                        location: index.location.clone()
+                    })?
+            }
+            ast::ExpressionKind::Unary(operator, operand) => self.evaluate_unary(expression, *operator, operand)?,
+            ast::ExpressionKind::Binary(operator, left, right) => self.evaluate_binary(expression, *operator, left, right)?,
+
+            ast::ExpressionKind::Call(callee, arguments) => {
+                let fields = self.evaluate_list(arguments)?;
+                let def_id = self.session.get_ref_target(callee.id).expect("Path should have been resolved by name resolution.");
+                match self.session.get_def(def_id) {
+                    Some(Definition::Constructor { .. }) => ConstValue::Aggregate(fields),
+                    Some(Definition::Variant { index, .. }) => ConstValue::Variant { index, fields },
+                    _ => self.call(expression, def_id, fields)?
+                }
+            }
+            ast::ExpressionKind::Match(scrutinee, arms) => {
+                let value = self.evaluate(scrutinee)?;
+                let arm = arms.iter().find(|arm| self.bind_pattern(&arm.pattern, &value)).ok_or_else(|| ResolutionError::EvaluationFailed {
+                    reason: "No arm matches this value.".to_string(),
+                    location: scrutinee.location.clone()
+                })?;
+                self.evaluate(&arm.body)?
+            }
+
+            ast::ExpressionKind::Slice { .. } | ast::ExpressionKind::AddressOf { .. } | ast::ExpressionKind::Deref(_) | ast::ExpressionKind::Closure(_) =>
+                unreachable!("Expressions without constant value should have been rejected before evaluation.")
+        };
+        Ok(value)
+    }
+
+    fn evaluate_list(&mut self, expressions: &[ast::Expression]) -> Result<Vec<ConstValue>, Unevaluated> {
+        expressions.iter().map(|expression| self.evaluate(expression)).collect()
+    }
+
+    fn evaluate_unary(&mut self, expression: &ast::Expression, operator: ast::UnaryOperator, operand: &ast::Expression) -> Result<ConstValue, Unevaluated> {
+        let value = match (operator, self.evaluate(operand)?) {
+            (ast::UnaryOperator::Negate, ConstValue::Integer(value)) => self.integer_result(expression, &operator, value.checked_neg())?,
+            (ast::UnaryOperator::Negate, ConstValue::Float(value)) => ConstValue::Float(-value),
+            (ast::UnaryOperator::Not, ConstValue::Bool(value)) => ConstValue::Bool(!value),
+            _ => unreachable!("Operand should have been checked by type resolution.")
+        };
+        Ok(value)
+    }
+
+    fn evaluate_binary(&mut self, expression: &ast::Expression, operator: ast::BinaryOperator, left: &ast::Expression, right: &ast::Expression) -> Result<ConstValue, Unevaluated> {
+        // Right operand of `&&` and `||` is only evaluated if it decides the result, just like at runtime.
+        if operator.is_logical() {
+            let ConstValue::Bool(left) = self.evaluate(left)? else { unreachable!("Operand should have been checked to be a bool.") };
+            if left == (operator == ast::BinaryOperator::Or) {
+                return Ok(ConstValue::Bool(left));
+            }
+            return self.evaluate(right);
+        }
+
+        let value = match (operator, self.evaluate(left)?, self.evaluate(right)?) {
+            (ast::BinaryOperator::Equal, left, right) => ConstValue::Bool(left == right),
+            (ast::BinaryOperator::NotEqual, left, right) => ConstValue::Bool(left != right),
+            (operator, ConstValue::Integer(left), ConstValue::Integer(right)) if operator.is_ordering() => ConstValue::Bool(compare(operator, left, right)),
+            (operator, ConstValue::Float(left), ConstValue::Float(right)) if operator.is_ordering() => ConstValue::Bool(compare(operator, left, right)),
+
+            (ast::BinaryOperator::Divide | ast::BinaryOperator::Remainder, ConstValue::Integer(_), ConstValue::Integer(0)) =>
+                return Err(Unevaluated::Error(ResolutionError::DivisionByZero { location: right.location.clone() })),
+            (operator, ConstValue::Integer(left), ConstValue::Integer(right)) => {
+                let result = match operator {
+                    ast::BinaryOperator::Add => left.checked_add(right),
+                    ast::BinaryOperator::Subtract => left.checked_sub(right),
+                    ast::BinaryOperator::Multiply => left.checked_mul(right),
+                    ast::BinaryOperator::Divide => left.checked_div(right),
+                    // Remainder of the smallest integer and -1 overflows just like their quotient.
+                    ast::BinaryOperator::Remainder => {
+                        self.integer_result(expression, &operator, left.checked_div(right))?;
+                        left.checked_rem(right)
+                    }
+                    _ => unreachable!("Operator {operator} should not take integers.")
+                };
+                self.integer_result(expression, &operator, result)?
+            }
+            (operator, ConstValue::Float(left), ConstValue::Float(right)) => {
+                let result = match operator {
+                    ast::BinaryOperator::Add => left + right,
+                    ast::BinaryOperator::Subtract => left - right,
+                    ast::BinaryOperator::Multiply => left * right,
+                    ast::BinaryOperator::Divide => left / right,
+                    ast::BinaryOperator::Remainder => left % right,
+                    _ => unreachable!("Operator {operator} should not take floats.")
+                };
+                float_value(result, &self.get_type(expression.id))
+            }
+            _ => unreachable!("Operands should have been checked by type resolution.")
+        };
+        Ok(value)
+    }
+
+    /// Result of integer arithmetic, which has to fit into the type of the expression.
+    fn integer_result(&self, expression: &ast::Expression, operator: &dyn std::fmt::Display, result: Option<i128>) -> Result<ConstValue, Unevaluated> {
+        let ty = self.get_type(expression.id);
+        let RamenType::Integer(width) = ty else { unreachable!("Integer arithmetic should have integer type.") };
+        match result {
+            Some(result) if fits_integer(result, width) => Ok(ConstValue::Integer(result)),
+            _ => Err(Unevaluated::Error(ResolutionError::ArithmeticOverflow {
+                operator: operator.to_string(),
+                ty,
+                location: expression.location.clone()
+            }))
+        }
+    }
+
+    /// Runs body of `const func` with its own variables.
+    fn call(&mut self, call: &ast::Expression, def_id: DefId, arguments: Vec<ConstValue>) -> Result<ConstValue, Unevaluated> {
+        let (_, function) = self.session.get_const_function(def_id).expect("Constant expression can only call `const` functions.");
+        TypeResolutionPass::check_const_function(self.session, def_id)?;
+        if self.depth == MAX_CALL_DEPTH {
+            return Err(Unevaluated::Error(ResolutionError::EvaluationFailed {
+                reason: format!("Calls of `const` functions are nested more than {MAX_CALL_DEPTH} levels deep."),
+                location: call.location.clone()
+            }));
+        }
+
+        let caller_locals = std::mem::take(&mut self.locals);
+        self.depth += 1;
+        let result = self.evaluate_body(call, &function, arguments);
+        self.depth -= 1;
+        self.locals = caller_locals;
+        result
+    }
+
+    fn evaluate_body(&mut self, call: &ast::Expression, function: &ast::Function, arguments: Vec<ConstValue>) -> Result<ConstValue, Unevaluated> {
+        for (parameter, argument) in function.parameters.iter().zip(arguments.iter()) {
+            if !self.bind_pattern(&parameter.parameter.pattern, argument) {
+                return Err(Unevaluated::Error(ResolutionError::EvaluationFailed {
+                    reason: "Argument does not match pattern of its parameter.".to_string(),
+                    location: parameter.location.clone()
+                }));
+            }
+        }
+
+        let body = function.body.as_ref().expect("Constant functions should have a body.");
+        for statement in body.statements.iter() {
+            match &statement.kind {
+                ast::StatementKind::Let { pattern, value, .. } => {
+                    let value = self.evaluate(value)?;
+                    if !self.bind_pattern(pattern, &value) {
+                        return Err(Unevaluated::Error(ResolutionError::EvaluationFailed {
+                            reason: "Value does not match pattern of `let` statement.".to_string(),
+                            location: pattern.location.clone()
+                        }));
+                    }
+                }
+                ast::StatementKind::Expression(expression) => { self.evaluate(expression)?; }
+                ast::StatementKind::Return(value) => return self.evaluate(value),
+                ast::StatementKind::Item(_) => (),
+                ast::StatementKind::Assign { .. } => unreachable!("Assignments should have been rejected before evaluation.")
+            }
+        }
+
+        match function.return_type {
+            None => Ok(ConstValue::Unit),
+            Some(_) => Err(Unevaluated::Error(ResolutionError::EvaluationFailed {
+                reason: "Function ended without returning a value.".to_string(),
+                location: call.location.clone()
+            }))
+        }
+    }
+
+    fn evaluate_path(&mut self, id: NodeId, path: &ast::Path) -> Result<ConstValue, Unevaluated> {
+        let def_id = self.session.get_ref_target(id).expect("Path should have been resolved by name resolution.");
+        let mut value = match self.session.get_def(def_id) {
+            Some(Definition::Const) => self.session.get_constant(def_id).ok_or(Unevaluated::Pending(def_id))?,
+            Some(Definition::Variable) => self.locals.get(&def_id).cloned().expect("Variable should have been bound by a pattern."),
+            Some(Definition::Variant { index, .. }) => ConstValue::Variant { index, fields: Vec::new() },
+            definition => unreachable!("Constant expression cannot refer to {definition:?}.")
+        };
+
+        let fields = self.session.get_path_fields(id);
+        let mut ty = self.get_type(def_id);
+        for segment in path.segments[path.segments.len() - fields..].iter() {
+            (value, ty) = field_value(value, &ty, &segment.name);
+        }
+        Ok(value)
+    }
+
+    /// Whether the pattern matches value, binding its variables if it does.
+    fn bind_pattern(&mut self, pattern: &ast::Pattern, value: &ConstValue) -> bool {
+        match (&pattern.kind, value) {
+            (ast::PatternKind::Wildcard, _) => true,
+            (ast::PatternKind::Binding(_), _) => {
+                let def_id = self.session.get_ref_target(pattern.id).expect("Cannot find binding definition reference.");
+                self.locals.insert(def_id, value.clone());
+                true
+            }
+            (ast::PatternKind::Integer(expected), ConstValue::Integer(value)) => expected == value,
+            (ast::PatternKind::Bool(expected), ConstValue::Bool(value)) => expected == value,
+            (ast::PatternKind::Range { start, end, inclusive }, ConstValue::Integer(value)) =>
+                start <= value && (value < end || (*inclusive && value == end)),
+            (ast::PatternKind::Variant(_, patterns), ConstValue::Variant { index, fields }) => {
+                let def_id = self.session.get_ref_target(pattern.id).expect("Variant pattern should have been resolved by name resolution.");
+                let Some(Definition::Variant { index: expected, .. }) = self.session.get_def(def_id) else {
+                    unreachable!("Variant pattern should refer to a variant.")
+                };
+                expected == *index && patterns.iter().zip(fields.iter()).all(|(pattern, field)| self.bind_pattern(pattern, field))
+            }
+            (ast::PatternKind::Tuple(patterns), ConstValue::Aggregate(elements)) =>
+                patterns.iter().zip(elements.iter()).all(|(pattern, element)| self.bind_pattern(pattern, element)),
+            (ast::PatternKind::Or(alternatives), _) => alternatives.iter().any(|alternative| self.bind_pattern(alternative, value)),
+            _ => unreachable!("Pattern should have been checked against the type of its value.")
+        }
+    }
+}
+
+/// Value and type of field `name` of struct value.
+fn field_value(value: ConstValue, ty: &RamenType, name: &str) -> (ConstValue, RamenType) {
+    let RamenType::Struct(struct_type) = ty else { unreachable!("Only structs have named fields.") };
+    let index = struct_type.fields.iter().position(|field| field.name == name).expect("Field should have been checked by type resolution.");
+    let field = value.field(index).cloned().expect("Struct value should have all of its fields.");
+    (field, struct_type.fields[index].ty.clone())
+}
+
+fn compare<T: PartialOrd>(operator: ast::BinaryOperator, left: T, right: T) -> bool {
+    match operator {
+        ast::BinaryOperator::Less => left < right,
+        ast::BinaryOperator::LessEqual => left <= right,
+        ast::BinaryOperator::Greater => left > right,
+        ast::BinaryOperator::GreaterEqual => left >= right,
+        _ => unreachable!("Operator {operator} does not compare order.")
+    }
+}
+
+/// Floats of type `float32` are rounded to single precision, just like at runtime.
+fn float_value(value: f64, ty: &RamenType) -> ConstValue {
+    match ty {
+        RamenType::Float(32) => ConstValue::Float(value as f32 as f64),
+        _ => ConstValue::Float(value)
+    }
+}
+
+/// Whether integer of given width in bits can hold the value, which is the case if truncating it keeps it the same.
+/// Parser rejects integer types wider than 128 bits, so the widest ones hold every value.
+pub(crate) fn fits_integer(value: i128, width: usize) -> bool {
+    if width >= 128 { return true; }
+    let shift = 128 - width as u32;
+    (value << shift) >> shift == value
+}
@@ -0,0 +1,341 @@
+use ramen_common::{ast::{self, NodeId}, defs::Definition, error::ResolutionError, scope::ScopeRef, session::Session, types::RamenType, visitor::{walk_closure_expression, walk_let_statement, walk_match_expression, walk_parameter, ASTPass, ScopeStack, Visitor}};
+
+/// Maximum number of missing patterns listed in a single diagnostic.
+const MAX_REPORTED_WITNESSES: usize = 3;
+
+/// Checks that every match covers all possible values of its scrutinee
+/// and warns about arms that can never be reached.
+/// Patterns of `let` statements and parameters have to match every value.
+///
+/// Based on the usefulness algorithm from "Warnings for pattern matching" by Luc Maranget.
+pub struct MatchCheckingPass<'sess> {
+    pub session: &'sess Session,
+    pub stack: ScopeStack
+}
+
+impl<'sess> ASTPass<'sess, ()> for MatchCheckingPass<'sess> {
+    type Error = ResolutionError;
+
+    fn run_on_module(session: &'sess Session, scope: ScopeRef, mod_id: NodeId, module: &ast::Module) -> Result<(), Self::Error> {
+        let mut match_checker = Self {
+            session,
+            stack: ScopeStack::new()
+        };
+
+        match_checker.stack.push_scope(scope);
+        match_checker.visit_module(mod_id, module)?;
+
+        session.exit_if_errors();
+        Ok(())
+    }
+}
+
+/// Pattern reduced to the parts which matter for usefulness.
+#[derive(Debug, Clone)]
+enum Pat {
+    Wild,
+    Ctor(Ctor, Vec<Pat>),
+    Or(Vec<Pat>)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Ctor {
+    /// Inclusive range of integers, single literal is just a range with one value.
+    Range(i128, i128),
+    Variant(usize),
+    Tuple
+}
+
+impl Ctor {
+    fn covers(&self, other: &Ctor) -> bool {
+        match (self, other) {
+            (Ctor::Range(start, end), Ctor::Range(other_start, other_end)) => start <= other_start && other_end <= end,
+            (Ctor::Variant(index), Ctor::Variant(other_index)) => index == other_index,
+            (Ctor::Tuple, Ctor::Tuple) => true,
+            _ => false
+        }
+    }
+}
+
+/// Example of value that is not matched by any of the patterns.
+#[derive(Debug, Clone)]
+enum Witness {
+    Wild,
+    Range(i128, i128),
+    Bool(bool),
+    Variant { enum_name: String, name: String, index: usize, fields: Vec<Witness> },
+    Tuple(Vec<Witness>)
+}
+
+impl Witness {
+    fn to_pat(&self) -> Pat {
+        match self {
+            Witness::Wild => Pat::Wild,
+            Witness::Range(start, end) => Pat::Ctor(Ctor::Range(*start, *end), Vec::new()),
+            Witness::Bool(value) => Pat::Ctor(Ctor::Range(*value as i128, *value as i128), Vec::new()),
+            Witness::Tuple(elements) => Pat::Ctor(Ctor::Tuple, elements.iter().map(Witness::to_pat).collect()),
+            Witness::Variant { index, fields, .. } => Pat::Ctor(Ctor::Variant(*index), fields.iter().map(Witness::to_pat).collect())
+        }
+    }
+}
+
+impl std::fmt::Display for Witness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Witness::Wild => write!(f, "_"),
+            Witness::Range(start, end) if start == end => write!(f, "{start}"),
+            Witness::Range(start, end) => write!(f, "{start}..={end}"),
+            Witness::Bool(value) => write!(f, "{value}"),
+            Witness::Tuple(elements) if elements.len() == 1 => write!(f, "({},)", elements[0]),
+            Witness::Tuple(elements) => write!(f, "({})", elements.iter()
+                .map(|element| element.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")),
+            Witness::Variant { enum_name, name, fields, .. } if fields.is_empty() => write!(f, "{enum_name}.{name}"),
+            Witness::Variant { enum_name, name, fields, .. } => write!(f, "{enum_name}.{name}({})", fields.iter()
+                .map(|field| field.to_string())
+                .collect::<Vec<_>>()
+                .join(", "))
+        }
+    }
+}
+
+impl<'sess> MatchCheckingPass<'sess> {
+    fn lower_pattern(&self, pattern: &ast::Pattern) -> Pat {
+        match &pattern.kind {
+            ast::PatternKind::Wildcard | ast::PatternKind::Binding(_) => Pat::Wild,
+            ast::PatternKind::Integer(value) => Pat::Ctor(Ctor::Range(*value, *value), Vec::new()),
+            // Booleans are treated like a single bit integer.
+            ast::PatternKind::Bool(value) => Pat::Ctor(Ctor::Range(*value as i128, *value as i128), Vec::new()),
+            ast::PatternKind::Range { start, end, inclusive } => {
+                let end = if *inclusive { *end } else { end - 1 };
+                // Empty range matches nothing, just like an or-pattern without alternatives.
+                if end < *start { Pat::Or(Vec::new()) }
+                else { Pat::Ctor(Ctor::Range(*start, end), Vec::new()) }
+            }
+            ast::PatternKind::Variant(_, fields) => {
+                let def_id = self.session.get_ref_target(pattern.id).expect("Variant pattern should have been resolved.");
+                let Some(Definition::Variant { index, .. }) = self.session.get_def(def_id) else {
+                    unreachable!("Variant pattern should refer to a variant.")
+                };
+                Pat::Ctor(Ctor::Variant(index), fields.iter().map(|field| self.lower_pattern(field)).collect())
+            }
+            ast::PatternKind::Tuple(elements) => Pat::Ctor(Ctor::Tuple, elements.iter().map(|element| self.lower_pattern(element)).collect()),
+            ast::PatternKind::Or(alternatives) => Pat::Or(alternatives.iter().map(|alternative| self.lower_pattern(alternative)).collect())
+        }
+    }
+
+    fn field_types(ty: &RamenType, ctor: &Ctor) -> Vec<RamenType> {
+        match (ty, ctor) {
+            (RamenType::Enum(enum_type), Ctor::Variant(index)) => enum_type.variants[*index].fields.clone(),
+            (RamenType::Tuple(element_types), Ctor::Tuple) => element_types.clone(),
+            _ => Vec::new()
+        }
+    }
+
+    fn witness(ty: &RamenType, ctor: &Ctor, fields: Vec<Witness>) -> Witness {
+        match (ty, ctor) {
+            (RamenType::Enum(enum_type), Ctor::Variant(index)) => Witness::Variant {
+                enum_name: enum_type.name.clone(),
+                name: enum_type.variants[*index].name.clone(),
+                index: *index,
+                fields
+            },
+            (RamenType::Tuple(_), Ctor::Tuple) => Witness::Tuple(fields),
+            (RamenType::Bool, Ctor::Range(start, end)) if start == end => Witness::Bool(*start == 1),
+            (RamenType::Bool, Ctor::Range(..)) => Witness::Wild,
+            (_, Ctor::Range(start, end)) => Witness::Range(*start, *end),
+            _ => unreachable!()
+        }
+    }
+
+    /// Every constructor of the type, with integer ranges split so each piece is either fully inside
+    /// or fully outside of every range in `used`.
+    fn split_constructors(ty: &RamenType, used: &[Ctor]) -> Vec<Ctor> {
+        match ty {
+            RamenType::Enum(enum_type) => (0..enum_type.variants.len()).map(Ctor::Variant).collect(),
+            RamenType::Tuple(_) => vec![Ctor::Tuple],
+            RamenType::Bool => split_range(0, 1, used),
+            RamenType::Integer(width) => {
+                let (min, max) = integer_bounds(*width);
+                split_range(min, max, used)
+            }
+            _ => Vec::new()
+        }
+    }
+
+    /// Rows of the matrix which can match values built with `ctor`, with its fields unpacked in place of the first column.
+    fn specialize(rows: &[Vec<Pat>], ctor: &Ctor, arity: usize) -> Vec<Vec<Pat>> {
+        rows.iter().filter_map(|row| {
+            let mut specialized = match &row[0] {
+                Pat::Wild => vec![Pat::Wild; arity],
+                Pat::Ctor(row_ctor, fields) if row_ctor.covers(ctor) => fields.clone(),
+                _ => return None
+            };
+            specialized.extend_from_slice(&row[1..]);
+            Some(specialized)
+        }).collect()
+    }
+
+    /// Returns values matched by `vector` but not by any of the `rows`, or `None` if there are none.
+    fn is_useful(&self, rows: &[Vec<Pat>], vector: &[Pat], types: &[RamenType]) -> Option<Vec<Witness>> {
+        let Some((head, tail)) = vector.split_first() else {
+            return if rows.is_empty() { Some(Vec::new()) } else { None };
+        };
+
+        // Or-patterns in the first column are expanded into separate rows.
+        let rows = rows.iter().flat_map(|row| match &row[0] {
+            Pat::Or(alternatives) => alternatives.iter().map(|alternative| {
+                let mut expanded = vec![alternative.clone()];
+                expanded.extend_from_slice(&row[1..]);
+                expanded
+            }).collect(),
+            _ => vec![row.clone()]
+        }).collect::<Vec<_>>();
+        let used_ctors = rows.iter().filter_map(|row| match &row[0] {
+            Pat::Ctor(ctor, _) => Some(*ctor),
+            _ => None
+        }).collect::<Vec<_>>();
+
+        let candidates = match head {
+            Pat::Or(alternatives) => return alternatives.iter().find_map(|alternative| {
+                let mut expanded = vec![alternative.clone()];
+                expanded.extend_from_slice(tail);
+                self.is_useful(&rows, &expanded, types)
+            }),
+            Pat::Ctor(Ctor::Range(start, end), _) => split_range(*start, *end, &used_ctors),
+            Pat::Ctor(ctor, _) => vec![*ctor],
+            // Enum without variants has no values, so no constructors is already all of them.
+            Pat::Wild if used_ctors.is_empty() && !is_empty_enum(&types[0]) => {
+                // Nothing in this column distinguishes constructors, so only the rest of the vector matters.
+                let default_rows = rows.iter()
+                    .filter(|row| matches!(row[0], Pat::Wild))
+                    .map(|row| row[1..].to_vec())
+                    .collect::<Vec<_>>();
+                let mut witnesses = self.is_useful(&default_rows, tail, &types[1..])?;
+                witnesses.insert(0, Witness::Wild);
+                return Some(witnesses);
+            }
+            Pat::Wild => Self::split_constructors(&types[0], &used_ctors)
+        };
+
+        candidates.iter().find_map(|ctor| {
+            let field_types = Self::field_types(&types[0], ctor);
+            let arity = field_types.len();
+
+            let mut specialized_vector = match head {
+                Pat::Ctor(_, fields) => fields.clone(),
+                _ => vec![Pat::Wild; arity]
+            };
+            specialized_vector.extend_from_slice(tail);
+            let mut specialized_types = field_types;
+            specialized_types.extend_from_slice(&types[1..]);
+
+            let mut witnesses = self.is_useful(&Self::specialize(&rows, ctor, arity), &specialized_vector, &specialized_types)?;
+            let fields = witnesses.drain(..arity).collect();
+            witnesses.insert(0, Self::witness(&types[0], ctor, fields));
+            Some(witnesses)
+        })
+    }
+
+    /// Patterns outside of match have to cover every value, as there is no other arm to fall back to.
+    fn check_irrefutable(&self, pattern: &ast::Pattern) -> Result<(), ResolutionError> {
+        let ty = self.session.get_type(pattern.id).expect("Pattern type should have been resolved.");
+        let rows = vec![vec![self.lower_pattern(pattern)]];
+        match self.is_useful(&rows, &[Pat::Wild], &[ty]) {
+            Some(mut witnesses) => Err(ResolutionError::RefutablePattern {
+                missing: vec![witnesses.remove(0).to_string()],
+                location: pattern.location.clone()
+            }),
+            None => Ok(())
+        }
+    }
+}
+
+fn is_empty_enum(ty: &RamenType) -> bool {
+    matches!(ty, RamenType::Enum(enum_type) if enum_type.variants.is_empty())
+}
+
+fn integer_bounds(width: usize) -> (i128, i128) {
+    if width >= 128 { return (i128::MIN, i128::MAX); }
+    let half = 1i128 << (width.max(1) - 1);
+    (-half, half - 1)
+}
+
+/// Splits `start..=end` on every boundary of ranges in `used`.
+fn split_range(start: i128, end: i128, used: &[Ctor]) -> Vec<Ctor> {
+    let mut boundaries = vec![start];
+    for ctor in used.iter() {
+        if let Ctor::Range(used_start, used_end) = ctor {
+            if *used_start > start && *used_start <= end { boundaries.push(*used_start); }
+            if *used_end >= start && *used_end < end { boundaries.push(used_end + 1); }
+        }
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    boundaries.iter().enumerate().map(|(index, boundary)| {
+        let next = boundaries.get(index + 1).map(|next| next - 1).unwrap_or(end);
+        Ctor::Range(*boundary, next)
+    }).collect()
+}
+
+impl<'sess> Visitor<()> for MatchCheckingPass<'sess> {
+    type Error = ResolutionError;
+
+    fn default_return(&self) {}
+    fn get_scope_stack(&self) -> &ScopeStack { &self.stack }
+    fn get_session(&self) -> &Session { self.session }
+
+    fn visit_let_statement(&mut self, _id: NodeId, pattern: &ast::Pattern, ty: Option<&ast::Type>, value: &ast::Expression) -> Result<(), Self::Error> {
+        walk_let_statement(self, pattern, ty, value)?;
+        self.check_irrefutable(pattern)
+    }
+
+    fn visit_parameter(&mut self, parameter: &ast::Parameter) -> Result<(), Self::Error> {
+        walk_parameter(self, parameter)?;
+        self.check_irrefutable(&parameter.pattern)
+    }
+
+    fn visit_closure_expression(&mut self, _id: NodeId, closure: &ast::Closure) -> Result<(), Self::Error> {
+        for parameter in closure.parameters.iter() {
+            self.check_irrefutable(&parameter.pattern)?;
+        }
+        walk_closure_expression(self, closure)
+    }
+
+    fn visit_match_expression(&mut self, _id: NodeId, scrutinee: &ast::Expression, arms: &[ast::MatchArm]) -> Result<(), Self::Error> {
+        walk_match_expression(self, scrutinee, arms)?;
+
+        let scrutinee_type = self.session.get_type(scrutinee.id).expect("Scrutinee type should have been resolved.");
+        let types = [scrutinee_type];
+
+        let mut rows = Vec::<Vec<Pat>>::new();
+        for arm in arms.iter() {
+            let row = vec![self.lower_pattern(&arm.pattern)];
+            if self.is_useful(&rows, &row, &types).is_none() {
+                self.session.print_diagnostic(&ResolutionError::UnreachablePattern {
+                    location: arm.pattern.location.clone()
+                });
+            }
+            rows.push(row);
+        }
+
+        // Each found witness is added as an extra row, so the next one has to be different.
+        let mut missing = Vec::<Witness>::new();
+        while missing.len() < MAX_REPORTED_WITNESSES {
+            let Some(mut witnesses) = self.is_useful(&rows, &[Pat::Wild], &types) else { break };
+            let witness = witnesses.remove(0);
+            rows.push(vec![witness.to_pat()]);
+            missing.push(witness);
+        }
+
+        if !missing.is_empty() {
+            return Err(ResolutionError::NonExhaustiveMatch {
+                missing: missing.iter().map(|witness| witness.to_string()).collect(),
+                location: scrutinee.location.clone()
+            });
+        }
+        Ok(())
+    }
+}
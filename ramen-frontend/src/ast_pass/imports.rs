@@ -0,0 +1,305 @@
+use std::rc::Rc;
+
+use ramen_common::{ast::{self, NodeId}, defs::{DefId, Definition, Privacy}, error::ResolutionError, scope::{Binding, Import, Scope, ScopeRef}, session::Session, visitor::{walk_item, walk_module, ASTPass, ScopeStack, Visitor}, Loc};
+
+use super::privacy::check_access;
+
+/// Binds names imported by `use` items in the scopes containing them.
+/// Imports can refer to names brought in by other imports, so they are resolved repeatedly until nothing changes.
+pub struct ImportResolutionPass<'sess> {
+    pub session: &'sess Session,
+    pub stack: ScopeStack,
+
+    /// Leaves of all use trees in the module, in order of appearance.
+    imports: Vec<PendingImport>,
+    /// Definitions of modules enclosing currently visited item, innermost last.
+    modules: Vec<DefId>
+}
+
+/// Name brought in by a leaf of use tree, glob imports are named by their path like `shapes.*`.
+#[derive(Debug, Clone)]
+pub struct ImportedName {
+    pub tree: NodeId,
+    pub name: String,
+    pub visibility: ast::Visibility,
+    pub location: Loc
+}
+
+struct PendingImport {
+    /// Scope containing the `use` item.
+    scope: ScopeRef,
+    /// Module containing the `use` item, only members visible in it can be imported.
+    module: DefId,
+    path: Vec<ast::PathSegment>,
+    /// Name the definition is imported under, glob imports have none.
+    alias: Option<String>,
+    tree: NodeId,
+    visibility: ast::Visibility,
+    location: Loc
+}
+
+/// Why path of import could not be resolved.
+enum Unresolved {
+    /// Segment with given index was not found in `scope`, it may still be brought in by another import.
+    Missing { scope: ScopeRef, segment: usize },
+    Error(ResolutionError)
+}
+
+impl From<ResolutionError> for Unresolved {
+    fn from(error: ResolutionError) -> Self { Self::Error(error) }
+}
+
+impl<'sess> ASTPass<'sess, Vec<ImportedName>> for ImportResolutionPass<'sess> {
+    type Error = ResolutionError;
+
+    fn run_on_module(session: &'sess Session, scope: ScopeRef, mod_id: NodeId, module: &ast::Module) -> Result<Vec<ImportedName>, Self::Error> {
+        let mut import_resolver = Self {
+            session,
+            stack: ScopeStack::new(),
+
+            imports: Vec::new(),
+            modules: Vec::new()
+        };
+
+        import_resolver.stack.push_scope(scope);
+        import_resolver.visit_module(mod_id, module)?;
+        import_resolver.resolve_imports()
+    }
+}
+
+/// Definition the name bound by `binding` refers to, marking the import it comes from as used.
+pub(crate) fn use_binding(session: &Session, binding: Binding, segment: &ast::PathSegment) -> Result<DefId, ResolutionError> {
+    if let Binding::Import(import) = &binding {
+        if import.is_ambiguous {
+            return Err(ResolutionError::AmbiguousName {
+                name: segment.name.clone(),
+                location: segment.location.clone()
+            });
+        }
+        session.mark_import_used(import.tree);
+    }
+    Ok(binding.target())
+}
+
+impl<'sess> ImportResolutionPass<'sess> {
+    fn collect_tree(&mut self, tree: &ast::UseTree, prefix: &[ast::PathSegment], visibility: ast::Visibility) {
+        let path = prefix.iter().chain(tree.prefix.iter()).cloned().collect::<Vec<_>>();
+        let alias = match &tree.kind {
+            ast::UseTreeKind::Simple(alias) => alias.clone().or_else(|| path.last().map(|segment| segment.name.clone())),
+            ast::UseTreeKind::Glob => None,
+            ast::UseTreeKind::Nested(trees) => {
+                for nested in trees.iter() {
+                    self.collect_tree(nested, &path, visibility);
+                }
+                return;
+            }
+        };
+
+        let module = *self.modules.last().expect("Imports should be inside of a module.");
+        self.session.set_privacy(tree.id, Privacy {
+            visibility,
+            module,
+            location: tree.location.clone()
+        });
+        self.imports.push(PendingImport {
+            scope: self.stack.get_scope(),
+            module,
+            path,
+            alias,
+            tree: tree.id,
+            visibility,
+            location: tree.location.clone()
+        });
+    }
+
+    /// Looks up segment of import path in both namespaces.
+    /// The first segment is searched in the scope of the import and its parents, the rest are members of `scope`.
+    fn lookup(&self, import: &PendingImport, scope: &ScopeRef, index: usize) -> [Option<Binding>; 2] {
+        let name = &import.path[index].name;
+        [Scope::NS_NAMES, Scope::NS_TYPES].map(|namespace| if index == 0 {
+            scope.search_binding(namespace, name)
+        } else {
+            scope.get_local_binding(namespace, name)
+        })
+    }
+
+    /// Definition segment of import path refers to, members have to be visible in the module of the import.
+    fn use_segment(&self, import: &PendingImport, binding: Binding, index: usize) -> Result<DefId, ResolutionError> {
+        let segment = &import.path[index];
+        if index > 0 {
+            check_access(self.session, binding.privacy_id(), import.module, &segment.name, &segment.location)?;
+        }
+        use_binding(self.session, binding, segment)
+    }
+
+    /// Import cannot make the definition it brings in visible in more places than the definition itself is.
+    fn check_reexport(&self, import: &PendingImport, binding: &Binding) -> Result<(), ResolutionError> {
+        let Some(privacy) = self.session.get_privacy(binding.privacy_id()) else { return Ok(()) };
+        if import.visibility <= privacy.visibility {
+            return Ok(());
+        }
+        let segment = import.path.last().unwrap();
+        Err(ResolutionError::PrivateReexport {
+            name: segment.name.clone(),
+            location: segment.location.clone(),
+            definition: privacy.location
+        })
+    }
+
+    /// Resolves first `length` segments of import path to something with members, like module or enum.
+    fn resolve_scope(&self, import: &PendingImport, length: usize) -> Result<ScopeRef, Unresolved> {
+        let mut scope = import.scope.clone();
+        for (index, segment) in import.path[..length].iter().enumerate() {
+            // Name of struct refers to its constructor, but its members are in the scope of the type.
+            let [name, ty] = self.lookup(import, &scope, index);
+            let binding = name
+                .filter(|binding| !matches!(self.session.get_def(binding.target()), Some(Definition::Constructor { .. })))
+                .or(ty)
+                .ok_or_else(|| Unresolved::Missing { scope: scope.clone(), segment: index })?;
+            let def_id = self.use_segment(import, binding, index)?;
+
+            scope = match self.session.get_def(def_id) {
+                Some(Definition::Module | Definition::Enum | Definition::Struct { .. } | Definition::Trait) => self.session.scopes.get(def_id),
+                _ => None
+            }.ok_or_else(|| ResolutionError::UnresolvedImport {
+                name: import.path.get(index + 1).unwrap_or(segment).name.clone(),
+                location: import.location.clone()
+            })?;
+        }
+        Ok(scope)
+    }
+
+    /// Binds the single name imported by `import`, in every namespace its target has a definition in.
+    fn resolve_single(&self, import: &PendingImport, alias: &str) -> Result<(), Unresolved> {
+        let last = import.path.len() - 1;
+        let scope = self.resolve_scope(import, last)?;
+        let bindings = self.lookup(import, &scope, last);
+        if bindings.iter().all(Option::is_none) {
+            return Err(Unresolved::Missing { scope, segment: last });
+        }
+
+        for (namespace, binding) in bindings.into_iter().enumerate() {
+            let Some(binding) = binding else { continue };
+            self.check_reexport(import, &binding)?;
+            let target = self.use_segment(import, binding, last)?;
+            import.scope.import(namespace, alias, Import {
+                target,
+                tree: import.tree,
+                is_glob: false,
+                is_ambiguous: false
+            }).map_err(|_| ResolutionError::ConflictingImport {
+                name: alias.to_string(),
+                location: import.location.clone()
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Binds every member of `members` visible to glob `import` and not shadowed in its scope, returns whether any was added.
+    fn expand_glob(&self, import: &PendingImport, members: &ScopeRef) -> bool {
+        let mut changed = false;
+        for namespace in [Scope::NS_NAMES, Scope::NS_TYPES] {
+            for (name, binding) in members.get_members(namespace) {
+                if !self.session.is_visible(binding.privacy_id(), import.module) { continue; }
+                changed |= import.scope.import(namespace, name, Import {
+                    target: binding.target(),
+                    tree: import.tree,
+                    is_glob: true,
+                    is_ambiguous: false
+                }).unwrap_or(false);
+            }
+        }
+        changed
+    }
+
+    fn resolve_imports(mut self) -> Result<Vec<ImportedName>, ResolutionError> {
+        let names = self.imports.iter()
+            .map(|import| ImportedName {
+                tree: import.tree,
+                name: match &import.alias {
+                    Some(alias) => alias.clone(),
+                    None => format!("{}.*", import.path.iter().map(|segment| segment.name.as_str()).collect::<Vec<_>>().join("."))
+                },
+                visibility: import.visibility,
+                location: import.location.clone()
+            })
+            .collect();
+
+        // Members of glob imported scopes can grow as their own imports are resolved, so globs are expanded every round.
+        let mut pending = std::mem::take(&mut self.imports);
+        let mut globs = Vec::<(PendingImport, ScopeRef)>::new();
+        let mut missing = Vec::new();
+        loop {
+            let mut changed = false;
+            missing.clear();
+            for import in std::mem::take(&mut pending) {
+                let result = match &import.alias {
+                    Some(alias) => self.resolve_single(&import, alias).map(|_| None),
+                    None => self.resolve_scope(&import, import.path.len()).map(Some)
+                };
+                match result {
+                    Ok(members) => {
+                        changed = true;
+                        if let Some(members) = members {
+                            globs.push((import, members));
+                        }
+                    }
+                    Err(Unresolved::Missing { scope, segment }) => {
+                        missing.push((scope, segment));
+                        pending.push(import);
+                    }
+                    Err(Unresolved::Error(error)) => return Err(error)
+                }
+            }
+            for (import, members) in globs.iter() {
+                changed |= self.expand_glob(import, members);
+            }
+
+            if !changed { break; }
+        }
+
+        // Name missing in a scope that another unresolved import would bind it in can only come from imports depending on each other.
+        if let Some((import, (scope, segment))) = pending.iter().zip(missing).next() {
+            let segment = &import.path[segment];
+            let is_cycle = pending.iter().any(|other| Rc::ptr_eq(&other.scope, &scope) && other.alias.as_ref() == Some(&segment.name));
+            return Err(if is_cycle {
+                ResolutionError::ImportCycle {
+                    name: segment.name.clone(),
+                    location: import.location.clone()
+                }
+            } else {
+                ResolutionError::UnresolvedImport {
+                    name: segment.name.clone(),
+                    location: segment.location.clone()
+                }
+            });
+        }
+        Ok(names)
+    }
+}
+
+impl<'sess> Visitor<()> for ImportResolutionPass<'sess> {
+    type Error = ResolutionError;
+
+    fn default_return(&self) {}
+    fn get_scope_stack(&self) -> &ScopeStack { &self.stack }
+    fn get_session(&self) -> &Session { self.session }
+
+    fn visit_item(&mut self, item: &ast::Item) -> Result<(), Self::Error> {
+        match &item.kind {
+            ast::ItemKind::Use(use_) => {
+                self.collect_tree(&use_.tree, &[], item.visibility);
+                Ok(())
+            }
+            _ => walk_item(self, item)
+        }
+    }
+
+    fn visit_module(&mut self, id: NodeId, module: &ast::Module) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find module definition reference.");
+        self.modules.push(def_id);
+        let result = walk_module(self, id, module);
+        self.modules.pop();
+        result
+    }
+}
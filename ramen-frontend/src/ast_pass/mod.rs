@@ -1,2 +1,9 @@
+pub mod attributes;
 pub mod binding;
+pub mod const_eval;
+pub mod exhaustiveness;
+pub mod imports;
+pub mod name_resolution;
+pub mod privacy;
+pub mod type_declaration;
 pub mod type_resolution;
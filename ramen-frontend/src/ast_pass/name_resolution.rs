@@ -0,0 +1,410 @@
+use hashbrown::HashSet;
+use ramen_common::{ast::{self, NodeId}, defs::{DefId, Definition}, error::ResolutionError, scope::{Scope, ScopeRef}, session::Session, visitor::{walk_closure_expression, walk_enum, walk_function, walk_global, walk_impl, walk_module, walk_struct, walk_trait, walk_match_arm, walk_pattern, walk_type, ASTPass, ScopeStack, Visitor}};
+
+use super::{attributes::check_deprecated, imports::{use_binding, ImportResolutionPass}, privacy::check_access};
+
+/// Binds local names (parameters, pattern bindings) and resolves every path used in expressions and types to its definition.
+pub struct NameResolutionPass<'sess> {
+    pub session: &'sess Session,
+    pub stack: ScopeStack,
+
+    /// Definitions of functions and types enclosing currently visited node, innermost last.
+    owners: Vec<DefId>,
+    /// Closures enclosing currently visited node together with variables defined inside of them, innermost last.
+    closures: Vec<(NodeId, HashSet<DefId>)>,
+    /// Definitions of modules enclosing currently visited node, innermost last.
+    modules: Vec<DefId>
+}
+
+impl<'sess> ASTPass<'sess, ()> for NameResolutionPass<'sess> {
+    type Error = ResolutionError;
+
+    fn run_on_module(session: &'sess Session, scope: ScopeRef, mod_id: NodeId, module: &ast::Module) -> Result<(), Self::Error> {
+        let mut name_resolver = Self {
+            session,
+            stack: ScopeStack::new(),
+
+            owners: Vec::new(),
+            closures: Vec::new(),
+            modules: Vec::new()
+        };
+
+        // Imported names and methods have to be known before any path can refer to them.
+        let imports = ImportResolutionPass::run_on_module(session, scope.clone(), mod_id, module)?;
+        let mut method_collector = InherentImplCollector {
+            session,
+            stack: ScopeStack::new(),
+            modules: Vec::new()
+        };
+        method_collector.stack.push_scope(scope.clone());
+        method_collector.visit_module(mod_id, module)?;
+
+        name_resolver.stack.push_scope(scope);
+        name_resolver.visit_module(mod_id, module)?;
+
+        // Imports visible outside of their module can be used by other modules, so only private ones can be unused.
+        for import in imports.into_iter().filter(|import| import.visibility == ast::Visibility::Private && !session.is_import_used(import.tree)) {
+            session.print_diagnostic(&ResolutionError::UnusedImport {
+                name: import.name,
+                location: import.location
+            });
+        }
+
+        session.exit_if_errors();
+        Ok(())
+    }
+}
+
+impl<'sess> NameResolutionPass<'sess> {
+    /// Resolves types in explicit type arguments of path resolved to `def_id`, ignoring its `fields`.
+    /// Only the segment naming the definition can have them, or the enum segment in paths to variants like `Option<int32>.None`.
+    fn visit_type_arguments(&mut self, path: &ast::Path, def_id: DefId, fields: usize, allow_arguments: bool) -> Result<(), ResolutionError> {
+        let target = path.segments.len() - fields - 1;
+        let is_variant = matches!(self.session.get_def(def_id), Some(Definition::Variant { .. }));
+
+        let mut has_arguments = false;
+        for (index, segment) in path.segments.iter().enumerate() {
+            if segment.type_arguments.is_empty() { continue; }
+
+            let is_allowed = index == target || (is_variant && index + 1 == target);
+            if !(is_allowed && allow_arguments) || has_arguments {
+                return Err(ResolutionError::GenericArgumentCountMismatch {
+                    expected: 0,
+                    found: segment.type_arguments.len(),
+                    location: segment.location.clone()
+                });
+            }
+
+            for argument in segment.type_arguments.iter() {
+                self.visit_type(argument)?;
+            }
+            has_arguments = true;
+        }
+        Ok(())
+    }
+
+    /// Resolves path to a trait, like the one in bound or implementation.
+    fn resolve_trait(&self, trait_ref: &ast::TraitRef) -> Result<DefId, ResolutionError> {
+        let (def_id, _) = resolve_path(self.session, &self.stack.get_scope(), self.current_module(), &trait_ref.path, Scope::NS_TYPES)?;
+        if !matches!(self.session.get_def(def_id), Some(Definition::Trait)) {
+            return Err(ResolutionError::NotATrait {
+                name: trait_ref.path.segments.last().unwrap().name.clone(),
+                location: trait_ref.location.clone()
+            });
+        }
+
+        self.session.set_ref(trait_ref.id, def_id);
+        check_deprecated(self.session, def_id, &trait_ref.path.segments.last().unwrap().name, &trait_ref.location);
+        Ok(def_id)
+    }
+
+    fn resolve_bounds(&self, generics: &[ast::GenericParameter]) -> Result<(), ResolutionError> {
+        for generic in generics.iter() {
+            let bounds = generic.bounds.iter()
+                .map(|bound| self.resolve_trait(bound))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let def_id = self.session.get_ref_target(generic.id).expect("Cannot find type parameter definition reference.");
+            self.session.set_trait_bounds(def_id, bounds);
+        }
+        Ok(())
+    }
+
+    fn current_module(&self) -> DefId {
+        *self.modules.last().expect("Names should be resolved inside of a module.")
+    }
+
+    /// Constants and statics are owners of their own, so type parameters of enclosing functions cannot be used in them.
+    fn visit_global(&mut self, id: NodeId, ty: &ast::Type, value: &ast::Expression) -> Result<(), ResolutionError> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find constant definition reference.");
+        self.owners.push(def_id);
+        let result = walk_global(self, ty, value);
+        self.owners.pop();
+        result
+    }
+
+    /// Records use of variable in every enclosing closure that does not define it.
+    fn capture(&self, def_id: DefId) {
+        for (closure_id, locals) in self.closures.iter().rev() {
+            if locals.contains(&def_id) { break; }
+            self.session.add_capture(*closure_id, def_id);
+        }
+    }
+
+    /// Whether type parameter of `owner` can be used in currently visited item.
+    /// `Self` of a trait is usable in signatures of its methods.
+    fn is_visible_parameter(&self, owner: DefId) -> bool {
+        match self.owners.last() {
+            Some(&innermost) if innermost == owner => true,
+            Some(&innermost) => matches!(self.session.get_def(innermost), Some(Definition::TraitMethod { parent }) if parent == owner),
+            None => false
+        }
+    }
+}
+
+/// Resolves path in `scope` inside of `module`, last segment is searched in `namespace`.
+/// Leading segments have to name something with its own scope, like module, enum or struct, whose members have to be visible in `module`.
+/// Segments following a variable, constant or static in value paths are its fields, their count is returned along with the definition.
+fn resolve_path(session: &Session, scope: &ScopeRef, module: DefId, path: &ast::Path, namespace: usize) -> Result<(DefId, usize), ResolutionError> {
+    let (first, rest) = path.segments.split_first().expect("Path should have at least one segment.");
+
+    let binding = if rest.is_empty() {
+        scope.search_binding(namespace, &first.name)
+    } else {
+        scope.search_binding(Scope::NS_NAMES, &first.name)
+            .filter(|binding| continues_path(session.get_def(binding.target()), namespace))
+            .or_else(|| scope.search_binding(Scope::NS_TYPES, &first.name))
+    }.ok_or_else(|| unresolved(first, if rest.is_empty() { namespace } else { Scope::NS_NAMES }))?;
+    let mut def_id = use_binding(session, binding, first)?;
+
+    let mut parent = first;
+    for (index, segment) in rest.iter().enumerate() {
+        if namespace == Scope::NS_NAMES && session.get_def(def_id).is_some_and(|def| def.has_value_fields()) {
+            return Ok((def_id, rest.len() - index));
+        }
+
+        let member_scope = match session.get_def(def_id) {
+            Some(Definition::Module | Definition::Enum | Definition::Struct { .. } | Definition::Trait) => session.scopes.get(def_id),
+            _ => None
+        }.ok_or_else(|| unresolved(parent, Scope::NS_NAMES))?;
+
+        let is_last = index == rest.len() - 1;
+        let binding = if is_last {
+            member_scope.get_local_binding(namespace, &segment.name)
+        } else {
+            member_scope.get_local_binding(Scope::NS_NAMES, &segment.name)
+                .filter(|binding| continues_path(session.get_def(binding.target()), namespace))
+                .or_else(|| member_scope.get_local_binding(Scope::NS_TYPES, &segment.name))
+        }.ok_or_else(|| unresolved(segment, if is_last { namespace } else { Scope::NS_NAMES }))?;
+        check_access(session, binding.privacy_id(), module, &segment.name, &segment.location)?;
+        def_id = use_binding(session, binding, segment)?;
+        parent = segment;
+    }
+
+    Ok((def_id, 0))
+}
+
+/// Whether value `def` can be followed by further segments of path searched in `namespace`, as modules and values with fields can.
+/// Other values, like functions or struct constructors, leave the name to the type it shares, so `Shape.Circle` names
+/// the variant even if there is a function `Shape`.
+fn continues_path(def: Option<Definition>, namespace: usize) -> bool {
+    match def {
+        Some(Definition::Module) => true,
+        Some(def) => namespace == Scope::NS_NAMES && def.has_value_fields(),
+        None => false
+    }
+}
+
+fn unresolved(segment: &ast::PathSegment, namespace: usize) -> ResolutionError {
+    let (name, location) = (segment.name.clone(), segment.location.clone());
+    match namespace {
+        Scope::NS_TYPES => ResolutionError::UnresolvedType { name, location },
+        _ => ResolutionError::UnresolvedName { name, location }
+    }
+}
+
+fn find_binding(pattern: &ast::Pattern) -> Option<&ast::Pattern> {
+    match &pattern.kind {
+        ast::PatternKind::Binding(_) => Some(pattern),
+        ast::PatternKind::Variant(_, patterns) | ast::PatternKind::Tuple(patterns) | ast::PatternKind::Or(patterns) =>
+            patterns.iter().find_map(find_binding),
+        _ => None
+    }
+}
+
+impl<'sess> Visitor<()> for NameResolutionPass<'sess> {
+    type Error = ResolutionError;
+
+    fn default_return(&self) {}
+    fn get_scope_stack(&self) -> &ScopeStack { &self.stack }
+    fn get_session(&self) -> &Session { self.session }
+
+    fn visit_module(&mut self, id: NodeId, module: &ast::Module) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find module definition reference.");
+        self.modules.push(def_id);
+        let result = walk_module(self, id, module);
+        self.modules.pop();
+        result
+    }
+
+    fn visit_function(&mut self, id: NodeId, function: &ast::Function) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find function definition reference.");
+        self.resolve_bounds(&function.generics)?;
+        self.owners.push(def_id);
+        let result = walk_function(self, id, function);
+        self.owners.pop();
+        result
+    }
+
+    fn visit_enum(&mut self, id: NodeId, enum_: &ast::Enum) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find enum definition reference.");
+        self.resolve_bounds(&enum_.generics)?;
+        self.owners.push(def_id);
+        let result = walk_enum(self, id, enum_);
+        self.owners.pop();
+        result
+    }
+
+    fn visit_struct(&mut self, id: NodeId, struct_: &ast::Struct) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find struct definition reference.");
+        self.resolve_bounds(&struct_.generics)?;
+        self.owners.push(def_id);
+        let result = walk_struct(self, id, struct_);
+        self.owners.pop();
+        result
+    }
+
+    fn visit_trait(&mut self, id: NodeId, trait_: &ast::Trait) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find trait definition reference.");
+        self.owners.push(def_id);
+        let result = walk_trait(self, id, trait_);
+        self.owners.pop();
+        result
+    }
+
+    fn visit_impl(&mut self, id: NodeId, impl_: &ast::Impl) -> Result<(), Self::Error> {
+        if let Some(trait_ref) = &impl_.trait_ {
+            self.resolve_trait(trait_ref)?;
+        }
+        walk_impl(self, id, impl_)
+    }
+
+    fn visit_const(&mut self, id: NodeId, const_: &ast::Const) -> Result<(), Self::Error> {
+        self.visit_global(id, &const_.ty, &const_.value)
+    }
+
+    fn visit_static(&mut self, id: NodeId, static_: &ast::Static) -> Result<(), Self::Error> {
+        self.visit_global(id, &static_.ty, &static_.value)
+    }
+
+    fn visit_path_expression(&mut self, id: NodeId, path: &ast::Path) -> Result<(), Self::Error> {
+        let (def_id, fields) = resolve_path(self.session, &self.stack.get_scope(), self.current_module(), path, Scope::NS_NAMES)?;
+        self.session.set_ref(id, def_id);
+        let named = &path.segments[path.segments.len() - 1 - fields];
+        check_deprecated(self.session, def_id, &named.name, &named.location);
+        if let Some(Definition::Variable) = self.session.get_def(def_id) {
+            self.capture(def_id);
+        }
+        if fields > 0 {
+            self.session.set_path_fields(id, fields);
+        }
+        self.visit_type_arguments(path, def_id, fields, true)
+    }
+
+    fn visit_match_arm(&mut self, arm: &ast::MatchArm) -> Result<(), Self::Error> {
+        let arm_scope = self.session.scopes.add(arm.id, Some(self.stack.get_scope()), None);
+        self.with_scope(arm_scope, |resolver| walk_match_arm(resolver, arm))
+    }
+
+    fn visit_closure_expression(&mut self, id: NodeId, closure: &ast::Closure) -> Result<(), Self::Error> {
+        let closure_scope = self.session.scopes.add(id, Some(self.stack.get_scope()), None);
+        self.closures.push((id, HashSet::new()));
+        let result = self.with_scope(closure_scope, |resolver| walk_closure_expression(resolver, closure));
+        self.closures.pop();
+        result
+    }
+
+    fn visit_pattern(&mut self, pattern: &ast::Pattern) -> Result<(), Self::Error> {
+        match &pattern.kind {
+            ast::PatternKind::Binding(name) => {
+                let binding_def_id = self.session.alloc_def(pattern.id);
+                self.session.set_def(binding_def_id, Definition::Variable);
+                self.stack.get_scope().define_name(name, binding_def_id);
+                for (_, locals) in self.closures.iter_mut() {
+                    locals.insert(binding_def_id);
+                }
+            }
+            ast::PatternKind::Variant(path, _) => {
+                let (def_id, fields) = resolve_path(self.session, &self.stack.get_scope(), self.current_module(), path, Scope::NS_NAMES)?;
+                if fields > 0 {
+                    return Err(unresolved(&path.segments[path.segments.len() - fields], Scope::NS_NAMES));
+                }
+                self.session.set_ref(pattern.id, def_id);
+                self.visit_type_arguments(path, def_id, 0, false)?;
+            }
+            ast::PatternKind::Or(alternatives) => {
+                if let Some(binding) = alternatives.iter().find_map(find_binding) {
+                    return Err(ResolutionError::BindingInOrPattern { location: binding.location.clone() });
+                }
+            }
+            _ => ()
+        }
+
+        walk_pattern(self, pattern)
+    }
+
+    fn visit_type(&mut self, ty: &ast::Type) -> Result<(), Self::Error> {
+        if let ast::TypeKind::Dyn(trait_ref) = &ty.kind {
+            self.resolve_trait(trait_ref)?;
+        }
+        if let ast::TypeKind::Path(path) = &ty.kind {
+            let (def_id, _) = resolve_path(self.session, &self.stack.get_scope(), self.current_module(), path, Scope::NS_TYPES)?;
+            if let Some(Definition::TypeParameter { owner }) = self.session.get_def(def_id) {
+                if !self.is_visible_parameter(owner) {
+                    let segment = path.segments.last().unwrap();
+                    return Err(ResolutionError::OuterTypeParameter {
+                        name: segment.name.clone(),
+                        location: segment.location.clone()
+                    });
+                }
+            }
+
+            self.session.set_ref(ty.id, def_id);
+            let segment = path.segments.last().unwrap();
+            check_deprecated(self.session, def_id, &segment.name, &segment.location);
+            self.visit_type_arguments(path, def_id, 0, true)?;
+        }
+        walk_type(self, ty)
+    }
+}
+
+/// Defines methods of inherent implementations in the scope of their type, next to its variants,
+/// so that paths like `Point.new` resolve to them.
+struct InherentImplCollector<'sess> {
+    session: &'sess Session,
+    stack: ScopeStack,
+    modules: Vec<DefId>
+}
+
+impl<'sess> Visitor<()> for InherentImplCollector<'sess> {
+    type Error = ResolutionError;
+
+    fn default_return(&self) {}
+    fn get_scope_stack(&self) -> &ScopeStack { &self.stack }
+    fn get_session(&self) -> &Session { self.session }
+
+    fn visit_module(&mut self, id: NodeId, module: &ast::Module) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find module definition reference.");
+        self.modules.push(def_id);
+        let result = walk_module(self, id, module);
+        self.modules.pop();
+        result
+    }
+
+    fn visit_impl(&mut self, id: NodeId, impl_: &ast::Impl) -> Result<(), Self::Error> {
+        if impl_.trait_.is_none() {
+            let invalid_target = || ResolutionError::InvalidImplTarget { location: impl_.self_type.location.clone() };
+            let ast::TypeKind::Path(path) = &impl_.self_type.kind else { return Err(invalid_target()) };
+            let (type_def_id, _) = resolve_path(self.session, &self.stack.get_scope(), *self.modules.last().unwrap(), path, Scope::NS_TYPES)?;
+            if !matches!(self.session.get_def(type_def_id), Some(Definition::Struct { .. } | Definition::Enum)) {
+                return Err(invalid_target());
+            }
+
+            let type_scope = self.session.scopes.get(type_def_id).expect("Cannot find type scope.");
+            for item in impl_.items.iter() {
+                let ast::ItemKind::Function(function) = &item.kind else { continue };
+                if type_scope.get_local(Scope::NS_NAMES, &function.name).is_some() {
+                    return Err(ResolutionError::DuplicateMember {
+                        name: function.name.clone(),
+                        location: item.location.clone()
+                    });
+                }
+
+                let method_def_id = self.session.get_ref_target(item.id).expect("Cannot find method definition reference.");
+                type_scope.define_name(&function.name, method_def_id);
+            }
+        }
+
+        walk_impl(self, id, impl_)
+    }
+}
@@ -0,0 +1,201 @@
+use ramen_common::{ast::{self, NodeId, Visibility}, defs::{DefId, Definition, Privacy}, error::ResolutionError, scope::ScopeRef, session::{AnyId, Session}, types::RamenType, visitor::{walk_call_expression, walk_expression, walk_expression_list, walk_impl, walk_item, walk_module, ASTPass, ScopeStack, Visitor}, Loc};
+
+/// Rejects uses of private struct fields and methods outside of the module they are defined in,
+/// and public functions whose signatures name types less visible than they are.
+/// Members reached through paths are checked while resolving them, as only then it is known which import they come through.
+pub struct PrivacyCheckingPass<'sess> {
+    pub session: &'sess Session,
+    pub stack: ScopeStack,
+
+    /// Definitions of modules enclosing currently visited node, innermost last.
+    modules: Vec<DefId>,
+    /// Visibility of the type of inherent implementation being visited, its methods cannot be used where the type cannot.
+    impl_visibility: Option<Visibility>
+}
+
+impl<'sess> ASTPass<'sess, ()> for PrivacyCheckingPass<'sess> {
+    type Error = ResolutionError;
+
+    fn run_on_module(session: &'sess Session, scope: ScopeRef, mod_id: NodeId, module: &ast::Module) -> Result<(), Self::Error> {
+        let mut privacy_checker = Self {
+            session,
+            stack: ScopeStack::new(),
+
+            modules: Vec::new(),
+            impl_visibility: None
+        };
+
+        privacy_checker.stack.push_scope(scope);
+        privacy_checker.visit_module(mod_id, module)?;
+
+        session.exit_if_errors();
+        Ok(())
+    }
+}
+
+/// Makes sure definition, field or import with privacy recorded for `id` can be used inside of module `from`.
+pub(crate) fn check_access(session: &Session, id: impl Into<AnyId>, from: DefId, name: &str, location: &Loc) -> Result<(), ResolutionError> {
+    let id = id.into();
+    if session.is_visible(id, from) {
+        return Ok(());
+    }
+    Err(ResolutionError::PrivateAccess {
+        name: name.to_string(),
+        location: location.clone(),
+        definition: session.get_privacy(id).expect("Private definition should have its privacy recorded.").location
+    })
+}
+
+impl<'sess> PrivacyCheckingPass<'sess> {
+    fn current_module(&self) -> DefId {
+        *self.modules.last().expect("Privacy should be checked inside of a module.")
+    }
+
+    /// Checks access to field `name` of value with type `base_type` and returns the type of the field.
+    fn check_field(&self, base_type: &RamenType, name: &str, location: &Loc) -> Result<Option<RamenType>, ResolutionError> {
+        let RamenType::Struct(struct_type) = self.session.complete_type(base_type.pointee().unwrap_or(base_type)) else { return Ok(None) };
+        let Some(field) = struct_type.fields.iter().find(|field| field.name == name) else { return Ok(None) };
+
+        check_access(self.session, field.id, self.current_module(), name, location)?;
+        Ok(Some(field.ty.clone()))
+    }
+
+    /// Checks access to the first `count` fields following `variable`, constant or static in path expression `id`.
+    fn check_path_fields(&self, id: NodeId, variable: DefId, path: &ast::Path, count: usize) -> Result<(), ResolutionError> {
+        let start = path.segments.len() - self.session.get_path_fields(id);
+        let mut ty = self.session.get_type(variable);
+        for segment in path.segments[start..start + count].iter() {
+            let Some(base_type) = ty else { break };
+            ty = self.check_field(&base_type, &segment.name, &segment.location)?;
+        }
+        Ok(())
+    }
+
+    /// Enum, struct or trait in type which is less visible than `visibility`, together with its name.
+    fn less_visible_type(&self, ty: &RamenType, visibility: Visibility) -> Option<(String, Privacy)> {
+        let nominal = match ty {
+            RamenType::Enum(enum_type) => Some((enum_type.def_id, &enum_type.name)),
+            RamenType::Struct(struct_type) => Some((struct_type.def_id, &struct_type.name)),
+            RamenType::Dyn { trait_id, name } => Some((*trait_id, name)),
+            _ => None
+        };
+        let privacy = nominal.and_then(|(def_id, name)| Some((name.clone(), self.session.get_privacy(def_id)?)));
+        if let Some((name, privacy)) = privacy.filter(|(_, privacy)| privacy.visibility < visibility) {
+            return Some((name, privacy));
+        }
+
+        let inner = match ty {
+            RamenType::Tuple(elements) => elements.iter().collect(),
+            RamenType::Array(element, _) | RamenType::Slice(element) => vec![&**element],
+            RamenType::Reference { target, .. } | RamenType::Pointer { target, .. } => vec![&**target],
+            RamenType::Callable(callable) => callable.parameter_types.iter().chain(std::iter::once(&callable.return_type)).collect(),
+            _ => ty.type_arguments().iter().collect::<Vec<_>>()
+        };
+        inner.into_iter().find_map(|inner| self.less_visible_type(inner, visibility))
+    }
+
+    /// Makes sure types of parameters and return value of function are visible wherever the function is.
+    fn check_signature(&self, function: &ast::Function, visibility: Visibility) -> Result<(), ResolutionError> {
+        let types = function.parameters.iter().map(|parameter| &parameter.parameter.ty).chain(function.return_type.iter());
+        for ty in types {
+            let Some(resolved) = self.session.get_type(ty.id) else { continue };
+            if let Some((name, privacy)) = self.less_visible_type(&resolved, visibility) {
+                return Err(ResolutionError::PrivateTypeInPublicInterface {
+                    name,
+                    location: ty.location.clone(),
+                    definition: privacy.location
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'sess> Visitor<()> for PrivacyCheckingPass<'sess> {
+    type Error = ResolutionError;
+
+    fn default_return(&self) {}
+    fn get_scope_stack(&self) -> &ScopeStack { &self.stack }
+    fn get_session(&self) -> &Session { self.session }
+
+    fn visit_module(&mut self, id: NodeId, module: &ast::Module) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find module definition reference.");
+        self.modules.push(def_id);
+        let result = walk_module(self, id, module);
+        self.modules.pop();
+        result
+    }
+
+    fn visit_item(&mut self, item: &ast::Item) -> Result<(), Self::Error> {
+        if let ast::ItemKind::Function(function) = &item.kind {
+            let visibility = match self.impl_visibility {
+                Some(impl_visibility) if impl_visibility < item.visibility => impl_visibility,
+                _ => item.visibility
+            };
+            self.check_signature(function, visibility)?;
+        }
+        walk_item(self, item)
+    }
+
+    fn visit_impl(&mut self, id: NodeId, impl_: &ast::Impl) -> Result<(), Self::Error> {
+        let self_visibility = self.session.get_type(impl_.self_type.id)
+            .and_then(|self_type| self.session.get_privacy(self_type.def_id()?))
+            .map(|privacy| privacy.visibility);
+        let previous = std::mem::replace(&mut self.impl_visibility, self_visibility);
+        let result = walk_impl(self, id, impl_);
+        self.impl_visibility = previous;
+        result
+    }
+
+    fn visit_path_expression(&mut self, id: NodeId, path: &ast::Path) -> Result<(), Self::Error> {
+        let Some(def_id) = self.session.get_ref_target(id) else { return Ok(()) };
+        match self.session.get_def(def_id) {
+            // Struct can be constructed only where all of its fields are visible.
+            Some(Definition::Constructor { parent }) => {
+                let Some(RamenType::Struct(struct_type)) = self.session.get_type(parent) else { return Ok(()) };
+                let location = &path.segments.last().unwrap().location;
+                for field in struct_type.fields.iter() {
+                    check_access(self.session, field.id, self.current_module(), &field.name, location)?;
+                }
+            }
+            // Segments following a variable are its fields.
+            Some(def) if def.has_value_fields() => self.check_path_fields(id, def_id, path, self.session.get_path_fields(id))?,
+            _ => ()
+        }
+        Ok(())
+    }
+
+    fn visit_expression(&mut self, expression: &ast::Expression) -> Result<(), Self::Error> {
+        if let ast::ExpressionKind::Field(base, name) = &expression.kind {
+            if let Some(base_type) = self.session.get_type(base.id) {
+                self.check_field(&base_type, name, &expression.location)?;
+            }
+        }
+        walk_expression(self, expression)
+    }
+
+    fn visit_call_expression(&mut self, id: NodeId, callee: &ast::Expression, arguments: &[ast::Expression]) -> Result<(), Self::Error> {
+        // Method calls refer to the method directly, without any path leading to it.
+        // Callee names the method instead of a field, so only its receiver is visited.
+        let method = self.session.get_ref_target(id)
+            .filter(|&method| matches!(self.session.get_def(method), Some(Definition::Method { .. })));
+        let Some(method) = method else { return walk_call_expression(self, callee, arguments) };
+
+        match &callee.kind {
+            ast::ExpressionKind::Field(receiver, name) => {
+                check_access(self.session, method, self.current_module(), name, &callee.location)?;
+                self.visit_expression(receiver)?;
+            }
+            ast::ExpressionKind::Path(path) => {
+                let name = path.segments.last().unwrap();
+                check_access(self.session, method, self.current_module(), &name.name, &name.location)?;
+                if let Some(variable) = self.session.get_ref_target(callee.id) {
+                    let fields = self.session.get_path_fields(callee.id);
+                    self.check_path_fields(callee.id, variable, path, fields.saturating_sub(1))?;
+                }
+            }
+            _ => self.visit_expression(callee)?
+        }
+        walk_expression_list(self, arguments)
+    }
+}
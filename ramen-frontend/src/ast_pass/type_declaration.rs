@@ -0,0 +1,593 @@
+use std::rc::Rc;
+
+use hashbrown::HashMap;
+use ramen_common::{ast::{self, NodeId}, attributes::KnownAttribute, consts::ConstValue, error::ResolutionError, scope::ScopeRef, session::Session, defs::{DefId, Definition, Implementation}, types::{CallableType, EnumType, FieldType, RamenType, StructType, TypeParameter, VariantType}, visitor::{walk_function, walk_impl, walk_trait, ASTPass, ScopeStack, Visitor}};
+
+use super::const_eval::{array_length, evaluate_constant, Unevaluated};
+
+/// Resolves types of type declarations and function signatures before any function body is checked,
+/// so that items can be used before the place they are declared in.
+///
+/// Values of constants are computed here as well, since array types can depend on them.
+///
+/// Declarations are visited repeatedly until no more of them can be resolved,
+/// anything left unresolved after that has to contain itself.
+pub struct TypeDeclarationPass<'sess> {
+    pub session: &'sess Session,
+    pub stack: ScopeStack,
+
+    progress: bool,
+    pending: bool,
+    unresolved: Option<ResolutionError>,
+    /// Constants waiting for value of another constant, with the error reported if they turn out to depend on each other.
+    waiting: Vec<(DefId, DefId, ResolutionError)>
+}
+
+impl<'sess> ASTPass<'sess, ()> for TypeDeclarationPass<'sess> {
+    type Error = ResolutionError;
+
+    fn run_on_module(session: &'sess Session, scope: ScopeRef, mod_id: NodeId, module: &ast::Module) -> Result<(), Self::Error> {
+        let mut declaration_resolver = Self {
+            session,
+            stack: ScopeStack::new(),
+
+            progress: false,
+            pending: false,
+            unresolved: None,
+            waiting: Vec::new()
+        };
+
+        declaration_resolver.stack.push_scope(scope);
+        loop {
+            declaration_resolver.progress = false;
+            declaration_resolver.pending = false;
+            declaration_resolver.unresolved = None;
+            declaration_resolver.waiting.clear();
+            declaration_resolver.visit_module(mod_id, module)?;
+
+            if !declaration_resolver.pending { break; }
+            if !declaration_resolver.progress {
+                return Err(declaration_resolver.find_cycle()
+                    .or(declaration_resolver.unresolved.take())
+                    .expect("Only declarations can depend on themselves."));
+            }
+        }
+
+        session.exit_if_errors();
+        Ok(())
+    }
+}
+
+/// Makes sure constant marked `@static_assert` is a boolean which holds.
+fn check_static_assert(name: &str, ty: &RamenType, value: &ConstValue, note: Option<String>, written: &ast::Type, expression: &ast::Expression) -> Result<(), ResolutionError> {
+    match value {
+        ConstValue::Bool(true) => Ok(()),
+        ConstValue::Bool(false) => Err(ResolutionError::StaticAssertionFailed {
+            name: name.to_string(),
+            note,
+            location: expression.location.clone()
+        }),
+        _ => Err(ResolutionError::TypeMismatch {
+            expected: RamenType::Bool,
+            written: None,
+            found: ty.clone(),
+            location: written.location.clone()
+        })
+    }
+}
+
+/// Converts type from the AST into its resolved form.
+/// Returns `None` if type refers to declaration that was not resolved yet.
+pub(crate) fn resolve_type(session: &Session, ty: &ast::Type) -> Result<Option<RamenType>, ResolutionError> {
+    let resolved_type = match &ty.kind {
+        ast::TypeKind::Unit => RamenType::Unit,
+        ast::TypeKind::Bool => RamenType::Bool,
+        ast::TypeKind::Integer(width) => RamenType::Integer(*width),
+        ast::TypeKind::Float(width) => RamenType::Float(*width),
+        ast::TypeKind::Tuple(elements) => {
+            let Some(elements) = resolve_types(session, elements)? else { return Ok(None) };
+            RamenType::Tuple(elements)
+        }
+        ast::TypeKind::Array(element, length) => {
+            let Some(element) = resolve_type(session, element)? else { return Ok(None) };
+            let Some(length) = array_length(session, length)? else { return Ok(None) };
+            RamenType::Array(Box::new(element), length)
+        }
+        ast::TypeKind::Slice(element) => {
+            let Some(element) = resolve_type(session, element)? else { return Ok(None) };
+            RamenType::Slice(Box::new(element))
+        }
+        ast::TypeKind::Reference { target, mutable } => {
+            let Some(target) = resolve_pointee(session, target)? else { return Ok(None) };
+            RamenType::Reference { target: Box::new(target), mutable: *mutable }
+        }
+        ast::TypeKind::Pointer { target, mutable } => {
+            let Some(target) = resolve_pointee(session, target)? else { return Ok(None) };
+            RamenType::Pointer { target: Box::new(target), mutable: *mutable }
+        }
+        ast::TypeKind::Path(path) => {
+            let def_id = session.get_ref_target(ty.id).expect("Type path should have been resolved by name resolution.");
+            let Some(declared_type) = session.get_type(def_id) else { return Ok(None) };
+            let Some(path_type) = apply_path_arguments(session, def_id, path, declared_type)? else { return Ok(None) };
+            path_type
+        }
+        ast::TypeKind::Function { parameters, return_type } => {
+            let Some(parameter_types) = resolve_types(session, parameters)? else { return Ok(None) };
+            let return_type = match return_type {
+                Some(return_type) => resolve_type(session, return_type)?,
+                None => Some(RamenType::Unit)
+            };
+            let Some(return_type) = return_type else { return Ok(None) };
+            RamenType::Callable(Box::new(CallableType::new(return_type, parameter_types)))
+        }
+        ast::TypeKind::Dyn(trait_ref) => {
+            let trait_id = session.get_ref_target(trait_ref.id).expect("Trait should have been resolved by name resolution.");
+            RamenType::Dyn {
+                trait_id,
+                name: session.get_trait(trait_id).expect("Cannot find trait definition.").name
+            }
+        }
+    };
+
+    session.set_type(ty.id, resolved_type.clone());
+    Ok(Some(resolved_type))
+}
+
+/// Pointers do not need layout of their target, so struct or enum behind them can be used before its declaration
+/// is complete, letting it refer to itself. Its fields are then left out, see `Session::complete_type`.
+fn resolve_pointee(session: &Session, target: &ast::Type) -> Result<Option<RamenType>, ResolutionError> {
+    if let Some(target_type) = resolve_type(session, target)? { return Ok(Some(target_type)) }
+
+    let ast::TypeKind::Path(path) = &target.kind else { return Ok(None) };
+    let def_id = session.get_ref_target(target.id).expect("Type path should have been resolved by name resolution.");
+    let Some(header) = session.get_type_header(def_id) else { return Ok(None) };
+    let Some(target_type) = apply_path_arguments(session, def_id, path, header)? else { return Ok(None) };
+
+    session.set_type(target.id, target_type.clone());
+    Ok(Some(target_type))
+}
+
+/// Instantiates type named by the path with type arguments of its last segment.
+fn apply_path_arguments(session: &Session, def_id: DefId, path: &ast::Path, declared_type: RamenType) -> Result<Option<RamenType>, ResolutionError> {
+    // Generic types can only be used with all of their type arguments.
+    // Anything else, like `Self` of an implementation, is already a complete type.
+    let segment = path.segments.last().expect("Path should have at least one segment.");
+    let expected_count = match session.get_def(def_id) {
+        Some(Definition::Enum | Definition::Struct { .. }) => declared_type.type_arguments().len(),
+        _ => 0
+    };
+    if segment.type_arguments.len() != expected_count {
+        return Err(ResolutionError::GenericArgumentCountMismatch {
+            expected: expected_count,
+            found: segment.type_arguments.len(),
+            location: segment.location.clone()
+        });
+    }
+
+    let Some(arguments) = resolve_types(session, &segment.type_arguments)? else { return Ok(None) };
+    Ok(Some(declared_type.apply_type_arguments(&arguments)))
+}
+
+/// Resolves all of the types, `None` if any of them refers to declaration that was not resolved yet.
+pub(crate) fn resolve_types(session: &Session, types: &[ast::Type]) -> Result<Option<Vec<RamenType>>, ResolutionError> {
+    let mut resolved_types = Vec::new();
+    for ty in types.iter() {
+        let Some(resolved_type) = resolve_type(session, ty)? else { return Ok(None) };
+        resolved_types.push(resolved_type);
+    }
+    Ok(Some(resolved_types))
+}
+
+/// Type the way it was written if it names any type alias, diagnostics show it instead of what the aliases expand to.
+/// Expects the type to be resolved.
+pub(crate) fn written_type(session: &Session, ty: &ast::Type) -> Option<Rc<String>> {
+    let mut names_alias = false;
+    let written = write_type(session, ty, &mut names_alias);
+    names_alias.then(|| Rc::new(written))
+}
+
+fn write_type(session: &Session, ty: &ast::Type, names_alias: &mut bool) -> String {
+    match &ty.kind {
+        ast::TypeKind::Tuple(elements) if elements.len() == 1 => format!("({},)", write_type(session, &elements[0], names_alias)),
+        ast::TypeKind::Tuple(elements) => format!("({})", write_types(session, elements, names_alias)),
+        ast::TypeKind::Array(element, _) => {
+            let Some(RamenType::Array(_, length)) = session.get_type(ty.id) else { unreachable!("Array type should have been resolved.") };
+            format!("[{}; {length}]", write_type(session, element, names_alias))
+        }
+        ast::TypeKind::Slice(element) => format!("[{}]", write_type(session, element, names_alias)),
+        ast::TypeKind::Reference { target, mutable } => format!("&{}{}", if *mutable { "mut " } else { "" }, write_type(session, target, names_alias)),
+        ast::TypeKind::Pointer { target, mutable } => format!("*{}{}", if *mutable { "mut " } else { "" }, write_type(session, target, names_alias)),
+        ast::TypeKind::Function { parameters, return_type } => format!("func({}): {}",
+            write_types(session, parameters, names_alias),
+            return_type.as_ref().map_or_else(|| RamenType::Unit.to_string(), |return_type| write_type(session, return_type, names_alias))
+        ),
+        ast::TypeKind::Path(path) => {
+            let def_id = session.get_ref_target(ty.id).expect("Type path should have been resolved by name resolution.");
+            *names_alias |= matches!(session.get_def(def_id), Some(Definition::TypeAlias));
+            path.segments.iter().map(|segment| match segment.type_arguments.is_empty() {
+                true => segment.name.clone(),
+                false => format!("{}<{}>", segment.name, write_types(session, &segment.type_arguments, names_alias))
+            }).collect::<Vec<_>>().join(".")
+        }
+        _ => session.get_type(ty.id).expect("Type should have been resolved.").to_string()
+    }
+}
+
+fn write_types(session: &Session, types: &[ast::Type], names_alias: &mut bool) -> String {
+    types.iter().map(|ty| write_type(session, ty, names_alias)).collect::<Vec<_>>().join(", ")
+}
+
+/// Assigns parameter types to type parameters of a generic item.
+fn declare_generics(session: &Session, generics: &[ast::GenericParameter]) -> Vec<TypeParameter> {
+    generics.iter().map(|generic| {
+        let generic_def_id = session.get_ref_target(generic.id).expect("Cannot find type parameter definition reference.");
+        let type_parameter = TypeParameter {
+            def_id: generic_def_id,
+            name: generic.name.clone()
+        };
+        session.set_type(generic_def_id, RamenType::Parameter(type_parameter.clone()));
+        type_parameter
+    }).collect()
+}
+
+impl<'sess> TypeDeclarationPass<'sess> {
+    /// Resolves type of field of type declaration `name`, remembering the error in case it never gets resolved.
+    fn resolve_field(&mut self, name: &str, field: &ast::Type) -> Result<Option<RamenType>, ResolutionError> {
+        let field_type = resolve_type(self.session, field)?;
+        if field_type.is_none() {
+            self.pending = true;
+            self.unresolved.get_or_insert(ResolutionError::RecursiveType {
+                name: name.to_string(),
+                location: field.location.clone()
+            });
+        }
+        Ok(field_type)
+    }
+
+    /// Resolves type of constant or static `def_id` and computes its value once everything it uses is known.
+    fn declare_global(&mut self, id: NodeId, name: &str, ty: &ast::Type, value: &ast::Expression) -> Result<(), ResolutionError> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find global definition reference.");
+        if self.session.get_constant(def_id).is_some() { return Ok(()); }
+
+        let cycle = || ResolutionError::ConstantCycle {
+            name: name.to_string(),
+            location: value.location.clone()
+        };
+        let Some(global_type) = resolve_type(self.session, ty)? else {
+            self.pending = true;
+            self.unresolved.get_or_insert_with(cycle);
+            return Ok(());
+        };
+        self.session.set_type(def_id, global_type.clone());
+        if let Some(written) = written_type(self.session, ty) {
+            self.session.set_written_type(def_id, written);
+        }
+
+        match evaluate_constant(self.session, value, ty) {
+            Ok(constant) => {
+                let note = self.session.find_attribute(def_id, |attribute| match attribute {
+                    KnownAttribute::StaticAssert { note } => Some(note.clone()),
+                    _ => None
+                });
+                if let Some(note) = note {
+                    check_static_assert(name, &global_type, &constant, note, ty, value)?;
+                }
+                self.session.set_constant(def_id, constant);
+                self.progress = true;
+            }
+            Err(Unevaluated::Error(error)) => return Err(error),
+            Err(Unevaluated::Pending(constant)) => {
+                self.pending = true;
+                self.waiting.push((def_id, constant, cycle()));
+            }
+            Err(Unevaluated::Untyped) => {
+                self.pending = true;
+                self.unresolved.get_or_insert_with(cycle);
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds constant whose value depends on itself by following constants waiting for each other.
+    fn find_cycle(&mut self) -> Option<ResolutionError> {
+        let waits_for = |constant: DefId| self.waiting.iter().position(|(waiting, _, _)| *waiting == constant);
+        'starts: for start in 0..self.waiting.len() {
+            let mut current = start;
+            for _ in 0..self.waiting.len() {
+                let Some(next) = waits_for(self.waiting[current].1) else { continue 'starts };
+                if next == start {
+                    return Some(self.waiting.swap_remove(start).2);
+                }
+                current = next;
+            }
+        }
+        None
+    }
+
+    /// Registers implementation of its trait for `self_type`, pairing its methods with the ones declared by the trait.
+    fn declare_trait_impl(&self, def_id: DefId, impl_: &ast::Impl, trait_ref: &ast::TraitRef, self_type: RamenType) -> Result<(), ResolutionError> {
+        let trait_id = self.session.get_ref_target(trait_ref.id).expect("Cannot find trait reference.");
+        let trait_definition = self.session.get_trait(trait_id).expect("Cannot find trait definition.");
+
+        if self.session.find_impl(trait_id, &self_type).is_some() {
+            return Err(ResolutionError::ConflictingImpl {
+                trait_name: trait_definition.name,
+                ty: self_type,
+                location: impl_.self_type.location.clone()
+            });
+        }
+
+        // Named by the paths of the type and the trait, not where the impl is, as there can be only one such impl.
+        let trait_symbol = self.session.get_symbol(trait_id).expect("Cannot find trait symbol.");
+        let impl_symbol = format!("<{} as {trait_symbol}>", self.session.type_symbol(&self_type));
+        self.session.set_symbol(def_id, &impl_symbol);
+
+        let mut methods = HashMap::new();
+        for item in impl_.items.iter() {
+            // Only functions can be trait members, other items are reported by their keyword.
+            let name = item.name().unwrap_or(match item.kind {
+                ast::ItemKind::Use(_) => "use",
+                _ => "impl"
+            });
+            let trait_method = trait_definition.methods.iter()
+                .find(|(method_name, _)| method_name == name)
+                .filter(|_| matches!(item.kind, ast::ItemKind::Function(_)))
+                .ok_or_else(|| ResolutionError::NotTraitMember {
+                    name: name.to_string(),
+                    trait_name: trait_definition.name.clone(),
+                    location: item.location.clone()
+                })?;
+
+            let method_def_id = self.session.get_ref_target(item.id).expect("Cannot find method definition reference.");
+            self.session.set_symbol(method_def_id, format!("{impl_symbol}.{name}"));
+            methods.insert(trait_method.1, method_def_id);
+        }
+
+        let mut missing = trait_definition.methods.iter()
+            .filter(|(_, method)| !methods.contains_key(method))
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            missing.sort();
+            return Err(ResolutionError::MissingTraitMethods {
+                trait_name: trait_definition.name,
+                missing,
+                location: trait_ref.location.clone()
+            });
+        }
+
+        self.session.set_type(def_id, self_type.clone());
+        self.session.add_impl(trait_id, Implementation {
+            def_id,
+            self_type,
+            methods
+        });
+        Ok(())
+    }
+
+    /// Names methods of inherent implementation after the implemented type, like `Point.len`.
+    fn declare_inherent_impl(&self, def_id: DefId, impl_: &ast::Impl, self_type: RamenType) -> Result<(), ResolutionError> {
+        // Methods are looked up by the definition of the type, so they cannot differ between its instances.
+        if !self_type.type_arguments().is_empty() {
+            return Err(ResolutionError::InvalidImplTarget { location: impl_.self_type.location.clone() });
+        }
+
+        // Methods are named by the path of the type, so same-named types of different modules keep them apart.
+        let impl_symbol = self.session.type_symbol(&self_type);
+        self.session.set_symbol(def_id, &impl_symbol);
+        for item in impl_.items.iter() {
+            let ast::ItemKind::Function(function) = &item.kind else { continue };
+            let method_def_id = self.session.get_ref_target(item.id).expect("Cannot find method definition reference.");
+            self.session.set_symbol(method_def_id, format!("{impl_symbol}.{}", function.name));
+        }
+
+        self.session.set_type(def_id, self_type);
+        Ok(())
+    }
+}
+
+impl<'sess> Visitor<()> for TypeDeclarationPass<'sess> {
+    type Error = ResolutionError;
+
+    fn default_return(&self) {}
+    fn get_scope_stack(&self) -> &ScopeStack { &self.stack }
+    fn get_session(&self) -> &Session { self.session }
+
+    fn visit_function(&mut self, id: NodeId, function: &ast::Function) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find function definition reference.");
+        if self.session.get_type(def_id).is_none() {
+            let mut type_parameters = declare_generics(self.session, &function.generics);
+
+            // Trait methods are generic over the implementing type as well.
+            if let Some(Definition::TraitMethod { parent }) = self.session.get_def(def_id) {
+                let trait_definition = self.session.get_trait(parent).expect("Cannot find trait definition.");
+                type_parameters.insert(0, TypeParameter {
+                    def_id: trait_definition.self_parameter,
+                    name: "Self".to_string()
+                });
+            }
+
+            let mut parameter_types = Vec::new();
+            for parameter in function.parameters.iter() {
+                parameter_types.push(resolve_type(self.session, &parameter.parameter.ty)?);
+            }
+            let parameter_types = parameter_types.into_iter().collect::<Option<Vec<_>>>();
+            let return_type = match &function.return_type {
+                Some(return_type) => resolve_type(self.session, return_type)?,
+                None => Some(RamenType::Unit)
+            };
+
+            if let (Some(parameter_types), Some(return_type)) = (parameter_types, return_type) {
+                self.session.set_type(def_id, RamenType::Callable(Box::new(CallableType {
+                    type_parameters,
+                    is_vararg: function.is_vararg,
+                    ..CallableType::new(return_type, parameter_types)
+                })));
+                self.session.try_bind_type(id, def_id);
+                if let Some(written) = function.return_type.as_ref().and_then(|ty| written_type(self.session, ty)) {
+                    self.session.set_written_type(def_id, written);
+                }
+                self.progress = true;
+            } else {
+                // Signature depends on declaration which is not resolved yet, try again in the next round.
+                self.pending = true;
+            }
+        }
+
+        // Nested items still have to be visited.
+        walk_function(self, id, function)
+    }
+
+    fn visit_const(&mut self, id: NodeId, const_: &ast::Const) -> Result<(), Self::Error> {
+        self.declare_global(id, &const_.name, &const_.ty, &const_.value)
+    }
+
+    fn visit_static(&mut self, id: NodeId, static_: &ast::Static) -> Result<(), Self::Error> {
+        self.declare_global(id, &static_.name, &static_.ty, &static_.value)
+    }
+
+    fn visit_type_alias(&mut self, id: NodeId, alias: &ast::TypeAlias) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find type alias definition reference.");
+        if self.session.get_type(def_id).is_some() { return Ok(()); }
+
+        match resolve_type(self.session, &alias.ty)? {
+            Some(aliased_type) => {
+                self.session.set_type(def_id, aliased_type);
+                self.progress = true;
+            }
+            // Alias that is never resolved has to wait for itself.
+            None => {
+                self.pending = true;
+                self.unresolved.get_or_insert(ResolutionError::AliasCycle {
+                    name: alias.name.clone(),
+                    location: alias.ty.location.clone()
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_trait(&mut self, id: NodeId, trait_: &ast::Trait) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find trait definition reference.");
+        let trait_definition = self.session.get_trait(def_id).expect("Cannot find trait definition.");
+        self.session.set_type(trait_definition.self_parameter, RamenType::Parameter(TypeParameter {
+            def_id: trait_definition.self_parameter,
+            name: "Self".to_string()
+        }));
+
+        walk_trait(self, id, trait_)
+    }
+
+    fn visit_impl(&mut self, id: NodeId, impl_: &ast::Impl) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find impl definition reference.");
+        if self.session.get_type(def_id).is_none() {
+            // Methods can refer to `Self`, so they have to wait for the implemented type.
+            let Some(self_type) = resolve_type(self.session, &impl_.self_type)? else {
+                self.pending = true;
+                return Ok(());
+            };
+            match &impl_.trait_ {
+                Some(trait_ref) => self.declare_trait_impl(def_id, impl_, trait_ref, self_type)?,
+                None => self.declare_inherent_impl(def_id, impl_, self_type)?
+            }
+            self.progress = true;
+        }
+
+        walk_impl(self, id, impl_)
+    }
+
+    fn visit_enum(&mut self, id: NodeId, enum_: &ast::Enum) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find enum definition reference.");
+        if self.session.get_type(def_id).is_some() { return Ok(()); }
+
+        let type_parameters = declare_generics(self.session, &enum_.generics);
+        self.session.set_type_header(def_id, RamenType::Enum(Box::new(EnumType {
+            def_id,
+            name: enum_.name.clone(),
+            type_arguments: type_parameters.iter().cloned().map(RamenType::Parameter).collect(),
+            variants: Vec::new()
+        })));
+
+        let mut variants = Vec::new();
+        for variant in enum_.variants.iter() {
+            let mut fields = Vec::new();
+            for field in variant.fields.iter() {
+                let Some(field_type) = self.resolve_field(&enum_.name, field)? else { return Ok(()) };
+                fields.push(field_type);
+            }
+
+            variants.push(VariantType {
+                name: variant.name.clone(),
+                fields
+            });
+        }
+
+        let enum_type = RamenType::Enum(Box::new(EnumType {
+            def_id,
+            name: enum_.name.clone(),
+            type_arguments: type_parameters.iter().cloned().map(RamenType::Parameter).collect(),
+            variants: variants.clone()
+        }));
+
+        // Variants with payload act as constructor functions, others are plain values.
+        // Variants of generic enums are generic as well, with the same type parameters.
+        for (variant, variant_type) in enum_.variants.iter().zip(variants) {
+            let variant_def_id = self.session.get_ref_target(variant.id).expect("Cannot find variant definition reference.");
+            self.session.set_type(variant_def_id, if variant_type.fields.is_empty() {
+                enum_type.clone()
+            } else {
+                RamenType::Callable(Box::new(CallableType {
+                    type_parameters: type_parameters.clone(),
+                    ..CallableType::new(enum_type.clone(), variant_type.fields)
+                }))
+            });
+        }
+
+        self.session.set_type(def_id, enum_type);
+        self.progress = true;
+        Ok(())
+    }
+
+    fn visit_struct(&mut self, id: NodeId, struct_: &ast::Struct) -> Result<(), Self::Error> {
+        let def_id = self.session.get_ref_target(id).expect("Cannot find struct definition reference.");
+        if self.session.get_type(def_id).is_some() { return Ok(()); }
+
+        let type_parameters = declare_generics(self.session, &struct_.generics);
+        self.session.set_type_header(def_id, RamenType::Struct(Box::new(StructType {
+            def_id,
+            name: struct_.name.clone(),
+            type_arguments: type_parameters.iter().cloned().map(RamenType::Parameter).collect(),
+            fields: Vec::new()
+        })));
+
+        let mut fields = Vec::new();
+        for field in struct_.fields.iter() {
+            let Some(field_type) = self.resolve_field(&struct_.name, &field.ty)? else { return Ok(()) };
+            self.session.set_type(field.id, field_type.clone());
+            fields.push(FieldType {
+                id: field.id,
+                name: field.name.clone(),
+                ty: field_type
+            });
+        }
+
+        let struct_type = RamenType::Struct(Box::new(StructType {
+            def_id,
+            name: struct_.name.clone(),
+            type_arguments: type_parameters.iter().cloned().map(RamenType::Parameter).collect(),
+            fields: fields.clone()
+        }));
+
+        let Some(Definition::Struct { constructor }) = self.session.get_def(def_id) else {
+            unreachable!("Struct should have been bound to a struct definition.")
+        };
+        self.session.set_type(constructor, RamenType::Callable(Box::new(CallableType {
+            type_parameters,
+            ..CallableType::new(struct_type.clone(), fields.into_iter().map(|field| field.ty).collect())
+        })));
+
+        self.session.set_type(def_id, struct_type);
+        self.progress = true;
+        Ok(())
+    }
+}
@@ -1,57 +1,1005 @@
-use ramen_common::{ast::{self, NodeId}, error::ResolutionError, scope::ScopeRef, session::Session, types::{CallableType, RamenType}, visitor::{walk_function, walk_parameter, ASTPass, ScopeStack, Visitor}};
+use std::{collections::VecDeque, rc::Rc};
+
+use hashbrown::{HashMap, HashSet};
+use ramen_common::{ast::{self, NodeId}, attributes::KnownAttribute, defs::{DefId, Definition}, error::{ResolutionError, WrittenTypes}, Loc, scope::{Scope, ScopeRef}, session::Session, types::{CallableType, RamenType}, visitor::{walk_expression, walk_function, walk_impl, walk_parameter, ASTPass, ScopeStack, Visitor}};
+
+use super::{attributes::{check_deprecated, check_noreturn_body}, const_eval::{array_length, check_const_function_body, fits_integer}, privacy::PrivacyCheckingPass, type_declaration::{resolve_type, resolve_types, written_type, TypeDeclarationPass}};
+
+/// Largest allowed size of type arguments of a single instance, counted in nested types.
+/// Generic function instantiating itself with types like `(T, T)` would otherwise produce instances forever.
+const INSTANCE_SIZE_LIMIT: usize = 256;
 
 pub struct TypeResolutionPass<'sess> {
     pub session: &'sess Session,
-    pub stack: ScopeStack
+    pub stack: ScopeStack,
+
+    /// Type expected by the parent of currently visited expression, used to infer literal types.
+    expected: Option<RamenType>,
+    /// Return types of enclosing functions, with the way they were written if they name type aliases.
+    return_types: Vec<(RamenType, Option<Rc<String>>)>,
+    /// Definitions of functions enclosing currently visited node, innermost last.
+    functions: Vec<DefId>,
+    /// Variables holding closures which refer to variables of the current frame, these cannot be returned.
+    frame_closures: HashSet<DefId>,
+    instantiations: Vec<Instantiation>
+}
+
+/// Use of generic function or trait method with given type arguments.
+struct Instantiation {
+    /// Generic function the use is inside of, its type parameters can appear in the arguments.
+    caller: Option<DefId>,
+    callee: DefId,
+    arguments: Vec<RamenType>,
+    name: String,
+    location: Loc
+}
+
+/// Value a method is called on, like `point` in `point.len()`.
+enum Receiver<'a> {
+    Expression(&'a ast::Expression),
+    /// Variable and its fields, method calls on them are parsed as a single path like `line.start.len`.
+    Path(DefId, &'a [ast::PathSegment])
 }
 
 impl<'sess> ASTPass<'sess, ()> for TypeResolutionPass<'sess> {
     type Error = ResolutionError;
 
     fn run_on_module(session: &'sess Session, scope: ScopeRef, mod_id: NodeId, module: &ast::Module) -> Result<(), Self::Error> {
-        let mut name_binder = Self {
-            session,
-            stack: ScopeStack::new()
-        };
+        TypeDeclarationPass::run_on_module(session, scope.clone(), mod_id, module)?;
 
-        name_binder.stack.push_scope(scope);
+        let mut name_binder = Self::new(session);
+        name_binder.stack.push_scope(scope.clone());
         name_binder.visit_module(mod_id, module)?;
+        name_binder.collect_instances()?;
+
+        // Fields and methods are known only once the types they belong to are.
+        PrivacyCheckingPass::run_on_module(session, scope, mod_id, module)?;
 
         session.exit_if_errors();
         Ok(())
     }
 }
 
+impl<'sess> TypeResolutionPass<'sess> {
+    fn new(session: &'sess Session) -> Self {
+        Self {
+            session,
+            stack: ScopeStack::new(),
+
+            expected: None,
+            return_types: Vec::new(),
+            functions: Vec::new(),
+            frame_closures: HashSet::new(),
+            instantiations: Vec::new()
+        }
+    }
+
+    /// Type checks initializer of constant or static, which has to be done before its value is computed.
+    pub(crate) fn check_constant(session: &'sess Session, value: &ast::Expression, ty: &ast::Type) -> Result<(), ResolutionError> {
+        let expected = session.get_type(ty.id).expect("Type of constant should have been resolved.");
+        Self::new(session).check_annotated_expression(value, expected, written_type(session, ty))
+    }
+
+    /// Type checks body of `const func` called by constant expression before this pass gets to it.
+    /// It is checked again once the pass reaches it, to find out which generic functions it uses.
+    pub(crate) fn check_const_function(session: &'sess Session, def_id: DefId) -> Result<(), ResolutionError> {
+        if !session.mark_const_function_checked(def_id) { return Ok(()); }
+
+        let (id, function) = session.get_const_function(def_id).expect("Cannot find declaration of constant function.");
+        let Some(RamenType::Callable(callable)) = session.get_type(def_id) else {
+            unreachable!("Function signature should have been resolved by type declaration pass.")
+        };
+        Self::new(session).check_function_body(id, def_id, &function, callable.return_type)
+    }
+
+    fn check_function_body(&mut self, id: NodeId, def_id: DefId, function: &ast::Function, return_type: RamenType) -> Result<(), ResolutionError> {
+        let written = function.return_type.as_ref().and_then(|ty| written_type(self.session, ty));
+        self.return_types.push((return_type, written));
+        self.functions.push(def_id);
+        let result = walk_function(self, id, function);
+        self.functions.pop();
+        self.return_types.pop();
+        result
+    }
+
+    /// Whether value holds a closure referring to variables of the current frame, directly, through a variable,
+    /// or inside of a tuple, array, struct, variant or result of match.
+    /// Only `move` closures copy what they capture, so they can outlive the frame unless they capture such closure.
+    fn borrows_frame(&self, value: &ast::Expression) -> bool {
+        match &value.kind {
+            ast::ExpressionKind::Closure(closure) => {
+                let captures = self.session.get_captures(value.id);
+                if closure.by_value {
+                    captures.iter().any(|capture| self.frame_closures.contains(capture))
+                } else {
+                    !captures.is_empty()
+                }
+            }
+            ast::ExpressionKind::Path(_) => self.session.get_ref_target(value.id).is_some_and(|def_id| self.frame_closures.contains(&def_id)),
+            ast::ExpressionKind::Tuple(elements) | ast::ExpressionKind::Array(elements) => elements.iter().any(|element| self.borrows_frame(element)),
+            ast::ExpressionKind::ArrayRepeat(base, _) | ast::ExpressionKind::TupleIndex(base, _)
+            | ast::ExpressionKind::Field(base, _) | ast::ExpressionKind::Index(base, _) => self.borrows_frame(base),
+            ast::ExpressionKind::Call(callee, arguments) => {
+                let constructor = self.session.get_ref_target(callee.id).and_then(|def_id| self.session.get_def(def_id));
+                matches!(constructor, Some(Definition::Constructor { .. } | Definition::Variant { .. }))
+                    && arguments.iter().any(|argument| self.borrows_frame(argument))
+            }
+            ast::ExpressionKind::Match(_, arms) => arms.iter().any(|arm| self.borrows_frame(&arm.body)),
+            _ => false
+        }
+    }
+
+    /// Marks variables bound by pattern as holding parts of value which borrows the current frame.
+    fn mark_frame_bindings(&mut self, pattern: &ast::Pattern) {
+        match &pattern.kind {
+            ast::PatternKind::Binding(_) => {
+                let def_id = self.session.get_ref_target(pattern.id).expect("Cannot find binding definition reference.");
+                self.frame_closures.insert(def_id);
+            }
+            ast::PatternKind::Variant(_, patterns) | ast::PatternKind::Tuple(patterns) | ast::PatternKind::Or(patterns) =>
+                patterns.iter().for_each(|pattern| self.mark_frame_bindings(pattern)),
+            _ => ()
+        }
+    }
+
+    /// Array lengths are checked just like indices.
+    pub(crate) fn check_array_length(session: &'sess Session, length: &ast::Expression) -> Result<(), ResolutionError> {
+        Self::new(session).check_index(length)
+    }
+
+    /// Checks expression against type annotation, mismatch of the whole expression shows the type the way it was written.
+    fn check_annotated_expression(&mut self, expression: &ast::Expression, expected: RamenType, written: Option<Rc<String>>) -> Result<(), ResolutionError> {
+        self.check_expression(expression, expected.clone()).map_err(|error| match error {
+            ResolutionError::TypeMismatch { expected: mismatched, found, written: found_written, location } if mismatched == expected && location == expression.location =>
+                ResolutionError::TypeMismatch { expected, found, written: WrittenTypes::new(written, found_written.and_then(|found_written| found_written.found.clone())), location },
+            error => error
+        })
+    }
+
+    /// Resolves type of the expression and makes sure it matches the expected one.
+    fn check_expression(&mut self, expression: &ast::Expression, expected: RamenType) -> Result<(), ResolutionError> {
+        self.expected = Some(expected.clone());
+        self.visit_expression(expression)?;
+
+        let found = self.session.get_type(expression.id).expect("Expression type should have been resolved.");
+        if !self.coerce(expression, &found, &expected) {
+            return Err(ResolutionError::TypeMismatch {
+                expected,
+                written: WrittenTypes::new(None, self.found_written(expression)),
+                found,
+                location: expression.location.clone()
+            });
+        }
+        Ok(())
+    }
+
+    /// Integer literals have to fit into the type they were given, as they are never truncated.
+    fn check_literal_range(&self, literal: &ast::Expression, value: i128) -> Result<(), ResolutionError> {
+        match self.session.get_type(literal.id).expect("Literal type should have been resolved.") {
+            RamenType::Integer(width) if !fits_integer(value, width) => Err(ResolutionError::LiteralOutOfRange {
+                value,
+                ty: RamenType::Integer(width),
+                location: literal.location.clone()
+            }),
+            _ => Ok(())
+        }
+    }
+
+    /// Type of variable or call result the way it was declared, if the declaration names type aliases.
+    /// Generic functions are left out, as their declared return type names type parameters instead of what they were instantiated with.
+    fn found_written(&self, expression: &ast::Expression) -> Option<Rc<String>> {
+        let def_id = match &expression.kind {
+            ast::ExpressionKind::Path(_) if self.session.get_path_fields(expression.id) == 0 => {
+                self.session.get_ref_target(expression.id)
+                    .filter(|&def_id| !matches!(self.session.get_def(def_id), Some(Definition::Function | Definition::Method { .. })))?
+            }
+            ast::ExpressionKind::Call(callee, _) => {
+                let def_id = self.session.get_ref_target(expression.id).or_else(|| self.session.get_ref_target(callee.id))?;
+                match (self.session.get_def(def_id)?, self.session.get_type(def_id)?) {
+                    (Definition::Function | Definition::Method { .. }, RamenType::Callable(callable)) if callable.type_parameters.is_empty() => def_id,
+                    _ => return None
+                }
+            }
+            _ => return None
+        };
+        self.session.get_written_type(def_id)
+    }
+
+    fn check_argument_count(callable: &CallableType, callee: &ast::Expression, arguments: &[ast::Expression]) -> Result<(), ResolutionError> {
+        let expected_count = callable.parameter_types.len();
+        if arguments.len() < expected_count || (arguments.len() > expected_count && !callable.is_vararg) {
+            return Err(ResolutionError::ArgumentCountMismatch {
+                expected: expected_count,
+                found: arguments.len(),
+                location: callee.location.clone()
+            });
+        }
+        Ok(())
+    }
+
+    /// Definition and type of generic function called without explicit type arguments.
+    fn generic_callee<'a>(&self, callee: &'a ast::Expression) -> Option<(&'a ast::PathSegment, DefId, CallableType)> {
+        let ast::ExpressionKind::Path(path) = &callee.kind else { return None };
+        let segment = path.segments.last()?;
+        if !explicit_type_arguments(path).is_empty() { return None; }
+
+        let def_id = self.session.get_ref_target(callee.id)?;
+        match self.session.get_type(def_id)? {
+            RamenType::Callable(callable) if !callable.type_parameters.is_empty() => Some((segment, def_id, *callable)),
+            _ => None
+        }
+    }
+
+    /// Checks arguments of generic function call, inferring type arguments from them and from the expected return type.
+    /// Returns the instantiated callable.
+    fn check_generic_call(
+        &mut self,
+        callee: &ast::Expression,
+        (segment, def_id, callable): (&ast::PathSegment, DefId, &CallableType),
+        arguments: &[ast::Expression],
+        expected: Option<RamenType>
+    ) -> Result<CallableType, ResolutionError> {
+        Self::check_argument_count(callable, callee, arguments)?;
+        let parameter_ids = callable.type_parameters.iter().map(|parameter| parameter.def_id).collect::<Vec<_>>();
+
+        let mut inferred = HashMap::new();
+        if let Some(expected) = &expected {
+            infer_type_arguments(&callable.return_type, expected, &mut inferred);
+        }
+
+        let mut argument_types = Vec::new();
+        for (argument, parameter_type) in arguments.iter().zip(callable.parameter_types.iter()) {
+            let parameter_type = parameter_type.substitute(&inferred);
+            self.expected = if parameter_type.contains_parameter(&parameter_ids) { None } else { Some(parameter_type.clone()) };
+            self.visit_expression(argument)?;
+
+            let argument_type = self.session.get_type(argument.id).expect("Argument type should have been resolved.");
+            infer_type_arguments(&parameter_type, &argument_type, &mut inferred);
+            argument_types.push(argument_type);
+        }
+
+        let type_arguments = parameter_ids.iter()
+            .map(|parameter_id| inferred.get(parameter_id).cloned())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| ResolutionError::TypeAnnotationsNeeded { location: callee.location.clone() })?;
+        let instance = self.instantiate(callee.id, segment, def_id, callable, type_arguments)?;
+
+        for ((argument, argument_type), parameter_type) in arguments.iter().zip(argument_types).zip(instance.parameter_types.iter()) {
+            if !self.coerce(argument, &argument_type, parameter_type) {
+                return Err(ResolutionError::TypeMismatch {
+                    expected: parameter_type.clone(),
+                    written: None,
+                    found: argument_type,
+                    location: argument.location.clone()
+                });
+            }
+        }
+        Ok(instance)
+    }
+
+    /// Records use of generic function through path `path_id` ending with `segment`,
+    /// so the function can be generated for given type arguments.
+    fn instantiate(
+        &mut self,
+        path_id: NodeId,
+        segment: &ast::PathSegment,
+        def_id: DefId,
+        callable: &CallableType,
+        type_arguments: Vec<RamenType>
+    ) -> Result<CallableType, ResolutionError> {
+        if type_arguments.len() != callable.type_parameters.len() {
+            return Err(ResolutionError::GenericArgumentCountMismatch {
+                expected: callable.type_parameters.len(),
+                found: type_arguments.len(),
+                location: segment.location.clone()
+            });
+        }
+
+        for (parameter, argument) in callable.type_parameters.iter().zip(type_arguments.iter()) {
+            for bound in self.session.get_trait_bounds(parameter.def_id) {
+                if !self.implements(argument, bound) {
+                    return Err(ResolutionError::MissingImpl {
+                        trait_name: self.session.get_trait(bound).expect("Cannot find trait definition.").name,
+                        ty: argument.clone(),
+                        location: segment.location.clone()
+                    });
+                }
+            }
+        }
+
+        // Only uses inside of generic functions depend on how their caller is instantiated.
+        // Constructors of generic types have no code of their own, so they need no instances.
+        let caller = self.functions.last().copied().filter(|caller| matches!(
+            self.session.get_type(*caller),
+            Some(RamenType::Callable(caller_type)) if !caller_type.type_parameters.is_empty()
+        ));
+        if let Some(Definition::Function | Definition::TraitMethod { .. } | Definition::Method { .. }) = self.session.get_def(def_id) {
+            self.instantiations.push(Instantiation {
+                caller,
+                callee: def_id,
+                arguments: type_arguments.clone(),
+                name: segment.name.clone(),
+                location: segment.location.clone()
+            });
+        }
+
+        let instance = callable.instantiate(&type_arguments);
+        self.session.set_type_arguments(path_id, type_arguments);
+        Ok(instance)
+    }
+
+    /// Whether value of type `found` can be used where `expected` is.
+    /// Besides plain coercions, pointers to implementing types can become pointers to trait objects, which is recorded for codegen.
+    fn coerce(&self, expression: &ast::Expression, found: &RamenType, expected: &RamenType) -> bool {
+        if found.coerces_to(expected) { return true; }
+
+        let (Some(pointee), Some(RamenType::Dyn { trait_id, .. })) = (found.pointee(), expected.pointee()) else { return false };
+        let retargeted = match expected {
+            RamenType::Reference { mutable, .. } => RamenType::Reference { target: Box::new(pointee.clone()), mutable: *mutable },
+            RamenType::Pointer { mutable, .. } => RamenType::Pointer { target: Box::new(pointee.clone()), mutable: *mutable },
+            _ => unreachable!("Only references and pointers have pointees.")
+        };
+        if !found.coerces_to(&retargeted) || matches!(pointee, RamenType::Dyn { .. }) || !self.implements(pointee, *trait_id) {
+            return false;
+        }
+
+        self.session.set_coercion(expression.id, expected.clone());
+        true
+    }
+
+    /// Whether type implements the trait, type parameters only implement traits they are bounded by.
+    fn implements(&self, ty: &RamenType, trait_id: DefId) -> bool {
+        match ty {
+            RamenType::Parameter(parameter) => self.session.get_trait_bounds(parameter.def_id).contains(&trait_id),
+            RamenType::Dyn { trait_id: object_trait, .. } => *object_trait == trait_id,
+            _ => self.session.find_impl(trait_id, ty).is_some()
+        }
+    }
+
+    /// Finds every distinct instance of generic functions reachable from non-generic code.
+    /// Calls of trait methods are instances of the method of the implementation for their `Self` type.
+    fn collect_instances(&self) -> Result<(), ResolutionError> {
+        let mut queue = self.instantiations.iter()
+            .filter(|instantiation| instantiation.caller.is_none())
+            .map(|instantiation| (instantiation, instantiation.callee, instantiation.arguments.clone()))
+            .collect::<VecDeque<_>>();
+
+        while let Some((instantiation, callee, arguments)) = queue.pop_front() {
+            let (callee, arguments) = match self.session.get_def(callee) {
+                Some(Definition::TraitMethod { parent }) => {
+                    // Calls through trait objects can reach any implementation, but those are never generic.
+                    if matches!(arguments[0], RamenType::Dyn { .. }) { continue; }
+                    let implementation = self.session.find_impl(parent, &arguments[0]).ok_or_else(|| ResolutionError::MissingImpl {
+                        trait_name: self.session.get_trait(parent).expect("Cannot find trait definition.").name,
+                        ty: arguments[0].clone(),
+                        location: instantiation.location.clone()
+                    })?;
+                    (implementation.methods[&callee], arguments[1..].to_vec())
+                }
+                _ => (callee, arguments)
+            };
+            // Methods without type parameters of their own are generated like any other function.
+            if arguments.is_empty() { continue; }
+
+            if arguments.iter().map(type_size).sum::<usize>() > INSTANCE_SIZE_LIMIT {
+                return Err(ResolutionError::RecursiveInstantiation {
+                    name: instantiation.name.clone(),
+                    location: instantiation.location.clone()
+                });
+            }
+            if !self.session.add_instance(callee, arguments.clone()) { continue; }
+
+            let Some(RamenType::Callable(callable)) = self.session.get_type(callee) else {
+                unreachable!("Only functions can be instantiated.")
+            };
+            let type_arguments = callable.type_argument_map(&arguments);
+            for inner in self.instantiations.iter().filter(|inner| inner.caller == Some(callee)) {
+                let inner_arguments = inner.arguments.iter().map(|argument| argument.substitute(&type_arguments)).collect();
+                queue.push_back((inner, inner.callee, inner_arguments));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that trait objects are only used behind pointers and only for traits whose methods can be called through vtables.
+    fn check_trait_objects(&self, ty: &ast::Type, behind_pointer: bool) -> Result<(), ResolutionError> {
+        match &ty.kind {
+            ast::TypeKind::Dyn(_) => {
+                let object_type = self.session.get_type(ty.id).expect("Trait object type should have been resolved.");
+                if !behind_pointer {
+                    return Err(ResolutionError::UnsizedTraitObject {
+                        ty: object_type,
+                        location: ty.location.clone()
+                    });
+                }
+
+                let RamenType::Dyn { trait_id, .. } = object_type else { unreachable!("Trait object should have `dyn` type.") };
+                self.check_object_safety(trait_id, &ty.location)
+            }
+            ast::TypeKind::Reference { target, .. } | ast::TypeKind::Pointer { target, .. } => self.check_trait_objects(target, true),
+            ast::TypeKind::Array(element, _) | ast::TypeKind::Slice(element) => self.check_trait_objects(element, false),
+            ast::TypeKind::Tuple(elements) => elements.iter().try_for_each(|element| self.check_trait_objects(element, false)),
+            ast::TypeKind::Path(path) => path.segments.iter()
+                .flat_map(|segment| segment.type_arguments.iter())
+                .try_for_each(|argument| self.check_trait_objects(argument, false)),
+            _ => Ok(())
+        }
+    }
+
+    /// Methods can only be called through a vtable if they are not generic
+    /// and `Self` only appears as the pointer in their first parameter, which becomes the data pointer.
+    fn check_object_safety(&self, trait_id: DefId, location: &Loc) -> Result<(), ResolutionError> {
+        let trait_definition = self.session.get_trait(trait_id).expect("Cannot find trait definition.");
+        let self_parameter = [trait_definition.self_parameter];
+
+        for (name, method) in trait_definition.methods.iter() {
+            let Some(RamenType::Callable(callable)) = self.session.get_type(*method) else {
+                unreachable!("Trait method signature should have been resolved by type declaration pass.")
+            };
+
+            let receiver = callable.parameter_types.first().and_then(|ty| ty.pointee());
+            let reason = if callable.type_parameters.len() > 1 {
+                Some("has type parameters")
+            } else if callable.return_type.contains_parameter(&self_parameter) {
+                Some("returns `Self`")
+            } else if !matches!(receiver, Some(RamenType::Parameter(parameter)) if parameter.def_id == trait_definition.self_parameter) {
+                Some("does not take `Self` behind a reference or pointer as its first parameter")
+            } else if callable.parameter_types[1..].iter().any(|ty| ty.contains_parameter(&self_parameter)) {
+                Some("uses `Self` in parameters other than the first one")
+            } else { None };
+
+            if let Some(reason) = reason {
+                return Err(ResolutionError::NotObjectSafe {
+                    trait_name: trait_definition.name,
+                    method: name.clone(),
+                    reason,
+                    location: location.clone()
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that signature of method in implementation matches the one declared by the trait,
+    /// with `Self` replaced by the implementing type.
+    fn check_impl_method(&self, item: &ast::Item, self_type: &RamenType, trait_method: DefId, method: DefId) -> Result<(), ResolutionError> {
+        let Some(RamenType::Callable(declared)) = self.session.get_type(trait_method) else {
+            unreachable!("Trait method signature should have been resolved by type declaration pass.")
+        };
+        let Some(RamenType::Callable(found)) = self.session.get_type(method) else {
+            unreachable!("Method signature should have been resolved by type declaration pass.")
+        };
+
+        // Besides `Self`, both have to have the same type parameters.
+        if declared.type_parameters.len() != found.type_parameters.len() + 1 {
+            return Err(ResolutionError::GenericArgumentCountMismatch {
+                expected: declared.type_parameters.len() - 1,
+                found: found.type_parameters.len(),
+                location: item.location.clone()
+            });
+        }
+
+        let mut arguments = vec![self_type.clone()];
+        arguments.extend(found.type_parameters.iter().cloned().map(RamenType::Parameter));
+        let expected = CallableType {
+            type_parameters: found.type_parameters.clone(),
+            ..declared.instantiate(&arguments)
+        };
+        if expected != *found {
+            return Err(ResolutionError::TypeMismatch {
+                expected: RamenType::Callable(Box::new(expected)),
+                written: None,
+                found: RamenType::Callable(found),
+                location: item.location.clone()
+            });
+        }
+        Ok(())
+    }
+
+    /// Type arguments of payload-less variant of generic enum, taken from the path or the expected type.
+    fn variant_type_arguments(&self, path: &ast::Path, enum_type: &RamenType, expected: Option<RamenType>) -> Result<Vec<RamenType>, ResolutionError> {
+        let segment = path.segments.last().expect("Path should have at least one segment.");
+        let explicit = explicit_type_arguments(path);
+        if explicit.is_empty() {
+            return match expected {
+                Some(RamenType::Enum(expected)) if Some(expected.def_id) == enum_type.def_id() => Ok(expected.type_arguments),
+                _ => Err(ResolutionError::TypeAnnotationsNeeded { location: segment.location.clone() })
+            };
+        }
+
+        let expected_count = enum_type.type_arguments().len();
+        if explicit.len() != expected_count {
+            return Err(ResolutionError::GenericArgumentCountMismatch {
+                expected: expected_count,
+                found: explicit.len(),
+                location: segment.location.clone()
+            });
+        }
+        Ok(resolve_types(self.session, explicit)?.expect("Type arguments should have been resolved by type declaration pass."))
+    }
+
+    /// Types of the variable and every field accessed through the path after it, the last one is type of the whole path.
+    fn path_field_types(&self, variable_type: RamenType, fields: &[ast::PathSegment]) -> Result<Vec<RamenType>, ResolutionError> {
+        let mut types = vec![variable_type];
+        for field in fields.iter() {
+            let field_type = field_type(self.session, types.last().unwrap(), &field.name, &field.location)?;
+            types.push(field_type);
+        }
+        Ok(types)
+    }
+
+    /// Resolves type of indexed expression and returns type of its elements.
+    fn check_indexable(&mut self, base: &ast::Expression) -> Result<RamenType, ResolutionError> {
+        self.expected = None;
+        self.visit_expression(base)?;
+
+        match self.session.get_type(base.id).expect("Indexed expression type should have been resolved.") {
+            RamenType::Array(element_type, _) | RamenType::Slice(element_type) => Ok(*element_type),
+            found => Err(ResolutionError::NotIndexable {
+                found,
+                location: base.location.clone()
+            })
+        }
+    }
+
+    /// Indices can be integers of any width, literals default to 64 bits.
+    fn check_index(&mut self, index: &ast::Expression) -> Result<(), ResolutionError> {
+        self.expected = Some(RamenType::Integer(64));
+        self.visit_expression(index)?;
+
+        let found = self.session.get_type(index.id).expect("Index type should have been resolved.");
+        if !matches!(found, RamenType::Integer(_)) {
+            return Err(ResolutionError::TypeMismatch {
+                expected: RamenType::Integer(64),
+                written: None,
+                found,
+                location: index.location.clone()
+            });
+        }
+        Ok(())
+    }
+
+    /// Makes sure the place can be mutated, either through `&mut` or assignment.
+    /// Only assignments require the place to be behind a pointer, variables can be borrowed mutably directly.
+    fn check_mutable_place(&self, place: &ast::Expression, is_assignment: bool) -> Result<(), ResolutionError> {
+        let invalid_target = || ResolutionError::InvalidAssignmentTarget { location: place.location.clone() };
+
+        match &place.kind {
+            ast::ExpressionKind::Deref(pointer) => check_mutable_pointer(
+                self.session.get_type(pointer.id).expect("Dereferenced expression type should have been resolved."),
+                &pointer.location
+            ),
+            ast::ExpressionKind::Path(path) => {
+                let fields = self.session.get_path_fields(place.id);
+                let def_id = self.session.get_ref_target(place.id).expect("Path should have been resolved by name resolution.");
+                self.check_mutable_path(def_id, &path.segments[path.segments.len() - fields..], &place.location, is_assignment)
+            }
+            ast::ExpressionKind::TupleIndex(base, _) | ast::ExpressionKind::Index(base, _) | ast::ExpressionKind::Field(base, _) => {
+                match self.session.get_type(base.id) {
+                    // Slices always point to mutable elements.
+                    Some(RamenType::Slice(_)) => Ok(()),
+                    // Fields of structs behind pointers are accessed through the pointer.
+                    Some(pointer_type @ (RamenType::Reference { .. } | RamenType::Pointer { .. })) =>
+                        check_mutable_pointer(pointer_type, &base.location),
+                    _ => self.check_mutable_place(base, is_assignment).map_err(|error| match error {
+                        ResolutionError::InvalidAssignmentTarget { .. } => invalid_target(),
+                        error => error
+                    })
+                }
+            }
+            _ if is_assignment => Err(invalid_target()),
+            _ => Ok(())
+        }
+    }
+
+    /// Makes sure variable `def_id` or its field reached through `fields` can be mutated.
+    /// Only fields reached through a pointer can be assigned to, the last pointer decides whether they are mutable.
+    /// Statics are places of their own, which can be assigned to and borrowed mutably only if they are `static mut`.
+    fn check_mutable_path(&self, def_id: DefId, fields: &[ast::PathSegment], location: &Loc, is_assignment: bool) -> Result<(), ResolutionError> {
+        let variable_type = self.session.get_type(def_id).expect("Variable type should have been resolved.");
+        let field_types = self.path_field_types(variable_type, fields)?;
+
+        match (field_types[..fields.len()].iter().rev().find(|ty| ty.pointee().is_some()), self.session.get_def(def_id)) {
+            (Some(pointer_type), _) => check_mutable_pointer(pointer_type.clone(), location),
+            (None, Some(Definition::Static { mutable: true })) => Ok(()),
+            (None, Some(Definition::Static { mutable: false })) => Err(ResolutionError::ImmutableStatic {
+                name: self.session.get_symbol(def_id).expect("Static should have a symbol.").rsplit('.').next().unwrap().to_string(),
+                location: location.clone()
+            }),
+            (None, _) if is_assignment => Err(ResolutionError::InvalidAssignmentTarget { location: location.clone() }),
+            (None, _) => Ok(())
+        }
+    }
+
+    /// Receiver and method name of call written with method syntax, `None` for calls of plain paths.
+    fn method_receiver<'a>(&self, callee: &'a ast::Expression) -> Option<(Receiver<'a>, &'a str)> {
+        match &callee.kind {
+            ast::ExpressionKind::Field(base, name) => Some((Receiver::Expression(base), name)),
+            ast::ExpressionKind::Path(path) => {
+                let fields = self.session.get_path_fields(callee.id);
+                if fields == 0 { return None; }
+
+                let def_id = self.session.get_ref_target(callee.id).expect("Path should have been resolved by name resolution.");
+                let (method, receiver_fields) = path.segments.split_last().expect("Path should have at least one segment.");
+                Some((Receiver::Path(def_id, &receiver_fields[receiver_fields.len() + 1 - fields..]), &method.name))
+            }
+            _ => None
+        }
+    }
+
+    fn check_receiver_type(&mut self, receiver: &Receiver) -> Result<RamenType, ResolutionError> {
+        match receiver {
+            Receiver::Expression(base) => {
+                self.expected = None;
+                self.visit_expression(base)?;
+                Ok(self.session.get_type(base.id).expect("Receiver type should have been resolved."))
+            }
+            Receiver::Path(def_id, fields) => {
+                let variable_type = self.session.get_type(*def_id).expect("Variable type should have been resolved.");
+                Ok(self.path_field_types(variable_type, fields)?.pop().unwrap())
+            }
+        }
+    }
+
+    /// Method of inherent implementation of the receiver's type, or of a trait implemented for it.
+    /// References and pointers are dereferenced automatically.
+    fn find_method(&self, receiver_type: &RamenType, name: &str, location: &Loc) -> Result<Option<DefId>, ResolutionError> {
+        let self_type = receiver_type.pointee().unwrap_or(receiver_type);
+        // Trait objects have just the methods of their trait, which are called through the vtable.
+        if let RamenType::Dyn { trait_id, .. } = self_type {
+            let trait_definition = self.session.get_trait(*trait_id).expect("Cannot find trait definition.");
+            return Ok(trait_definition.methods.iter().find(|(method_name, _)| method_name == name).map(|&(_, method)| method));
+        }
+        let inherent = self_type.def_id()
+            .and_then(|type_def_id| self.session.scopes.get(type_def_id)?.get_local(Scope::NS_NAMES, name))
+            .filter(|&method| matches!(self.session.get_def(method), Some(Definition::Method { has_receiver: true, .. })));
+        if inherent.is_some() {
+            return Ok(inherent);
+        }
+
+        // Otherwise the method comes from a trait implemented for the receiver, or the type it points to.
+        let mut candidates = Vec::new();
+        for (&trait_id, trait_definition) in self.session.traits.borrow().iter() {
+            let Some(&(_, trait_method)) = trait_definition.methods.iter().find(|(method_name, _)| method_name == name) else { continue };
+            let implementation = self.session.find_impl(trait_id, receiver_type)
+                .or_else(|| self.session.find_impl(trait_id, receiver_type.pointee()?));
+            // Methods without parameters have nothing to take the receiver.
+            let method = implementation.map(|implementation| implementation.methods[&trait_method])
+                .filter(|&method| matches!(self.session.get_type(method), Some(RamenType::Callable(callable)) if !callable.parameter_types.is_empty()));
+            if let Some(method) = method {
+                candidates.push((trait_definition.name.clone(), method));
+            }
+        }
+
+        match candidates.len() {
+            0 | 1 => Ok(candidates.pop().map(|(_, method)| method)),
+            _ => {
+                let mut traits = candidates.into_iter().map(|(trait_name, _)| trait_name).collect::<Vec<_>>();
+                traits.sort();
+                Err(ResolutionError::AmbiguousMethod {
+                    name: name.to_string(),
+                    traits,
+                    location: location.clone()
+                })
+            }
+        }
+    }
+
+    /// Makes sure the receiver can be passed as the first parameter of method.
+    /// Receivers are referenced or dereferenced automatically when the method takes them the other way.
+    fn check_receiver(&self, receiver: &Receiver, receiver_type: &RamenType, parameter_type: &RamenType, location: &Loc) -> Result<(), ResolutionError> {
+        if receiver_type.coerces_to(parameter_type) || receiver_type.pointee() == Some(parameter_type) {
+            return Ok(());
+        }
+
+        if parameter_type.pointee() == Some(receiver_type) {
+            if let RamenType::Reference { mutable: true, .. } = parameter_type {
+                match receiver {
+                    Receiver::Expression(base) => self.check_mutable_place(base, false)?,
+                    Receiver::Path(def_id, fields) => self.check_mutable_path(*def_id, fields, location, false)?
+                }
+            }
+            return Ok(());
+        }
+
+        if receiver_type.pointee().is_some() && receiver_type.pointee() == parameter_type.pointee() {
+            check_mutable_pointer(receiver_type.clone(), location)?;
+        }
+        Err(ResolutionError::TypeMismatch {
+            expected: parameter_type.clone(),
+            written: None,
+            found: receiver_type.clone(),
+            location: location.clone()
+        })
+    }
+
+    /// Checks call of method with the receiver as its first argument.
+    /// The call itself refers to the method, as its callee is a path to the receiver.
+    fn check_method_call(
+        &mut self,
+        id: NodeId,
+        callee: &ast::Expression,
+        (receiver, receiver_type, name): (Receiver, RamenType, &str),
+        method: DefId,
+        arguments: &[ast::Expression],
+        expected: Option<RamenType>
+    ) -> Result<(), ResolutionError> {
+        let Some(RamenType::Callable(mut callable)) = self.session.get_type(method) else {
+            unreachable!("Method signature should have been resolved by type declaration pass.")
+        };
+        check_deprecated(self.session, method, name, &callee.location);
+        let segment = ast::PathSegment {
+            location: callee.location.clone(),
+            name: name.to_string(),
+            type_arguments: Vec::new()
+        };
+        // Object safety leaves `Self` as the only type parameter, and the object itself stands in for it.
+        if let Some(object @ RamenType::Dyn { .. }) = receiver_type.pointee() {
+            *callable = self.instantiate(callee.id, &segment, method, &callable, vec![object.clone()])?;
+        }
+        self.check_receiver(&receiver, &receiver_type, &callable.parameter_types[0], &callee.location)?;
+        self.session.set_ref(id, method);
+
+        let mut rest = (*callable).clone();
+        rest.parameter_types.remove(0);
+        let instance = if callable.type_parameters.is_empty() {
+            Self::check_argument_count(&rest, callee, arguments)?;
+            for (argument, parameter_type) in arguments.iter().zip(rest.parameter_types.iter()) {
+                self.check_expression(argument, parameter_type.clone())?;
+            }
+            *callable
+        } else {
+            self.check_generic_call(callee, (&segment, method, &rest), arguments, expected)?;
+            callable.instantiate(&self.session.get_type_arguments(callee.id).expect("Method type arguments should have been inferred."))
+        };
+
+        self.session.set_type(id, instance.return_type.clone());
+        self.session.set_type(callee.id, RamenType::Callable(Box::new(instance)));
+        Ok(())
+    }
+
+    /// Makes sure pattern can match values of the expected type and assigns types to its bindings.
+    fn check_pattern(&mut self, pattern: &ast::Pattern, expected: &RamenType) -> Result<(), ResolutionError> {
+        let invalid_pattern = || ResolutionError::InvalidPattern {
+            ty: expected.clone(),
+            location: pattern.location.clone()
+        };
+
+        match &pattern.kind {
+            ast::PatternKind::Wildcard => (),
+            ast::PatternKind::Binding(_) => {
+                let def_id = self.session.get_ref_target(pattern.id).expect("Cannot find binding definition reference.");
+                self.session.set_type(def_id, expected.clone());
+            }
+            ast::PatternKind::Integer(_) | ast::PatternKind::Range { .. } => {
+                let RamenType::Integer(width) = expected else { return Err(invalid_pattern()); };
+                // Exclusive range may end just past the largest value, like `0..128` for `int8`.
+                let bounds = match pattern.kind {
+                    ast::PatternKind::Range { start, end, inclusive } => [start, if inclusive { end } else { end - 1 }],
+                    ast::PatternKind::Integer(value) => [value, value],
+                    _ => unreachable!()
+                };
+                if let Some(&value) = bounds.iter().find(|&&value| !fits_integer(value, *width)) {
+                    return Err(ResolutionError::PatternOutOfRange {
+                        value,
+                        ty: expected.clone(),
+                        location: pattern.location.clone()
+                    });
+                }
+            }
+            ast::PatternKind::Bool(_) => {
+                if *expected != RamenType::Bool { return Err(invalid_pattern()); }
+            }
+            ast::PatternKind::Tuple(elements) => {
+                let RamenType::Tuple(element_types) = expected else { return Err(invalid_pattern()); };
+                if elements.len() != element_types.len() {
+                    return Err(ResolutionError::PatternFieldCountMismatch {
+                        expected: element_types.len(),
+                        found: elements.len(),
+                        location: pattern.location.clone()
+                    });
+                }
+                for (element, element_type) in elements.iter().zip(element_types.iter()) {
+                    self.check_pattern(element, element_type)?;
+                }
+            }
+            ast::PatternKind::Variant(_, fields) => {
+                let def_id = self.session.get_ref_target(pattern.id).expect("Variant pattern should have been resolved by name resolution.");
+                let Some(Definition::Variant { parent, index }) = self.session.get_def(def_id) else {
+                    return Err(invalid_pattern());
+                };
+                let RamenType::Enum(enum_type) = expected else { return Err(invalid_pattern()); };
+                if enum_type.def_id != parent {
+                    return Err(ResolutionError::TypeMismatch {
+                        expected: expected.clone(),
+                        written: None,
+                        found: self.session.get_type(parent).expect("Enum type should have been resolved."),
+                        location: pattern.location.clone()
+                    });
+                }
+
+                let field_types = &enum_type.variants[index].fields;
+                if fields.len() != field_types.len() {
+                    return Err(ResolutionError::PatternFieldCountMismatch {
+                        expected: field_types.len(),
+                        found: fields.len(),
+                        location: pattern.location.clone()
+                    });
+                }
+                for (field, field_type) in fields.iter().zip(field_types.iter()) {
+                    self.check_pattern(field, field_type)?;
+                }
+            }
+            ast::PatternKind::Or(alternatives) => {
+                for alternative in alternatives.iter() {
+                    self.check_pattern(alternative, expected)?;
+                }
+            }
+        }
+
+        self.session.set_type(pattern.id, expected.clone());
+        Ok(())
+    }
+}
+
+/// Number of types nested in the type, including itself.
+fn type_size(ty: &RamenType) -> usize {
+    1 + match ty {
+        RamenType::Tuple(elements) => elements.iter().map(type_size).sum(),
+        RamenType::Array(element, _) | RamenType::Slice(element) |
+        RamenType::Reference { target: element, .. } | RamenType::Pointer { target: element, .. } => type_size(element),
+        RamenType::Callable(callable) => type_size(&callable.return_type) + callable.parameter_types.iter().map(type_size).sum::<usize>(),
+        RamenType::Enum(_) | RamenType::Struct(_) => ty.type_arguments().iter().map(type_size).sum(),
+        RamenType::Unit | RamenType::Bool | RamenType::Integer(_) | RamenType::Float(_) | RamenType::Dyn { .. } | RamenType::Parameter(_) => 0
+    }
+}
+
+fn check_mutable_pointer(pointer_type: RamenType, location: &Loc) -> Result<(), ResolutionError> {
+    match pointer_type {
+        RamenType::Reference { mutable: true, .. } | RamenType::Pointer { mutable: true, .. } => Ok(()),
+        ty => Err(ResolutionError::MutationThroughImmutable {
+            ty,
+            location: location.clone()
+        })
+    }
+}
+
+/// Parameters and return value of extern or exported function have to be representable in C, returning nothing is fine too.
+/// Tuples can be returned, as C structs with the same fields.
+fn check_ffi_signature(function: &ast::Function, callable: &CallableType) -> Result<(), ResolutionError> {
+    for (parameter, ty) in function.parameters.iter().zip(callable.parameter_types.iter()) {
+        if !ty.is_ffi_safe() {
+            return Err(ResolutionError::NotFfiSafe {
+                ty: ty.clone(),
+                location: parameter.parameter.ty.location.clone()
+            });
+        }
+    }
+    match &function.return_type {
+        Some(return_type) if !callable.return_type.is_ffi_safe_return() && callable.return_type != RamenType::Unit => Err(ResolutionError::NotFfiSafe {
+            ty: callable.return_type.clone(),
+            location: return_type.location.clone()
+        }),
+        _ => Ok(())
+    }
+}
+
+/// Explicit type arguments of the path, they are either on its last segment or on the enum segment of path to variant.
+fn explicit_type_arguments(path: &ast::Path) -> &[ast::Type] {
+    path.segments.iter().rev()
+        .map(|segment| segment.type_arguments.as_slice())
+        .find(|arguments| !arguments.is_empty())
+        .unwrap_or_default()
+}
+
+/// Type of field `name` of a struct, references and pointers to structs are dereferenced automatically.
+fn field_type(session: &Session, base_type: &RamenType, name: &str, location: &Loc) -> Result<RamenType, ResolutionError> {
+    let struct_type = match session.complete_type(base_type.pointee().unwrap_or(base_type)) {
+        RamenType::Struct(struct_type) => Some(struct_type),
+        _ => None
+    };
+    struct_type
+        .and_then(|struct_type| struct_type.fields.into_iter().find(|field| field.name == name))
+        .map(|field| field.ty)
+        .ok_or_else(|| ResolutionError::NoField {
+            ty: base_type.clone(),
+            name: name.to_string(),
+            location: location.clone()
+        })
+}
+
+/// Maps type parameters in `parameter` to parts of `argument` at the same position,
+/// parameters that are already inferred are kept.
+fn infer_type_arguments(parameter: &RamenType, argument: &RamenType, inferred: &mut HashMap<DefId, RamenType>) {
+    match (parameter, argument) {
+        (RamenType::Parameter(parameter), _) => {
+            inferred.entry(parameter.def_id).or_insert_with(|| argument.clone());
+        }
+        (RamenType::Tuple(parameters), RamenType::Tuple(arguments)) if parameters.len() == arguments.len() => {
+            for (parameter, argument) in parameters.iter().zip(arguments.iter()) {
+                infer_type_arguments(parameter, argument, inferred);
+            }
+        }
+        (RamenType::Array(parameter, _), RamenType::Array(argument, _)) |
+        (RamenType::Slice(parameter), RamenType::Slice(argument)) |
+        (
+            RamenType::Reference { target: parameter, .. } | RamenType::Pointer { target: parameter, .. },
+            RamenType::Reference { target: argument, .. } | RamenType::Pointer { target: argument, .. }
+        ) => infer_type_arguments(parameter, argument, inferred),
+        (RamenType::Enum(_) | RamenType::Struct(_), RamenType::Enum(_) | RamenType::Struct(_)) if parameter.def_id() == argument.def_id() => {
+            for (parameter, argument) in parameter.type_arguments().iter().zip(argument.type_arguments().iter()) {
+                infer_type_arguments(parameter, argument, inferred);
+            }
+        }
+        (RamenType::Callable(parameter), RamenType::Callable(argument)) => {
+            for (parameter, argument) in parameter.parameter_types.iter().zip(argument.parameter_types.iter()) {
+                infer_type_arguments(parameter, argument, inferred);
+            }
+            infer_type_arguments(&parameter.return_type, &argument.return_type, inferred);
+        }
+        _ => ()
+    }
+}
+
 impl<'sess> Visitor<()> for TypeResolutionPass<'sess> {
     type Error = ResolutionError;
 
-    fn default_return(&self) -> () { () }
-    fn get_scope_stack<'a>(&'a self) -> &'a ScopeStack { &self.stack }
-    fn get_session<'a>(&'a self) -> &'a Session { &self.session }
+    fn default_return(&self) {}
+    fn get_scope_stack(&self) -> &ScopeStack { &self.stack }
+    fn get_session(&self) -> &Session { self.session }
+
+    fn visit_expression(&mut self, expression: &ast::Expression) -> Result<(), Self::Error> {
+        walk_expression(self, expression)?;
+        if self.session.get_type(expression.id).is_none() {
+            return Err(ResolutionError::TypeAnnotationsNeeded { location: expression.location.clone() });
+        }
+        match expression.kind {
+            ast::ExpressionKind::Literal(ast::Literal::Integer(value)) => self.check_literal_range(expression, value as i128),
+            _ => Ok(())
+        }
+    }
 
     fn visit_function(&mut self, id: NodeId, function: &ast::Function) -> Result<(), Self::Error> {
         let def_id = self.session.get_ref_target(id).expect("Cannot find function definition reference.");
-        walk_function(self, id, function)?;
+        let Some(RamenType::Callable(callable)) = self.session.get_type(def_id) else {
+            unreachable!("Function signature should have been resolved by type declaration pass.")
+        };
+        if self.session.get_extern(def_id).is_some() || self.session.is_exported(def_id) {
+            check_ffi_signature(function, &callable)?;
+        }
+        if self.session.get_const_function(def_id).is_some() {
+            check_const_function_body(self.session, def_id, function)?;
+        }
 
-        let mut parameter_types = Vec::new();
-        for parameter in function.parameters.iter() {
-            let parameter_type = self.session.get_type(parameter.id);
-            parameter_types.push(parameter_type.unwrap());
+        self.check_function_body(id, def_id, function, callable.return_type)?;
+        match &function.body {
+            Some(body) if self.session.has_attribute(def_id, &KnownAttribute::NoReturn) => check_noreturn_body(self.session, &function.name, body),
+            _ => Ok(())
         }
+    }
 
-        let return_type = if let Some(return_type) = &function.return_type {
-            self.session.get_type(return_type.id).unwrap()
-        } else { RamenType::Unit };
+    fn visit_impl(&mut self, id: NodeId, impl_: &ast::Impl) -> Result<(), Self::Error> {
+        let Some(trait_ref) = &impl_.trait_ else { return walk_impl(self, id, impl_) };
+        let def_id = self.session.get_ref_target(id).expect("Cannot find impl definition reference.");
+        let trait_id = self.session.get_ref_target(trait_ref.id).expect("Cannot find trait reference.");
+        let self_type = self.session.get_type(def_id).expect("Implemented type should have been resolved by type declaration pass.");
+        let implementation = self.session.find_impl(trait_id, &self_type).expect("Implementation should have been registered by type declaration pass.");
 
-        self.session.set_type(def_id, RamenType::Callable(Box::new(CallableType::new(
-            return_type,
-            parameter_types
-        ))));
-        self.session.try_bind_type(id, def_id);
+        for item in impl_.items.iter() {
+            let method = self.session.get_ref_target(item.id).expect("Cannot find method definition reference.");
+            let (&trait_method, _) = implementation.methods.iter()
+                .find(|(_, &implemented)| implemented == method)
+                .expect("Every method of implementation should belong to the trait.");
+            self.check_impl_method(item, &self_type, trait_method, method)?;
+        }
 
-        Ok(())
+        walk_impl(self, id, impl_)
     }
 
+    // Initializers of constants and statics are checked when they are evaluated by type declaration pass.
+    fn visit_const(&mut self, _id: NodeId, _const: &ast::Const) -> Result<(), Self::Error> { Ok(()) }
+    fn visit_static(&mut self, _id: NodeId, _static: &ast::Static) -> Result<(), Self::Error> { Ok(()) }
+
     fn visit_value_parameter(&mut self, parameter: &ast::ValueParameter) -> Result<(), Self::Error> {
         self.visit_parameter(&parameter.parameter)?;
         self.session.try_bind_type(parameter.id, parameter.parameter.id);
@@ -61,24 +1009,445 @@ impl<'sess> Visitor<()> for TypeResolutionPass<'sess> {
     fn visit_parameter(&mut self, parameter: &ast::Parameter) -> Result<(), Self::Error> {
         walk_parameter(self, parameter)?;
         self.session.try_bind_type(parameter.id, parameter.ty.id);
+        if let (ast::PatternKind::Binding(_), Some(written)) = (&parameter.pattern.kind, written_type(self.session, &parameter.ty)) {
+            let def_id = self.session.get_ref_target(parameter.pattern.id).expect("Cannot find parameter definition reference.");
+            self.session.set_written_type(def_id, written);
+        }
+
+        let parameter_type = self.session.get_type(parameter.ty.id).expect("Parameter type should have been resolved.");
+        self.check_pattern(&parameter.pattern, &parameter_type)
+    }
+
+    fn visit_let_statement(&mut self, _id: NodeId, pattern: &ast::Pattern, ty: Option<&ast::Type>, value: &ast::Expression) -> Result<(), Self::Error> {
+        let value_type = match ty {
+            Some(ty) => {
+                self.visit_type(ty)?;
+                let expected = self.session.get_type(ty.id).expect("Let type should have been resolved.");
+                self.check_annotated_expression(value, expected.clone(), written_type(self.session, ty))?;
+                expected
+            }
+            None => {
+                self.expected = None;
+                self.visit_expression(value)?;
+                self.session.get_type(value.id).expect("Expression type should have been resolved.")
+            }
+        };
+
+        if self.borrows_frame(value) {
+            self.mark_frame_bindings(pattern);
+        }
+        if let ast::PatternKind::Binding(_) = pattern.kind {
+            let def_id = self.session.get_ref_target(pattern.id).expect("Cannot find binding definition reference.");
+            if let Some(written) = ty.and_then(|ty| written_type(self.session, ty)) {
+                self.session.set_written_type(def_id, written);
+            }
+        }
+        self.check_pattern(pattern, &value_type)
+    }
+
+    fn visit_assign_statement(&mut self, _id: NodeId, target: &ast::Expression, value: &ast::Expression) -> Result<(), Self::Error> {
+        self.expected = None;
+        self.visit_expression(target)?;
+        self.check_mutable_place(target, true)?;
+
+        let target_type = self.session.get_type(target.id).expect("Assignment target type should have been resolved.");
+        self.check_expression(value, target_type)
+    }
+
+    fn visit_return_statement(&mut self, _id: NodeId, value: &ast::Expression) -> Result<(), Self::Error> {
+        let (return_type, written) = self.return_types.last().cloned().expect("Return statement should be inside of a function.");
+        self.check_annotated_expression(value, return_type, written)?;
+        if self.borrows_frame(value) {
+            return Err(ResolutionError::EscapingClosure { location: value.location.clone() });
+        }
         Ok(())
     }
 
     fn visit_literal_expression(&mut self, id: NodeId, literal: &ast::Literal) -> Result<(), Self::Error> {
-        match literal {
-            ast::Literal::Integer(_) => self.session.set_type(id, RamenType::Integer(32)), // Default width... change to minimum required in the future
-        } 
+        let expected = self.expected.take();
+        let literal_type = match (literal, expected) {
+            (ast::Literal::Integer(_), Some(RamenType::Integer(width))) => RamenType::Integer(width),
+            (ast::Literal::Integer(_), _) => RamenType::Integer(32),
+            (ast::Literal::Float(_), Some(RamenType::Float(width))) => RamenType::Float(width),
+            (ast::Literal::Float(_), _) => RamenType::Float(64),
+            (ast::Literal::Bool(_), _) => RamenType::Bool,
+        };
 
+        self.session.set_type(id, literal_type);
         Ok(())
     }
 
-    fn visit_type(&mut self, ty: &ast::Type) -> Result<(), Self::Error> {
-        let resolved_type = match &ty.kind {
-            ast::TypeKind::Unit => RamenType::Unit,
-            ast::TypeKind::Integer(width) => RamenType::Integer(*width),
+    fn visit_path_expression(&mut self, id: NodeId, path: &ast::Path) -> Result<(), Self::Error> {
+        let expected = self.expected.take();
+        let def_id = self.session.get_ref_target(id).expect("Path should have been resolved by name resolution.");
+        let path_type = self.session.get_type(def_id).expect("Type of definition should have been resolved.");
+
+        let fields = self.session.get_path_fields(id);
+        if fields > 0 {
+            let field_types = self.path_field_types(path_type, &path.segments[path.segments.len() - fields..])?;
+            self.session.set_type(id, field_types.last().cloned().unwrap());
+            return Ok(());
+        }
+
+        let segment = path.segments.last().expect("Path should have at least one segment.");
+        let path_type = match path_type {
+            RamenType::Callable(callable) if !callable.type_parameters.is_empty() => {
+                // Without call arguments there is nothing to infer type arguments from.
+                let explicit = explicit_type_arguments(path);
+                if explicit.is_empty() {
+                    return Err(ResolutionError::TypeAnnotationsNeeded { location: segment.location.clone() });
+                }
+
+                let type_arguments = resolve_types(self.session, explicit)?
+                    .expect("Type arguments should have been resolved by type declaration pass.");
+                RamenType::Callable(Box::new(self.instantiate(id, segment, def_id, &callable, type_arguments)?))
+            }
+            // Only variants are generic, variables and constants already have their type arguments.
+            RamenType::Enum(_) if matches!(self.session.get_def(def_id), Some(Definition::Variant { .. })) && !path_type.type_arguments().is_empty() => {
+                let type_arguments = self.variant_type_arguments(path, &path_type, expected)?;
+                self.session.set_type_arguments(id, type_arguments.clone());
+                path_type.apply_type_arguments(&type_arguments)
+            }
+            _ if !segment.type_arguments.is_empty() => return Err(ResolutionError::GenericArgumentCountMismatch {
+                expected: 0,
+                found: segment.type_arguments.len(),
+                location: segment.location.clone()
+            }),
+            path_type => path_type
+        };
+
+        self.session.set_type(id, path_type);
+        Ok(())
+    }
+
+    fn visit_tuple_expression(&mut self, id: NodeId, elements: &[ast::Expression]) -> Result<(), Self::Error> {
+        let expected_elements = match self.expected.take() {
+            Some(RamenType::Tuple(expected_elements)) if expected_elements.len() == elements.len() => Some(expected_elements),
+            _ => None
         };
 
-        self.session.set_type(ty.id, resolved_type);
+        let mut element_types = Vec::new();
+        for (index, element) in elements.iter().enumerate() {
+            self.expected = expected_elements.as_ref().map(|expected_elements| expected_elements[index].clone());
+            self.visit_expression(element)?;
+            element_types.push(self.session.get_type(element.id).expect("Element type should have been resolved."));
+        }
+
+        self.session.set_type(id, if element_types.is_empty() { RamenType::Unit } else { RamenType::Tuple(element_types) });
+        Ok(())
+    }
+
+    fn visit_tuple_index_expression(&mut self, id: NodeId, tuple: &ast::Expression, index: usize) -> Result<(), Self::Error> {
+        self.expected = None;
+        self.visit_expression(tuple)?;
+
+        let tuple_type = self.session.get_type(tuple.id).expect("Tuple type should have been resolved.");
+        let element_type = match &tuple_type {
+            RamenType::Tuple(element_types) => element_types.get(index).cloned(),
+            // Value wrapped by newtype is its field `0`.
+            RamenType::Struct(struct_type) => struct_type.fields.iter()
+                .find(|field| field.name == index.to_string())
+                .map(|field| field.ty.clone()),
+            _ => None
+        }.ok_or_else(|| ResolutionError::InvalidTupleIndex {
+            ty: tuple_type.clone(),
+            index,
+            location: tuple.location.clone()
+        })?;
+
+        self.session.set_type(id, element_type);
+        Ok(())
+    }
+
+    fn visit_field_expression(&mut self, id: NodeId, base: &ast::Expression, name: &str) -> Result<(), Self::Error> {
+        self.expected = None;
+        self.visit_expression(base)?;
+
+        let base_type = self.session.get_type(base.id).expect("Accessed expression type should have been resolved.");
+        self.session.set_type(id, field_type(self.session, &base_type, name, &base.location)?);
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn visit_array_expression(&mut self, id: NodeId, elements: &[ast::Expression]) -> Result<(), Self::Error> {
+        // Without expected type the first element decides type of the rest.
+        let mut element_type = match self.expected.take() {
+            Some(RamenType::Array(element_type, _) | RamenType::Slice(element_type)) => Some(*element_type),
+            _ => None
+        };
+
+        for element in elements.iter() {
+            match &element_type {
+                Some(element_type) => self.check_expression(element, element_type.clone())?,
+                None => {
+                    self.visit_expression(element)?;
+                    element_type = self.session.get_type(element.id);
+                }
+            }
+        }
+
+        // Empty array without expected type is left untyped and reported by `visit_expression`.
+        if let Some(element_type) = element_type {
+            self.session.set_type(id, RamenType::Array(Box::new(element_type), elements.len()));
+        }
+        Ok(())
+    }
+
+    fn visit_array_repeat_expression(&mut self, id: NodeId, value: &ast::Expression, count: &ast::Expression) -> Result<(), Self::Error> {
+        let count = array_length(self.session, count)?.expect("Constants should have been evaluated by type declaration pass.");
+        match self.expected.take() {
+            Some(RamenType::Array(element_type, _) | RamenType::Slice(element_type)) => self.check_expression(value, *element_type)?,
+            _ => self.visit_expression(value)?
+        }
+
+        let element_type = self.session.get_type(value.id).expect("Repeated value type should have been resolved.");
+        self.session.set_type(id, RamenType::Array(Box::new(element_type), count));
+        Ok(())
+    }
+
+    fn visit_index_expression(&mut self, id: NodeId, base: &ast::Expression, index: &ast::Expression) -> Result<(), Self::Error> {
+        let element_type = self.check_indexable(base)?;
+        self.check_index(index)?;
+
+        self.session.set_type(id, element_type);
+        Ok(())
+    }
+
+    fn visit_slice_expression(&mut self, id: NodeId, base: &ast::Expression, start: Option<&ast::Expression>, end: Option<&ast::Expression>, _inclusive: bool) -> Result<(), Self::Error> {
+        let element_type = self.check_indexable(base)?;
+        for bound in [start, end].into_iter().flatten() {
+            self.check_index(bound)?;
+        }
+
+        self.session.set_type(id, RamenType::Slice(Box::new(element_type)));
+        Ok(())
+    }
+
+    fn visit_address_of_expression(&mut self, id: NodeId, value: &ast::Expression, mutable: bool) -> Result<(), Self::Error> {
+        self.expected = self.expected.take().and_then(|expected| expected.pointee().cloned());
+        self.visit_expression(value)?;
+        if mutable {
+            self.check_mutable_place(value, false)?;
+        }
+
+        let target = self.session.get_type(value.id).expect("Referenced expression type should have been resolved.");
+        self.session.set_type(id, RamenType::Reference { target: Box::new(target), mutable });
+        Ok(())
+    }
+
+    fn visit_deref_expression(&mut self, id: NodeId, value: &ast::Expression) -> Result<(), Self::Error> {
+        self.expected = None;
+        self.visit_expression(value)?;
+
+        let pointer_type = self.session.get_type(value.id).expect("Dereferenced expression type should have been resolved.");
+        let target = pointer_type.pointee().map(|target| self.session.complete_type(target)).ok_or_else(|| ResolutionError::NotDereferenceable {
+            found: pointer_type.clone(),
+            location: value.location.clone()
+        })?;
+        if let RamenType::Dyn { .. } = target {
+            return Err(ResolutionError::UnsizedTraitObject {
+                ty: target,
+                location: value.location.clone()
+            });
+        }
+
+        self.session.set_type(id, target);
+        Ok(())
+    }
+
+    fn visit_unary_expression(&mut self, id: NodeId, operator: ast::UnaryOperator, operand: &ast::Expression) -> Result<(), Self::Error> {
+        let expected = self.expected.take();
+        // Literal is negated before its range is checked, so that `-128` fits into `int8` even though `128` does not.
+        if let (ast::UnaryOperator::Negate, ast::ExpressionKind::Literal(literal @ ast::Literal::Integer(value))) = (operator, &operand.kind) {
+            self.expected = expected.filter(|expected| matches!(expected, RamenType::Integer(_)));
+            self.visit_literal_expression(operand.id, literal)?;
+            self.session.try_bind_type(id, operand.id);
+            return self.check_literal_range(operand, -(*value as i128));
+        }
+
+        let operand_type = match operator {
+            // Expected type of the result is the type of the operand, so `-1` can be of any numeric type.
+            ast::UnaryOperator::Negate => {
+                match expected {
+                    Some(expected @ (RamenType::Integer(_) | RamenType::Float(_))) => self.check_expression(operand, expected)?,
+                    _ => self.visit_expression(operand)?
+                }
+                let operand_type = self.session.get_type(operand.id).expect("Operand type should have been resolved.");
+                if !matches!(operand_type, RamenType::Integer(_) | RamenType::Float(_)) {
+                    return Err(ResolutionError::InvalidOperands {
+                        operator: operator.to_string(),
+                        ty: operand_type,
+                        location: operand.location.clone()
+                    });
+                }
+                operand_type
+            }
+            ast::UnaryOperator::Not => {
+                self.check_expression(operand, RamenType::Bool)?;
+                RamenType::Bool
+            }
+        };
+
+        self.session.set_type(id, operand_type);
+        Ok(())
+    }
+
+    fn visit_binary_expression(&mut self, id: NodeId, operator: ast::BinaryOperator, left: &ast::Expression, right: &ast::Expression) -> Result<(), Self::Error> {
+        let expected = self.expected.take();
+        if operator.is_logical() {
+            self.check_expression(left, RamenType::Bool)?;
+            self.check_expression(right, RamenType::Bool)?;
+            self.session.set_type(id, RamenType::Bool);
+            return Ok(());
+        }
+
+        // Left operand decides type of the right one, result of arithmetic decides type of the left one.
+        match expected {
+            Some(expected @ (RamenType::Integer(_) | RamenType::Float(_))) if operator.is_arithmetic() => self.check_expression(left, expected)?,
+            _ => self.visit_expression(left)?
+        }
+        let operand_type = self.session.get_type(left.id).expect("Operand type should have been resolved.");
+        self.check_expression(right, operand_type.clone())?;
+
+        let is_valid = if operator.is_arithmetic() || operator.is_ordering() {
+            matches!(operand_type, RamenType::Integer(_) | RamenType::Float(_))
+        } else {
+            matches!(operand_type,
+                RamenType::Bool | RamenType::Integer(_) | RamenType::Float(_) |
+                RamenType::Reference { .. } | RamenType::Pointer { .. }
+            )
+        };
+        if !is_valid {
+            return Err(ResolutionError::InvalidOperands {
+                operator: operator.to_string(),
+                ty: operand_type,
+                location: left.location.clone()
+            });
+        }
+
+        self.session.set_type(id, if operator.is_arithmetic() { operand_type } else { RamenType::Bool });
+        Ok(())
+    }
+
+    fn visit_call_expression(&mut self, id: NodeId, callee: &ast::Expression, arguments: &[ast::Expression]) -> Result<(), Self::Error> {
+        let expected = self.expected.take();
+        if let Some((receiver, name)) = self.method_receiver(callee) {
+            let receiver_type = self.check_receiver_type(&receiver)?;
+            if let Some(method) = self.find_method(&receiver_type, name, &callee.location)? {
+                return self.check_method_call(id, callee, (receiver, receiver_type, name), method, arguments, expected);
+            }
+
+            // Without such method, the callee has to be a field.
+            let field_type = field_type(self.session, &receiver_type, name, &callee.location).map_err(|_| ResolutionError::NoMethod {
+                ty: receiver_type,
+                name: name.to_string(),
+                location: callee.location.clone()
+            })?;
+            self.session.set_type(callee.id, field_type);
+        } else if let Some((segment, def_id, generic)) = self.generic_callee(callee) {
+            let instance = self.check_generic_call(callee, (segment, def_id, &generic), arguments, expected)?;
+            self.session.set_type(id, instance.return_type.clone());
+            self.session.set_type(callee.id, RamenType::Callable(Box::new(instance)));
+            return Ok(());
+        } else {
+            self.visit_expression(callee)?;
+        }
+
+        let callee_type = self.session.get_type(callee.id).expect("Callee type should have been resolved.");
+        let RamenType::Callable(callable) = callee_type else {
+            return Err(ResolutionError::NotCallable {
+                found: callee_type,
+                location: callee.location.clone()
+            });
+        };
+        Self::check_argument_count(&callable, callee, arguments)?;
+
+        for (index, argument) in arguments.iter().enumerate() {
+            match callable.parameter_types.get(index) {
+                Some(parameter_type) => self.check_expression(argument, parameter_type.clone())?,
+                // Variable arguments are passed to C, which only understands some types.
+                None => {
+                    self.expected = None;
+                    self.visit_expression(argument)?;
+                    let argument_type = self.session.get_type(argument.id).expect("Argument type should have been resolved.");
+                    if !argument_type.is_ffi_safe() {
+                        return Err(ResolutionError::NotFfiSafe {
+                            ty: argument_type,
+                            location: argument.location.clone()
+                        });
+                    }
+                }
+            }
+        }
+
+        self.session.set_type(id, callable.return_type);
+        Ok(())
+    }
+
+    fn visit_match_expression(&mut self, id: NodeId, scrutinee: &ast::Expression, arms: &[ast::MatchArm]) -> Result<(), Self::Error> {
+        // Without expected type the first arm decides type of the whole match.
+        let mut result_type = self.expected.take();
+        self.visit_expression(scrutinee)?;
+        let scrutinee_type = self.session.get_type(scrutinee.id).expect("Scrutinee type should have been resolved.");
+        let borrows_frame = self.borrows_frame(scrutinee);
+
+        for arm in arms.iter() {
+            self.check_pattern(&arm.pattern, &scrutinee_type)?;
+            if borrows_frame {
+                self.mark_frame_bindings(&arm.pattern);
+            }
+            match &result_type {
+                Some(result_type) => self.check_expression(&arm.body, result_type.clone())?,
+                None => {
+                    self.visit_expression(&arm.body)?;
+                    result_type = self.session.get_type(arm.body.id);
+                }
+            }
+        }
+
+        self.session.set_type(id, result_type.unwrap_or(RamenType::Unit));
+        Ok(())
+    }
+
+    fn visit_closure_expression(&mut self, id: NodeId, closure: &ast::Closure) -> Result<(), Self::Error> {
+        // Types left out of the closure are taken from the function type it is expected to have.
+        let expected = match self.expected.take() {
+            Some(RamenType::Callable(callable)) if callable.parameter_types.len() == closure.parameters.len() => Some(callable),
+            _ => None
+        };
+
+        let mut parameter_types = Vec::new();
+        for (index, parameter) in closure.parameters.iter().enumerate() {
+            let parameter_type = match &parameter.ty {
+                Some(ty) => {
+                    self.visit_type(ty)?;
+                    self.session.get_type(ty.id).expect("Parameter type should have been resolved.")
+                }
+                None => expected.as_ref()
+                    .map(|expected| expected.parameter_types[index].clone())
+                    .ok_or_else(|| ResolutionError::TypeAnnotationsNeeded { location: parameter.location.clone() })?
+            };
+            self.check_pattern(&parameter.pattern, &parameter_type)?;
+            self.session.set_type(parameter.id, parameter_type.clone());
+            parameter_types.push(parameter_type);
+        }
+
+        let return_type = match expected {
+            Some(expected) => {
+                self.check_expression(&closure.body, expected.return_type.clone())?;
+                expected.return_type
+            }
+            None => {
+                self.visit_expression(&closure.body)?;
+                self.session.get_type(closure.body.id).expect("Closure body type should have been resolved.")
+            }
+        };
+
+        self.session.set_type(id, RamenType::Callable(Box::new(CallableType::new(return_type, parameter_types))));
+        Ok(())
+    }
+
+    fn visit_type(&mut self, ty: &ast::Type) -> Result<(), Self::Error> {
+        resolve_type(self.session, ty)?.expect("Declared types should have been resolved by type declaration pass.");
+        self.check_trait_objects(ty, false)
+    }
+}
@@ -1,4 +1,6 @@
-use ramen_common::error::Diagnostic;
+use std::path::PathBuf;
+
+use ramen_common::{error::Diagnostic, session::SourceId, Loc};
 
 use crate::lex::{Token, TokenInfo};
 
@@ -18,6 +20,21 @@ pub enum SyntaxError {
     },
     ExpectedType {
         found: TokenInfo
+    },
+    ExpectedPattern {
+        found: TokenInfo
+    },
+    /// Anything but `module` in `pub(...)`.
+    InvalidVisibility {
+        found: TokenInfo
+    },
+    /// Integer type wider than 128 bits or without any bits, like `int256` or `int0`.
+    InvalidIntegerWidth {
+        found: TokenInfo
+    },
+    /// Integer literal which does not fit into 64 bits, or 128 bits in patterns.
+    IntegerLiteralTooLarge {
+        found: TokenInfo
     }
 }
 
@@ -30,6 +47,10 @@ impl Diagnostic for SyntaxError {
             Self::ExpectedItem { found } => found.location(),
             Self::ExpectedExpression { found } => found.location(),
             Self::ExpectedType { found } => found.location(),
+            Self::ExpectedPattern { found } => found.location(),
+            Self::InvalidVisibility { found } => found.location(),
+            Self::InvalidIntegerWidth { found } => found.location(),
+            Self::IntegerLiteralTooLarge { found } => found.location(),
         }
     }
 
@@ -56,7 +77,7 @@ impl Diagnostic for SyntaxError {
                 report.with_code("S02").with_message("Expected top-level item.")
                 .with_label(
                     Label::new(loc)
-                    .with_message(format!("Expected top-level item but got this."))
+                    .with_message("Expected top-level item but got this.")
                     .with_priority(4)
                 )
             }
@@ -64,7 +85,7 @@ impl Diagnostic for SyntaxError {
                 report.with_code("S03").with_message("Expected expression.")
                 .with_label(
                     Label::new(loc)
-                    .with_message(format!("Expected expression but got this."))
+                    .with_message("Expected expression but got this.")
                     .with_priority(4)
                 )
             }
@@ -72,7 +93,39 @@ impl Diagnostic for SyntaxError {
                 report.with_code("S04").with_message("Expected type literal.")
                 .with_label(
                     Label::new(loc)
-                    .with_message(format!("Expected type literal like int32 or unit, but found this."))
+                    .with_message("Expected type literal like int32 or unit, but found this.")
+                    .with_priority(4)
+                )
+            }
+            Self::ExpectedPattern { .. } => {
+                report.with_code("S05").with_message("Expected pattern.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Expected pattern like `_`, `1..=5` or `Shape.Circle(r)`, but found this.")
+                    .with_priority(4)
+                )
+            }
+            Self::InvalidVisibility { .. } => {
+                report.with_code("S06").with_message("Invalid visibility.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Only `pub` and `pub(module)` are allowed.")
+                    .with_priority(4)
+                )
+            }
+            Self::InvalidIntegerWidth { found } => {
+                report.with_code("S07").with_message(format!("Invalid integer type `{}`.", found.text()))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Integers can have from 1 to 128 bits.")
+                    .with_priority(4)
+                )
+            }
+            Self::IntegerLiteralTooLarge { .. } => {
+                report.with_code("S08").with_message("Integer literal is too large.")
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Integer literals have to fit into 64 bits, use arithmetic for wider constants.")
                     .with_priority(4)
                 )
             }
@@ -80,4 +133,80 @@ impl Diagnostic for SyntaxError {
 
         report.finish()
     }
-}
\ No newline at end of file
+}
+
+/// Error originating from loading files of module tree.
+#[derive(Debug, Clone)]
+pub enum LoadError {
+    MissingModuleFile {
+        name: String,
+        candidates: Vec<PathBuf>,
+        location: Loc
+    },
+    ModuleCycle {
+        name: String,
+        path: PathBuf,
+        location: Loc
+    },
+    UnreadableFile {
+        path: PathBuf,
+        reason: String,
+        location: Option<Loc>
+    },
+    Syntax(SyntaxError)
+}
+
+impl Diagnostic for LoadError {
+    fn is_fatal(&self) -> bool { true }
+
+    fn get_location(&self) -> Loc {
+        match self {
+            Self::MissingModuleFile { location, .. } => location.clone(),
+            Self::ModuleCycle { location, .. } => location.clone(),
+            Self::UnreadableFile { location, .. } => location.clone().unwrap_or_else(|| Loc::new(SourceId::dummy(), 0..0)),
+            Self::Syntax(error) => error.get_location()
+        }
+    }
+
+    fn build_report(&self, session: &ramen_common::session::Session) -> ramen_common::ariadne::Report<'static, Loc> {
+        use ramen_common::ariadne::{Report, ReportKind, Config, Label};
+
+        let loc = self.get_location();
+        let report = Report::build(
+            ReportKind::Error,
+            loc.file,
+            loc.span.start
+        ).with_config(Config::default().with_cross_gap(true));
+
+        let report = match self {
+            Self::MissingModuleFile { name, candidates, .. } => {
+                let candidates = candidates.iter().map(|path| format!("`{}`", path.display())).collect::<Vec<_>>().join(" or ");
+                report.with_code("L01").with_message(format!("Cannot find file of module `{name}`."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("Expected module to be in {candidates}."))
+                    .with_priority(4)
+                )
+            }
+            Self::ModuleCycle { name, path, .. } => {
+                report.with_code("L02").with_message(format!("Module `{name}` includes itself."))
+                .with_label(
+                    Label::new(loc)
+                    .with_message(format!("File `{}` is already being loaded as one of the parent modules.", path.display()))
+                    .with_priority(4)
+                )
+            }
+            Self::UnreadableFile { path, reason, .. } => {
+                report.with_code("L03").with_message(format!("Cannot read file `{}`: {reason}.", path.display()))
+                .with_label(
+                    Label::new(loc)
+                    .with_message("Module is declared here.")
+                    .with_priority(4)
+                )
+            }
+            Self::Syntax(error) => return error.build_report(session)
+        };
+
+        report.finish()
+    }
+}
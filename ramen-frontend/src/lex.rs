@@ -1,5 +1,5 @@
 use logos::{Lexer, Logos};
-use ramen_common::{Loc, session::SourceId};
+use ramen_common::{ast::{NodeId, NodeIds}, Loc, session::SourceId};
 
 use crate::error::SyntaxError;
 
@@ -51,18 +51,43 @@ pub enum Token {
     #[token("<=")] LessEqual,
     #[token(">=")] GreaterEqual,
     #[token("!=")] NotEqual,
+    #[token("!")] Bang,
     #[token("==")] EqualEqual,
 
     // ==< Keywords >==
+    #[token("mod")] ModKW,
     #[token("func")] FuncKW,
+    #[token("enum")] EnumKW,
+    #[token("struct")] StructKW,
+    #[token("trait")] TraitKW,
+    #[token("impl")] ImplKW,
+    #[token("for")] ForKW,
+    #[token("dyn")] DynKW,
+    #[token("match")] MatchKW,
+    #[token("let")] LetKW,
+    #[token("return")] ReturnKW,
+    #[token("mut")] MutKW,
+    #[token("move")] MoveKW,
+    #[token("use")] UseKW,
+    #[token("as")] AsKW,
+    #[token("const")] ConstKW,
+    #[token("static")] StaticKW,
+    #[token("type")] TypeKW,
+    #[token("newtype")] NewtypeKW,
 
     // ==< Type literals >==
     #[regex(r"int[0-9]+")] IntegerType,
+    #[regex(r"float(32|64)")] FloatType,
+    #[token("bool")] BoolType,
 
     // ==< Value literals >==
     #[regex(r"[0-9][0-9_]*")] IntegerLiteral,
+    #[regex(r"[0-9][0-9_]*\.[0-9][0-9_]*")] FloatLiteral,
+    #[token("true")] #[token("false")] BoolLiteral,
+    #[regex(r#""([^"\\\n]|\\.)*""#)] StringLiteral,
 
     // ==< Modifiers >==
+    #[token("pub")] PubKW,
 
     // ==< Other >==
     #[token("\n")] NL,
@@ -87,26 +112,34 @@ pub struct Tokens<'src> {
     stack: Vec<TokenInfo>,
     current: usize,
     pub(crate) source: SourceId,
+    /// Allocator of ids for parsed nodes, usually the one of the session.
+    node_ids: &'src NodeIds,
 
     span_stack: Vec<usize>
 }
 
 impl<'src> Tokens<'src> {
-    pub fn from_lexer(iter: Lexer<'src, Token>, source: SourceId) -> Self {
+    pub fn from_lexer(iter: Lexer<'src, Token>, source: SourceId, node_ids: &'src NodeIds) -> Self {
         Self {
             iter,
             stack: Vec::new(),
             current: 0,
             source,
+            node_ids,
 
             span_stack: Vec::new()
         }
     }
     
-    pub fn from_string(text: &'src str, source: SourceId) -> Self {
-        Self::from_lexer(Token::lexer(text), source)
+    pub fn from_string(text: &'src str, source: SourceId, node_ids: &'src NodeIds) -> Self {
+        Self::from_lexer(Token::lexer(text), source, node_ids)
     }
 
+    pub(crate) fn node_id(&self) -> NodeId {
+        self.node_ids.next()
+    }
+
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Option<Token> {
         if self.current == self.stack.len() {
             let Some(Ok(next_elem)) = self.iter.next() else { return None; };
@@ -169,7 +202,7 @@ impl<'src> Tokens<'src> {
     pub fn is(&mut self, token: Token) -> bool {
         let next = self.next();
         if next == Some(token) { true } 
-        else if next == None { false }
+        else if next.is_none() { false }
         else { self.current -= 1; false }
     }
 
@@ -194,6 +227,16 @@ impl<'src> Tokens<'src> {
         Some(self.stack[self.current - 1].1.as_str())
     }
 
+    /// Position to come back to when speculatively parsed tokens turn out to mean something else.
+    pub fn checkpoint(&self) -> (usize, usize) {
+        (self.current, self.span_stack.len())
+    }
+
+    pub fn rewind(&mut self, (current, spans): (usize, usize)) {
+        self.current = current;
+        self.span_stack.truncate(spans);
+    }
+
     // ==< Parser utilities >==
     pub fn expect(&mut self, token: Token) -> Result<TokenInfo, SyntaxError> {
         if self.next() != Some(token) { Err(SyntaxError::UnexpectedToken { 
@@ -213,4 +256,10 @@ impl<'src> Tokens<'src> {
             self.span_stack.pop().unwrap()..self.loc().unwrap().span.end
         )
     }
+
+    /// Span from given offset up to the end of current token,
+    /// useful for left-recursive constructs like calls.
+    pub fn span_from(&self, start: usize) -> Loc {
+        Loc::new(self.source, start..self.loc().unwrap().span.end)
+    }
 }
\ No newline at end of file
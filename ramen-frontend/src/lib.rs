@@ -1,4 +1,5 @@
 pub mod lex;
 pub mod parse;
+pub mod load;
 pub mod error;
 pub mod ast_pass;
\ No newline at end of file
@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+
+use ramen_common::{ast, session::Session, Loc};
+
+use crate::{error::LoadError, lex::Tokens, parse::parse_ramen};
+
+/// Loads module tree rooted at file `path`, every `mod name` without body is read from
+/// `name.rm` or `name/mod.rm` next to the file declaring it, like in Rust.
+/// Loaded files are registered as sources of the session.
+pub fn load_module_tree(session: &Session, name: String, path: &Path) -> Result<ast::Module, LoadError> {
+    let mut loader = ModuleLoader {
+        session,
+        stack: Vec::new()
+    };
+    loader.load_file(name, path, None)
+}
+
+struct ModuleLoader<'sess> {
+    session: &'sess Session,
+    /// Canonical paths of files currently being loaded, used to detect modules including themselves.
+    stack: Vec<PathBuf>
+}
+
+impl<'sess> ModuleLoader<'sess> {
+    fn load_file(&mut self, name: String, path: &Path, location: Option<&Loc>) -> Result<ast::Module, LoadError> {
+        let unreadable = |error: std::io::Error| LoadError::UnreadableFile {
+            path: path.to_path_buf(),
+            reason: error.to_string(),
+            location: location.cloned()
+        };
+        let canonical = path.canonicalize().map_err(unreadable)?;
+        if self.stack.contains(&canonical) {
+            return Err(LoadError::ModuleCycle {
+                name,
+                path: path.to_path_buf(),
+                location: location.cloned().expect("Root module cannot be part of a cycle.")
+            });
+        }
+        let text = std::fs::read_to_string(path).map_err(unreadable)?;
+
+        let source = self.session.add_source(path.to_path_buf(), text.clone());
+        let mut tokens = Tokens::from_string(&text, source, &self.session.node_ids);
+        let mut module = parse_ramen(name, &mut tokens).map_err(LoadError::Syntax)?;
+
+        // Only the root file and `mod.rm` files own their directory, children of `name.rm` live in `name/`.
+        let directory = path.parent().unwrap_or(Path::new("."));
+        let directory = match path.file_stem() {
+            Some(stem) if location.is_some() && stem != "mod" => directory.join(stem),
+            _ => directory.to_path_buf()
+        };
+
+        self.stack.push(canonical);
+        let result = self.load_items(&mut module.items, &directory);
+        self.stack.pop();
+        result.map(|_| module)
+    }
+
+    fn load_items(&mut self, items: &mut [ast::Item], directory: &Path) -> Result<(), LoadError> {
+        for item in items {
+            let ast::ItemKind::Module(module) = &mut item.kind else { continue };
+            if !module.is_file_module {
+                self.load_items(&mut module.items, &directory.join(&module.name))?;
+                continue;
+            }
+
+            let candidates = vec![
+                directory.join(format!("{}.rm", module.name)),
+                directory.join(&module.name).join("mod.rm")
+            ];
+            let Some(path) = candidates.iter().find(|path| path.is_file()) else {
+                return Err(LoadError::MissingModuleFile {
+                    name: module.name.clone(),
+                    candidates,
+                    location: item.location.clone()
+                });
+            };
+
+            module.items = self.load_file(module.name.clone(), path, Some(&item.location))?.items;
+        }
+        Ok(())
+    }
+}
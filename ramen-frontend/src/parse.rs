@@ -1,23 +1,20 @@
-use ramen_common::ast::{self, Attribute, Attributes, NodeId};
-use crate::{error::SyntaxError, lex::{Token, Tokens}};
+use ramen_common::ast::{self, Attribute, Attributes};
+use crate::{error::SyntaxError, lex::{Token, TokenInfo, Tokens}};
 
 pub fn parse_ramen(module_name: String, tokens: &mut Tokens) -> Result<ast::Module, SyntaxError> {
     Ok(ast::Module {
         name: module_name,
-        items: parse_item_stream(tokens)?
+        items: parse_item_stream(tokens)?,
+        is_file_module: false
     })
 }
 
 fn parse_item_stream(tokens: &mut Tokens) -> Result<Vec<ast::Item>, SyntaxError> {
     let mut items = Vec::<ast::Item>::new();
-    loop {
-        match tokens.peek() {
-            Some(_) => {
-                items.push(parse_item(tokens)?);
-                new_lines(tokens);
-            }
-            None => break,
-        }
+    new_lines(tokens);
+    while tokens.peek().is_some() {
+        items.push(parse_item(tokens)?);
+        new_lines(tokens);
     }
     Ok(items)
 }
@@ -26,8 +23,25 @@ fn parse_item(tokens: &mut Tokens) -> Result<ast::Item, SyntaxError> {
     let attributes = parse_attributes(tokens, false)?;
 
     tokens.begin_span();
+    let visibility = parse_visibility(tokens)?;
     let kind = match tokens.peek() {
+        Some(Token::ModKW) => ast::ItemKind::Module(parse_module_definition(tokens)?),
         Some(Token::FuncKW) => ast::ItemKind::Function(parse_function_definition(tokens)?),
+        Some(Token::EnumKW) => ast::ItemKind::Enum(parse_enum_definition(tokens)?),
+        Some(Token::StructKW) => ast::ItemKind::Struct(parse_struct_definition(tokens)?),
+        Some(Token::TraitKW) => ast::ItemKind::Trait(parse_trait_definition(tokens)?),
+        Some(Token::ImplKW) => ast::ItemKind::Impl(parse_impl(tokens)?),
+        Some(Token::UseKW) => ast::ItemKind::Use(parse_use(tokens)?),
+        Some(Token::ConstKW) => {
+            tokens.next();
+            let is_function = tokens.peek() == Some(Token::FuncKW);
+            tokens.back();
+            if is_function { ast::ItemKind::Function(parse_function_definition(tokens)?) }
+            else { ast::ItemKind::Const(parse_const(tokens)?) }
+        }
+        Some(Token::StaticKW) => ast::ItemKind::Static(parse_static(tokens)?),
+        Some(Token::TypeKW) => ast::ItemKind::TypeAlias(parse_type_alias(tokens)?),
+        Some(Token::NewtypeKW) => ast::ItemKind::Struct(parse_newtype(tokens)?),
 
         _ => return Err(SyntaxError::ExpectedItem { found: tokens.next_info().unwrap() }),
     };
@@ -35,27 +49,383 @@ fn parse_item(tokens: &mut Tokens) -> Result<ast::Item, SyntaxError> {
     Ok(ast::Item {
         location: tokens.end_span(),
         attributes,
+        visibility,
         kind,
-        id: NodeId::next()
+        id: tokens.node_id()
     }) 
 }
 
+fn parse_module_definition(tokens: &mut Tokens) -> Result<ast::Module, SyntaxError> {
+    tokens.expect(Token::ModKW)?;
+
+    let name = tokens.expect(Token::Identifier)?.text();
+    // Items of module without body are filled in once its file is loaded.
+    if !tokens.is(Token::LeftCurly) {
+        return Ok(ast::Module {
+            name,
+            items: Vec::new(),
+            is_file_module: true
+        });
+    }
+    semis(tokens);
+
+    let mut items = Vec::<ast::Item>::new();
+    while !tokens.is(Token::RightCurly) {
+        items.push(parse_item(tokens)?);
+        semis(tokens);
+    }
+
+    Ok(ast::Module {
+        name,
+        items,
+        is_file_module: false
+    })
+}
+
+/// Parses `pub` or `pub(module)`, items and fields without them are private.
+fn parse_visibility(tokens: &mut Tokens) -> Result<ast::Visibility, SyntaxError> {
+    if !tokens.is(Token::PubKW) {
+        return Ok(ast::Visibility::Private);
+    }
+    if !tokens.is(Token::LeftParen) {
+        return Ok(ast::Visibility::Public);
+    }
+
+    let scope = tokens.expect(Token::Identifier)?;
+    if scope.text() != "module" {
+        return Err(SyntaxError::InvalidVisibility { found: scope });
+    }
+    tokens.expect(Token::RightParen)?;
+    Ok(ast::Visibility::Module)
+}
+
+fn parse_use(tokens: &mut Tokens) -> Result<ast::Use, SyntaxError> {
+    tokens.expect(Token::UseKW)?;
+
+    Ok(ast::Use {
+        tree: parse_use_tree(tokens)?
+    })
+}
+
+fn parse_use_tree(tokens: &mut Tokens) -> Result<ast::UseTree, SyntaxError> {
+    tokens.begin_span();
+
+    // Tree always starts with a name, `*` and `{` can only follow a dot.
+    let mut prefix = Vec::<ast::PathSegment>::new();
+    let kind = loop {
+        let name = tokens.expect(Token::Identifier)?;
+        prefix.push(ast::PathSegment {
+            location: name.location(),
+            name: name.text(),
+            type_arguments: Vec::new()
+        });
+
+        if !tokens.is(Token::Dot) {
+            let alias = if tokens.is(Token::AsKW) { Some(tokens.expect(Token::Identifier)?.text()) }
+                else { None };
+            break ast::UseTreeKind::Simple(alias);
+        }
+        if tokens.is(Token::Star) {
+            break ast::UseTreeKind::Glob;
+        }
+        if tokens.is(Token::LeftCurly) {
+            let mut trees = Vec::<ast::UseTree>::new();
+            new_lines(tokens);
+            while !tokens.is(Token::RightCurly) {
+                trees.push(parse_use_tree(tokens)?);
+                new_lines(tokens);
+                if !tokens.is(Token::Comma) {
+                    new_lines(tokens);
+                    tokens.expect(Token::RightCurly)?;
+                    break;
+                }
+                new_lines(tokens);
+            }
+            break ast::UseTreeKind::Nested(trees);
+        }
+    };
+
+    Ok(ast::UseTree {
+        location: tokens.end_span(),
+        prefix,
+        kind,
+        id: tokens.node_id()
+    })
+}
+
+fn parse_const(tokens: &mut Tokens) -> Result<ast::Const, SyntaxError> {
+    tokens.expect(Token::ConstKW)?;
+    let (name, ty, value) = parse_global(tokens)?;
+    Ok(ast::Const { name, ty, value })
+}
+
+fn parse_static(tokens: &mut Tokens) -> Result<ast::Static, SyntaxError> {
+    tokens.expect(Token::StaticKW)?;
+    let mutable = tokens.is(Token::MutKW);
+    let (name, ty, value) = parse_global(tokens)?;
+    Ok(ast::Static { name, ty, value, mutable })
+}
+
+fn parse_type_alias(tokens: &mut Tokens) -> Result<ast::TypeAlias, SyntaxError> {
+    tokens.expect(Token::TypeKW)?;
+    let name = tokens.expect(Token::Identifier)?.text();
+    tokens.expect(Token::Assign)?;
+    new_lines(tokens);
+    Ok(ast::TypeAlias { name, ty: parse_type(tokens)? })
+}
+
+/// Newtype is a struct whose only field is the wrapped value.
+fn parse_newtype(tokens: &mut Tokens) -> Result<ast::Struct, SyntaxError> {
+    tokens.expect(Token::NewtypeKW)?;
+    let name = tokens.expect(Token::Identifier)?.text();
+    let generics = if tokens.peek() == Some(Token::LeftAngle) { parse_generic_parameter_list(tokens)? }
+        else { Vec::new() };
+    tokens.expect(Token::Assign)?;
+    new_lines(tokens);
+
+    let ty = parse_type(tokens)?;
+    Ok(ast::Struct {
+        name,
+        generics,
+        fields: vec![ast::Field {
+            location: ty.location.clone(),
+            visibility: ast::Visibility::Public,
+            name: "0".to_string(),
+            ty,
+            id: tokens.node_id()
+        }]
+    })
+}
+
+/// Name, type and value of constant or static, the type cannot be inferred from the value.
+fn parse_global(tokens: &mut Tokens) -> Result<(String, ast::Type, ast::Expression), SyntaxError> {
+    let name = tokens.expect(Token::Identifier)?.text();
+    tokens.expect(Token::Colon)?;
+    let ty = parse_type(tokens)?;
+    tokens.expect(Token::Assign)?;
+    new_lines(tokens);
+    Ok((name, ty, parse_expression(tokens)?))
+}
+
 fn parse_function_definition(tokens: &mut Tokens) -> Result<ast::Function, SyntaxError> {
+    let mut function = parse_function_signature(tokens)?;
+    // Declarations of external functions have no body, which is checked once their attributes are known.
+    if matches!(tokens.peek(), Some(Token::LeftCurly | Token::FatArrow)) {
+        function.body = Some(parse_block_or_expression_shorthand(tokens)?);
+    }
+    Ok(function)
+}
+
+/// Parses function up to its body, which is left empty.
+fn parse_function_signature(tokens: &mut Tokens) -> Result<ast::Function, SyntaxError> {
+    let is_const = tokens.is(Token::ConstKW);
     tokens.expect(Token::FuncKW)?;
 
     let name = tokens.expect(Token::Identifier)?.text();
-    let parameters = parse_enclosed_value_parameter_list(tokens)?;
+    let generics = if tokens.peek() == Some(Token::LeftAngle) { parse_generic_parameter_list(tokens)? }
+        else { Vec::new() };
+    let (parameters, has_receiver, is_vararg) = parse_enclosed_value_parameter_list(tokens)?;
 
     let return_type = if tokens.is(Token::Colon) { Some(parse_type(tokens)?) }
     else { None };
 
-    let body = parse_block_or_expression_shorthand(tokens)?;
-
     Ok(ast::Function {
         name,
+        generics,
         parameters,
         return_type,
-        body
+        has_receiver,
+        is_vararg,
+        body: None,
+        is_const
+    })
+}
+
+fn parse_trait_definition(tokens: &mut Tokens) -> Result<ast::Trait, SyntaxError> {
+    tokens.expect(Token::TraitKW)?;
+
+    let name = tokens.expect(Token::Identifier)?.text();
+    tokens.expect(Token::LeftCurly)?;
+    semis(tokens);
+
+    // Traits only contain method signatures, which are as visible as the trait itself.
+    let mut items = Vec::<ast::Item>::new();
+    while !tokens.is(Token::RightCurly) {
+        let attributes = parse_attributes(tokens, false)?;
+        tokens.begin_span();
+        let kind = ast::ItemKind::Function(parse_function_signature(tokens)?);
+        items.push(ast::Item {
+            location: tokens.end_span(),
+            attributes,
+            visibility: ast::Visibility::Public,
+            kind,
+            id: tokens.node_id()
+        });
+        semis(tokens);
+    }
+
+    Ok(ast::Trait {
+        name,
+        items
+    })
+}
+
+fn parse_impl(tokens: &mut Tokens) -> Result<ast::Impl, SyntaxError> {
+    tokens.expect(Token::ImplKW)?;
+
+    // Trait is only known to be there once `for` follows it.
+    let ty = parse_type(tokens)?;
+    let (trait_, self_type) = if tokens.is(Token::ForKW) {
+        let ast::TypeKind::Path(path) = ty.kind else {
+            return Err(SyntaxError::UnexpectedToken {
+                expected: vec![Token::LeftCurly],
+                found: tokens.current_info().unwrap()
+            });
+        };
+        let trait_ = ast::TraitRef {
+            location: ty.location,
+            path,
+            id: tokens.node_id()
+        };
+        (Some(trait_), parse_type(tokens)?)
+    } else { (None, ty) };
+    tokens.expect(Token::LeftCurly)?;
+    semis(tokens);
+
+    let mut items = Vec::<ast::Item>::new();
+    while !tokens.is(Token::RightCurly) {
+        items.push(parse_item(tokens)?);
+        semis(tokens);
+    }
+
+    Ok(ast::Impl {
+        trait_,
+        self_type,
+        items
+    })
+}
+
+fn parse_trait_ref(tokens: &mut Tokens) -> Result<ast::TraitRef, SyntaxError> {
+    tokens.begin_span();
+    let path = parse_path(tokens)?;
+    Ok(ast::TraitRef {
+        location: tokens.end_span(),
+        path,
+        id: tokens.node_id()
+    })
+}
+
+fn parse_generic_parameter_list(tokens: &mut Tokens) -> Result<Vec<ast::GenericParameter>, SyntaxError> {
+    let mut generics = Vec::<ast::GenericParameter>::new();
+    tokens.expect(Token::LeftAngle)?;
+
+    while !tokens.is(Token::RightAngle) {
+        let name = tokens.expect(Token::Identifier)?;
+
+        let mut bounds = Vec::<ast::TraitRef>::new();
+        if tokens.is(Token::Colon) {
+            bounds.push(parse_trait_ref(tokens)?);
+            while tokens.is(Token::Plus) {
+                bounds.push(parse_trait_ref(tokens)?);
+            }
+        }
+
+        generics.push(ast::GenericParameter {
+            location: name.location(),
+            name: name.text(),
+            bounds,
+            id: tokens.node_id()
+        });
+        if !tokens.is(Token::Comma) {
+            tokens.expect(Token::RightAngle)?;
+            break;
+        }
+    }
+
+    Ok(generics)
+}
+
+fn parse_enum_definition(tokens: &mut Tokens) -> Result<ast::Enum, SyntaxError> {
+    tokens.expect(Token::EnumKW)?;
+
+    let name = tokens.expect(Token::Identifier)?.text();
+    let generics = if tokens.peek() == Some(Token::LeftAngle) { parse_generic_parameter_list(tokens)? }
+        else { Vec::new() };
+    tokens.expect(Token::LeftCurly)?;
+    new_lines(tokens);
+
+    let mut variants = Vec::<ast::Variant>::new();
+    while !tokens.is(Token::RightCurly) {
+        variants.push(parse_variant(tokens)?);
+        tokens.is(Token::Comma);
+        new_lines(tokens);
+    }
+
+    Ok(ast::Enum {
+        name,
+        generics,
+        variants
+    })
+}
+
+fn parse_struct_definition(tokens: &mut Tokens) -> Result<ast::Struct, SyntaxError> {
+    tokens.expect(Token::StructKW)?;
+
+    let name = tokens.expect(Token::Identifier)?.text();
+    let generics = if tokens.peek() == Some(Token::LeftAngle) { parse_generic_parameter_list(tokens)? }
+        else { Vec::new() };
+    tokens.expect(Token::LeftCurly)?;
+    new_lines(tokens);
+
+    let mut fields = Vec::<ast::Field>::new();
+    while !tokens.is(Token::RightCurly) {
+        tokens.begin_span();
+        let visibility = parse_visibility(tokens)?;
+        let name = tokens.expect(Token::Identifier)?.text();
+        tokens.expect(Token::Colon)?;
+        let ty = parse_type(tokens)?;
+        fields.push(ast::Field {
+            location: tokens.end_span(),
+            visibility,
+            name,
+            ty,
+            id: tokens.node_id()
+        });
+
+        tokens.is(Token::Comma);
+        new_lines(tokens);
+    }
+
+    Ok(ast::Struct {
+        name,
+        generics,
+        fields
+    })
+}
+
+fn parse_variant(tokens: &mut Tokens) -> Result<ast::Variant, SyntaxError> {
+    tokens.begin_span();
+    let name = tokens.expect(Token::Identifier)?.text();
+
+    let mut fields = Vec::<ast::Type>::new();
+    if tokens.is(Token::LeftParen) {
+        new_lines(tokens);
+        while !tokens.is(Token::RightParen) {
+            fields.push(parse_type(tokens)?);
+            new_lines(tokens);
+            if !tokens.is(Token::Comma) {
+                tokens.expect(Token::RightParen)?;
+                break;
+            }
+            new_lines(tokens);
+        }
+    }
+
+    Ok(ast::Variant {
+        location: tokens.end_span(),
+        name,
+        fields,
+        id: tokens.node_id()
     })
 }
 
@@ -70,39 +440,539 @@ fn parse_block_or_expression_shorthand(tokens: &mut Tokens) -> Result<ast::Block
             statements: vec![ast::Statement {
                 location: expression.location.clone(),
                 kind: ast::StatementKind::Return(expression),
-                id: NodeId::next(),
+                id: tokens.node_id(),
             }],
-            id: NodeId::next(),
+            id: tokens.node_id(),
         })
-    } else { unimplemented!("Only expression syntax is supported currently") }
+    } else { parse_block(tokens) }
+}
+
+fn parse_block(tokens: &mut Tokens) -> Result<ast::Block, SyntaxError> {
+    tokens.begin_span();
+    tokens.expect(Token::LeftCurly)?;
+    semis(tokens);
+
+    let mut statements = Vec::<ast::Statement>::new();
+    while !tokens.is(Token::RightCurly) {
+        statements.push(parse_statement(tokens)?);
+        semis(tokens);
+    }
+
+    Ok(ast::Block {
+        location: tokens.end_span(),
+        statements,
+        id: tokens.node_id()
+    })
+}
+
+fn parse_statement(tokens: &mut Tokens) -> Result<ast::Statement, SyntaxError> {
+    tokens.begin_span();
+    let kind = match tokens.peek() {
+        Some(Token::FuncKW | Token::EnumKW | Token::StructKW | Token::TraitKW | Token::ImplKW | Token::UseKW |
+            Token::ConstKW | Token::StaticKW | Token::At) => ast::StatementKind::Item(parse_item(tokens)?),
+        Some(Token::LetKW) => {
+            tokens.next();
+            let pattern = parse_pattern(tokens)?;
+            let ty = if tokens.is(Token::Colon) { Some(parse_type(tokens)?) }
+                else { None };
+            tokens.expect(Token::Assign)?;
+            new_lines(tokens);
+            ast::StatementKind::Let { pattern, ty, value: parse_expression(tokens)? }
+        }
+        Some(Token::ReturnKW) => {
+            tokens.next();
+            let value = match tokens.peek() {
+                // Return without value gives back unit.
+                None | Some(Token::NL | Token::Semicolon | Token::RightCurly) => ast::Expression {
+                    location: tokens.loc().unwrap(),
+                    kind: ast::ExpressionKind::Tuple(Vec::new()),
+                    id: tokens.node_id()
+                },
+                _ => parse_expression(tokens)?
+            };
+            ast::StatementKind::Return(value)
+        }
+        _ => {
+            let expression = parse_expression(tokens)?;
+            if tokens.is(Token::Assign) {
+                new_lines(tokens);
+                ast::StatementKind::Assign { target: expression, value: parse_expression(tokens)? }
+            } else { ast::StatementKind::Expression(expression) }
+        }
+    };
+
+    Ok(ast::Statement {
+        location: tokens.end_span(),
+        kind,
+        id: tokens.node_id()
+    })
 }
 
+/// Binary operators from the weakest binding to the strongest, operators on the same level are left associative.
+const BINARY_OPERATORS: &[&[(Token, ast::BinaryOperator)]] = &[
+    &[(Token::Disjunction, ast::BinaryOperator::Or)],
+    &[(Token::Conjunction, ast::BinaryOperator::And)],
+    &[
+        (Token::EqualEqual, ast::BinaryOperator::Equal),
+        (Token::NotEqual, ast::BinaryOperator::NotEqual),
+        (Token::LeftAngle, ast::BinaryOperator::Less),
+        (Token::LessEqual, ast::BinaryOperator::LessEqual),
+        (Token::RightAngle, ast::BinaryOperator::Greater),
+        (Token::GreaterEqual, ast::BinaryOperator::GreaterEqual)
+    ],
+    &[(Token::Plus, ast::BinaryOperator::Add), (Token::Minus, ast::BinaryOperator::Subtract)],
+    &[
+        (Token::Star, ast::BinaryOperator::Multiply),
+        (Token::Slash, ast::BinaryOperator::Divide),
+        (Token::Percent, ast::BinaryOperator::Remainder)
+    ]
+];
+
 fn parse_expression(tokens: &mut Tokens) -> Result<ast::Expression, SyntaxError> {
-    parse_primary_expression(tokens)
+    parse_binary_expression(tokens, 0)
+}
+
+/// Parses operands of operators with given precedence level and the levels binding stronger.
+/// Operator has to be on the same line as its left operand, as new line ends the statement.
+fn parse_binary_expression(tokens: &mut Tokens, level: usize) -> Result<ast::Expression, SyntaxError> {
+    let Some(operators) = BINARY_OPERATORS.get(level) else { return parse_prefix_expression(tokens) };
+    let mut left = parse_binary_expression(tokens, level + 1)?;
+
+    while let Some(&(_, operator)) = tokens.peek().and_then(|next| operators.iter().find(|(token, _)| *token == next)) {
+        tokens.next();
+        new_lines(tokens);
+        let right = parse_binary_expression(tokens, level + 1)?;
+
+        left = ast::Expression {
+            location: tokens.span_from(left.location.span.start),
+            kind: ast::ExpressionKind::Binary(operator, Box::new(left), Box::new(right)),
+            id: tokens.node_id()
+        };
+    }
+
+    Ok(left)
+}
+
+/// Prefix operators bind weaker than postfix ones, so `&a[0]` takes address of the element.
+fn parse_prefix_expression(tokens: &mut Tokens) -> Result<ast::Expression, SyntaxError> {
+    tokens.begin_span();
+    let kind = match tokens.peek() {
+        Some(Token::Ampersand) => {
+            tokens.next();
+            let mutable = tokens.is(Token::MutKW);
+            ast::ExpressionKind::AddressOf { value: Box::new(parse_prefix_expression(tokens)?), mutable }
+        }
+        // `&&value` is lexed as a single token.
+        Some(Token::Conjunction) => {
+            tokens.next();
+            let inner_start = tokens.loc().unwrap().span.start + 1;
+            let mutable = tokens.is(Token::MutKW);
+            let value = parse_prefix_expression(tokens)?;
+            let inner = ast::Expression {
+                location: tokens.span_from(inner_start),
+                kind: ast::ExpressionKind::AddressOf { value: Box::new(value), mutable },
+                id: tokens.node_id()
+            };
+            ast::ExpressionKind::AddressOf { value: Box::new(inner), mutable: false }
+        }
+        Some(Token::Star) => {
+            tokens.next();
+            ast::ExpressionKind::Deref(Box::new(parse_prefix_expression(tokens)?))
+        }
+        Some(Token::Minus) => {
+            tokens.next();
+            ast::ExpressionKind::Unary(ast::UnaryOperator::Negate, Box::new(parse_prefix_expression(tokens)?))
+        }
+        Some(Token::Bang) => {
+            tokens.next();
+            ast::ExpressionKind::Unary(ast::UnaryOperator::Not, Box::new(parse_prefix_expression(tokens)?))
+        }
+        _ => {
+            tokens.end_span();
+            return parse_postfix_expression(tokens);
+        }
+    };
+
+    Ok(ast::Expression {
+        location: tokens.end_span(),
+        kind,
+        id: tokens.node_id()
+    })
+}
+
+fn parse_postfix_expression(tokens: &mut Tokens) -> Result<ast::Expression, SyntaxError> {
+    let mut expression = parse_primary_expression(tokens)?;
+
+    loop {
+        let start = expression.location.span.start;
+        let kind = match tokens.peek() {
+            Some(Token::LeftParen) => {
+                let arguments = parse_enclosed_argument_list(tokens)?;
+                ast::ExpressionKind::Call(Box::new(expression), arguments)
+            }
+            Some(Token::LeftBracket) => {
+                tokens.next();
+                new_lines(tokens);
+                let start_bound = match tokens.peek() {
+                    Some(Token::Range | Token::RangeInclusive) => None,
+                    _ => Some(Box::new(parse_expression(tokens)?))
+                };
+
+                let kind = match tokens.is_any(&[Token::Range, Token::RangeInclusive]) {
+                    Some(range) => {
+                        let end_bound = if tokens.peek() == Some(Token::RightBracket) { None }
+                            else { Some(Box::new(parse_expression(tokens)?)) };
+                        ast::ExpressionKind::Slice {
+                            base: Box::new(expression),
+                            start: start_bound,
+                            end: end_bound,
+                            inclusive: range == Token::RangeInclusive
+                        }
+                    }
+                    None => ast::ExpressionKind::Index(
+                        Box::new(expression),
+                        start_bound.ok_or_else(|| SyntaxError::ExpectedExpression { found: tokens.next_info().unwrap() })?
+                    )
+                };
+                new_lines(tokens);
+                tokens.expect(Token::RightBracket)?;
+                kind
+            }
+            Some(Token::Dot) => {
+                tokens.next();
+                match tokens.next() {
+                    // Fields of paths are parsed as part of the path and split off during name resolution.
+                    Some(Token::Identifier) => ast::ExpressionKind::Field(Box::new(expression), tokens.text().unwrap().to_string()),
+                    Some(Token::IntegerLiteral) => ast::ExpressionKind::TupleIndex(
+                        Box::new(expression),
+                        tokens.text().unwrap().parse().unwrap()
+                    ),
+                    // Nested index like `a.0.1` is lexed as a float literal.
+                    Some(Token::FloatLiteral) => {
+                        let text = tokens.text().unwrap();
+                        let (outer, inner) = text.split_once('.').unwrap();
+                        let location = tokens.span_from(start);
+                        let outer = ast::Expression {
+                            location,
+                            kind: ast::ExpressionKind::TupleIndex(Box::new(expression), outer.parse().unwrap()),
+                            id: tokens.node_id()
+                        };
+                        ast::ExpressionKind::TupleIndex(Box::new(outer), inner.parse().unwrap())
+                    }
+                    _ => return Err(SyntaxError::ExpectedExpression { found: tokens.current_info().unwrap() })
+                }
+            }
+            _ => break
+        };
+
+        expression = ast::Expression {
+            location: tokens.span_from(start),
+            kind,
+            id: tokens.node_id(),
+        };
+    }
+
+    Ok(expression)
+}
+
+fn parse_enclosed_argument_list(tokens: &mut Tokens) -> Result<Vec<ast::Expression>, SyntaxError> {
+    let mut arguments = Vec::<ast::Expression>::new();
+    tokens.expect(Token::LeftParen)?;
+    new_lines(tokens);
+
+    while !tokens.is(Token::RightParen) {
+        arguments.push(parse_expression(tokens)?);
+        new_lines(tokens);
+        if !tokens.is(Token::Comma) {
+            tokens.expect(Token::RightParen)?;
+            break;
+        }
+        new_lines(tokens);
+    }
+
+    Ok(arguments)
 }
 
 fn parse_primary_expression(tokens: &mut Tokens) -> Result<ast::Expression, SyntaxError> {
     tokens.begin_span();
     let kind = match tokens.next() {
         Some(Token::IntegerLiteral) => ast::ExpressionKind::Literal(ast::Literal::Integer(
-            tokens.text().unwrap().parse().unwrap() 
+            parse_integer_literal(tokens.current_info().unwrap())?
+        )),
+        Some(Token::FloatLiteral) => ast::ExpressionKind::Literal(ast::Literal::Float(
+            tokens.text().unwrap().replace('_', "").parse().unwrap()
         )),
+        Some(Token::BoolLiteral) => ast::ExpressionKind::Literal(ast::Literal::Bool(
+            tokens.text().unwrap() == "true"
+        )),
+        Some(Token::Identifier) => {
+            tokens.back();
+            ast::ExpressionKind::Path(parse_path_with(tokens, true)?)
+        }
+        Some(Token::LeftParen) => {
+            new_lines(tokens);
+            let mut elements = Vec::<ast::Expression>::new();
+            let mut is_tuple = false;
+            while !tokens.is(Token::RightParen) {
+                elements.push(parse_expression(tokens)?);
+                new_lines(tokens);
+                if !tokens.is(Token::Comma) {
+                    tokens.expect(Token::RightParen)?;
+                    break;
+                }
+                is_tuple = true;
+                new_lines(tokens);
+            }
+
+            // Single element without trailing comma is just a parenthesized expression.
+            if elements.len() == 1 && !is_tuple {
+                tokens.end_span();
+                return Ok(elements.pop().unwrap());
+            }
+            ast::ExpressionKind::Tuple(elements)
+        }
+        Some(Token::LeftBracket) => {
+            new_lines(tokens);
+            let mut elements = Vec::<ast::Expression>::new();
+            while !tokens.is(Token::RightBracket) {
+                elements.push(parse_expression(tokens)?);
+                new_lines(tokens);
+
+                if elements.len() == 1 && tokens.is(Token::Semicolon) {
+                    let count = parse_array_length(tokens)?;
+                    new_lines(tokens);
+                    tokens.expect(Token::RightBracket)?;
+                    return Ok(ast::Expression {
+                        location: tokens.end_span(),
+                        kind: ast::ExpressionKind::ArrayRepeat(Box::new(elements.pop().unwrap()), count),
+                        id: tokens.node_id()
+                    });
+                }
+
+                if !tokens.is(Token::Comma) {
+                    tokens.expect(Token::RightBracket)?;
+                    break;
+                }
+                new_lines(tokens);
+            }
+            ast::ExpressionKind::Array(elements)
+        }
+        Some(Token::MatchKW) => {
+            let scrutinee = parse_expression(tokens)?;
+            let arms = parse_match_arms(tokens)?;
+            ast::ExpressionKind::Match(Box::new(scrutinee), arms)
+        }
+        Some(Token::Pipe | Token::Disjunction | Token::MoveKW) => {
+            tokens.back();
+            ast::ExpressionKind::Closure(parse_closure(tokens)?)
+        }
         _ => return Err(SyntaxError::ExpectedExpression { found: tokens.current_info().unwrap() })
     };
 
     Ok(ast::Expression {
         location: tokens.end_span(),
         kind,
-        id: NodeId::next(),
+        id: tokens.node_id(),
+    })
+}
+
+fn parse_closure(tokens: &mut Tokens) -> Result<ast::Closure, SyntaxError> {
+    let by_value = tokens.is(Token::MoveKW);
+
+    // Closure without parameters starts with `||`, which is lexed as a single token.
+    let mut parameters = Vec::<ast::ClosureParameter>::new();
+    if !tokens.is(Token::Disjunction) {
+        tokens.expect(Token::Pipe)?;
+        while !tokens.is(Token::Pipe) {
+            tokens.begin_span();
+            // Alternatives would be ambiguous with the closing `|`.
+            let pattern = parse_primary_pattern(tokens)?;
+            let ty = if tokens.is(Token::Colon) { Some(parse_type(tokens)?) }
+                else { None };
+            parameters.push(ast::ClosureParameter {
+                location: tokens.end_span(),
+                pattern,
+                ty,
+                id: tokens.node_id()
+            });
+
+            if !tokens.is(Token::Comma) {
+                tokens.expect(Token::Pipe)?;
+                break;
+            }
+        }
+    }
+
+    new_lines(tokens);
+    Ok(ast::Closure {
+        parameters,
+        body: Box::new(parse_expression(tokens)?),
+        by_value
+    })
+}
+
+fn parse_match_arms(tokens: &mut Tokens) -> Result<Vec<ast::MatchArm>, SyntaxError> {
+    let mut arms = Vec::<ast::MatchArm>::new();
+    tokens.expect(Token::LeftCurly)?;
+    new_lines(tokens);
+
+    while !tokens.is(Token::RightCurly) {
+        tokens.begin_span();
+        let pattern = parse_pattern(tokens)?;
+        tokens.expect(Token::FatArrow)?;
+        new_lines(tokens);
+        let body = parse_expression(tokens)?;
+
+        arms.push(ast::MatchArm {
+            location: tokens.end_span(),
+            pattern,
+            body,
+            id: tokens.node_id()
+        });
+
+        tokens.is(Token::Comma);
+        new_lines(tokens);
+    }
+
+    Ok(arms)
+}
+
+fn parse_pattern(tokens: &mut Tokens) -> Result<ast::Pattern, SyntaxError> {
+    tokens.begin_span();
+    let mut alternatives = vec![parse_primary_pattern(tokens)?];
+    while tokens.is(Token::Pipe) {
+        new_lines(tokens);
+        alternatives.push(parse_primary_pattern(tokens)?);
+    }
+
+    let location = tokens.end_span();
+    if alternatives.len() == 1 {
+        return Ok(alternatives.pop().unwrap());
+    }
+
+    Ok(ast::Pattern {
+        location,
+        kind: ast::PatternKind::Or(alternatives),
+        id: tokens.node_id()
+    })
+}
+
+fn parse_primary_pattern(tokens: &mut Tokens) -> Result<ast::Pattern, SyntaxError> {
+    tokens.begin_span();
+    let kind = match tokens.peek() {
+        Some(Token::IntegerLiteral | Token::Minus) => {
+            let start = parse_integer_pattern_bound(tokens)?;
+            match tokens.is_any(&[Token::Range, Token::RangeInclusive]) {
+                Some(range) => ast::PatternKind::Range {
+                    start,
+                    end: parse_integer_pattern_bound(tokens)?,
+                    inclusive: range == Token::RangeInclusive
+                },
+                None => ast::PatternKind::Integer(start)
+            }
+        }
+        Some(Token::BoolLiteral) => {
+            tokens.next();
+            ast::PatternKind::Bool(tokens.text().unwrap() == "true")
+        }
+        Some(Token::LeftParen) => {
+            tokens.next();
+            new_lines(tokens);
+            let mut elements = Vec::<ast::Pattern>::new();
+            let mut is_tuple = false;
+            while !tokens.is(Token::RightParen) {
+                elements.push(parse_pattern(tokens)?);
+                new_lines(tokens);
+                if !tokens.is(Token::Comma) {
+                    tokens.expect(Token::RightParen)?;
+                    break;
+                }
+                is_tuple = true;
+                new_lines(tokens);
+            }
+
+            if elements.len() == 1 && !is_tuple {
+                tokens.end_span();
+                return Ok(elements.pop().unwrap());
+            }
+            ast::PatternKind::Tuple(elements)
+        }
+        Some(Token::Identifier) => {
+            let path = parse_path(tokens)?;
+            if tokens.peek() == Some(Token::LeftParen) {
+                ast::PatternKind::Variant(path, parse_enclosed_pattern_list(tokens)?)
+            } else if path.segments.len() > 1 {
+                ast::PatternKind::Variant(path, Vec::new())
+            } else if path.segments[0].name == "_" {
+                ast::PatternKind::Wildcard
+            } else {
+                ast::PatternKind::Binding(path.segments[0].name.clone())
+            }
+        }
+        _ => return Err(SyntaxError::ExpectedPattern { found: tokens.next_info().unwrap() })
+    };
+
+    Ok(ast::Pattern {
+        location: tokens.end_span(),
+        kind,
+        id: tokens.node_id()
     })
 }
 
-fn parse_enclosed_value_parameter_list(tokens: &mut Tokens) -> Result<Vec<ast::ValueParameter>, SyntaxError> {
+fn parse_integer_pattern_bound(tokens: &mut Tokens) -> Result<i128, SyntaxError> {
+    let negative = tokens.is(Token::Minus);
+    let value: i128 = parse_integer_literal(tokens.expect(Token::IntegerLiteral)?)?;
+    Ok(if negative { -value } else { value })
+}
+
+fn parse_integer_literal<T: std::str::FromStr>(literal: TokenInfo) -> Result<T, SyntaxError> {
+    literal.text().replace('_', "").parse::<T>().map_err(|_| SyntaxError::IntegerLiteralTooLarge { found: literal })
+}
+
+fn parse_enclosed_pattern_list(tokens: &mut Tokens) -> Result<Vec<ast::Pattern>, SyntaxError> {
+    let mut patterns = Vec::<ast::Pattern>::new();
+    tokens.expect(Token::LeftParen)?;
+    new_lines(tokens);
+
+    while !tokens.is(Token::RightParen) {
+        patterns.push(parse_pattern(tokens)?);
+        new_lines(tokens);
+        if !tokens.is(Token::Comma) {
+            tokens.expect(Token::RightParen)?;
+            break;
+        }
+        new_lines(tokens);
+    }
+
+    Ok(patterns)
+}
+
+/// Parses parameters of function, also returns whether the first one is a `self` receiver.
+fn parse_enclosed_value_parameter_list(tokens: &mut Tokens) -> Result<(Vec<ast::ValueParameter>, bool, bool), SyntaxError> {
     let mut parameters = Vec::<ast::ValueParameter>::new();
     tokens.expect(Token::LeftParen)?;
     new_lines(tokens);
 
+    let receiver = parse_receiver(tokens)?;
+    let has_receiver = receiver.is_some();
+    if let Some(receiver) = receiver {
+        parameters.push(receiver);
+        new_lines(tokens);
+        if !tokens.is(Token::Comma) {
+            tokens.expect(Token::RightParen)?;
+            return Ok((parameters, has_receiver, false));
+        }
+        new_lines(tokens);
+    }
+
     while !tokens.is(Token::RightParen) {
+        // Variable arguments can only follow all of the declared parameters.
+        if tokens.is(Token::Spread) {
+            new_lines(tokens);
+            tokens.expect(Token::RightParen)?;
+            return Ok((parameters, has_receiver, true));
+        }
+
         parameters.push(parse_value_parameter(tokens)?);
         new_lines(tokens);
         if !tokens.is(Token::Comma) {
@@ -112,7 +982,63 @@ fn parse_enclosed_value_parameter_list(tokens: &mut Tokens) -> Result<Vec<ast::V
         new_lines(tokens);
     }
 
-    Ok(parameters)
+    Ok((parameters, has_receiver, false))
+}
+
+/// Parses `self`, `&self` or `&mut self` as parameter `self` of type `Self`, `&Self` or `&mut Self`.
+/// Nothing is consumed if the parameter list does not start with a receiver.
+fn parse_receiver(tokens: &mut Tokens) -> Result<Option<ast::ValueParameter>, SyntaxError> {
+    let start = tokens.peek_info().unwrap().location().span.start;
+    let is_reference = tokens.is(Token::Ampersand);
+    let mutable = is_reference && tokens.is(Token::MutKW);
+
+    let is_receiver = tokens.peek_info().is_some_and(|info| info.kind() == Token::Identifier && info.text() == "self") && {
+        tokens.next();
+        let has_type = tokens.peek() == Some(Token::Colon);
+        tokens.back();
+        !has_type
+    };
+    if !is_receiver {
+        for _ in 0..(is_reference as usize + mutable as usize) {
+            tokens.back();
+        }
+        return Ok(None);
+    }
+
+    let self_location = tokens.expect(Token::Identifier)?.location();
+    let location = tokens.span_from(start);
+    let self_type = ast::Type {
+        location: self_location.clone(),
+        kind: ast::TypeKind::Path(ast::Path {
+            segments: vec![ast::PathSegment {
+                location: self_location.clone(),
+                name: "Self".to_string(),
+                type_arguments: Vec::new()
+            }]
+        }),
+        id: tokens.node_id()
+    };
+    let ty = if is_reference { ast::Type {
+        location: location.clone(),
+        kind: ast::TypeKind::Reference { target: Box::new(self_type), mutable },
+        id: tokens.node_id()
+    } } else { self_type };
+
+    Ok(Some(ast::ValueParameter {
+        location: location.clone(),
+        parameter: ast::Parameter {
+            location: location.clone(),
+            pattern: ast::Pattern {
+                location: self_location,
+                kind: ast::PatternKind::Binding("self".to_string()),
+                id: tokens.node_id()
+            },
+            ty,
+            id: tokens.node_id()
+        },
+        initializer: None,
+        id: tokens.node_id()
+    }))
 }
 
 fn parse_value_parameter(tokens: &mut Tokens) -> Result<ast::ValueParameter, SyntaxError> {
@@ -125,21 +1051,21 @@ fn parse_value_parameter(tokens: &mut Tokens) -> Result<ast::ValueParameter, Syn
         location: tokens.end_span(),
         parameter,
         initializer,
-        id: NodeId::next()
+        id: tokens.node_id()
     })
 }
 
 fn parse_parameter(tokens: &mut Tokens) -> Result<ast::Parameter, SyntaxError> {
     tokens.begin_span();
-    let name = tokens.expect(Token::Identifier)?.text();
+    let pattern = parse_pattern(tokens)?;
     tokens.expect(Token::Colon)?;
     let ty = parse_type(tokens)?;
 
     Ok(ast::Parameter {
-        name,
+        pattern,
         location: tokens.end_span(),
         ty,
-        id: NodeId::next()
+        id: tokens.node_id()
     })
 }
 
@@ -147,9 +1073,85 @@ fn parse_type(tokens: &mut Tokens) -> Result<ast::Type, SyntaxError> {
     tokens.begin_span();
     let kind = match tokens.next() {
         Some(Token::IntegerType) => {
+            let info = tokens.current_info().unwrap();
+            match info.text()[3..].parse::<usize>() {
+                Ok(width @ 1..=128) => ast::TypeKind::Integer(width),
+                _ => return Err(SyntaxError::InvalidIntegerWidth { found: info })
+            }
+        }
+        Some(Token::FloatType) => {
             let text = tokens.text().unwrap();
-            let width = text[3..].parse::<usize>().unwrap();
-            ast::TypeKind::Integer(width)
+            let width = text[5..].parse::<usize>().unwrap();
+            ast::TypeKind::Float(width)
+        }
+        Some(Token::BoolType) => ast::TypeKind::Bool,
+        Some(Token::Ampersand) => {
+            let mutable = tokens.is(Token::MutKW);
+            ast::TypeKind::Reference { target: Box::new(parse_type(tokens)?), mutable }
+        }
+        // `&&T` is lexed as a single token.
+        Some(Token::Conjunction) => {
+            let inner_start = tokens.loc().unwrap().span.start + 1;
+            let mutable = tokens.is(Token::MutKW);
+            let target = parse_type(tokens)?;
+            let inner = ast::Type {
+                location: tokens.span_from(inner_start),
+                kind: ast::TypeKind::Reference { target: Box::new(target), mutable },
+                id: tokens.node_id()
+            };
+            ast::TypeKind::Reference { target: Box::new(inner), mutable: false }
+        }
+        Some(Token::Star) => {
+            let mutable = tokens.is(Token::MutKW);
+            ast::TypeKind::Pointer { target: Box::new(parse_type(tokens)?), mutable }
+        }
+        Some(Token::LeftBracket) => {
+            let element = Box::new(parse_type(tokens)?);
+            let kind = if tokens.is(Token::Semicolon) { ast::TypeKind::Array(element, parse_array_length(tokens)?) }
+                else { ast::TypeKind::Slice(element) };
+            tokens.expect(Token::RightBracket)?;
+            kind
+        }
+        Some(Token::Identifier) => {
+            tokens.back();
+            ast::TypeKind::Path(parse_path(tokens)?)
+        }
+        Some(Token::DynKW) => ast::TypeKind::Dyn(parse_trait_ref(tokens)?),
+        Some(Token::FuncKW) => {
+            let mut parameters = Vec::<ast::Type>::new();
+            tokens.expect(Token::LeftParen)?;
+            while !tokens.is(Token::RightParen) {
+                parameters.push(parse_type(tokens)?);
+                if !tokens.is(Token::Comma) {
+                    tokens.expect(Token::RightParen)?;
+                    break;
+                }
+            }
+
+            let return_type = if tokens.is(Token::Colon) { Some(Box::new(parse_type(tokens)?)) }
+                else { None };
+            ast::TypeKind::Function { parameters, return_type }
+        }
+        Some(Token::LeftParen) => {
+            let mut elements = Vec::<ast::Type>::new();
+            let mut is_tuple = false;
+            while !tokens.is(Token::RightParen) {
+                elements.push(parse_type(tokens)?);
+                if !tokens.is(Token::Comma) {
+                    tokens.expect(Token::RightParen)?;
+                    break;
+                }
+                is_tuple = true;
+            }
+
+            match elements.len() {
+                0 => ast::TypeKind::Unit,
+                1 if !is_tuple => {
+                    tokens.end_span();
+                    return Ok(elements.pop().unwrap());
+                }
+                _ => ast::TypeKind::Tuple(elements)
+            }
         }
 
         _ => return Err(SyntaxError::ExpectedType { found: tokens.current_info().unwrap() })
@@ -158,19 +1160,90 @@ fn parse_type(tokens: &mut Tokens) -> Result<ast::Type, SyntaxError> {
     Ok(ast::Type {
         location: tokens.end_span(),
         kind,
-        id: NodeId::next()
+        id: tokens.node_id()
     })
 }
 
+/// Array lengths can be any constant expression, like `4` or `SIZE`.
+fn parse_array_length(tokens: &mut Tokens) -> Result<Box<ast::Expression>, SyntaxError> {
+    new_lines(tokens);
+    Ok(Box::new(parse_expression(tokens)?))
+}
+
+fn parse_path(tokens: &mut Tokens) -> Result<ast::Path, SyntaxError> {
+    parse_path_with(tokens, false)
+}
+
+/// In expressions `<` after a name can also be comparison, it starts type arguments only if they parse as such.
+/// Comparisons like `(a < b, c > d)` still look like type arguments and have to be parenthesized.
+fn parse_path_with(tokens: &mut Tokens, in_expression: bool) -> Result<ast::Path, SyntaxError> {
+    let mut segments = Vec::<ast::PathSegment>::new();
+    loop {
+        let name = tokens.expect(Token::Identifier)?;
+        let type_arguments = match tokens.peek() {
+            Some(Token::LeftAngle) if in_expression => {
+                let checkpoint = tokens.checkpoint();
+                parse_type_argument_list(tokens).unwrap_or_else(|_| {
+                    tokens.rewind(checkpoint);
+                    Vec::new()
+                })
+            }
+            Some(Token::LeftAngle) => parse_type_argument_list(tokens)?,
+            _ => Vec::new()
+        };
+        segments.push(ast::PathSegment {
+            location: name.location(),
+            name: name.text(),
+            type_arguments
+        });
+
+        if !tokens.is(Token::Dot) { break; }
+        if tokens.peek() != Some(Token::Identifier) {
+            // Dot belongs to something else, like tuple index.
+            tokens.back();
+            break;
+        }
+    }
+
+    Ok(ast::Path { segments })
+}
+
+fn parse_type_argument_list(tokens: &mut Tokens) -> Result<Vec<ast::Type>, SyntaxError> {
+    let mut arguments = Vec::<ast::Type>::new();
+    tokens.expect(Token::LeftAngle)?;
+
+    while !tokens.is(Token::RightAngle) {
+        arguments.push(parse_type(tokens)?);
+        if !tokens.is(Token::Comma) {
+            tokens.expect(Token::RightAngle)?;
+            break;
+        }
+    }
+
+    Ok(arguments)
+}
+
 fn parse_attributes(tokens: &mut Tokens, _top_level: bool) -> Result<ast::Attributes, SyntaxError> {
     let mut attributes = Vec::<ast::Attribute>::new();
     while tokens.is(Token::At) {
+        let start = tokens.loc().unwrap().span.start;
         let name = tokens.expect(Token::Identifier)?.text();
-        // Currently only marker attributes are supported
+        let kind = if tokens.is(Token::LeftParen) {
+            let mut arguments = Vec::<ast::AttributeArgument>::new();
+            while !tokens.is(Token::RightParen) {
+                arguments.push(parse_attribute_argument(tokens)?);
+                if !tokens.is(Token::Comma) {
+                    tokens.expect(Token::RightParen)?;
+                    break;
+                }
+            }
+            ast::AttributeKind::Call(name, arguments)
+        } else { ast::AttributeKind::Marker(name) };
+
         attributes.push(Attribute {
-            location: tokens.loc().unwrap(),
-            kind: ast::AttributeKind::Marker(name),
-            id: NodeId::next()
+            location: tokens.span_from(start),
+            kind,
+            id: tokens.node_id()
         });
 
         new_lines(tokens);
@@ -178,6 +1251,40 @@ fn parse_attributes(tokens: &mut Tokens, _top_level: bool) -> Result<ast::Attrib
     Ok(Attributes(attributes))
 }
 
+/// Parses string or identifier, optionally preceded by `name =`.
+/// What arguments are accepted is up to each attribute, the parser only checks their shape.
+fn parse_attribute_argument(tokens: &mut Tokens) -> Result<ast::AttributeArgument, SyntaxError> {
+    tokens.begin_span();
+    let mut name = None;
+    if tokens.peek() == Some(Token::Identifier) {
+        tokens.next();
+        let identifier = tokens.text().unwrap().to_string();
+        if tokens.is(Token::Assign) {
+            name = Some(identifier);
+        } else {
+            tokens.back();
+        }
+    }
+
+    let value = match tokens.next() {
+        Some(Token::StringLiteral) => {
+            let text = tokens.text().unwrap();
+            ast::AttributeValue::String(text[1..text.len() - 1].to_string())
+        }
+        Some(Token::Identifier) => ast::AttributeValue::Identifier(tokens.text().unwrap().to_string()),
+        _ => return Err(SyntaxError::UnexpectedToken {
+            expected: vec![Token::StringLiteral, Token::Identifier],
+            found: tokens.current_info().unwrap()
+        })
+    };
+
+    Ok(ast::AttributeArgument {
+        location: tokens.end_span(),
+        name,
+        value
+    })
+}
+
 fn new_lines(tokens: &mut Tokens) {
     while tokens.is(Token::NL) {}
 }
@@ -0,0 +1,1742 @@
+use ramen_common::{ast, attributes::{InlineMode, KnownAttribute}, consts::ConstValue, defs::Definition, error::ResolutionError, scope::Scope, session::{Session, SourceId}, types::RamenType, visitor::ASTPass};
+use ramen_frontend::{lex, parse, load, error::{LoadError, SyntaxError}, ast_pass::{binding, exhaustiveness, name_resolution, type_resolution}};
+
+fn resolve(source: &str) -> (Session, ast::Module, Result<(), ResolutionError>) {
+    let session = Session::new();
+    let mut tokens = lex::Tokens::from_string(source, SourceId::dummy(), &session.node_ids);
+    let ast = parse::parse_ramen("main".to_string(), &mut tokens).expect("Something went wrong during parsing");
+
+    let result = run_passes(&session, &ast);
+    (session, ast, result)
+}
+
+fn run_passes(session: &Session, ast: &ast::Module) -> Result<(), ResolutionError> {
+    let module_id = session.node_ids.next();
+    let global_scope = Scope::new_ref(None, None);
+
+    binding::ItemNameBindingPass::run_on_module(session, global_scope.clone(), module_id, ast)
+        .and_then(|_| name_resolution::NameResolutionPass::run_on_module(session, global_scope.clone(), module_id, ast))
+        .and_then(|_| type_resolution::TypeResolutionPass::run_on_module(session, global_scope.clone(), module_id, ast))
+        .and_then(|_| exhaustiveness::MatchCheckingPass::run_on_module(session, global_scope.clone(), module_id, ast))
+}
+
+fn returned_expression(item: &ast::Item) -> &ast::Expression {
+    let ast::ItemKind::Function(function) = &item.kind else { panic!("Expected function item.") };
+    let Some(ast::StatementKind::Return(expression)) = function.body.as_ref().unwrap().statements.last().map(|s| &s.kind) else {
+        panic!("Expected function to end with return.")
+    };
+    expression
+}
+
+#[test]
+fn enum_constructors() {
+    const SOURCE: &str = "
+        func circle(radius: float64): Shape => Shape.Circle(radius)
+        func empty(): Shape => Shape.Empty
+        enum Shape {
+            Circle(float64),
+            Rect(float64, float64)
+            Empty
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Enum program should resolve.");
+
+    let Some(RamenType::Enum(shape)) = session.get_type(returned_expression(&ast.items[0]).id) else {
+        panic!("Constructor should produce enum value.")
+    };
+    assert_eq!(shape.name, "Shape");
+    assert_eq!(shape.variants.len(), 3);
+    assert_eq!(shape.variants[1].fields, vec![RamenType::Float(64), RamenType::Float(64)]);
+    assert!(matches!(session.get_type(returned_expression(&ast.items[1]).id), Some(RamenType::Enum(_))));
+}
+
+#[test]
+fn enum_constructor_payload_mismatch() {
+    const SOURCE: &str = "
+        enum Shape { Circle(float64) }
+        func circle(radius: int32): Shape => Shape.Circle(radius)
+    ";
+    let (_, _, result) = resolve(SOURCE);
+    assert!(matches!(result, Err(ResolutionError::TypeMismatch { expected: RamenType::Float(64), found: RamenType::Integer(32), .. })));
+}
+
+#[test]
+fn recursive_enum() {
+    const SOURCE: &str = "enum List { Cons(int32, List), Nil }";
+    let (_, _, result) = resolve(SOURCE);
+    assert!(matches!(result, Err(ResolutionError::RecursiveType { .. })));
+}
+
+#[test]
+fn recursion_through_pointers() {
+    const SOURCE: &str = "
+        struct Node { value: int32, next: *Node }
+        enum List<T> { Cons(T, &List<T>), Nil }
+        func second(node: &Node): int32 => node.next.value
+        func third(node: &Node): int32 => (*(*node).next).next.value
+        func head(list: &List<int64>): int64 => match *list {
+            List.Cons(value, _) => value,
+            List.Nil => 0
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Types should be able to refer to themselves through pointers.");
+    assert_eq!(session.get_type(returned_expression(&ast.items[2]).id), Some(RamenType::Integer(32)));
+    assert_eq!(session.get_type(returned_expression(&ast.items[4]).id), Some(RamenType::Integer(64)));
+}
+
+#[test]
+fn match_expression_type() {
+    const SOURCE: &str = "
+        enum Shape { Circle(float64), Square(float64), Empty }
+        func area(shape: Shape): float64 => match shape {
+            Shape.Circle(radius) => radius,
+            Shape.Square(side) => side,
+            Shape.Empty => 0.0
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Exhaustive match should resolve.");
+    assert_eq!(session.get_type(returned_expression(&ast.items[1]).id), Some(RamenType::Float(64)));
+    assert_eq!(session.warnings.get(), 0);
+}
+
+#[test]
+fn exhaustive_integer_ranges() {
+    const SOURCE: &str = "
+        func sign(value: int8): int32 => match value {
+            -128..0 => 0,
+            0 => 1,
+            1..=100 | 101..=127 => 2
+        }
+    ";
+    let (_, _, result) = resolve(SOURCE);
+    result.expect("Ranges covering whole int8 should be exhaustive.");
+}
+
+#[test]
+fn integer_pattern_out_of_range() {
+    const SOURCE: &str = "
+        func small(value: int8): int32 => match value {
+            300 => 1,
+            _ => 0
+        }
+    ";
+    let (_, _, result) = resolve(SOURCE);
+    assert!(matches!(result, Err(ResolutionError::PatternOutOfRange { value: 300, ty: RamenType::Integer(8), .. })));
+
+    const RANGES: &str = "
+        func wide(value: int8): int32 => match value {
+            -128..0 => 0,
+            0..128 => 1
+        }
+        func narrow(value: int8): int32 => match value {
+            -129..=0 => 0,
+            _ => 1
+        }
+    ";
+    let (_, _, result) = resolve(RANGES);
+    assert!(matches!(result, Err(ResolutionError::PatternOutOfRange { value: -129, .. })));
+}
+
+#[test]
+fn integer_literal_out_of_range() {
+    let (_, _, result) = resolve("
+        func main() {
+            let small: int8 = 300
+        }
+    ");
+    assert!(matches!(&result, Err(ResolutionError::LiteralOutOfRange { value: 300, ty: RamenType::Integer(8), location }) if location.span.len() == 3), "Expected literal out of range, got {result:?}.");
+
+    let (_, _, result) = resolve("func main(): int32 => 3000000000");
+    assert!(matches!(result, Err(ResolutionError::LiteralOutOfRange { value: 3000000000, .. })), "Expected literal out of range, got {result:?}.");
+
+    let (_, _, result) = resolve("func main(): (int8, int8, int128) => (-128, 127, 18446744073709551615)");
+    result.expect("Literals at the ends of the range should fit.");
+}
+
+#[test]
+fn non_exhaustive_match() {
+    const SOURCE: &str = "func small(value: int32): int32 => match value { 0..=9 => 1 }";
+    let (_, _, result) = resolve(SOURCE);
+    let Err(ResolutionError::NonExhaustiveMatch { missing, .. }) = result else {
+        panic!("Match without all cases should not be exhaustive.")
+    };
+    assert_eq!(missing, vec!["-2147483648..=-1", "10..=2147483647"]);
+}
+
+#[test]
+fn non_exhaustive_enum_match() {
+    const SOURCE: &str = "
+        enum Shape { Circle(float64), Square(float64), Empty }
+        func kind(shape: Shape): int32 => match shape { Shape.Square(_) => 1 }
+    ";
+    let (_, _, result) = resolve(SOURCE);
+    let Err(ResolutionError::NonExhaustiveMatch { missing, .. }) = result else {
+        panic!("Match without all variants should not be exhaustive.")
+    };
+    assert_eq!(missing, vec!["Shape.Circle(_)", "Shape.Empty"]);
+}
+
+#[test]
+fn empty_enum_match() {
+    const SOURCE: &str = "
+        enum Never {}
+        func absurd(never: Never): int32 => match never {}
+        func first(pair: (Never, bool)): int32 => match pair { (_, true) => 1 }
+    ";
+    let (_, _, result) = resolve(SOURCE);
+    result.expect("Match on enum without variants should not need any arms.");
+}
+
+#[test]
+fn unreachable_match_arm() {
+    const SOURCE: &str = "
+        func pick(value: int32): int32 => match value {
+            0 | 1 => 1,
+            _ => 2,
+            1 => 3
+        }
+    ";
+    let (session, _, result) = resolve(SOURCE);
+    result.expect("Unreachable arm should only be a warning.");
+    assert_eq!(session.warnings.get(), 1);
+}
+
+#[test]
+fn tuple_destructuring() {
+    const SOURCE: &str = "
+        func divide(a: int32, b: int32): (int32, int32) => (a, b)
+        func sum((a, b): (int32, int32)): int32 => a
+        func first(): (int32, bool) {
+            let (quotient, _) = divide(7, 2)
+            let pair: (int64, bool) = (1, true)
+            return (quotient, pair.1)
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Tuple program should resolve.");
+    assert_eq!(
+        session.get_type(returned_expression(&ast.items[2]).id),
+        Some(RamenType::Tuple(vec![RamenType::Integer(32), RamenType::Bool]))
+    );
+}
+
+#[test]
+fn refutable_let_pattern() {
+    const SOURCE: &str = "
+        func pick(pair: (int32, bool)): int32 {
+            let (value, true) = pair
+            return value
+        }
+    ";
+    let (_, _, result) = resolve(SOURCE);
+    let Err(ResolutionError::RefutablePattern { missing, .. }) = result else {
+        panic!("Let pattern which does not cover every value should be rejected.")
+    };
+    assert_eq!(missing, vec!["(_, false)"]);
+}
+
+#[test]
+fn invalid_tuple_index() {
+    const SOURCE: &str = "func second(pair: (int32, int32)): int32 => pair.2";
+    let (_, _, result) = resolve(SOURCE);
+    assert!(matches!(result, Err(ResolutionError::InvalidTupleIndex { index: 2, .. })));
+}
+
+#[test]
+fn array_indexing_and_slicing() {
+    const SOURCE: &str = "
+        func middle(values: [int32; 4]): [int32] => values[1..3]
+        func first(values: [int32]): int32 => values[0]
+        func zeros(): [float32; 16] => [0.0; 16]
+        func last(): int32 {
+            let values = [1, 2, 3]
+            return values[2]
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Array program should resolve.");
+    assert_eq!(
+        session.get_type(returned_expression(&ast.items[0]).id),
+        Some(RamenType::Slice(Box::new(RamenType::Integer(32))))
+    );
+    assert_eq!(session.get_type(returned_expression(&ast.items[3]).id), Some(RamenType::Integer(32)));
+}
+
+#[test]
+fn empty_array_needs_annotation() {
+    const SOURCE: &str = "
+        func empty(): int32 {
+            let values = []
+            return 0
+        }
+    ";
+    let (_, _, result) = resolve(SOURCE);
+    assert!(matches!(result, Err(ResolutionError::TypeAnnotationsNeeded { .. })));
+}
+
+#[test]
+fn index_into_non_array() {
+    const SOURCE: &str = "func first(value: int32): int32 => value[0]";
+    let (_, _, result) = resolve(SOURCE);
+    assert!(matches!(result, Err(ResolutionError::NotIndexable { found: RamenType::Integer(32), .. })));
+}
+
+#[test]
+fn references_and_pointers() {
+    const SOURCE: &str = "
+        func read(value: &int32): int32 => *value
+        func write(out: &mut int32, value: int32) {
+            *out = value
+        }
+        func first(values: &mut [int64; 4]): *int64 => &mut (*values)[0]
+        func same(a: &int32, b: &int32): bool => a == b
+        func main(): int32 {
+            let value = 1
+            write(&mut value, 2)
+            return read(&value)
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Pointer program should resolve.");
+    assert_eq!(
+        session.get_type(returned_expression(&ast.items[2]).id),
+        Some(RamenType::Reference { target: Box::new(RamenType::Integer(64)), mutable: true })
+    );
+    assert_eq!(session.get_type(returned_expression(&ast.items[3]).id), Some(RamenType::Bool));
+}
+
+#[test]
+fn assign_through_immutable_reference() {
+    const SOURCE: &str = "
+        func write(out: &int32) {
+            *out = 5
+        }
+    ";
+    let (_, _, result) = resolve(SOURCE);
+    assert!(matches!(result, Err(ResolutionError::MutationThroughImmutable { .. })));
+
+    const DEREF_SOURCE: &str = "func read(value: int32): int32 => *value";
+    let (_, _, result) = resolve(DEREF_SOURCE);
+    assert!(matches!(result, Err(ResolutionError::NotDereferenceable { found: RamenType::Integer(32), .. })));
+}
+
+#[test]
+fn generic_function_instantiation() {
+    const SOURCE: &str = "
+        func first<T>(a: T, b: T): T => a
+        func pair<A, B>(a: A, b: B): (A, B) => (a, b)
+        func main(): int64 {
+            let inferred: int64 = first(1, 2)
+            let both = pair(true, &inferred)
+            return first<int64>(inferred, 3)
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Generic program should resolve.");
+    assert_eq!(session.get_type(returned_expression(&ast.items[2]).id), Some(RamenType::Integer(64)));
+
+    let first = session.get_ref_target(ast.items[0].id).unwrap();
+    let pair = session.get_ref_target(ast.items[1].id).unwrap();
+    assert_eq!(session.get_instances(first), vec![vec![RamenType::Integer(64)]]);
+    assert_eq!(session.get_instances(pair), vec![vec![
+        RamenType::Bool,
+        RamenType::Reference { target: Box::new(RamenType::Integer(64)), mutable: false }
+    ]]);
+}
+
+#[test]
+fn generic_function_errors() {
+    let (_, _, result) = resolve("
+        func first<T>(a: T, b: T): T => a
+        func main(): int32 => first(1, true)
+    ");
+    assert!(matches!(result, Err(ResolutionError::TypeMismatch { expected: RamenType::Integer(32), found: RamenType::Bool, .. })));
+
+    let (_, _, result) = resolve("
+        func first<T>(a: T, b: T): T => a
+        func main(): int32 => first<int32, bool>(1, 2)
+    ");
+    assert!(matches!(result, Err(ResolutionError::GenericArgumentCountMismatch { expected: 1, found: 2, .. })));
+
+    let (_, _, result) = resolve("
+        func make<T>(): int32 => 0
+        func main(): int32 => make()
+    ");
+    assert!(matches!(result, Err(ResolutionError::TypeAnnotationsNeeded { .. })));
+
+    let (_, _, result) = resolve("
+        func outer<T>(value: T) {
+            func inner(value: T) {}
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::OuterTypeParameter { .. })));
+
+    let (_, _, result) = resolve("
+        func grow<T>(value: T) {
+            grow((value, value))
+        }
+        func main() => grow(1)
+    ");
+    assert!(matches!(result, Err(ResolutionError::RecursiveInstantiation { .. })));
+}
+
+#[test]
+fn generic_structs_and_enums() {
+    const SOURCE: &str = "
+        struct Pair<A, B> { first: A, second: B }
+        enum Option<T> { Some(T), None }
+        func swap<A, B>(pair: &Pair<A, B>): Pair<B, A> => Pair(pair.second, pair.first)
+        func main(): Option<bool> {
+            let pair = Pair(1, true)
+            let swapped: Pair<bool, int32> = swap(&pair)
+            let none: Option<int64> = Option.None
+            let explicit = Option<int64>.None
+            return Option.Some(swapped.first)
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Generic types should resolve.");
+
+    let Some(RamenType::Enum(option)) = session.get_type(returned_expression(&ast.items[3]).id) else {
+        panic!("Main should return an enum.")
+    };
+    assert_eq!(option.type_arguments, vec![RamenType::Bool]);
+    assert_eq!(option.variants[0].fields, vec![RamenType::Bool]);
+    assert_eq!(RamenType::Enum(option).to_string(), "Option<bool>");
+}
+
+#[test]
+fn generic_enum_values() {
+    const SOURCE: &str = "
+        enum Option<T> { Some(T), None }
+        func unwrap_or(value: Option<int32>, default: int32): int32 {
+            let copy = value
+            return match copy {
+                Option.Some(inner) => inner,
+                Option.None => default
+            }
+        }
+        func first(value: Option<float64>): Option<float64> => value
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Values of generic enums should not need type annotations.");
+
+    let Some(RamenType::Enum(option)) = session.get_type(returned_expression(&ast.items[2]).id) else {
+        panic!("Parameter should keep its enum type.")
+    };
+    assert_eq!(option.type_arguments, vec![RamenType::Float(64)]);
+}
+
+#[test]
+fn generic_type_errors() {
+    let (_, _, result) = resolve("
+        struct Pair<A, B> { first: A, second: B }
+        func main(pair: Pair<int32>) {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::GenericArgumentCountMismatch { expected: 2, found: 1, .. })));
+
+    let (_, _, result) = resolve("
+        enum Option<T> { Some(T), None }
+        func main() {
+            let none = Option.None
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::TypeAnnotationsNeeded { .. })));
+
+    let (_, _, result) = resolve("
+        struct Point { x: int32, y: int32 }
+        func main(point: Point): int32 => point.z
+    ");
+    assert!(matches!(result, Err(ResolutionError::NoField { .. })));
+
+    let (_, _, result) = resolve("
+        struct Point { x: int32, y: int32 }
+        func main(point: &Point) {
+            point.x = 1
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::MutationThroughImmutable { .. })));
+
+    let (_, _, result) = resolve("
+        struct List<T> { value: T, next: List<T> }
+    ");
+    assert!(matches!(result, Err(ResolutionError::RecursiveType { .. })));
+}
+
+#[test]
+fn trait_static_dispatch() {
+    const SOURCE: &str = "
+        trait Eq {
+            func equals(a: &Self, b: &Self): bool
+        }
+        struct Point { x: int32, y: int32 }
+        impl Eq for int32 {
+            func equals(a: &int32, b: &Self): bool => *a == *b
+        }
+        impl Eq for Point {
+            func equals(a: &Point, b: &Point): bool => match Eq.equals(&a.x, &b.x) {
+                true => Eq.equals(&a.y, &b.y),
+                false => false
+            }
+        }
+        func same<T: Eq>(a: T, b: T): bool => Eq.equals(&a, &b)
+        func main(): bool => same(Point(1, 2), Point(1, 2))
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Trait program should resolve.");
+
+    let same = session.get_ref_target(ast.items[4].id).unwrap();
+    assert_eq!(session.get_instances(same).len(), 1);
+    assert_eq!(session.get_type(returned_expression(&ast.items[5]).id), Some(RamenType::Bool));
+}
+
+#[test]
+fn trait_method_call() {
+    const SOURCE: &str = "
+        trait Eq {
+            func equals(a: &Self, b: &Self): bool
+        }
+        struct Point { x: int32, y: int32 }
+        impl Eq for int32 {
+            func equals(a: &int32, b: &int32): bool => *a == *b
+        }
+        impl Eq for Point {
+            func equals(a: &Point, b: &Point): bool => match a.x.equals(&b.x) {
+                true => a.y.equals(&b.y),
+                false => false
+            }
+        }
+        func main(): bool {
+            let a = Point(1, 2)
+            let b = &a
+            return a.equals(b) && b.equals(&a)
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Trait method calls should resolve.");
+    assert_eq!(session.get_type(returned_expression(&ast.items[4]).id), Some(RamenType::Bool));
+
+    let (_, _, result) = resolve("
+        trait Eq { func equals(a: &Self, b: &Self): bool }
+        trait Same { func equals(a: &Self, b: &Self): bool }
+        impl Eq for int32 { func equals(a: &int32, b: &int32): bool => true }
+        impl Same for int32 { func equals(a: &int32, b: &int32): bool => true }
+        func main(): bool {
+            let a = 1
+            return a.equals(&a)
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::AmbiguousMethod { traits, .. }) if traits == ["Eq", "Same"]));
+}
+
+#[test]
+fn trait_errors() {
+    let (_, _, result) = resolve("
+        trait Eq { func equals(a: &Self, b: &Self): bool }
+        trait Show { func show(value: &Self); func debug(value: &Self) }
+        impl Show for int32 {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::MissingTraitMethods { missing, .. }) if missing == ["debug", "show"]));
+
+    let (_, _, result) = resolve("
+        trait Eq { func equals(a: &Self, b: &Self): bool }
+        impl Eq for int32 { func equals(a: &int32, b: &int32): bool => true }
+        impl Eq for int32 { func equals(a: &int32, b: &int32): bool => false }
+    ");
+    assert!(matches!(result, Err(ResolutionError::ConflictingImpl { ty: RamenType::Integer(32), .. })));
+
+    let (_, _, result) = resolve("
+        trait Eq { func equals(a: &Self, b: &Self): bool }
+        func same<T: Eq>(a: T, b: T): bool => Eq.equals(&a, &b)
+        func main(): bool => same(true, false)
+    ");
+    assert!(matches!(result, Err(ResolutionError::MissingImpl { ty: RamenType::Bool, .. })));
+
+    let (_, _, result) = resolve("
+        trait Eq { func equals(a: &Self, b: &Self): bool }
+        func same<T>(a: T, b: T): bool => Eq.equals(&a, &b)
+    ");
+    assert!(matches!(result, Err(ResolutionError::MissingImpl { .. })));
+
+    let (_, _, result) = resolve("
+        trait Eq { func equals(a: &Self, b: &Self): bool }
+        impl Eq for int32 { func equals(a: int32, b: int32): bool => true }
+    ");
+    assert!(matches!(result, Err(ResolutionError::TypeMismatch { .. })));
+
+    let (_, _, result) = resolve("
+        trait Eq { func equals(a: &Self, b: &Self): bool }
+        impl Eq for int32 {
+            func equals(a: &int32, b: &int32): bool => true
+            func hash(a: &int32): int32 => 0
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::NotTraitMember { .. })));
+
+    let (_, _, result) = resolve("
+        struct Point { x: int32, y: int32 }
+        func same<T: Point>(a: T, b: T) {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::NotATrait { .. })));
+}
+
+#[test]
+fn trait_objects() {
+    const SOURCE: &str = "
+        trait Show {
+            func show(value: &Self): int32
+        }
+        struct Point { x: int32, y: int32 }
+        impl Show for Point {
+            func show(value: &Point): int32 => value.x
+        }
+        func show_twice(value: &dyn Show): int32 => Show.show(value)
+        func main(): int32 {
+            let point = Point(1, 2)
+            let object: &dyn Show = &point
+            return show_twice(&point)
+        }
+        func show_once(value: &dyn Show): int32 => value.show()
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Trait object program should resolve.");
+    assert_eq!(session.get_type(returned_expression(&ast.items[4]).id), Some(RamenType::Integer(32)));
+    assert_eq!(session.get_type(returned_expression(&ast.items[5]).id), Some(RamenType::Integer(32)));
+}
+
+#[test]
+fn trait_object_errors() {
+    let (_, _, result) = resolve("
+        trait Clone { func clone(value: &Self): Self }
+        func main(value: &dyn Clone) {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::NotObjectSafe { .. })));
+
+    let (_, _, result) = resolve("
+        trait Visit { func visit<T>(value: &Self, other: T) }
+        func main(value: &dyn Visit) {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::NotObjectSafe { .. })));
+
+    let (_, _, result) = resolve("
+        trait Show { func show(value: &Self) }
+        func main(value: dyn Show) {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::UnsizedTraitObject { .. })));
+
+    let (_, _, result) = resolve("
+        trait Show { func show(value: &Self) }
+        func main() {
+            let object: &dyn Show = &true
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::TypeMismatch { .. })));
+}
+
+#[test]
+fn inherent_methods() {
+    const SOURCE: &str = "
+        struct Point { x: int32, y: int32 }
+        struct Line { start: Point, end: Point }
+        impl Point {
+            func new(x: int32, y: int32): Self => Point(x, y)
+            func first(&self): int32 => self.x
+            func reset(&mut self, x: int32) {
+                self.x = x
+            }
+            func swapped(self): Point => Point.new(self.y, self.x)
+        }
+        func main(line: &Line): int32 {
+            let point = Point.new(1, 2)
+            point.reset(3)
+            let swapped = line.start.swapped()
+            return point.swapped().first()
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Inherent methods should resolve.");
+    assert_eq!(session.get_type(returned_expression(&ast.items[3]).id), Some(RamenType::Integer(32)));
+}
+
+#[test]
+fn inherent_method_errors() {
+    let (_, _, result) = resolve("
+        struct Point { x: int32 }
+        impl Point { func first(&self): int32 => self.x }
+        func main(point: Point): int32 => point.second()
+    ");
+    assert!(matches!(result, Err(ResolutionError::NoMethod { .. })));
+
+    let (_, _, result) = resolve("
+        enum Shape { Circle, Square }
+        impl Shape { func Circle(&self) {} }
+    ");
+    assert!(matches!(result, Err(ResolutionError::DuplicateMember { .. })));
+
+    let (_, _, result) = resolve("
+        impl int32 { func zero(): int32 => 0 }
+    ");
+    assert!(matches!(result, Err(ResolutionError::InvalidImplTarget { .. })));
+
+    let (_, _, result) = resolve("
+        struct Point { x: int32 }
+        impl Point {
+            func reset(&mut self) {
+                self.x = 0
+            }
+        }
+        func main(point: &Point) {
+            point.reset()
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::MutationThroughImmutable { .. })));
+}
+
+#[test]
+fn closures_and_function_values() {
+    const SOURCE: &str = "
+        func apply(f: func(int32): int32, value: int32): int32 => f(value)
+        func same(x: int32): int32 => x
+        func main(): int32 {
+            let offset = 5
+            let first = apply(same, 1)
+            let pick = |x: int32| offset
+            let equals: func(int32): bool = |x| x == offset
+            let copied = move || pick(offset)
+            return apply(|x| x, 2)
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Closures should resolve.");
+
+    let ast::ItemKind::Function(function) = &ast.items[2].kind else { panic!("Expected function item.") };
+    let capture_count = |index: usize| {
+        let ast::StatementKind::Let { value, .. } = &function.body.as_ref().unwrap().statements[index].kind else {
+            panic!("Expected let statement.")
+        };
+        session.get_captures(value.id).len()
+    };
+    assert_eq!(capture_count(2), 1);
+    assert_eq!(capture_count(4), 2);
+    assert_eq!(session.get_type(returned_expression(&ast.items[2]).id), Some(RamenType::Integer(32)));
+}
+
+#[test]
+fn closure_errors() {
+    let (_, _, result) = resolve("
+        func main() {
+            let identity = |x| x
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::TypeAnnotationsNeeded { .. })));
+
+    let (_, _, result) = resolve("
+        func apply(f: func(int32): int32): int32 => f(1)
+        func main(): int32 => apply(|x: bool| 1)
+    ");
+    assert!(matches!(result, Err(ResolutionError::TypeMismatch { .. })));
+
+    let (_, _, result) = resolve("
+        func main(f: func(int32)) {
+            f(true)
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::TypeMismatch { .. })));
+
+    let (_, _, result) = resolve("
+        func adder(offset: int32): func(int32): int32 => |x| x + offset
+    ");
+    assert!(matches!(result, Err(ResolutionError::EscapingClosure { .. })));
+
+    let (_, _, result) = resolve("
+        func adder(offset: int32): func(int32): int32 {
+            let add = |x: int32| x + offset
+            return add
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::EscapingClosure { .. })));
+
+    let (_, _, result) = resolve("
+        func adder(offset: int32): (func(int32): int32, int32) {
+            return (|x| x + offset, 1)
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::EscapingClosure { .. })));
+
+    let (_, _, result) = resolve("
+        enum Holder { Has(func(int32): int32), Empty }
+        func adder(offset: int32): Holder {
+            return Holder.Has(|x| x + offset)
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::EscapingClosure { .. })));
+
+    let (_, _, result) = resolve("
+        func adder(offset: int32): func(int32): int32 {
+            return match offset { _ => |x| x + offset }
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::EscapingClosure { .. })));
+
+    let (_, _, result) = resolve("
+        func adder(offset: int32): func(int32): int32 {
+            let (add, count) = (|x: int32| x + offset, 1)
+            return add
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::EscapingClosure { .. })));
+
+    let (_, _, result) = resolve("
+        func adder(offset: int32): func(int32): int32 {
+            let add = |x: int32| x + offset
+            return move |x| add(x)
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::EscapingClosure { .. })));
+
+    let (_, _, result) = resolve("
+        func adder(offset: int32): func(int32): int32 => move |x| x + offset
+    ");
+    result.expect("Returned move closure should resolve.");
+
+    let (_, _, result) = resolve("
+        func adder(offset: int32): (func(int32): int32, int32) {
+            let add = move |x: int32| x + offset
+            return (move |x| add(x), offset)
+        }
+    ");
+    result.expect("Move closures capturing move closures should resolve.");
+}
+
+#[test]
+fn extern_functions() {
+    const SOURCE: &str = "
+        @extern(\"C\") func abs(value: int32): int32
+        @extern(\"C\") func printf(format: *int8, ...): int32
+        func main(): int32 {
+            let text: [int8; 3] = [104, 105, 0]
+            printf(&text[0], 1, 2.5, true)
+            return abs(3)
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Extern functions should resolve.");
+
+    let abs = session.get_ref_target(ast.items[0].id).unwrap();
+    assert_eq!(session.get_symbol(abs).as_deref(), Some("abs"));
+    let printf = session.get_ref_target(ast.items[1].id).unwrap();
+    assert!(matches!(session.get_type(printf), Some(RamenType::Callable(callable)) if callable.is_vararg));
+}
+
+#[test]
+fn extern_function_errors() {
+    let (_, _, result) = resolve("
+        func missing(): int32
+    ");
+    assert!(matches!(result, Err(ResolutionError::MissingFunctionBody { .. })));
+
+    let (_, _, result) = resolve("
+        @extern(\"C\") func defined(): int32 => 1
+    ");
+    assert!(matches!(result, Err(ResolutionError::InvalidExternFunction { .. })));
+
+    let (_, _, result) = resolve("
+        @extern(\"Rust\") func foreign()
+    ");
+    assert!(matches!(result, Err(ResolutionError::UnsupportedAbi { .. })));
+
+    let (_, _, result) = resolve("
+        func sum(first: int32, ...): int32 => first
+    ");
+    assert!(matches!(result, Err(ResolutionError::InvalidVararg { .. })));
+
+    let (_, _, result) = resolve("
+        struct Point { x: int32, y: int32 }
+        @extern(\"C\") func draw(point: Point)
+    ");
+    assert!(matches!(result, Err(ResolutionError::NotFfiSafe { .. })));
+
+    let (_, _, result) = resolve("
+        @extern(\"C\") func printf(format: *int8, ...): int32
+        func main(format: *int8) {
+            printf(format, (1, 2))
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::NotFfiSafe { .. })));
+}
+
+#[test]
+fn exported_functions() {
+    const SOURCE: &str = "
+        @export func first(a: int32, b: int32): int32 => a
+        @no_mangle func is_zero(value: int8): bool => value == 0
+        func main(): int32 => first(1, 2)
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Exported functions should resolve.");
+
+    let symbol = |index: usize| session.get_symbol(session.get_ref_target(ast.items[index].id).unwrap());
+    assert_eq!(symbol(0).as_deref(), Some("first"));
+    assert_eq!(symbol(1).as_deref(), Some("is_zero"));
+    assert_eq!(symbol(2).as_deref(), Some("main.main"));
+
+    let (_, _, result) = resolve("
+        @export func identity<T>(value: T): T => value
+    ");
+    assert!(matches!(result, Err(ResolutionError::InvalidExport { .. })));
+
+    let (_, _, result) = resolve("
+        @extern(\"C\") func divide(numerator: int32, denominator: int32): (int32, int32)
+        @export func pair(): (int32, (float64, bool)) => (1, (2.0, true))
+    ");
+    result.expect("Tuples should be returned from C functions.");
+
+    let (_, _, result) = resolve("
+        @export func first(pair: (int32, int32)): int32 => pair.0
+    ");
+    assert!(matches!(result, Err(ResolutionError::NotFfiSafe { .. })));
+
+    let (_, _, result) = resolve("
+        @export func pair(): (int32, [int32; 2]) => (1, [2, 3])
+    ");
+    assert!(matches!(result, Err(ResolutionError::NotFfiSafe { .. })));
+
+    let (_, _, result) = resolve("
+        mod a { @export func run(): int32 => 1 }
+        mod b { @no_mangle func run(): int32 => 2 }
+    ");
+    assert!(matches!(result, Err(ResolutionError::DuplicateSymbol { symbol, .. }) if symbol == "run"));
+
+    let (_, _, result) = resolve("
+        mod a { @extern(\"C\") func abs(value: int32): int32 }
+        mod b { @extern(\"C\") func abs(value: int32): int32 }
+        @export func abs(value: int32): int32 => value
+    ");
+    assert!(matches!(result, Err(ResolutionError::DuplicateSymbol { symbol, .. }) if symbol == "abs"));
+
+    let (_, _, result) = resolve("
+        @extern(\"C\") @export func abs(value: int32): int32
+    ");
+    assert!(matches!(result, Err(ResolutionError::InvalidExternFunction { .. })));
+}
+
+#[test]
+fn attributes_with_arguments() {
+    const SOURCE: &str = "
+        @inline(always)
+        @deprecated(note = \"Use `larger` instead.\")
+        func max(a: int32, b: int32): int32 => a
+
+        @deprecated
+        struct Legacy { value: int32 }
+
+        @inline
+        func main(): int32 {
+            let legacy = Legacy(1)
+            return max(legacy.value, 2)
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Attributes should be valid.");
+
+    let max = session.get_ref_target(ast.items[0].id).unwrap();
+    assert_eq!(session.get_attributes(max), vec![
+        KnownAttribute::Inline(InlineMode::Always),
+        KnownAttribute::Deprecated { note: Some("Use `larger` instead.".to_string()) }
+    ]);
+    let main = session.get_ref_target(ast.items[2].id).unwrap();
+    assert!(session.has_attribute(main, &KnownAttribute::Inline(InlineMode::Hint)));
+    assert_eq!(session.warnings.get(), 2);
+}
+
+#[test]
+fn attribute_errors() {
+    let (_, _, result) = resolve("
+        @fast
+        func main() {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::UnknownAttribute { .. })));
+
+    let (_, _, result) = resolve("
+        @inline
+        struct Point { x: int32, y: int32 }
+    ");
+    assert!(matches!(result, Err(ResolutionError::MisplacedAttribute { .. })));
+
+    let (_, _, result) = resolve("
+        @inline(sometimes)
+        func main() {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::InvalidAttributeArguments { .. })));
+
+    let (_, _, result) = resolve("
+        @deprecated(\"Use something else.\")
+        func main() {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::InvalidAttributeArguments { .. })));
+
+    let (_, _, result) = resolve("
+        @cold(always)
+        func main() {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::InvalidAttributeArguments { .. })));
+    let (_, _, result) = resolve("
+        @inline @inline(never)
+        func main() {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::ConflictingAttribute { name, .. }) if name == "inline"));
+
+    let (_, _, result) = resolve("
+        @noreturn func fail(code: int32): int32 => code
+    ");
+    assert!(matches!(result, Err(ResolutionError::NoReturnReturns { .. })));
+
+    let (_, _, result) = resolve("
+        @extern @noreturn func abort()
+        @noreturn func fail(code: int32) {
+            match code {
+                0 => abort(),
+                _ => ()
+            }
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::NoReturnReturns { .. })));
+
+    let (_, _, result) = resolve("
+        @extern @noreturn func abort()
+        @noreturn func fail(code: int32): int32 {
+            match code {
+                0 => abort(),
+                _ => abort()
+            }
+            return code
+        }
+    ");
+    result.expect("Function calling `@noreturn` function on every path should resolve.");
+}
+
+#[test]
+fn nested_modules() {
+    const SOURCE: &str = "
+        mod geometry {
+            pub struct Point { pub x: int32, y: int32 }
+
+            pub mod shapes {
+                pub func origin(): geometry.Point => geometry.Point(0, 0)
+            }
+        }
+
+        mod app {
+            func run(): int32 {
+                let point = geometry.shapes.origin()
+                return point.x
+            }
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Nested modules should resolve.");
+
+    let ast::ItemKind::Module(geometry) = &ast.items[0].kind else { panic!("Expected module item.") };
+    let ast::ItemKind::Module(shapes) = &geometry.items[1].kind else { panic!("Expected module item.") };
+    let origin = session.get_ref_target(shapes.items[0].id).unwrap();
+    assert_eq!(session.get_symbol(origin).as_deref(), Some("main.geometry.shapes.origin"));
+
+    let ast::ItemKind::Module(app) = &ast.items[1].kind else { panic!("Expected module item.") };
+    let run = session.get_ref_target(app.items[0].id).unwrap();
+    assert_eq!(session.get_symbol(run).as_deref(), Some("main.app.run"));
+
+    let (_, _, result) = resolve("
+        mod geometry {
+            func area(): int32 => 0
+        }
+        func main(): int32 => geometry.volume()
+    ");
+    assert!(matches!(result, Err(ResolutionError::UnresolvedName { .. })));
+}
+
+#[test]
+fn file_modules() {
+    let root = std::env::temp_dir().join(format!("ramen-file-modules-{}", std::process::id()));
+    let write = |path: &str, text: &str| {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    };
+    write("main.rm", "
+        mod shapes
+        mod util
+        func main(): int32 => util.inner.sides(shapes.square())
+    ");
+    write("shapes.rm", "
+        pub struct Shape { pub sides: int32 }
+        pub func square(): Shape => Shape(4)
+    ");
+    write("util/mod.rm", "pub mod inner");
+    write("util/inner.rm", "pub func sides(shape: shapes.Shape): int32 => shape.sides");
+
+    let session = Session::new();
+    let ast = load::load_module_tree(&session, "main".to_string(), &root.join("main.rm")).expect("Module tree should load.");
+    run_passes(&session, &ast).expect("File modules should resolve.");
+    assert_eq!(session.sources.borrow().len(), 4);
+
+    let ast::ItemKind::Module(util) = &ast.items[1].kind else { panic!("Expected module item.") };
+    let ast::ItemKind::Module(inner) = &util.items[0].kind else { panic!("Expected module item.") };
+    let sides = session.get_ref_target(inner.items[0].id).unwrap();
+    assert_eq!(session.get_symbol(sides).as_deref(), Some("main.util.inner.sides"));
+
+    write("missing.rm", "mod nowhere");
+    let result = load::load_module_tree(&Session::new(), "main".to_string(), &root.join("missing.rm"));
+    assert!(matches!(result, Err(LoadError::MissingModuleFile { name, candidates, .. }) if name == "nowhere" && candidates.len() == 2));
+
+    write("cycle.rm", "mod cycle");
+    let result = load::load_module_tree(&Session::new(), "cycle".to_string(), &root.join("cycle.rm"));
+    assert!(matches!(result, Err(LoadError::ModuleCycle { name, .. }) if name == "cycle"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn use_imports() {
+    const SOURCE: &str = "
+        mod geometry {
+            mod shapes {
+                pub struct Circle { pub radius: int32 }
+                pub func area(circle: Circle): int32 => circle.radius
+            }
+            pub use shapes.{Circle, area as circle_area}
+        }
+        mod colors {
+            pub enum Color { Red, Green }
+            pub func red(): Color => Color.Red
+            pub func green(): Color => Color.Green
+        }
+
+        use geometry.Circle
+        use colors.*
+        use colors.green as unused
+
+        func value(): int32 => 2
+        func main(): int32 {
+            let color: Color = red()
+            return geometry.circle_area(Circle(value()))
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Imports should resolve.");
+    assert_eq!(session.warnings.get(), 1);
+
+    let ast::ExpressionKind::Call(callee, arguments) = &returned_expression(&ast.items[6]).kind else { panic!("Expected call.") };
+    let ast::ItemKind::Module(geometry) = &ast.items[0].kind else { panic!("Expected module item.") };
+    let ast::ItemKind::Module(shapes) = &geometry.items[0].kind else { panic!("Expected module item.") };
+    let area = session.get_ref_target(shapes.items[1].id).unwrap();
+    assert_eq!(session.get_ref_target(callee.id), Some(area));
+    let circle = session.get_ref_target(shapes.items[0].id).unwrap();
+    let Some(RamenType::Struct(argument)) = session.get_type(arguments[0].id) else { panic!("Expected struct argument.") };
+    assert_eq!(argument.def_id, circle);
+
+    // Definitions shadow glob imports of the same name.
+    let (session, ast, result) = resolve("
+        mod a { pub func value(): int32 => 1 }
+        use a.*
+        func value(): int32 => 2
+        func main(): int32 => value()
+    ");
+    result.expect("Shadowed glob import should resolve.");
+    let ast::ExpressionKind::Call(callee, _) = &returned_expression(&ast.items[3]).kind else { panic!("Expected call.") };
+    assert_eq!(session.get_ref_target(callee.id), session.get_ref_target(ast.items[2].id));
+
+    let (_, _, result) = resolve("
+        mod a { func f(): int32 => 1 }
+        use a.g
+    ");
+    assert!(matches!(result, Err(ResolutionError::UnresolvedImport { name, .. }) if name == "g"));
+
+    let (_, _, result) = resolve("
+        mod a { pub func f(): int32 => 1 }
+        mod b { pub func f(): int32 => 2 }
+        use a.*
+        use b.*
+        func main(): int32 => f()
+    ");
+    assert!(matches!(result, Err(ResolutionError::AmbiguousName { name, .. }) if name == "f"));
+
+    let (_, _, result) = resolve("
+        mod a { pub func f(): int32 => 1 }
+        use a.f
+        func f(): int32 => 2
+    ");
+    assert!(matches!(result, Err(ResolutionError::ConflictingImport { name, .. }) if name == "f"));
+
+    let (_, _, result) = resolve("
+        mod a { pub use b.x }
+        mod b { pub use a.x }
+    ");
+    assert!(matches!(result, Err(ResolutionError::ImportCycle { name, .. }) if name == "x"));
+
+    // Imports are private unless made public with `pub use`.
+    let (_, _, result) = resolve("
+        mod b { pub func f(): int32 => 1 }
+        mod a { use b.f }
+        func main(): int32 => a.f()
+    ");
+    assert!(matches!(result, Err(ResolutionError::PrivateAccess { name, .. }) if name == "f"));
+}
+
+#[test]
+fn privacy() {
+    const SOURCE: &str = "
+        mod bank {
+            pub struct Account { pub owner: int32, balance: int32 }
+            impl Account {
+                pub func open(owner: int32): Account => Account(owner, 0)
+                pub func balance(&self): int32 => self.balance
+                func audit(&self): int32 => self.balance
+            }
+
+            mod ledger {
+                // Private items of enclosing modules are visible in nested ones.
+                pub(module) func total(account: &bank.Account): (int32, int32) => (account.balance, account.audit())
+            }
+            pub(module) use ledger.total
+        }
+
+        func main(): (int32, int32, (int32, int32)) {
+            let account = bank.Account.open(1)
+            return (account.owner, account.balance(), bank.total(&account))
+        }
+    ";
+    let (_, _, result) = resolve(SOURCE);
+    result.expect("Accesses to visible items should resolve.");
+
+    let private_access = |source: &str, expected: &str| {
+        let (_, _, result) = resolve(source);
+        let Err(ResolutionError::PrivateAccess { name, location, definition }) = result else { panic!("Expected private access, got {result:?}.") };
+        assert_eq!(name, expected);
+        assert!(definition.span.end <= location.span.start, "Definition site should be reported.");
+    };
+    private_access("
+        mod bank { func secret(): int32 => 1 }
+        func main(): int32 => bank.secret()
+    ", "secret");
+    private_access("
+        mod bank { pub struct Account { owner: int32 } }
+        func main(account: bank.Account): int32 => account.owner
+    ", "owner");
+    private_access("
+        mod bank { pub struct Account { pub owner: int32, balance: int32 } }
+        func main(): bank.Account => bank.Account(1, 0)
+    ", "balance");
+    private_access("
+        mod bank {
+            pub struct Account { pub owner: int32 }
+            impl Account { func audit(&self): int32 => self.owner }
+        }
+        func main(account: bank.Account): int32 => account.audit()
+    ", "audit");
+    private_access("
+        mod bank {
+            mod ledger { pub func total(): int32 => 0 }
+        }
+        func main(): int32 => bank.ledger.total()
+    ", "ledger");
+
+    // Imports cannot make definitions more visible than they are.
+    let (_, _, result) = resolve("
+        mod bank {
+            func secret(): int32 => 1
+            pub use secret as open
+        }
+        func main(): int32 => bank.open()
+    ");
+    assert!(matches!(result, Err(ResolutionError::PrivateReexport { name, .. }) if name == "secret"));
+
+    let (_, _, result) = resolve("
+        mod bank {
+            mod ledger { pub(module) func total(): int32 => 0 }
+            pub use ledger.total
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::PrivateReexport { name, .. }) if name == "total"));
+
+    // Signatures of functions cannot name types less visible than the functions themselves.
+    let private_interface = |source: &str, expected: &str| {
+        let (_, _, result) = resolve(source);
+        let Err(ResolutionError::PrivateTypeInPublicInterface { name, location, definition }) = result else {
+            panic!("Expected private type in public interface, got {result:?}.")
+        };
+        assert_eq!(name, expected);
+        assert!(definition.span.end <= location.span.start, "Definition site should be reported.");
+    };
+    private_interface("
+        mod bank {
+            struct Secret { code: int32 }
+            pub func reveal(): Secret => Secret(1)
+        }
+    ", "Secret");
+    private_interface("
+        mod bank {
+            enum Level { Low, High }
+            pub(module) func raise(levels: &[(int32, Level)]) {}
+        }
+    ", "Level");
+    private_interface("
+        mod bank {
+            trait Audit { func audit(value: &Self): int32 }
+            pub struct Account { pub owner: int32 }
+            impl Account {
+                pub func check(&self, auditor: &dyn Audit): int32 => self.owner
+            }
+        }
+    ", "Audit");
+
+    let (_, _, result) = resolve("
+        mod bank {
+            struct Secret { code: int32 }
+            pub(module) struct Vault { code: int32 }
+            func reveal(): Secret => Secret(1)
+            pub(module) func open(): Vault => Vault(2)
+            impl Secret {
+                pub func new(): Secret => Secret(3)
+            }
+        }
+    ");
+    result.expect("Functions as visible as the types they name should resolve.");
+
+    // Glob imports bring in only the visible members.
+    let (_, _, result) = resolve("
+        mod bank { func secret(): int32 => 1 }
+        use bank.*
+        func main(): int32 => secret()
+    ");
+    assert!(matches!(result, Err(ResolutionError::UnresolvedName { name, .. }) if name == "secret"));
+
+    let session = Session::new();
+    let mut tokens = lex::Tokens::from_string("pub(crate) func main(): int32 => 0", SourceId::dummy(), &session.node_ids);
+    let result = parse::parse_ramen("main".to_string(), &mut tokens);
+    assert!(matches!(result, Err(SyntaxError::InvalidVisibility { .. })));
+}
+
+#[test]
+fn constants() {
+    const SOURCE: &str = "
+        func main(): [int32; COUNT] => [FIRST; COUNT]
+        const COUNT: int64 = LIMITS.1
+        const LIMITS: (int64, int64) = (1, SIZES[1])
+        const SIZES: [int64; 2] = [2, 4]
+        const FIRST: int32 = match (ORIGIN.x, ORIGIN.y) {
+            (x, 0) => x,
+            _ => 0
+        }
+        const ORIGIN: Point = Point(3, 0)
+        const SMALLEST: int8 = -128
+        static mut COUNTER: int32 = FIRST
+        struct Point { x: int32, y: int32 }
+
+        func bump(): int32 {
+            COUNTER = ORIGIN.x
+            return COUNTER
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Constants should be evaluated.");
+
+    assert_eq!(
+        session.get_type(returned_expression(&ast.items[0]).id),
+        Some(RamenType::Array(Box::new(RamenType::Integer(32)), 4))
+    );
+    let constant = |index: usize| session.get_constant(session.get_ref_target(ast.items[index].id).unwrap());
+    assert_eq!(constant(4), Some(ConstValue::Integer(3)));
+    assert_eq!(constant(6), Some(ConstValue::Integer(-128)));
+    assert_eq!(constant(7), Some(ConstValue::Integer(3)));
+}
+
+#[test]
+fn constant_errors() {
+    let (_, _, result) = resolve("
+        const A: int32 = B
+        const B: int32 = A
+    ");
+    assert!(matches!(result, Err(ResolutionError::ConstantCycle { .. })), "Expected constant cycle, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        const A: int32 = one()
+        func one(): int32 => 1
+    ");
+    assert!(matches!(result, Err(ResolutionError::NotConstant { what: "Calls of functions that are not `const`", .. })), "Expected non-constant, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        static VALUE: int32 = 1
+        const A: int32 = VALUE
+    ");
+    assert!(matches!(result, Err(ResolutionError::NotConstant { what: "Statics", .. })), "Expected non-constant, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        const SIZES: [int32; 2] = [1, 2]
+        const A: int32 = SIZES[2]
+    ");
+    assert!(matches!(result, Err(ResolutionError::EvaluationFailed { .. })), "Expected failed evaluation, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        const LENGTH: int8 = -56
+        func main(value: [int32; LENGTH]) {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::InvalidArrayLength { value: -56, .. })), "Expected invalid length, got {result:?}.");
+
+    let (_, _, result) = resolve("const OVERFLOW: int8 = 200");
+    assert!(matches!(result, Err(ResolutionError::LiteralOutOfRange { value: 200, .. })), "Expected literal out of range, got {result:?}.");
+
+    let (_, _, result) = resolve("const OVERFLOW: int8 = -129");
+    assert!(matches!(result, Err(ResolutionError::LiteralOutOfRange { value: -129, .. })), "Expected literal out of range, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        static VALUE: int32 = 1
+        func main() {
+            VALUE = 2
+        }
+    ");
+    assert!(matches!(&result, Err(ResolutionError::ImmutableStatic { name, .. }) if name == "VALUE"), "Expected immutable static, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        const VALUE: int32 = 1
+        func main() {
+            VALUE = 2
+        }
+    ");
+    assert!(matches!(result, Err(ResolutionError::InvalidAssignmentTarget { .. })), "Expected invalid assignment, got {result:?}.");
+
+    let (_, _, result) = resolve("func main<T>() { const VALUE: [T; 0] = [] }");
+    assert!(matches!(result, Err(ResolutionError::OuterTypeParameter { .. })), "Expected outer type parameter, got {result:?}.");
+}
+
+#[test]
+fn const_functions() {
+    const SOURCE: &str = "
+        const FACTORIAL: int64 = factorial(10)
+        const SIGNS: (int8, bool, bool) = (-128, !(1 < 2) || 3 >= 3, 7 % -2 == 1 && -7 / 2 == -3)
+        const HALF: float32 = 1.0 / 3.0
+        const TABLE: [int32; side(3) * 2] = [square(Point(1, 2)); 6]
+        struct Point { x: int32, y: int32 }
+
+        const func factorial(n: int64): int64 => match n {
+            0 => 1,
+            n => n * factorial(n - 1)
+        }
+        const func side(n: int64): int64 {
+            let double = n + n
+            return double - n
+        }
+        const func square(point: Point): int32 {
+            let (x, y) = (point.x, point.y)
+            return x * x + y * y
+        }
+        func main(value: int64): bool => value < FACTORIAL && (value > 1) == (side(1) <= 2)
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Constant functions should be evaluated.");
+
+    let constant = |index: usize| session.get_constant(session.get_ref_target(ast.items[index].id).unwrap());
+    assert_eq!(constant(0), Some(ConstValue::Integer(3628800)));
+    assert_eq!(constant(1), Some(ConstValue::Aggregate(vec![ConstValue::Integer(-128), ConstValue::Bool(true), ConstValue::Bool(true)])));
+    assert_eq!(constant(2), Some(ConstValue::Float((1.0f32 / 3.0) as f64)));
+    assert_eq!(constant(3), Some(ConstValue::Aggregate(vec![ConstValue::Integer(5); 6])));
+}
+
+#[test]
+fn const_evaluation_errors() {
+    let (_, _, result) = resolve("const A: int8 = 100 + 28");
+    assert!(matches!(&result, Err(ResolutionError::ArithmeticOverflow { operator, .. }) if operator == "+"), "Expected overflow, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        const A: int32 = divide(1, 0)
+        const func divide(a: int32, b: int32): int32 => a / b
+    ");
+    assert!(matches!(result, Err(ResolutionError::DivisionByZero { .. })), "Expected division by zero, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        const A: int32 = twice(1)
+        const func twice(a: int32): int32 => a + one()
+        func one(): int32 => 1
+    ");
+    assert!(matches!(result, Err(ResolutionError::NotConstant { what: "Calls of functions that are not `const`", .. })), "Expected non-constant, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        const func forever(n: int32): int32 => forever(n)
+        const A: int32 = forever(1)
+    ");
+    assert!(matches!(result, Err(ResolutionError::EvaluationFailed { .. })), "Expected failed evaluation, got {result:?}.");
+
+    let mut session = Session::new();
+    session.options.const_eval_steps = 50;
+    let mut tokens = lex::Tokens::from_string("
+        const A: int32 = count(100)
+        const func count(n: int32): int32 => match n {
+            0 => 0,
+            n => 1 + count(n - 1)
+        }
+    ", SourceId::dummy(), &session.node_ids);
+    let ast = parse::parse_ramen("main".to_string(), &mut tokens).expect("Something went wrong during parsing");
+    let result = run_passes(&session, &ast);
+    assert!(matches!(&result, Err(ResolutionError::EvaluationFailed { reason, .. }) if reason.contains("50 steps")), "Expected failed evaluation, got {result:?}.");
+
+    // Integers are at most 128 bits wide, and literals have to fit into 64 of them.
+    let session = Session::new();
+    for source in ["const A: int256 = 1", "const A: int0 = 0"] {
+        let mut tokens = lex::Tokens::from_string(source, SourceId::dummy(), &session.node_ids);
+        let result = parse::parse_ramen("main".to_string(), &mut tokens);
+        assert!(matches!(result, Err(SyntaxError::InvalidIntegerWidth { .. })), "Expected invalid width, got {result:?}.");
+    }
+    let mut tokens = lex::Tokens::from_string("const A: int128 = 100000000000000000000", SourceId::dummy(), &session.node_ids);
+    let result = parse::parse_ramen("main".to_string(), &mut tokens);
+    assert!(matches!(result, Err(SyntaxError::IntegerLiteralTooLarge { .. })), "Expected too large literal, got {result:?}.");
+}
+
+#[test]
+fn static_assertions() {
+    const SOURCE: &str = "
+        @static_assert
+        const FITS: bool = SIZE * 2 <= 64
+        const SIZE: int64 = 16
+        const WIDE: int128 = 18446744073709551615 * 4
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Holding static assertion should resolve.");
+    let constant = |index: usize| session.get_constant(session.get_ref_target(ast.items[index].id).unwrap());
+    assert_eq!(constant(2), Some(ConstValue::Integer(18446744073709551615 * 4)));
+
+    let (_, _, result) = resolve("
+        @static_assert(note = \"Buffer has to fit into a page.\")
+        const FITS: bool = SIZE <= 4096
+        const SIZE: int64 = 8192
+    ");
+    assert!(matches!(&result, Err(ResolutionError::StaticAssertionFailed { note: Some(note), .. }) if note.contains("page")), "Expected failed assertion, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        @static_assert
+        const SIZE: int64 = 8192
+    ");
+    assert!(matches!(result, Err(ResolutionError::TypeMismatch { expected: RamenType::Bool, .. })));
+
+    let (_, _, result) = resolve("
+        @static_assert
+        func main() {}
+    ");
+    assert!(matches!(result, Err(ResolutionError::MisplacedAttribute { .. })));
+}
+
+#[test]
+fn type_aliases_and_newtypes() {
+    const SOURCE: &str = "
+        type Meters = float64
+        type Pair = (Meters, Meters)
+        newtype UserId = int64
+        newtype Wrapper<T> = T
+
+        impl UserId {
+            func next(self): UserId => UserId(self.0 + 1)
+        }
+
+        func distance(pair: Pair): Meters => pair.1 - pair.0
+        func main(id: UserId): (Meters, int64, Wrapper<bool>) {
+            let length: Meters = distance((1.0, 2.5))
+            return (length, id.next().0, Wrapper(true))
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Aliases and newtypes should be resolved.");
+
+    let def_type = |index: usize| session.get_type(session.get_ref_target(ast.items[index].id).unwrap());
+    assert_eq!(def_type(1), Some(RamenType::Tuple(vec![RamenType::Float(64), RamenType::Float(64)])));
+    assert!(matches!(def_type(2), Some(RamenType::Struct(struct_type)) if struct_type.name == "UserId"));
+    let Some(RamenType::Callable(distance)) = def_type(5) else { panic!("Expected function type.") };
+    assert_eq!(distance.return_type, RamenType::Float(64));
+}
+
+#[test]
+fn type_alias_errors() {
+    let (_, _, result) = resolve("
+        type Meters = float64
+        func main() {
+            let length: Meters = true
+        }
+    ");
+    assert!(
+        matches!(&result, Err(ResolutionError::TypeMismatch { expected: RamenType::Float(64), written: Some(written), .. }) if written.expected.as_deref().is_some_and(|expected| expected == "Meters")),
+        "Expected mismatch naming the alias, got {result:?}."
+    );
+
+    let (_, _, result) = resolve("
+        type Meters = float64
+        func main(): [&Meters; 2] => 1
+    ");
+    assert!(
+        matches!(&result, Err(ResolutionError::TypeMismatch { written: Some(written), .. }) if written.expected.as_deref().is_some_and(|expected| expected == "[&Meters; 2]")),
+        "Expected mismatch naming the alias, got {result:?}."
+    );
+
+    let (_, _, result) = resolve("
+        newtype UserId = int64
+        func main(id: int64): UserId => id
+    ");
+    assert!(matches!(result, Err(ResolutionError::TypeMismatch { written: None, .. })), "Expected mismatch, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        type Meters = float64
+        func main(length: Meters): bool => length
+    ");
+    assert!(
+        matches!(&result, Err(ResolutionError::TypeMismatch { found: RamenType::Float(64), written: Some(written), .. }) if written.found.as_deref().is_some_and(|found| found == "Meters")),
+        "Expected mismatch naming the alias of the parameter, got {result:?}."
+    );
+
+    let (_, _, result) = resolve("
+        type Meters = float64
+        func length(): Meters => 1.0
+        func main() {
+            let valid: bool = length()
+        }
+    ");
+    assert!(
+        matches!(&result, Err(ResolutionError::TypeMismatch { written: Some(written), .. }) if written.expected.is_none() && written.found.as_deref().is_some_and(|found| found == "Meters")),
+        "Expected mismatch naming the alias of the return type, got {result:?}."
+    );
+
+    let (_, _, result) = resolve("
+        type Tree = (int32, List)
+        type List = [Tree; 2]
+    ");
+    assert!(matches!(result, Err(ResolutionError::AliasCycle { .. })), "Expected alias cycle, got {result:?}.");
+
+    let (_, _, result) = resolve("type Loop = &Loop");
+    assert!(matches!(&result, Err(ResolutionError::AliasCycle { name, .. }) if name == "Loop"), "Expected alias cycle, got {result:?}.");
+}
+
+#[test]
+fn values_and_types_share_names() {
+    const SOURCE: &str = "
+        type Meters = float64
+        enum Shape { Empty, Square(Meters) }
+        const Meters: Meters = 2.5
+
+        func Shape(side: Meters): Shape => Shape.Square(side)
+        func main(Meters: Meters): (Shape, Meters) {
+            let Shape: Shape = Shape(Meters)
+            return (Shape, Meters)
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Values should not clash with types of the same name.");
+
+    let def = |index: usize| session.get_ref_target(ast.items[index].id).unwrap();
+    let ast::ExpressionKind::Call(callee, _) = &returned_expression(&ast.items[3]).kind else { panic!("Expected call.") };
+    assert!(matches!(session.get_def(session.get_ref_target(callee.id).unwrap()), Some(Definition::Variant { parent, index: 1 }) if parent == def(1)));
+    assert_eq!(session.get_type(def(2)), Some(RamenType::Float(64)));
+    assert!(matches!(
+        session.get_type(returned_expression(&ast.items[4]).id),
+        Some(RamenType::Tuple(elements)) if matches!(&elements[..], [RamenType::Enum(_), RamenType::Float(64)])
+    ));
+}
+
+#[test]
+fn duplicate_definitions() {
+    let (_, _, result) = resolve("
+        func f(): int32 => 1
+        func f(): int32 => 2
+    ");
+    assert!(matches!(&result, Err(ResolutionError::DuplicateDefinition { name, .. }) if name == "f"), "Expected duplicate function, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        struct S { x: int32 }
+        struct S { y: int32 }
+    ");
+    assert!(matches!(&result, Err(ResolutionError::DuplicateDefinition { name, .. }) if name == "S"), "Expected duplicate struct, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        func Point(): int32 => 1
+        struct Point { x: int32 }
+    ");
+    assert!(matches!(&result, Err(ResolutionError::DuplicateDefinition { name, .. }) if name == "Point"), "Expected constructor clashing with function, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        mod geometry {
+            enum Shape { Circle }
+            type Shape = int32
+        }
+    ");
+    assert!(matches!(&result, Err(ResolutionError::DuplicateDefinition { name, .. }) if name == "Shape"), "Expected duplicate type, got {result:?}.");
+
+    let (_, _, result) = resolve("
+        mod a { func f(): int32 => 1 }
+        mod b { func f(): int32 => 2 }
+        func main(): int32 {
+            func f(): int32 => 3
+            return f()
+        }
+    ");
+    result.expect("Items of different scopes should be able to share names.");
+}
+
+#[test]
+fn scope_introspection() {
+    const SOURCE: &str = "
+        mod shapes {
+            pub enum Shape { Empty, Square(float64) }
+            pub func square(side: float64): Shape => Shape.Square(side)
+        }
+        use shapes.square
+        func main(): shapes.Shape {
+            let side = 2.0
+            return square(side)
+        }
+    ";
+    let (session, ast, result) = resolve(SOURCE);
+    result.expect("Program should resolve.");
+
+    let main_scope = session.scopes.get(session.get_ref_target(ast.items[2].id).unwrap()).unwrap();
+    assert_eq!(main_scope.names(Scope::NS_NAMES), vec!["side"]);
+    assert_eq!(main_scope.ancestors().map(|scope| scope.name.as_deref()).collect::<Vec<_>>(), vec![Some("main"), Some("main"), None]);
+
+    let visible = main_scope.visible_members(Scope::NS_NAMES).into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+    assert_eq!(visible, vec!["main", "shapes", "side", "square"]);
+    let shapes_scope = session.scopes.get(session.get_ref_target(ast.items[0].id).unwrap()).unwrap();
+    assert_eq!(shapes_scope.names(Scope::NS_TYPES), vec!["Shape"]);
+
+    let dump = session.scopes.dump();
+    let dump = dump.lines().map(|line| line.split(" DEF:").next().unwrap()).collect::<Vec<_>>();
+    assert_eq!(dump, vec![
+        "<anonymous>",
+        "  names: main",
+        "  main",
+        "    names: main, shapes, square",
+        "    shapes",
+        "      names: square",
+        "      types: Shape",
+        "      Shape",
+        "        names: Empty, Square",
+        "      square",
+        "        names: side",
+        "    main",
+        "      names: side"
+    ]);
+}
+
+#[test]
+fn ids_are_allocated_per_session() {
+    const SOURCE: &str = "
+        enum Shape { Empty, Square(float64) }
+        func area(shape: Shape): float64 => match shape {
+            Shape.Empty => 0.0,
+            Shape.Square(side) => side * side
+        }
+    ";
+    let (first, first_ast, result) = resolve(SOURCE);
+    result.expect("Program should resolve.");
+    let (second, second_ast, result) = resolve(SOURCE);
+    result.expect("Program should resolve.");
+
+    assert_eq!(first_ast.items[1].id, second_ast.items[1].id);
+    assert_eq!(first.get_ref_target(first_ast.items[1].id), second.get_ref_target(second_ast.items[1].id));
+    assert_eq!(first.scopes.dump(), second.scopes.dump());
+}
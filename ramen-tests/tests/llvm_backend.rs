@@ -1,24 +1,635 @@
-use ramen_common::{ast::NodeId, scope::Scope, session::{Session, SourceId}, visitor::ASTPass};
-use ramen_frontend::{lex, parse, ast_pass::{binding, type_resolution}};
+use std::path::PathBuf;
+
+use ramen_backend_llvm::inkwell::context::Context;
+use ramen_common::{scope::Scope, session::{Session, SourceId}, visitor::ASTPass};
+use ramen_frontend::{lex, parse, ast_pass::{binding, exhaustiveness, name_resolution, type_resolution}};
 
 #[test]
 fn compile_function() {
-    const SOURCE: &'static str = "func identity(a: int32): int32 => 15";
-    let mut tokens = lex::Tokens::from_string(SOURCE, SourceId::dummy());
+    const SOURCE: &str = "func identity(a: int32): int32 => 15";
+    let session = Session::new();
+    let mut tokens = lex::Tokens::from_string(SOURCE, SourceId::dummy(), &session.node_ids);
     let ast = parse::parse_ramen("main".to_string(), &mut tokens).expect("Something went wrong during parsing");
 
-    let session = Session::new();
-    let module_id = NodeId::next();
+    let module_id = session.node_ids.next();
     let global_scope = Scope::new_ref(None, None);
 
     binding::ItemNameBindingPass::run_on_module(&session, global_scope.clone(), module_id, &ast)
         .expect("Something went wrong during item name binding pass.");
 
+    name_resolution::NameResolutionPass::run_on_module(&session, global_scope.clone(), module_id, &ast)
+        .expect("Something went wrong during name resolution pass.");
+
     type_resolution::TypeResolutionPass::run_on_module(&session, global_scope.clone(), module_id, &ast)
         .expect("Something went wrong during type resolution pass.");
 
-    ramen_backend_llvm::codegen::generate_llvm_module(&session, global_scope.clone(), module_id, &ast)
+    let context = Context::create();
+    ramen_backend_llvm::codegen::generate_llvm_module(&context, &session, global_scope.clone(), module_id, &ast)
         .expect("Failed to generate llvm module from AST.");
 
     panic!()
-}
\ No newline at end of file
+}
+
+/// Compiles the source and returns textual IR of the module.
+fn compile(source: &str) -> String {
+    let session = Session::new();
+    let source_id = session.add_source(PathBuf::from("main.rm"), source.to_string());
+    let mut tokens = lex::Tokens::from_string(source, source_id, &session.node_ids);
+    let ast = parse::parse_ramen("main".to_string(), &mut tokens).expect("Something went wrong during parsing");
+
+    let module_id = session.node_ids.next();
+    let global_scope = Scope::new_ref(None, None);
+
+    binding::ItemNameBindingPass::run_on_module(&session, global_scope.clone(), module_id, &ast)
+        .expect("Something went wrong during item name binding pass.");
+    name_resolution::NameResolutionPass::run_on_module(&session, global_scope.clone(), module_id, &ast)
+        .expect("Something went wrong during name resolution pass.");
+    type_resolution::TypeResolutionPass::run_on_module(&session, global_scope.clone(), module_id, &ast)
+        .expect("Something went wrong during type resolution pass.");
+    exhaustiveness::MatchCheckingPass::run_on_module(&session, global_scope.clone(), module_id, &ast)
+        .expect("Something went wrong during match checking pass.");
+
+    let context = Context::create();
+    let module = ramen_backend_llvm::codegen::generate_llvm_module(&context, &session, global_scope.clone(), module_id, &ast)
+        .expect("Failed to generate llvm module from AST.");
+    module.print_to_string().to_string()
+}
+
+/// Asserts that every snippet appears in the IR, printing the whole module otherwise.
+fn assert_ir_contains(ir: &str, snippets: &[&str]) {
+    for snippet in snippets {
+        assert!(ir.contains(snippet), "Expected `{snippet}` in IR:\n{ir}");
+    }
+}
+
+/// Text of the function definition with given symbol, from its signature to the closing brace.
+fn function_body<'a>(ir: &'a str, symbol: &str) -> &'a str {
+    let start = ir.find(&format!("{symbol}("))
+        .unwrap_or_else(|| panic!("Expected function `{symbol}` in IR:\n{ir}"));
+    let end = ir[start..].find("\n}\n").map_or(ir.len(), |end| start + end);
+    &ir[start..end]
+}
+
+#[test]
+fn compile_enum_constructors() {
+    let ir = compile("
+        enum Shape {
+            Circle(float64),
+            Rect(float64, float64),
+            Empty
+        }
+        func circle(radius: float64): Shape => Shape.Circle(radius)
+        func rect(): Shape => Shape.Rect(1.0, 2.5)
+        func empty(): Shape => Shape.Empty
+    ");
+    assert_ir_contains(&ir, &[
+        "define internal void @main.circle(",
+        "sret({ i8, [2 x i64] })",
+        "define internal void @main.empty("
+    ]);
+}
+
+#[test]
+fn compile_match() {
+    let ir = compile("
+        enum Shape {
+            Circle(float64),
+            Rect(float64, float64),
+            Empty
+        }
+        func width(shape: Shape): float64 => match shape {
+            Shape.Circle(radius) => radius,
+            Shape.Rect(width, _) => width,
+            Shape.Empty => 0.0
+        }
+        func classify(value: int32): int32 => match value {
+            0 | 1 => 10,
+            2..=9 => 20,
+            _ => 30
+        }
+        func unwrap_or(value: Option, default: int64): int64 => match value {
+            Option.Some(0) => default,
+            Option.Some(inner) => inner,
+            Option.None => default
+        }
+        enum Option { Some(int64), None }
+    ");
+    assert_ir_contains(function_body(&ir, "@main.width"), &["switch i8 %tag", "label %match.unreachable"]);
+    assert_ir_contains(function_body(&ir, "@main.classify"), &["icmp eq i32", "icmp sge i32", "icmp sle i32", "br i1"]);
+}
+
+#[test]
+fn compile_linked_list() {
+    let ir = compile("
+        struct Node { value: int64, next: *Node }
+        enum List { Cons(int64, &List), Nil }
+        func second(node: &Node): int64 => node.next.value
+        func length(list: &List): int64 => match *list {
+            List.Cons(_, rest) => 1 + length(rest),
+            List.Nil => 0
+        }
+    ");
+    // Field of the node behind the pointer is accessed with full layout of the struct.
+    assert_ir_contains(function_body(&ir, "@main.second"), &["getelementptr inbounds { i64, ", "load i64"]);
+    assert_ir_contains(function_body(&ir, "@main.length"), &["call i64 @main.length("]);
+}
+
+#[test]
+fn compile_tuples() {
+    let ir = compile("
+        func swap((a, b): (int32, float64)): (float64, int32) => (b, a)
+        func triple(value: int64): (int64, int64, int64) => (value, value, value)
+        func sum(): int64 {
+            let (first, _, third) = triple(4)
+            let nested = ((1, 2.0), true)
+            return match nested {
+                ((_, _), false) => first,
+                ((a, _), true) => third
+            }
+        }
+    ");
+    assert_ir_contains(&ir, &[
+        "define internal { double, i32 } @main.swap({ i32, double } %0)",
+        "sret({ i64, i64, i64 })"
+    ]);
+    assert_ir_contains(function_body(&ir, "@main.sum"), &["call void @main.triple(", "label %match.arm"]);
+}
+
+#[test]
+fn compile_arrays() {
+    let ir = compile("
+        func sum(values: [int64]): int64 => values[0]
+        func middle(values: [int64; 8]): int64 {
+            let slice = values[2..=5]
+            let grid = [[0; 4]; 4]
+            return sum(slice)
+        }
+        @no_bounds_check
+        func unchecked(values: [int64; 8], index: int32): int64 => values[index]
+        func wide(values: [int64], index: int128): int64 => values[index]
+        func through(values: [int64], end: int64): [int64] => values[1..=end]
+    ");
+    assert_ir_contains(&ir, &["declare void @ramen_panic_bounds_check(", "cold noreturn"]);
+    // Bounds checks report where the index is written.
+    assert_ir_contains(&ir, &["c\"main.rm:2:52\\00\"", "c\"main.rm:4:32\\00\"", "c\"main.rm:4:36\\00\""]);
+    assert_ir_contains(function_body(&ir, "@main.sum"), &["icmp uge i64 0, %length", "label %bounds.failure", "call void @ramen_panic_bounds_check("]);
+    assert_ir_contains(function_body(&ir, "@main.middle"), &["label %repeat.loop", "call i64 @main.sum("]);
+    assert!(!function_body(&ir, "@main.unchecked").contains("@ramen_panic_bounds_check"), "Expected no bounds check in IR:\n{ir}");
+    // Wide indices are checked before they are narrowed, so that `1 << 64` does not become 0.
+    let wide = function_body(&ir, "@main.wide");
+    assert_ir_contains(wide, &["zext i64 %length to i128", "icmp uge i128 ", "trunc i128 "]);
+    assert!(wide.find("icmp uge i128 ") < wide.find("trunc i128 "), "Expected index to be checked before it is narrowed:\n{wide}");
+    // Inclusive end is checked to be an element's index before one is added to it, which could otherwise overflow.
+    let through = function_body(&ir, "@main.through");
+    assert_ir_contains(through, &["icmp uge i64 ", "add i64 "]);
+    assert!(through.find("icmp uge i64 ") < through.find("add i64 "), "Expected end to be checked before it is incremented:\n{through}");
+}
+
+#[test]
+fn compile_pointers() {
+    let ir = compile("
+        func swap(a: &mut int64, b: &mut int64) {
+            let temporary = *a
+            *a = *b
+            *b = temporary
+        }
+        func set_first(values: &mut [int64; 4], value: int64) {
+            (*values)[0] = value
+        }
+        func is_same(a: *int64, b: *int64): bool => a == b
+        func main(): bool {
+            let x: int64 = 1
+            let y: int64 = 2
+            swap(&mut x, &mut y)
+            return is_same(&x, &y) != false
+        }
+    ");
+    assert_ir_contains(function_body(&ir, "@main.is_same"), &["icmp eq"]);
+    assert_ir_contains(function_body(&ir, "@main.main"), &["call void @main.swap(", "call i1 @main.is_same("]);
+}
+
+#[test]
+fn compile_generic_functions() {
+    let ir = compile("
+        func first<T>(a: T, b: T): T => a
+        func swap<A, B>((a, b): (A, B)): (B, A) => (b, a)
+        func twice<T>(value: T): (T, T) => (first(value, value), value)
+        func main(): int64 {
+            let small = first(1, 2)
+            let flag = first<bool>(true, false)
+            let (x, y) = twice(5.0)
+            let swapped: (int64, bool) = swap((flag, 7))
+            return first(swapped.0, 8)
+        }
+    ");
+    assert_ir_contains(&ir, &[
+        "define internal i32 @\"main.first<int32>\"(i32 %0, i32 %1)",
+        "define internal i1 @\"main.first<bool>\"(i1 %0, i1 %1)",
+        "define internal { i64, i1 } @\"main.swap<bool, int64>\"({ i1, i64 } %0)",
+        "call double @\"main.first<float64>\"("
+    ]);
+}
+
+#[test]
+fn compile_instances_of_same_named_types() {
+    let ir = compile("
+        mod a { pub struct P { pub x: int32 } }
+        mod b { pub struct P { pub x: float64 } }
+        impl a.P { func get(self): int32 => self.x }
+        impl b.P { func get(self): float64 => self.x }
+        func id<T>(value: T): T => value
+        func main(first: a.P, second: b.P): (a.P, b.P) => (id(first), id(second))
+    ");
+    assert_ir_contains(&ir, &[
+        "define internal { i32 } @\"main.id<main.a.P>\"({ i32 } %0)",
+        "define internal { double } @\"main.id<main.b.P>\"({ double } %0)",
+        "define internal i32 @main.a.P.get({ i32 } %0)",
+        "define internal double @main.b.P.get({ double } %0)"
+    ]);
+}
+
+#[test]
+fn compile_generic_types() {
+    let ir = compile("
+        struct Pair<A, B> { first: A, second: B }
+        enum Option<T> { Some(T), None }
+        func swap<A, B>(pair: &Pair<A, B>): Pair<B, A> => Pair(pair.second, pair.first)
+        func set_first(pair: &mut Pair<int64, float64>, value: int64) {
+            pair.first = value
+        }
+        func main(): Option<int64> {
+            let pair: Pair<float64, int64> = Pair(1.5, 2)
+            let swapped = swap(&pair)
+            set_first(&mut swapped, 3)
+            let pairs = [swapped, Pair(4, 5.5)]
+            return Option.Some(pairs[1].first)
+        }
+    ");
+    assert_ir_contains(&ir, &[
+        "define internal { i64, double } @\"main.swap<float64, int64>\"(",
+        "define internal { i8, [1 x i64] } @main.main()"
+    ]);
+}
+
+#[test]
+fn compile_traits() {
+    let ir = compile("
+        trait Eq {
+            func equals(a: &Self, b: &Self): bool
+        }
+        struct Point { x: int64, y: int64 }
+        impl Eq for int64 {
+            func equals(a: &int64, b: &int64): bool => *a == *b
+        }
+        impl Eq for Point {
+            func equals(a: &Point, b: &Point): bool => match Eq.equals(&a.x, &b.x) {
+                true => Eq.equals(&a.y, &b.y),
+                false => false
+            }
+        }
+        func same<T: Eq>(a: T, b: T): bool => Eq.equals(&a, &b)
+        func main(): bool {
+            let numbers = same<int64>(1, 2)
+            let point = Point(1, 2)
+            let points = point.equals(&point)
+            return same(Point(1, 2), Point(1, 2))
+        }
+    ");
+    assert_ir_contains(function_body(&ir, "@main.main"), &["call i1 @\"<main.Point as main.Eq>.equals\"("]);
+    assert_ir_contains(&ir, &[
+        "define internal i1 @\"<int64 as main.Eq>.equals\"(",
+        "define internal i1 @\"<main.Point as main.Eq>.equals\"("
+    ]);
+    assert_ir_contains(function_body(&ir, "@\"main.same<main.Point>\""), &["call i1 @\"<main.Point as main.Eq>.equals\"("]);
+}
+
+#[test]
+fn compile_trait_objects() {
+    let ir = compile("
+        trait Shape {
+            func area(shape: &Self): float64
+            func scale(shape: &mut Self, factor: float64)
+        }
+        struct Circle { radius: float64 }
+        struct Rect { width: float64, height: float64 }
+        impl Shape for Circle {
+            func area(shape: &Circle): float64 => shape.radius
+            func scale(shape: &mut Circle, factor: float64) {
+                shape.radius = factor
+            }
+        }
+        impl Shape for Rect {
+            func area(shape: &Rect): float64 => shape.width
+            func scale(shape: &mut Rect, factor: float64) {
+                shape.width = factor
+            }
+        }
+        func area<T: Shape>(shape: &T): float64 => Shape.area(shape)
+        func describe(shape: &dyn Shape): float64 => shape.area()
+        func resize(shape: &mut dyn Shape) {
+            shape.scale(2.0)
+        }
+        func main(): float64 {
+            let circle = Circle(1.0)
+            let rect = Rect(2.0, 3.0)
+            let shapes: [&dyn Shape; 2] = [&circle, &rect]
+            let resized: &mut dyn Shape = &mut circle
+            Shape.scale(resized, 2.0)
+            let generic = area(shapes[1])
+            return Shape.area(shapes[0])
+        }
+    ");
+    assert_ir_contains(&ir, &[
+        "@\"<main.Circle as main.Shape>.vtable\" = private constant [2 x",
+        "@\"<main.Rect as main.Shape>.vtable\" = private constant [2 x"
+    ]);
+    assert_ir_contains(function_body(&ir, "@\"main.area<dyn main.Shape>\""), &["call double %"]);
+    assert_ir_contains(function_body(&ir, "@main.describe"), &["call double %"]);
+    assert_ir_contains(function_body(&ir, "@main.resize"), &["call void %"]);
+}
+
+#[test]
+fn compile_methods() {
+    let ir = compile("
+        struct Circle { radius: float64 }
+        struct Scene { main: Circle, count: int32 }
+        impl Circle {
+            func new(radius: float64): Self => Circle(radius)
+            func radius(&self): float64 => self.radius
+            func resize(&mut self, radius: float64) {
+                self.radius = radius
+            }
+            func copy(self): Circle => Circle.new(self.radius)
+            func wrap<T>(&self, value: T): (float64, T) => (self.radius, value)
+        }
+        func scene_radius(scene: &Scene): float64 => scene.main.radius()
+        func main(): float64 {
+            let circle = Circle.new(1.0)
+            circle.resize(2.0)
+            let pointer = &circle
+            let copied = pointer.copy()
+            let wrapped = circle.wrap(true)
+            return circle.copy().radius()
+        }
+    ");
+    assert_ir_contains(&ir, &[
+        "define internal { double } @main.Circle.new(double %0)",
+        "define internal { double, i1 } @\"main.Circle.wrap<bool>\"("
+    ]);
+    assert_ir_contains(function_body(&ir, "@main.main"), &["call void @main.Circle.resize(", "call { double } @main.Circle.copy("]);
+}
+
+#[test]
+fn compile_closures() {
+    let ir = compile("
+        struct Big { a: int64, b: int64, c: int64 }
+        struct Holder { callback: func(int64): Big }
+        func make(value: int64): Big => Big(value, value, value)
+        func reset(value: &mut int64) {
+            *value = 0
+        }
+        func apply(f: func(int64): Big, value: int64): Big => f(value)
+        func main(): int64 {
+            let counter: int64 = 1
+            let by_reference = || reset(&mut counter)
+            let by_value = move |x: int64| Big(x, counter, counter)
+            by_reference()
+            let holder = Holder(make)
+            let first = holder.callback(2)
+            let second = apply(by_value, 3)
+            let nested = |x: int64| apply(|y| Big(x, y, counter), x)
+            return apply(make, counter).b
+        }
+        func adder(offset: int64): func(int64): int64 => move |x: int64| x + offset
+        func add(value: int64): int64 => adder(2)(value)
+    ");
+    assert_ir_contains(&ir, &[
+        "define private void @main.main.closure(",
+        "define private void @main.main.closure.2.closure(",
+        "define private void @main.make.trampoline("
+    ]);
+    assert_ir_contains(function_body(&ir, "@main.apply"), &["call void %code("]);
+    // Returned closure keeps its captured value after the frame of `adder` is gone.
+    assert_ir_contains(function_body(&ir, "@main.adder"), &["@malloc("]);
+    assert!(!function_body(&ir, "@main.adder").contains("%environment = alloca"), "Expected environment on the heap in IR:\n{ir}");
+    // Function values have no owner, so their environments are never freed.
+    assert!(!ir.contains("@free("), "Expected environments of move closures to be leaked in IR:\n{ir}");
+}
+
+#[test]
+fn compile_extern_functions() {
+    let ir = compile("
+        @extern(\"C\") func printf(format: *int8, ...): int32
+        @extern(\"C\") func isdigit(c: int8): bool
+        @extern func abs(value: int32): int32
+        @extern(\"C\") func narrow(value: int16, wide: int64): int8
+        func main(): int32 {
+            let text: [int8; 4] = [37, 100, 10, 0]
+            let small: int8 = narrow(7, 7)
+            let half: float32 = 0.5
+            printf(&text[0], small, half, isdigit(small))
+            let absolute = abs
+            return absolute(3)
+        }
+    ");
+    assert_ir_contains(&ir, &[
+        "declare i32 @printf(",
+        "declare zeroext i1 @isdigit(i8 signext)",
+        "declare i32 @abs(i32)",
+        "declare signext i8 @narrow(i16 signext, i64)",
+        "define private i32 @abs.trampoline("
+    ]);
+    assert!(!ir.contains("internal i32 @abs") && !ir.contains("@main.abs"), "Expected extern function to keep its C symbol in IR:\n{ir}");
+    // Variadic arguments are promoted to `int` and `double`.
+    assert_ir_contains(function_body(&ir, "@main.main"), &["call i32 (", "i32 %4, double %6, i32 %9)"]);
+}
+
+#[test]
+fn compile_exported_functions() {
+    let ir = compile("
+        @extern(\"C\") func abs(value: int32): int32
+        @export func magnitude(value: int32): int32 => helper(value)
+        @no_mangle func is_small(value: int16): bool => value == 0
+        func helper(value: int32): int32 => abs(value)
+    ");
+    assert_ir_contains(&ir, &[
+        "declare i32 @abs(i32)",
+        "define i32 @magnitude(i32 %0)",
+        "define zeroext i1 @is_small(i16 signext %0)",
+        "define internal i32 @main.helper(i32 %0)"
+    ]);
+}
+
+#[test]
+fn compile_c_tuple_returns() {
+    let ir = compile("
+        @extern(\"C\") func divide(numerator: int32, denominator: int32): (int32, int32)
+        @export func pair(): (int32, bool) => (1, true)
+        @export func point(): (float64, float64) => (1.0, 2.0)
+        @export func mixed(): (float32, float32, int8) => (1.0, 2.0, 3)
+        @export func bytes(): (int8, int8, int8) => (1, 2, 3)
+        @export func large(): (int64, int64, int64) => (1, 2, 3)
+        func main(): int32 {
+            let (quotient, remainder) = divide(7, 2)
+            let first = pair()
+            let half = divide
+            return quotient
+        }
+    ");
+    // Small tuples are returned in registers the way C returns structs with the same fields.
+    assert_ir_contains(&ir, &[
+        "declare i64 @divide(i32, i32)",
+        "define i64 @pair()",
+        "define { double, double } @point()",
+        "define { <2 x float>, i32 } @mixed()",
+        "define i24 @bytes()",
+        "define void @large(",
+        "define private { i32, i32 } @divide.trampoline("
+    ]);
+    assert_ir_contains(function_body(&ir, "@large"), &["sret({ i64, i64, i64 })"]);
+    assert_ir_contains(function_body(&ir, "@pair"), &["store { i32, i1 } ", "load i64, ", "ret i64 "]);
+    assert_ir_contains(function_body(&ir, "@main.main"), &["call i64 @divide(", "store i64 ", "load { i32, i32 }, "]);
+}
+
+#[test]
+fn compile_function_attributes() {
+    let ir = compile("
+        @extern(\"C\") @noreturn @nounwind @cold func abort()
+        @inline(always) func first(a: int32, b: int32): int32 => a
+        @inline(never) func second(a: int32, b: int32): int32 => b
+        @inline @pure func same(value: int32): int32 => value
+        @cold func fail() {
+            abort()
+        }
+        @noreturn func exit(code: int32) {
+            let message = code
+            abort()
+        }
+        func main(): int32 => first(same(1), second(2, 3))
+    ");
+    assert_ir_contains(&ir, &[
+        "; Function Attrs: cold noreturn nounwind\ndeclare void @abort()",
+        "; Function Attrs: alwaysinline\ndefine internal i32 @main.first(",
+        "; Function Attrs: noinline\ndefine internal i32 @main.second(",
+        "define internal i32 @main.same(",
+        "; Function Attrs: cold\ndefine internal void @main.fail(",
+        "; Function Attrs: noreturn\ndefine internal void @main.exit("
+    ]);
+    // Older LLVM spells `@pure` as `readnone` rather than `memory(none)`, and orders attributes differently.
+    let same_attributes = ir.lines().find(|line| line.starts_with("; Function Attrs:") && line.contains("inlinehint")).unwrap();
+    assert!(same_attributes.contains("memory(none)") || same_attributes.contains("readnone"), "Expected `@pure` to be lowered in IR:\n{ir}");
+}
+
+#[test]
+fn compile_nested_modules() {
+    let ir = compile("
+        mod math {
+            pub struct Pair { first: int32, second: int32 }
+            impl Pair {
+                pub func first(self): int32 => self.first
+            }
+
+            pub mod util {
+                pub func make(value: int32): math.Pair => math.Pair(value, value)
+            }
+        }
+
+        func main(): int32 => math.util.make(1).first()
+    ");
+    assert_ir_contains(&ir, &[
+        "define internal i32 @main.math.Pair.first(",
+        "define internal { i32, i32 } @main.math.util.make(i32 %0)"
+    ]);
+}
+
+#[test]
+fn compile_constants() {
+    let ir = compile("
+        enum Shape { Circle(float64), Square(int8, int32), Empty }
+        struct Point { x: int32, y: int32 }
+        impl Point {
+            func first(self): int32 => self.x
+        }
+
+        const SIZE: int64 = 3
+        const ORIGIN: Point = Point(1, 2)
+        const SHAPES: [Shape; SIZE] = [Shape.Circle(0.5), Shape.Square(1, 2), Shape.Empty]
+        static TABLE: [int32; SIZE] = [ORIGIN.x; SIZE]
+        static mut COUNTER: int64 = SIZE
+
+        func main(): (int32, Shape, int32) {
+            let buffer: [int8; SIZE] = [0; SIZE]
+            COUNTER = SIZE
+            return (ORIGIN.first(), SHAPES[1], TABLE[buffer[0]])
+        }
+    ");
+    assert_ir_contains(&ir, &[
+        "@main.TABLE = internal constant [3 x i32] [i32 1, i32 1, i32 1]",
+        "@main.COUNTER = internal global i64 3"
+    ]);
+    assert_ir_contains(function_body(&ir, "@main.main"), &["call i32 @main.Point.first(", "store i64 3, "]);
+}
+
+#[test]
+fn compile_operators() {
+    let ir = compile("
+        const func clamp(value: int32, low: int32, high: int32): int32 => match value < low {
+            true => low,
+            false => match value > high { true => high, false => value }
+        }
+
+        func main(a: int32, b: int32, x: float64): (int32, float64, bool, bool) {
+            let sum = -(a + b * 2 - a / b % 3)
+            let scaled = -x * 2.5 / (x - 1.0) % 2.0
+            let both = a <= b && !(x >= 0.5) || a != b
+            return (clamp(sum, 0, 10), scaled, both, x < 1.0)
+        }
+
+        func smallest(): int8 => -128
+    ");
+    // Negated literal is a single constant, as `128` alone does not fit into `int8`.
+    assert_ir_contains(function_body(&ir, "@main.smallest"), &["ret i8 -128"]);
+    assert_ir_contains(function_body(&ir, "@main.clamp"), &["icmp slt i32", "icmp sgt i32"]);
+    assert_ir_contains(function_body(&ir, "@main.main"), &[
+        // Division checks for zero divisor and divides by 1 instead of -1, whose result is the negated dividend.
+        "label %division.failure",
+        "call void @ramen_panic_division_by_zero(",
+        "icmp eq i32 %",
+        "select i1",
+        "sdiv i32",
+        "sub i32 0, ",
+        "srem i32",
+        "frem double",
+        "fcmp oge double",
+        "label %logical.right",
+        "call i32 @main.clamp("
+    ]);
+}
+
+#[test]
+fn compile_type_aliases() {
+    let ir = compile("
+        type Meters = float64
+        newtype UserId = int64
+        newtype Wrapper<T> = T
+
+        impl UserId {
+            func next(self): UserId => UserId(self.0 + 1)
+        }
+
+        func double(id: &mut UserId) {
+            (*id).0 = (*id).0 * 2
+        }
+
+        func main(id: UserId, length: Meters): (Meters, int64, Wrapper<bool>) {
+            let next = id.next()
+            double(&mut next)
+            return (length * 2.0, next.0, Wrapper(true))
+        }
+    ");
+    assert_ir_contains(&ir, &[
+        "define internal { i64 } @main.UserId.next({ i64 } %0)",
+        "sret({ double, i64, { i1 } })"
+    ]);
+    assert_ir_contains(function_body(&ir, "@main.main"), &["call void @main.double("]);
+}